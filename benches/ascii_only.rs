@@ -0,0 +1,61 @@
+//! Compare matching ASCII paths with and without [`PatternOpts::with_ascii_only`]
+//!
+//! Run with `cargo bench --bench ascii_only`
+
+mod common;
+
+use std::{hint::black_box, path::PathBuf};
+
+use globby::{MatchContext, Pattern, PatternOpts};
+
+use self::common::{bench, synthetic_tree};
+
+const ITERATIONS: u32 = 20;
+
+const PATTERNS: &[&str] = &[
+    "src/**/[[:alpha:]]*_?.{rs|toml}",
+    "**/mod_[!0-9]?/*.rs",
+    "**/{test|bench}*/**/*.[rt]s",
+];
+
+fn main() {
+    let paths = synthetic_tree()
+        .into_iter()
+        .map(PathBuf::from)
+        .collect::<Vec<_>>();
+
+    println!("Matching {} paths per iteration", paths.len());
+
+    for (name, opts) in [
+        ("Unicode-aware", PatternOpts::new()),
+        ("ASCII-only", PatternOpts::new().with_ascii_only(true)),
+        (
+            "Unicode-aware, case-insensitive",
+            PatternOpts::new().with_case_insensitive(true),
+        ),
+        (
+            "ASCII-only, case-insensitive",
+            PatternOpts::new()
+                .with_ascii_only(true)
+                .with_case_insensitive(true),
+        ),
+    ] {
+        // Don't share compiled expressions between modes
+        let opts = opts.with_bypass_regex_cache(true);
+
+        let patterns = PATTERNS
+            .iter()
+            .map(|pattern| Pattern::new_with_opts(pattern, opts).unwrap())
+            .collect::<Vec<_>>();
+
+        let mut ctx = MatchContext::new();
+
+        bench(name, ITERATIONS, || {
+            for pattern in &patterns {
+                for path in &paths {
+                    black_box(pattern.is_match_with_ctx(black_box(path), &mut ctx));
+                }
+            }
+        });
+    }
+}
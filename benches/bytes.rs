@@ -0,0 +1,50 @@
+//! Compare matching `/`-separated paths as [`Path`]s and as raw bytes
+//!
+//! Run with `cargo bench --bench bytes`
+
+mod common;
+
+use std::{hint::black_box, path::Path};
+
+use globby::{MatchContext, Pattern};
+
+use self::common::{bench, synthetic_tree};
+
+const ITERATIONS: u32 = 20;
+
+const PATTERNS: &[&str] = &[
+    "src/**/*.rs",
+    "**/mod_?*/sub_[0-4]/*.{rs|toml}",
+    "tests/mod_1a/sub_3/file_0_d.rs",
+];
+
+fn main() {
+    let paths = synthetic_tree();
+
+    let patterns = PATTERNS
+        .iter()
+        .map(|pattern| Pattern::new(pattern).unwrap())
+        .collect::<Vec<_>>();
+
+    println!("Matching {} paths per iteration", paths.len());
+
+    let mut ctx = MatchContext::new();
+
+    bench("Path", ITERATIONS, || {
+        for pattern in &patterns {
+            for path in &paths {
+                let path = Path::new(black_box(path.as_str()));
+                black_box(pattern.is_match_with_ctx(path, &mut ctx));
+            }
+        }
+    });
+
+    bench("bytes", ITERATIONS, || {
+        for pattern in &patterns {
+            for path in &paths {
+                let path = black_box(path.as_bytes());
+                black_box(pattern.match_against_bytes_with_ctx(path, &mut ctx));
+            }
+        }
+    });
+}
@@ -0,0 +1,37 @@
+//! Helpers shared by the benchmarks
+
+#![allow(dead_code)]
+
+use std::time::Instant;
+
+/// Run a function the provided number of times, then print the average duration of an iteration
+pub fn bench(name: &str, iterations: u32, mut func: impl FnMut()) {
+    let start = Instant::now();
+
+    for _ in 0..iterations {
+        func();
+    }
+
+    println!("{name}: {:?} per iteration", start.elapsed() / iterations);
+}
+
+/// Generate the paths of a synthetic source tree, separated with `/` and only made of ASCII characters
+pub fn synthetic_tree() -> Vec<String> {
+    let mut paths = vec![];
+
+    for top in ["src", "tests", "benches", "docs"] {
+        for i in 0..20 {
+            for j in 0..10 {
+                for (k, ext) in ["rs", "ts", "toml", "md", "txt"].into_iter().enumerate() {
+                    paths.push(format!(
+                        "{top}/mod_{i}{}/sub_{j}/file_{k}_{}.{ext}",
+                        char::from(b'a' + (i % 26) as u8),
+                        char::from(b'a' + (j % 26) as u8),
+                    ));
+                }
+            }
+        }
+    }
+
+    paths
+}
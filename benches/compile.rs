@@ -0,0 +1,47 @@
+//! Compare building many similar patterns with and without the regular expressions cache
+//!
+//! Run with `cargo bench --bench compile`
+
+use std::{hint::black_box, time::Instant};
+
+use globby::{Pattern, PatternOpts, clear_regex_cache};
+
+const PATTERNS: usize = 500;
+
+const ITERATIONS: u32 = 10;
+
+fn main() {
+    // Date-like rules, sharing their class-heavy components
+    let patterns = (0..PATTERNS)
+        .map(|i| {
+            format!(
+                "logs/service-{}/[[:digit:]][[:digit:]][[:digit:]][[:digit:]]-[[:digit:]][[:digit:]]-[[:digit:]][[:digit:]]-*.{}",
+                i % 50,
+                ["log", "txt"][i % 2]
+            )
+        })
+        .collect::<Vec<_>>();
+
+    bench("without cache", &patterns, true);
+    bench("with cache", &patterns, false);
+}
+
+fn bench(name: &str, patterns: &[String], bypass_regex_cache: bool) {
+    let opts = PatternOpts::new().with_bypass_regex_cache(bypass_regex_cache);
+
+    let start = Instant::now();
+
+    for _ in 0..ITERATIONS {
+        clear_regex_cache();
+
+        for pattern in patterns {
+            black_box(Pattern::new_with_opts(black_box(pattern), opts).unwrap());
+        }
+    }
+
+    println!(
+        "{name}: {:?} per {} patterns",
+        start.elapsed() / ITERATIONS,
+        patterns.len()
+    );
+}
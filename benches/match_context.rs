@@ -0,0 +1,78 @@
+//! Compare matching paths with and without a shared [`MatchContext`]
+//!
+//! Run with `cargo bench --bench match_context`
+
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    hint::black_box,
+    path::Path,
+    sync::atomic::{AtomicUsize, Ordering},
+    time::Instant,
+};
+
+use globby::{MatchContext, Pattern};
+
+/// Allocator counting all allocations
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+const ITERATIONS: usize = 1_000_000;
+
+fn main() {
+    let pattern = Pattern::new("src/**/*.{rs|toml}").unwrap();
+
+    let paths = [
+        "src/main.rs",
+        "src/a/b/c/mod.rs",
+        "docs/guide/intro.md",
+        "src/a/b/c/d/e/f/Cargo.toml",
+    ]
+    .map(Path::new);
+
+    bench("without context", || {
+        for path in paths {
+            black_box(pattern.is_match(black_box(path)));
+        }
+    });
+
+    let mut ctx = MatchContext::new();
+
+    bench("with shared context", || {
+        for path in paths {
+            black_box(pattern.is_match_with_ctx(black_box(path), &mut ctx));
+        }
+    });
+}
+
+fn bench(name: &str, mut func: impl FnMut()) {
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed);
+    let start = Instant::now();
+
+    for _ in 0..ITERATIONS {
+        func();
+    }
+
+    let elapsed = start.elapsed();
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed) - allocations;
+
+    println!(
+        "{name}: {:?} per iteration, {:.2} allocations per iteration",
+        elapsed / ITERATIONS as u32,
+        allocations as f64 / ITERATIONS as f64
+    );
+}
@@ -0,0 +1,47 @@
+//! Compare overriding the case sensitivity of a pattern for each match with building it again
+//!
+//! Run with `cargo bench --bench match_options`
+
+mod common;
+
+use std::{hint::black_box, path::Path};
+
+use globby::{MatchOptions, Pattern, PatternOpts};
+
+use self::common::bench;
+
+const ITERATIONS: u32 = 10_000;
+
+const PATTERN: &str = "src/{lib|bin}/**/[[:alpha:]]*.{rs|toml}";
+
+fn main() {
+    let paths = [
+        "src/lib/main.rs",
+        "SRC/Bin/a/b/Cargo.TOML",
+        "docs/guide/intro.md",
+    ]
+    .map(Path::new);
+
+    bench("building the pattern again", ITERATIONS, || {
+        for case_insensitive in [false, true] {
+            let opts = PatternOpts::new().with_case_insensitive(case_insensitive);
+            let pattern = Pattern::new_with_opts(black_box(PATTERN), opts).unwrap();
+
+            for path in paths {
+                black_box(pattern.is_match(black_box(path)));
+            }
+        }
+    });
+
+    let pattern = Pattern::new(PATTERN).unwrap();
+
+    bench("overriding match options", ITERATIONS, || {
+        for case_insensitive in [false, true] {
+            let opts = MatchOptions::new().with_case_insensitive(case_insensitive);
+
+            for path in paths {
+                black_box(pattern.is_match_with(black_box(path), opts));
+            }
+        }
+    });
+}
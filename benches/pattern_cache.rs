@@ -0,0 +1,49 @@
+//! Compare building the same few patterns over and over with and without the patterns cache
+//!
+//! Run with `cargo bench --bench pattern_cache`
+
+mod common;
+
+use std::hint::black_box;
+
+use globby::{Pattern, PatternOpts, clear_pattern_cache};
+
+use self::common::bench;
+
+const REPEATS: usize = 1000;
+
+const ITERATIONS: u32 = 10;
+
+const PATTERNS: &[&str] = &[
+    "src/**/*.{rs|toml}",
+    "logs/[[:digit:]][[:digit:]][[:digit:]][[:digit:]]-*.log",
+    "**/node_modules/**",
+    "assets/{images|fonts}/*.{png|jpg|woff2}",
+];
+
+fn main() {
+    let opts = PatternOpts::new();
+
+    println!(
+        "Building {} patterns per iteration",
+        REPEATS * PATTERNS.len()
+    );
+
+    bench("without cache", ITERATIONS, || {
+        for _ in 0..REPEATS {
+            for pattern in PATTERNS {
+                black_box(Pattern::new_with_opts(black_box(pattern), opts).unwrap());
+            }
+        }
+    });
+
+    bench("with cache", ITERATIONS, || {
+        clear_pattern_cache();
+
+        for _ in 0..REPEATS {
+            for pattern in PATTERNS {
+                black_box(Pattern::cached(black_box(pattern), opts).unwrap());
+            }
+        }
+    });
+}
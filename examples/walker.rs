@@ -1,22 +1,108 @@
-use std::path::Path;
+//! Walk a directory and print all entries matching a pattern
+//!
+//! Usage: `walker [--json] [--json-errors] [<pattern> [<base directory>]]`
+//!
+//! * `--json` prints one JSON object per result: `{"path": "...", "type": "file|dir|symlink", "depth": N}`
+//! * `--json-errors` prints errors inline as JSON objects: `{"error": "...", "path": "..."}`,
+//!   where the path is `null` for errors that don't relate to a specific directory
+//!
+//! Defaults to matching `**/*.*` from the root directory.
 
-use globby::{Pattern, PatternOpts, Walker};
+use std::{
+    io::{self, Write},
+    path::{Component, Path},
+};
+
+use globby::{Pattern, PatternOpts, WalkError, Walker};
+use serde_json::json;
 
 fn main() {
-    let pattern = Pattern::new_with_opts(
-        "**/*.*",
-        PatternOpts {
-            case_insensitive: false,
-        },
-    )
-    .unwrap();
-
-    let walker = Walker::new(pattern, Path::new("/"));
-
-    for path in walker {
-        match path {
-            Ok(path) => println!("OK: {}", path.display()),
-            Err(err) => println!("ERR: {err}"),
+    let mut stdout = io::stdout().lock();
+
+    if let Err(err) = run(std::env::args().skip(1), &mut stdout) {
+        eprintln!("{err}");
+        std::process::exit(1);
+    }
+}
+
+/// Run the walker with the provided command-line arguments, writing results to `out`
+pub fn run(args: impl IntoIterator<Item = String>, out: &mut impl Write) -> Result<(), String> {
+    let mut json = false;
+    let mut json_errors = false;
+    let mut positional = vec![];
+
+    for arg in args {
+        match arg.as_str() {
+            "--json" => json = true,
+            "--json-errors" => json_errors = true,
+            _ if arg.starts_with("--") => return Err(format!("unknown option: {arg}")),
+            _ => positional.push(arg),
         }
     }
+
+    let (pattern, base_dir) = match positional.as_slice() {
+        [] => ("**/*.*", "/"),
+        [pattern] => (pattern.as_str(), "/"),
+        [pattern, base_dir] => (pattern.as_str(), base_dir.as_str()),
+        _ => return Err("too many arguments".to_owned()),
+    };
+
+    let pattern = Pattern::new_with_opts(pattern, PatternOpts::new().with_case_insensitive(false))
+        .map_err(|err| err.to_string())?;
+
+    let base_dir = Path::new(base_dir);
+
+    for result in Walker::new(pattern, base_dir) {
+        let line = match result {
+            Ok(path) if json => json!({
+                "path": path.to_string_lossy(),
+                "type": entry_type(&base_dir.join(&path)),
+                "depth": depth(&path),
+            })
+            .to_string(),
+
+            Ok(path) => format!("OK: {}", path.display()),
+
+            Err(err) if json_errors => json!({
+                "error": err.to_string(),
+                "path": error_path(&err),
+            })
+            .to_string(),
+
+            Err(err) if json => {
+                eprintln!("ERR: {err}");
+                continue;
+            }
+
+            Err(err) => format!("ERR: {err}"),
+        };
+
+        writeln!(out, "{line}").map_err(|err| err.to_string())?;
+    }
+
+    Ok(())
+}
+
+/// Get the type of an entry, without following symbolic links
+fn entry_type(path: &Path) -> &'static str {
+    match path.symlink_metadata() {
+        Ok(metadata) if metadata.is_symlink() => "symlink",
+        Ok(metadata) if metadata.is_dir() => "dir",
+        _ => "file",
+    }
+}
+
+/// Get the depth of a result, i.e. the number of named components in its path
+fn depth(path: &Path) -> usize {
+    path.components()
+        .filter(|component| matches!(component, Component::Normal(_)))
+        .count()
+}
+
+/// Get the path an error relates to, if any
+fn error_path(err: &WalkError) -> Option<String> {
+    match err {
+        WalkError::Io { path, .. } => Some(path.to_string_lossy().into_owned()),
+        WalkError::LimitReached { .. } | WalkError::PatternInapplicable { .. } => None,
+    }
 }
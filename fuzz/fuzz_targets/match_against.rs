@@ -0,0 +1,43 @@
+//! Build arbitrary patterns and match them against arbitrary paths
+//!
+//! Run with `cargo fuzz run match_against` from the repository's root
+
+#![no_main]
+
+use globby::{
+    Pattern, PatternMatchResult,
+    check::{check_common_root_dir, check_normalization},
+    fuzzing::{RawPathInput, RawPatternInput},
+    normalize_path_with,
+};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|input: (RawPatternInput, RawPathInput)| {
+    let (RawPatternInput(pattern), RawPathInput(path)) = input;
+
+    let pattern = Pattern::new(&pattern).expect("generated patterns should be valid");
+    let result = pattern.match_against(&path);
+
+    assert_eq!(
+        pattern.is_match(&path),
+        matches!(result, PatternMatchResult::Matched)
+    );
+
+    // A starved pattern needs more components than the path has, so it may match descendants
+    if matches!(result, PatternMatchResult::Starved) {
+        let depth = normalize_path_with(&path, pattern.normalize_opts())
+            .expect("starved paths should have been normalized")
+            .components()
+            .len();
+
+        let (_, max) = pattern.depth_bounds();
+        assert!(max.is_none_or(|max| depth < max));
+    }
+
+    if let Some(simplified) = pattern.simplify() {
+        assert_eq!(simplified.is_match(&path), pattern.is_match(&path));
+    }
+
+    check_normalization(&path).unwrap();
+    check_common_root_dir(&pattern, &path).unwrap();
+});
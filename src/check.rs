@@ -0,0 +1,162 @@
+//! Helpers for checking the crate's invariants
+//!
+//! These are intended to be called from property tests and fuzz targets.
+//! Each helper returns the first violation it finds, if any.
+
+use std::{
+    collections::BTreeSet,
+    fmt,
+    path::{Path, PathBuf},
+};
+
+use crate::{Pattern, WalkError, Walker, normalize_path};
+
+/// An invariant that doesn't hold
+#[derive(Debug)]
+pub enum InvariantViolation {
+    /// Normalizing an already normalized path changed it
+    NormalizationNotIdempotent { path: PathBuf },
+
+    /// A path matched by the pattern doesn't start with the pattern's common root directory
+    MatchOutsideCommonRoot { path: PathBuf },
+
+    /// The walker yielded a path the pattern doesn't match
+    WalkerYieldedNonMatch { path: PathBuf },
+
+    /// The walker didn't yield an existing path the pattern matches
+    WalkerMissedMatch { path: PathBuf },
+
+    /// The walker failed
+    WalkFailed(WalkError),
+}
+
+impl fmt::Display for InvariantViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NormalizationNotIdempotent { path } => {
+                write!(
+                    f,
+                    "normalizing path {path:?} twice gives a different result"
+                )
+            }
+            Self::MatchOutsideCommonRoot { path } => write!(
+                f,
+                "matched path {path:?} is not under the pattern's common root directory"
+            ),
+            Self::WalkerYieldedNonMatch { path } => {
+                write!(
+                    f,
+                    "walker yielded path {path:?} which the pattern doesn't match"
+                )
+            }
+            Self::WalkerMissedMatch { path } => {
+                write!(
+                    f,
+                    "walker didn't yield path {path:?} which the pattern matches"
+                )
+            }
+            Self::WalkFailed(err) => write!(f, "walker failed: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for InvariantViolation {}
+
+/// Check that normalizing a path is idempotent
+///
+/// Paths with an unsupported prefix are ignored.
+pub fn check_normalization(path: &Path) -> Result<(), InvariantViolation> {
+    let Ok(normalized) = normalize_path(path) else {
+        return Ok(());
+    };
+
+    match normalize_path(&normalized.to_path_buf()) {
+        Ok(renormalized) if renormalized == normalized => Ok(()),
+        _ => Err(InvariantViolation::NormalizationNotIdempotent {
+            path: path.to_owned(),
+        }),
+    }
+}
+
+/// Check that a path matched by the pattern lives under its common root directory
+///
+/// Paths the pattern doesn't match are ignored.
+pub fn check_common_root_dir(pattern: &Pattern, path: &Path) -> Result<(), InvariantViolation> {
+    if !pattern.is_match(path) {
+        return Ok(());
+    }
+
+    let (Ok(path_normalized), Ok(root)) = (
+        normalize_path(path),
+        normalize_path(pattern.common_root_dir()),
+    ) else {
+        return Ok(());
+    };
+
+    let is_under_root = path_normalized.prefix() == pattern.prefix()
+        && path_normalized.components().starts_with(root.components());
+
+    if is_under_root {
+        Ok(())
+    } else {
+        Err(InvariantViolation::MatchOutsideCommonRoot {
+            path: path.to_owned(),
+        })
+    }
+}
+
+/// Check that walking a directory yields exactly the existing paths the pattern matches
+///
+/// Every yielded path must be matched by the pattern. For relative patterns that don't start
+/// with a parent directory component, all entries under the base directory are also listed
+/// to ensure none of the matching ones was missed.
+///
+/// Symbolic links are followed when listing entries, so the tree must not contain any cycle.
+pub fn check_walker_agreement(pattern: &Pattern, base: &Path) -> Result<(), InvariantViolation> {
+    let mut yielded = BTreeSet::new();
+
+    for path in Walker::new(pattern.clone(), base) {
+        let path = path.map_err(InvariantViolation::WalkFailed)?;
+
+        if !pattern.is_match_dir_aware(&path, base.join(&path).is_dir()) {
+            return Err(InvariantViolation::WalkerYieldedNonMatch { path });
+        }
+
+        yielded.insert(path);
+    }
+
+    if pattern.is_absolute() || pattern.common_root_dir().starts_with("..") {
+        return Ok(());
+    }
+
+    let mut existing = vec![];
+    list_entries(base, Path::new(""), &mut existing).map_err(InvariantViolation::WalkFailed)?;
+
+    match existing.into_iter().find(|path| {
+        pattern.is_match_dir_aware(path, base.join(path).is_dir()) && !yielded.contains(path)
+    }) {
+        Some(path) => Err(InvariantViolation::WalkerMissedMatch { path }),
+        None => Ok(()),
+    }
+}
+
+/// List all entries under a directory, recursively
+fn list_entries(dir: &Path, relative: &Path, out: &mut Vec<PathBuf>) -> Result<(), WalkError> {
+    let io_err = |err| WalkError::Io {
+        path: dir.to_path_buf(),
+        err,
+    };
+
+    for entry in std::fs::read_dir(dir).map_err(io_err)? {
+        let entry = entry.map_err(io_err)?;
+        let path = relative.join(entry.file_name());
+
+        out.push(path.clone());
+
+        if entry.path().is_dir() {
+            list_entries(&entry.path(), &path, out)?;
+        }
+    }
+
+    Ok(())
+}
@@ -1,19 +1,156 @@
-use regex::bytes::Regex;
+use std::{
+    collections::HashMap,
+    fmt::Write,
+    ops::Range,
+    ptr,
+    sync::{Mutex, OnceLock, PoisonError},
+};
 
-use crate::parser::{CharacterClass, CharsMatcher, RawComponent, SingleCharMatcher};
+use regex::bytes::{Regex, RegexBuilder};
+use unicode_normalization::{UnicodeNormalization as _, char::is_combining_mark};
+
+use crate::{
+    parser::{CharacterClass, CharsMatcher, ExtGlobOperator, RawComponent, SingleCharMatcher},
+    pattern::CaseFolding,
+};
 
 #[derive(Debug, Clone)]
 pub enum Component {
     Regex(Regex),
+    /// Regular expression matching one or more path components, joined with `/` separators
+    ///
+    /// Used when `*` is allowed to match path separators (see [`crate::PatternOpts::with_star_matches_separators`])
+    Spanning(Regex),
     Literal(String),
+    /// Literal matched case-insensitively
+    ///
+    /// With [`CaseFolding::Unicode`], the string is stored in its folded form (see [`fold_char`])
+    CaselessLiteral(String, CaseFolding),
+    /// Component containing a `!(...)` operator (see [`crate::PatternOpts::with_extglob`])
+    Negated(Box<NegatedComponent>),
     Wildcard,
     ParentDir,
 }
 
+/// Component containing a `!(...)` operator, which regular expressions can't express
+///
+/// The component is split around the operator: a path component matches if it can be split into three parts,
+/// the first and last ones being matched by the matchers preceding and following the operator,
+/// and the middle one not being matched by any of the operator's alternates.
+#[derive(Debug, Clone)]
+pub struct NegatedComponent {
+    /// Expression matching the part preceding the operator
+    head: Regex,
+
+    /// Expression matching the strings the operator excludes
+    excluded: Regex,
+
+    /// Expression matching the part following the operator
+    tail: Regex,
+
+    /// Only allow a leading `.` to be matched by a literal `.` (see [`CompileOpts::require_literal_leading_dot`])
+    ///
+    /// Contains the expression matching the part following the operator at the start of the component,
+    /// if there's no matcher preceding the operator.
+    literal_leading_dot: Option<Option<Regex>>,
+}
+
+impl NegatedComponent {
+    /// Check if a single path component matches
+    pub fn is_match(&self, bytes: &[u8]) -> bool {
+        // Only split at character boundaries, so the parts remain valid UTF-8 if the component is
+        let boundaries = (0..=bytes.len())
+            .filter(|&i| bytes.get(i).is_none_or(|byte| byte & 0xC0 != 0x80))
+            .collect::<Vec<_>>();
+
+        let tail_matches = boundaries
+            .iter()
+            .map(|&j| self.tail.is_match(&bytes[j..]))
+            .collect::<Vec<_>>();
+
+        for (n, &i) in boundaries.iter().enumerate() {
+            // The operator can't match a leading `.`, which must then be matched by the following matchers
+            if i == 0
+                && bytes.first() == Some(&b'.')
+                && let Some(leading_tail) = &self.literal_leading_dot
+            {
+                if let Some(leading_tail) = leading_tail
+                    && !self.excluded.is_match(b"")
+                    && leading_tail.is_match(bytes)
+                {
+                    return true;
+                }
+
+                continue;
+            }
+
+            if !self.head.is_match(&bytes[..i]) {
+                continue;
+            }
+
+            for (m, &j) in boundaries.iter().enumerate().skip(n) {
+                if tail_matches[m] && !self.excluded.is_match(&bytes[i..j]) {
+                    return true;
+                }
+            }
+        }
+
+        false
+    }
+}
+
 /// Determine if the built regular expressions should use case sensitivity or not
+#[derive(Clone, Copy)]
 pub enum CaseSensitivity {
     Sensitive,
-    Insensitive,
+    Insensitive(CaseFolding),
+}
+
+/// Options to compile components with (see [`compile_component`])
+#[derive(Clone, Copy)]
+pub struct CompileOpts {
+    /// Case sensitivity of the component
+    pub case_sensitivity: CaseSensitivity,
+
+    /// Maximum size of the compiled regular expression, if any
+    pub regex_size_limit: Option<usize>,
+
+    /// Compile components containing a `*` matcher into [`Component::Spanning`] expressions,
+    /// in which `*` can match path separators
+    pub star_matches_separators: bool,
+
+    /// Only allow a leading `.` in a path component to be matched by a literal `.` at the start of the component
+    pub require_literal_leading_dot: bool,
+
+    /// Compile regular expressions without Unicode support, so they match bytes instead of characters
+    /// (see [`crate::PatternOpts::with_ascii_only`])
+    pub ascii_only: bool,
+
+    /// Don't share compiled regular expressions through the process-wide cache
+    pub bypass_regex_cache: bool,
+
+    /// Emit capture groups for the `*`, `?`, brackets and groups matchers (see [`read_captures`])
+    ///
+    /// Runs of identical matchers are not compacted in that case, as each of them needs its own group.
+    pub captures: bool,
+
+    /// Prevent matchers from matching path separators, so the expression can be embedded
+    /// into one matching whole paths (see [`embeddable_regex`])
+    pub exportable: bool,
+}
+
+/// (Internal) How matchers handle path separators
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Separators {
+    /// Matched strings are single path components, so they can't contain separators
+    Absent,
+
+    /// Matched strings may contain separators, which can't be matched (see [`CompileOpts::exportable`])
+    Excluded,
+
+    /// Matched strings are suites of path components, whose separators can only be matched by `*`
+    /// (see [`Component::Spanning`])
+    Spanned,
 }
 
 /// Compile a parsed component to its final form
@@ -22,50 +159,845 @@ pub enum CaseSensitivity {
 /// into regular expressions to accelerate matching.
 ///
 /// The goal of this function is to make pattern matching faster.
-pub fn compile_component(component: RawComponent, case_sensitivity: CaseSensitivity) -> Component {
+///
+/// Fails if the regular expression exceeds the provided size limit (if any)
+pub fn compile_component(
+    component: RawComponent,
+    opts: CompileOpts,
+) -> Result<Component, CompileError> {
+    let CompileOpts {
+        case_sensitivity,
+        regex_size_limit,
+        star_matches_separators,
+        require_literal_leading_dot,
+        ascii_only,
+        bypass_regex_cache,
+        captures,
+        exportable,
+    } = opts;
+
     match component {
-        RawComponent::Wildcard => Component::Wildcard,
-        RawComponent::ParentDir => Component::ParentDir,
+        RawComponent::Wildcard => Ok(Component::Wildcard),
+        RawComponent::ParentDir => Ok(Component::ParentDir),
 
         RawComponent::Literal(lit) => match case_sensitivity {
-            CaseSensitivity::Insensitive => {
-                Component::Regex(Regex::new(&format!("(?i)^{}$", regex::escape(&lit))).unwrap())
+            CaseSensitivity::Insensitive(CaseFolding::Ascii) => {
+                Ok(Component::CaselessLiteral(lit, CaseFolding::Ascii))
             }
-            CaseSensitivity::Sensitive => Component::Literal(lit),
+            CaseSensitivity::Insensitive(CaseFolding::Unicode) => Ok(Component::CaselessLiteral(
+                lit.chars().flat_map(fold_char).collect(),
+                CaseFolding::Unicode,
+            )),
+            CaseSensitivity::Sensitive => Ok(Component::Literal(lit)),
         },
 
         RawComponent::Suite(chars_matchers) => {
-            let mut regex = match case_sensitivity {
-                CaseSensitivity::Sensitive => String::new(),
-                CaseSensitivity::Insensitive => String::from("(?i)"),
+            if let Some(pos) = chars_matchers.iter().position(|matcher| {
+                matches!(matcher, CharsMatcher::ExtGlob(ExtGlobOperator::Not, _))
+            }) {
+                return compile_negated(&chars_matchers, pos, opts)
+                    .map(|negated| Component::Negated(Box::new(negated)));
+            }
+
+            let spanning = star_matches_separators && contains_any_chars(&chars_matchers);
+
+            let separators = if spanning {
+                Separators::Spanned
+            } else if exportable {
+                Separators::Excluded
+            } else {
+                Separators::Absent
+            };
+
+            let capture_slots = captures.then(|| CaptureSlots::new(&chars_matchers));
+            let captures = capture_slots.as_ref();
+
+            // ASCII-only folding is performed matcher by matcher, see [`compile_chars_matcher`]
+            let (mut regex, fold_ascii) = match case_sensitivity {
+                CaseSensitivity::Sensitive => (String::new(), false),
+                CaseSensitivity::Insensitive(CaseFolding::Ascii) => (String::new(), true),
+                CaseSensitivity::Insensitive(CaseFolding::Unicode) => (String::from("(?i)"), false),
             };
 
             regex.push('^');
 
-            for matcher in chars_matchers {
-                compile_chars_matcher(&matcher, &mut regex);
+            if require_literal_leading_dot {
+                compile_leading_chars_matchers(
+                    &[&chars_matchers],
+                    separators,
+                    fold_ascii,
+                    true,
+                    captures,
+                    &mut regex,
+                );
+            } else {
+                compile_chars_matchers(
+                    &chars_matchers,
+                    separators,
+                    false,
+                    fold_ascii,
+                    captures,
+                    &mut regex,
+                );
             }
 
             regex.push('$');
 
-            Component::Regex(Regex::new(&regex).unwrap())
+            build_regex(&regex, regex_size_limit, !ascii_only, bypass_regex_cache).map(
+                if spanning {
+                    Component::Spanning
+                } else {
+                    Component::Regex
+                },
+            )
+        }
+    }
+}
+
+/// Compile a suite of matchers containing a `!(...)` operator at the provided position
+fn compile_negated(
+    chars_matchers: &[CharsMatcher],
+    pos: usize,
+    opts: CompileOpts,
+) -> Result<NegatedComponent, CompileError> {
+    let CompileOpts {
+        case_sensitivity,
+        regex_size_limit,
+        star_matches_separators: _,
+        require_literal_leading_dot,
+        ascii_only,
+        bypass_regex_cache,
+        captures: _,
+        exportable: _,
+    } = opts;
+
+    let compile = |matchers: &[CharsMatcher], leading: bool| {
+        let (mut regex, fold_ascii) = match case_sensitivity {
+            CaseSensitivity::Sensitive => (String::new(), false),
+            CaseSensitivity::Insensitive(CaseFolding::Ascii) => (String::new(), true),
+            CaseSensitivity::Insensitive(CaseFolding::Unicode) => (String::from("(?i)"), false),
+        };
+
+        regex.push('^');
+
+        if leading {
+            compile_leading_chars_matchers(
+                &[matchers],
+                Separators::Absent,
+                fold_ascii,
+                true,
+                None,
+                &mut regex,
+            );
+        } else {
+            compile_chars_matchers(
+                matchers,
+                Separators::Absent,
+                false,
+                fold_ascii,
+                None,
+                &mut regex,
+            );
+        }
+
+        regex.push('$');
+
+        build_regex(&regex, regex_size_limit, !ascii_only, bypass_regex_cache)
+    };
+
+    let CharsMatcher::ExtGlob(ExtGlobOperator::Not, excluded) = &chars_matchers[pos] else {
+        unreachable!()
+    };
+
+    let (head, tail) = (&chars_matchers[..pos], &chars_matchers[pos + 1..]);
+
+    Ok(NegatedComponent {
+        head: compile(head, require_literal_leading_dot)?,
+        excluded: compile(&[CharsMatcher::OneOfGroups(excluded.clone())], false)?,
+        tail: compile(tail, false)?,
+        literal_leading_dot: if require_literal_leading_dot {
+            Some(if head.is_empty() {
+                Some(compile(tail, true)?)
+            } else {
+                None
+            })
+        } else {
+            None
+        },
+    })
+}
+
+/// Fold a character for case-insensitive comparisons using [`CaseFolding::Unicode`]
+///
+/// This follows Unicode's full case folding (without the Turkic-specific mappings),
+/// which may produce multiple characters, e.g. `ß` is folded to `ss` and `İ` to `i̇` (`i` followed by U+0307).
+pub fn fold_char(c: char) -> impl Iterator<Item = char> {
+    // Round-tripping through the uppercase form handles e.g. `ß` and `ẞ`, which only fold to `ss` that way
+    let folded = c
+        .to_lowercase()
+        .flat_map(char::to_uppercase)
+        .flat_map(char::to_lowercase);
+
+    // Dotless `ı` would be folded to `i` through its uppercase form `I`, but is its own folding
+    folded.map(move |folded| if c == 'ı' { c } else { folded })
+}
+
+/// Remove diacritics from characters (see [`crate::PatternOpts::with_fold_diacritics`])
+///
+/// Characters are decomposed, stripped of their combining marks and composed again, e.g. `é` becomes `e`.
+pub fn strip_diacritics(chars: impl Iterator<Item = char>) -> impl Iterator<Item = char> {
+    chars.nfd().filter(|c| !is_combining_mark(*c)).nfc()
+}
+
+/// Check if a path component is equal to a [`Component::CaselessLiteral`]'s string
+pub fn caseless_eq(lit: &str, folding: CaseFolding, part: &[u8]) -> bool {
+    match folding {
+        CaseFolding::Ascii => part.eq_ignore_ascii_case(lit.as_bytes()),
+        CaseFolding::Unicode => match std::str::from_utf8(part) {
+            Ok(part) => part.chars().flat_map(fold_char).eq(lit.chars()),
+            // Components that aren't valid UTF-8 can only be equal to the literal byte by byte
+            Err(_) => part == lit.as_bytes(),
+        },
+    }
+}
+
+/// Compiled regular expressions, keyed by their source, size limit and Unicode support
+///
+/// Regular expressions are cheap to clone, as clones share their compiled form.
+type RegexCache = HashMap<(String, Option<usize>, bool), Regex>;
+
+/// Process-wide cache of compiled regular expressions
+static REGEX_CACHE: OnceLock<Mutex<RegexCache>> = OnceLock::new();
+
+/// Remove all regular expressions from the process-wide cache
+///
+/// Patterns that were already built are unaffected.
+pub fn clear_regex_cache() {
+    if let Some(cache) = REGEX_CACHE.get() {
+        cache.lock().unwrap_or_else(PoisonError::into_inner).clear();
+    }
+}
+
+/// Build a regular expression, with an optional size limit
+///
+/// Without `unicode`, the expression matches bytes instead of characters, and its classes are ASCII-only.
+///
+/// The expression is fetched from the process-wide cache if possible, unless `bypass_cache` is set
+fn build_regex(
+    source: &str,
+    size_limit: Option<usize>,
+    unicode: bool,
+    bypass_cache: bool,
+) -> Result<Regex, CompileError> {
+    if bypass_cache {
+        return build_uncached_regex(source, size_limit, unicode);
+    }
+
+    let key = (source.to_owned(), size_limit, unicode);
+
+    let cache = REGEX_CACHE.get_or_init(Default::default);
+
+    if let Some(regex) = cache
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .get(&key)
+    {
+        return Ok(regex.clone());
+    }
+
+    // Don't hold the lock while compiling, as it may take a while
+    let regex = build_uncached_regex(source, size_limit, unicode)?;
+
+    cache
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .insert(key, regex.clone());
+
+    Ok(regex)
+}
+
+/// Build a regular expression without going through the cache
+fn build_uncached_regex(
+    source: &str,
+    size_limit: Option<usize>,
+    unicode: bool,
+) -> Result<Regex, CompileError> {
+    let mut builder = RegexBuilder::new(source);
+    builder.unicode(unicode);
+
+    if let Some(size_limit) = size_limit {
+        builder.size_limit(size_limit);
+    }
+
+    builder.build().map_err(CompileError)
+}
+
+/// Failed to compile a component
+#[derive(Debug)]
+pub struct CompileError(pub regex::Error);
+
+/// Get a regular expression matching the same strings as a compiled component, to embed it into a larger one
+///
+/// Unlike the component's own expression, it isn't anchored, and its flags only apply to itself.
+/// The component must have been compiled with [`CompileOpts::exportable`], so it doesn't match path separators
+/// (unless it's [`Component::Spanning`]).
+///
+/// Wildcard components can't be represented on their own, and components containing a `!(...)` operator
+/// can't be represented at all, so [`None`] is returned for them.
+pub fn embeddable_regex(component: &Component) -> Option<String> {
+    match component {
+        Component::Wildcard | Component::Negated(_) => None,
+        Component::ParentDir => Some(regex::escape("..")),
+        Component::Literal(lit) => Some(regex::escape(lit)),
+        Component::CaselessLiteral(lit, CaseFolding::Ascii) => {
+            Some(format!("(?i-u:{})", regex::escape(lit)))
+        }
+        Component::CaselessLiteral(lit, CaseFolding::Unicode) => {
+            Some(format!("(?i:{})", regex::escape(lit)))
+        }
+
+        // Expressions are anchored and may start with a case-insensitivity flag (see [`compile_component`])
+        Component::Regex(regex) | Component::Spanning(regex) => {
+            let (flags, source) = match regex.as_str().strip_prefix("(?i)") {
+                Some(source) => ("i", source),
+                None => ("", regex.as_str()),
+            };
+
+            let source = source
+                .strip_prefix('^')
+                .and_then(|source| source.strip_suffix('$'))
+                .unwrap();
+
+            Some(format!("(?{flags}:{source})"))
+        }
+    }
+}
+
+/// Count the capturing matchers of a component, i.e. its `*`, `?`, brackets and groups
+///
+/// Matchers nested inside of groups are counted too, see [`read_captures`].
+pub fn capture_count(component: &RawComponent) -> usize {
+    match component {
+        // Components containing a `!(...)` operator don't support captures (see [`NegatedComponent`])
+        RawComponent::Suite(chars_matchers)
+            if chars_matchers.iter().any(|matcher| {
+                matches!(matcher, CharsMatcher::ExtGlob(ExtGlobOperator::Not, _))
+            }) =>
+        {
+            0
+        }
+        RawComponent::Suite(chars_matchers) => CaptureSlots::new(chars_matchers).matchers.len(),
+        RawComponent::Literal(_) | RawComponent::Wildcard | RawComponent::ParentDir => 0,
+    }
+}
+
+/// Read the text matched by each capturing matcher of a component compiled with [`CompileOpts::captures`]
+///
+/// Capturing matchers are numbered in order of appearance, groups coming before the matchers they contain
+/// (see [`capture_count`]). Matchers inside of group alternates that weren't used don't capture anything.
+///
+/// Returns [`None`] if the expression doesn't match the provided component.
+pub fn read_captures(
+    regex: &Regex,
+    component: &[u8],
+    count: usize,
+) -> Option<Vec<Option<Range<usize>>>> {
+    let captures = regex.captures(component)?;
+
+    let mut ranges = vec![None; count];
+    let mut starts = vec![None; count];
+
+    for (name, group) in regex.capture_names().zip(captures.iter()) {
+        let (Some(name), Some(group)) = (name, group) else {
+            continue;
+        };
+
+        // Names are made of the group's kind, its slot and a suffix making them unique (see [`open_capture`])
+        let (kind, name) = name.split_at(1);
+        let (slot, _) = name.split_once('_').unwrap();
+        let slot = slot.parse::<usize>().unwrap();
+
+        match kind {
+            "c" => ranges[slot] = Some(group.range()),
+            "s" => starts[slot] = Some(group.start()),
+            "e" => ranges[slot] = starts[slot].map(|start| start..group.end()),
+            _ => unreachable!(),
+        }
+    }
+
+    Some(ranges)
+}
+
+/// (Internal) Capturing matchers of a component (see [`CompileOpts::captures`])
+///
+/// Matchers are identified by their address, as the same matcher may be compiled at several places
+/// of the expression (see [`compile_leading_chars_matchers`]).
+struct CaptureSlots<'a> {
+    /// Capturing matchers, in order of appearance
+    matchers: Vec<&'a CharsMatcher>,
+
+    /// Empty literals marking the end of each group's alternates, along with the group's slot
+    ///
+    /// These are only used for groups leading a component, whose alternates are each compiled
+    /// along with the matchers following the group, and so can't be wrapped in a single capture group.
+    group_ends: Vec<(usize, CharsMatcher)>,
+}
+
+impl<'a> CaptureSlots<'a> {
+    fn new(chars_matchers: &'a [CharsMatcher]) -> Self {
+        let mut slots = Self {
+            matchers: vec![],
+            group_ends: vec![],
+        };
+
+        slots.collect(chars_matchers);
+        slots
+    }
+
+    fn collect(&mut self, chars_matchers: &'a [CharsMatcher]) {
+        for matcher in chars_matchers {
+            match matcher {
+                CharsMatcher::Literal(_) => {}
+
+                CharsMatcher::AnyChar
+                | CharsMatcher::AnyChars
+                | CharsMatcher::OneOfChars(_)
+                | CharsMatcher::NoneOfChars(_) => self.matchers.push(matcher),
+
+                CharsMatcher::OneOfGroups(groups) => {
+                    self.group_ends
+                        .push((self.matchers.len(), CharsMatcher::Literal(String::new())));
+
+                    self.matchers.push(matcher);
+
+                    for group in groups {
+                        self.collect(group);
+                    }
+                }
+
+                // Matchers inside of operators are repeated, so they only capture as a whole
+                CharsMatcher::ExtGlob(_, _) => {
+                    self.group_ends
+                        .push((self.matchers.len(), CharsMatcher::Literal(String::new())));
+
+                    self.matchers.push(matcher);
+                }
+            }
+        }
+    }
+
+    /// Get the slot of a capturing matcher
+    fn slot(&self, matcher: &CharsMatcher) -> Option<usize> {
+        self.matchers
+            .iter()
+            .position(|capturing| ptr::eq(*capturing, matcher))
+    }
+
+    /// Get the end marker of a group (see [`CaptureSlots::group_ends`])
+    fn group_end(&self, group: &CharsMatcher) -> &CharsMatcher {
+        let slot = self.slot(group).unwrap();
+
+        self.group_ends
+            .iter()
+            .find_map(|(end_slot, end)| (*end_slot == slot).then_some(end))
+            .unwrap()
+    }
+
+    /// Get the slot of the group a matcher is the end marker of
+    fn ending_group(&self, matcher: &CharsMatcher) -> Option<usize> {
+        self.group_ends
+            .iter()
+            .find_map(|(slot, end)| ptr::eq(end, matcher).then_some(*slot))
+    }
+}
+
+/// Open a named capture group for a capturing matcher, if captures are enabled
+///
+/// `kind` is either `c` for a group wrapping the matcher, or `s` and `e` for empty groups marking its start and end.
+/// Group names are made unique by the current length of the expression, as a matcher may be compiled several times.
+///
+/// Returns `true` if a group was opened, which must then be closed by the caller
+fn open_capture(
+    captures: Option<&CaptureSlots>,
+    matcher: &CharsMatcher,
+    kind: char,
+    out: &mut String,
+) -> bool {
+    match captures.and_then(|captures| captures.slot(matcher)) {
+        Some(slot) => {
+            write!(out, "(?P<{kind}{slot}_{}>", out.len()).unwrap();
+            true
+        }
+
+        None => false,
+    }
+}
+
+/// Check if a suite of matchers contains a [`CharsMatcher::AnyChars`], including inside groups
+fn contains_any_chars(chars_matchers: &[CharsMatcher]) -> bool {
+    chars_matchers.iter().any(|matcher| match matcher {
+        CharsMatcher::AnyChars => true,
+        CharsMatcher::OneOfGroups(groups) | CharsMatcher::ExtGlob(_, groups) => {
+            groups.iter().any(|group| contains_any_chars(group))
+        }
+        CharsMatcher::AnyChar
+        | CharsMatcher::Literal(_)
+        | CharsMatcher::OneOfChars(_)
+        | CharsMatcher::NoneOfChars(_) => false,
+    })
+}
+
+/// Compile a suite of [`CharsMatcher`] located at the start of a component to a regular expression,
+/// ensuring a leading `.` can only be matched by a literal `.` that starts the suite
+///
+/// If `literal_dot_allowed` is not set, the component can't start with a `.` at all, which
+/// is the case when previous matchers (e.g. `*`) matched an empty string.
+///
+/// The suite is made of the concatenation of the provided slices, which allows compiling
+/// each alternate of a group followed by the matchers coming after it without cloning them.
+///
+/// The resulting expression is appended to the provided mutable string reference
+fn compile_leading_chars_matchers(
+    suite: &[&[CharsMatcher]],
+    separators: Separators,
+    fold_ascii: bool,
+    literal_dot_allowed: bool,
+    captures: Option<&CaptureSlots>,
+    out: &mut String,
+) {
+    let Some(pos) = suite.iter().position(|matchers| !matchers.is_empty()) else {
+        return;
+    };
+
+    let (first, rest) = suite[pos].split_first().unwrap();
+
+    let mut following = Vec::with_capacity(suite.len() - pos);
+    following.push(rest);
+    following.extend_from_slice(&suite[pos + 1..]);
+
+    let compile_following = |out: &mut String| {
+        compile_chars_matchers(
+            following.iter().flat_map(|matchers| matchers.iter()),
+            separators,
+            true,
+            fold_ascii,
+            captures,
+            out,
+        );
+    };
+
+    // Wrap single-character matchers in capture groups
+    let compile_single_char = |class: &str, out: &mut String| {
+        let capturing = open_capture(captures, first, 'c', out);
+        out.push_str(class);
+
+        if capturing {
+            out.push(')');
+        }
+    };
+
+    match first {
+        CharsMatcher::Literal(lit) if lit.is_empty() => {
+            if let Some(slot) = captures.and_then(|captures| captures.ending_group(first)) {
+                write!(out, "(?P<e{slot}_{}>)", out.len()).unwrap();
+            }
+
+            compile_leading_chars_matchers(
+                &following,
+                separators,
+                fold_ascii,
+                literal_dot_allowed,
+                captures,
+                out,
+            )
+        }
+
+        // Empty character class, which never matches
+        CharsMatcher::Literal(lit) if lit.starts_with('.') && !literal_dot_allowed => {
+            out.push_str("[a&&b]")
+        }
+
+        CharsMatcher::Literal(_) => {
+            compile_chars_matcher(first, separators, true, fold_ascii, captures, out);
+            compile_following(out);
+        }
+
+        CharsMatcher::AnyChar => {
+            compile_single_char(
+                match separators {
+                    Separators::Absent => "[^.]",
+                    Separators::Excluded | Separators::Spanned => "[^./]",
+                },
+                out,
+            );
+            compile_following(out);
+        }
+
+        CharsMatcher::OneOfChars(single_char_matchers) => {
+            let mut class = String::from("[[");
+
+            for matcher in single_char_matchers {
+                compile_single_char_matcher(*matcher, fold_ascii, &mut class);
+            }
+
+            class.push_str("]&&[^.]]");
+            compile_single_char(&class, out);
+            compile_following(out);
+        }
+
+        CharsMatcher::NoneOfChars(single_char_matchers) => {
+            let mut class = String::from("[^");
+
+            for matcher in single_char_matchers {
+                compile_single_char_matcher(*matcher, fold_ascii, &mut class);
+            }
+
+            class.push_str(match separators {
+                Separators::Absent => ".]",
+                Separators::Excluded | Separators::Spanned => "./]",
+            });
+            compile_single_char(&class, out);
+            compile_following(out);
+        }
+
+        // Either match at least one character that isn't a dot, or match nothing
+        // and prevent the following matchers from matching a leading dot
+        CharsMatcher::AnyChars => {
+            out.push_str("(?:");
+            let capturing = open_capture(captures, first, 'c', out);
+            out.push_str(match separators {
+                Separators::Absent | Separators::Spanned => "[^.]",
+                Separators::Excluded => "[^./]",
+            });
+            compile_chars_matcher(first, separators, true, fold_ascii, None, out);
+
+            if capturing {
+                out.push(')');
+            }
+
+            compile_following(out);
+            out.push('|');
+
+            // The matcher captures an empty string in that case
+            if open_capture(captures, first, 'c', out) {
+                out.push(')');
+            }
+
+            compile_leading_chars_matchers(
+                &following, separators, fold_ascii, false, captures, out,
+            );
+            out.push(')');
+        }
+
+        // When capturing, each alternate is followed by a marker, as it's compiled along with the following matchers
+        CharsMatcher::OneOfGroups(groups) => {
+            if open_capture(captures, first, 's', out) {
+                out.push(')');
+            }
+
+            let group_end =
+                captures.map(|captures| std::slice::from_ref(captures.group_end(first)));
+
+            out.push_str("(?:");
+
+            for (i, group) in groups.iter().enumerate() {
+                if i > 0 {
+                    out.push('|');
+                }
+
+                let mut alternate = Vec::with_capacity(following.len() + 2);
+                alternate.push(group.as_slice());
+                alternate.extend(group_end);
+                alternate.extend_from_slice(&following);
+
+                compile_leading_chars_matchers(
+                    &alternate,
+                    separators,
+                    fold_ascii,
+                    literal_dot_allowed,
+                    captures,
+                    out,
+                );
+            }
+
+            out.push(')');
+        }
+
+        // Alternates are compiled along with the following matchers, like groups, while further repetitions
+        // are compiled as a following `*(...)` operator, which can't match a leading `.`
+        CharsMatcher::ExtGlob(op, groups) => {
+            if open_capture(captures, first, 's', out) {
+                out.push(')');
+            }
+
+            let group_end =
+                captures.map(|captures| std::slice::from_ref(captures.group_end(first)));
+
+            let repeated = [CharsMatcher::ExtGlob(
+                ExtGlobOperator::ZeroOrMore,
+                groups.clone(),
+            )];
+
+            let (optional, repeating) = match op {
+                ExtGlobOperator::ZeroOrOne => (true, false),
+                ExtGlobOperator::ZeroOrMore => (true, true),
+                ExtGlobOperator::OneOrMore => (false, true),
+                ExtGlobOperator::ExactlyOne => (false, false),
+                ExtGlobOperator::Not => unreachable!(),
+            };
+
+            out.push_str("(?:");
+
+            for (i, group) in groups.iter().enumerate() {
+                if i > 0 {
+                    out.push('|');
+                }
+
+                let mut alternate = Vec::with_capacity(following.len() + 3);
+                alternate.push(group.as_slice());
+
+                if repeating {
+                    alternate.push(repeated.as_slice());
+                }
+
+                alternate.extend(group_end);
+                alternate.extend_from_slice(&following);
+
+                compile_leading_chars_matchers(
+                    &alternate,
+                    separators,
+                    fold_ascii,
+                    literal_dot_allowed,
+                    captures,
+                    out,
+                );
+            }
+
+            if optional {
+                out.push('|');
+
+                let mut alternate = Vec::with_capacity(following.len() + 1);
+                alternate.extend(group_end);
+                alternate.extend_from_slice(&following);
+
+                compile_leading_chars_matchers(
+                    &alternate,
+                    separators,
+                    fold_ascii,
+                    literal_dot_allowed,
+                    captures,
+                    out,
+                );
+            }
+
+            out.push(')');
+        }
+    }
+}
+
+/// Compile a suite of [`CharsMatcher`] to a regular expression
+///
+/// Runs of identical single-character matchers are compacted into bounded repetitions,
+/// e.g. `[[:digit:]][[:digit:]]` becomes `[[:digit:]]{2}`, unless captures are enabled.
+///
+/// See [`compile_chars_matcher`] for the meaning of the flags.
+fn compile_chars_matchers<'a>(
+    chars_matchers: impl IntoIterator<Item = &'a CharsMatcher>,
+    separators: Separators,
+    literal_leading_dot: bool,
+    fold_ascii: bool,
+    captures: Option<&CaptureSlots>,
+    out: &mut String,
+) {
+    let mut chars_matchers = chars_matchers.into_iter().peekable();
+
+    while let Some(chars_matcher) = chars_matchers.next() {
+        compile_chars_matcher(
+            chars_matcher,
+            separators,
+            literal_leading_dot,
+            fold_ascii,
+            captures,
+            out,
+        );
+
+        if captures.is_some() {
+            continue;
+        }
+
+        if !matches!(
+            chars_matcher,
+            CharsMatcher::AnyChar | CharsMatcher::OneOfChars(_) | CharsMatcher::NoneOfChars(_)
+        ) {
+            continue;
+        }
+
+        let mut count = 1;
+
+        while chars_matchers.next_if_eq(&chars_matcher).is_some() {
+            count += 1;
+        }
+
+        if count > 1 {
+            write!(out, "{{{count}}}").unwrap();
         }
     }
 }
 
 /// Compile a [`CharsMatcher`] to a regular expression
 ///
+/// With [`Separators::Spanned`], only `*` matchers may match path separators.
+/// If `literal_leading_dot` is set too, they can't match a separator followed by a `.`,
+/// which would mean matching the leading dot of the next component.
+///
+/// With [`Separators::Excluded`], no matcher can match path separators. Other characters are matched
+/// exactly like with [`Separators::Absent`], so e.g. `?` doesn't match a newline either.
+///
+/// When `fold_ascii` is set, letters are matched case-insensitively, but only in the ASCII range
+/// (as opposed to the `(?i)` flag which performs Unicode case folding).
+///
+/// When `captures` is provided, capturing matchers are wrapped in capture groups (see [`CompileOpts::captures`]).
+///
 /// The resulting expression is appended to the provided mutable string reference
-fn compile_chars_matcher(chars_matcher: &CharsMatcher, out: &mut String) {
+fn compile_chars_matcher(
+    chars_matcher: &CharsMatcher,
+    separators: Separators,
+    literal_leading_dot: bool,
+    fold_ascii: bool,
+    captures: Option<&CaptureSlots>,
+    out: &mut String,
+) {
+    if let Some(slot) = captures.and_then(|captures| captures.ending_group(chars_matcher)) {
+        write!(out, "(?P<e{slot}_{}>)", out.len()).unwrap();
+        return;
+    }
+
+    let capturing = open_capture(captures, chars_matcher, 'c', out);
+
     match chars_matcher {
-        CharsMatcher::AnyChar => out.push('.'),
-        CharsMatcher::AnyChars => out.push_str(".*"),
-        CharsMatcher::Literal(lit) => out.push_str(&regex::escape(lit)),
+        CharsMatcher::AnyChar => out.push_str(match separators {
+            Separators::Absent => ".",
+            Separators::Excluded => "[^/\\n]",
+            Separators::Spanned => "[^/]",
+        }),
+        CharsMatcher::AnyChars => out.push_str(match separators {
+            Separators::Absent => ".*",
+            Separators::Excluded => "[^/\\n]*",
+            Separators::Spanned if literal_leading_dot => "(?:[^/]|/[^.])*",
+            Separators::Spanned => ".*",
+        }),
+        CharsMatcher::Literal(lit) => {
+            if fold_ascii {
+                write!(out, "(?i-u:{})", regex::escape(lit)).unwrap();
+            } else {
+                out.push_str(&regex::escape(lit));
+            }
+        }
         CharsMatcher::OneOfChars(single_char_matchers) => {
             out.push('[');
 
             for matcher in single_char_matchers {
-                compile_single_char_matcher(*matcher, out);
+                compile_single_char_matcher(*matcher, fold_ascii, out);
             }
 
             out.push(']');
@@ -74,7 +1006,11 @@ fn compile_chars_matcher(chars_matcher: &CharsMatcher, out: &mut String) {
             out.push_str("[^");
 
             for matcher in single_char_matchers {
-                compile_single_char_matcher(*matcher, out);
+                compile_single_char_matcher(*matcher, fold_ascii, out);
+            }
+
+            if separators != Separators::Absent {
+                out.push('/');
             }
 
             out.push(']');
@@ -87,30 +1023,114 @@ fn compile_chars_matcher(chars_matcher: &CharsMatcher, out: &mut String) {
                     out.push('|');
                 }
 
-                for matcher in matchers {
-                    compile_chars_matcher(matcher, out);
+                compile_chars_matchers(
+                    matchers,
+                    separators,
+                    literal_leading_dot,
+                    fold_ascii,
+                    captures,
+                    out,
+                );
+            }
+
+            out.push(')');
+        }
+        CharsMatcher::ExtGlob(op, groups) => {
+            out.push_str("(?:");
+
+            for (i, matchers) in groups.iter().enumerate() {
+                if i > 0 {
+                    out.push('|');
                 }
+
+                compile_chars_matchers(
+                    matchers,
+                    separators,
+                    literal_leading_dot,
+                    fold_ascii,
+                    None,
+                    out,
+                );
             }
 
             out.push(')');
+
+            match op {
+                ExtGlobOperator::ZeroOrOne => out.push('?'),
+                ExtGlobOperator::ZeroOrMore => out.push('*'),
+                ExtGlobOperator::OneOrMore => out.push('+'),
+                ExtGlobOperator::ExactlyOne => {}
+                // Negations are compiled separately (see [`compile_negated`])
+                ExtGlobOperator::Not => unreachable!(),
+            }
         }
     }
+
+    if capturing {
+        out.push(')');
+    }
 }
 
 /// Compile a [`SingleCharMatcher`] to a regular expression
 ///
+/// When `fold_ascii` is set, ASCII letters match both their lowercase and uppercase forms,
+/// which makes the uppercase and lowercase classes equivalent to the alphabetic one.
+///
 /// The resulting expression is appended to the provided mutable string reference
-fn compile_single_char_matcher(char_matcher: SingleCharMatcher, out: &mut String) {
+fn compile_single_char_matcher(
+    char_matcher: SingleCharMatcher,
+    fold_ascii: bool,
+    out: &mut String,
+) {
     match char_matcher {
+        SingleCharMatcher::Literal(lit) if fold_ascii && lit.is_ascii_alphabetic() => {
+            out.push(lit.to_ascii_lowercase());
+            out.push(lit.to_ascii_uppercase());
+        }
+
         SingleCharMatcher::Literal(lit) => out.push_str(&regex::escape(&lit.to_string())),
 
+        SingleCharMatcher::Range(start, end) => {
+            write!(
+                out,
+                "{}-{}",
+                regex::escape(&start.to_string()),
+                regex::escape(&end.to_string())
+            )
+            .unwrap();
+
+            // Ranges of letters sharing the same case (e.g. `a-z`) also match the other case
+            if fold_ascii
+                && start.is_ascii_alphabetic()
+                && end.is_ascii_alphabetic()
+                && start.is_ascii_lowercase() == end.is_ascii_lowercase()
+            {
+                let swap = |c: char| {
+                    if c.is_ascii_lowercase() {
+                        c.to_ascii_uppercase()
+                    } else {
+                        c.to_ascii_lowercase()
+                    }
+                };
+
+                write!(out, "{}-{}", swap(start), swap(end)).unwrap();
+            }
+        }
+
         SingleCharMatcher::Class(character_class) => out.push_str(match character_class {
             CharacterClass::Alpha => "[:alpha:]",
             CharacterClass::Digit => "[:digit:]",
             CharacterClass::Alphanumeric => "[:alnum:]",
+            CharacterClass::Uppercase | CharacterClass::Lowercase if fold_ascii => "[:alpha:]",
             CharacterClass::Uppercase => "[:upper:]",
             CharacterClass::Lowercase => "[:lower:]",
             CharacterClass::Whitespace => "[:space:]",
+            CharacterClass::HexDigit => "[:xdigit:]",
+            CharacterClass::Punctuation => "[:punct:]",
+            CharacterClass::Blank => "[:blank:]",
+            CharacterClass::Control => "[:cntrl:]",
+            CharacterClass::Graphic => "[:graph:]",
+            CharacterClass::Printable => "[:print:]",
         }),
     }
 }
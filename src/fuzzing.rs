@@ -0,0 +1,218 @@
+//! Generators of patterns and paths for fuzzing
+//!
+//! These implement [`Arbitrary`], so fuzz targets can receive them directly (see the `fuzz` directory).
+//! Generated patterns are always syntactically valid with the default options, so fuzzers spend their time
+//! on building and matching patterns instead of getting parsing errors.
+
+use std::path::PathBuf;
+
+use ::arbitrary::{Arbitrary, Result, Unstructured};
+
+/// Maximum number of components in generated patterns and paths
+const MAX_COMPONENTS: usize = 6;
+
+/// Maximum number of items in a generated component
+const MAX_ITEMS: usize = 4;
+
+/// Maximum nesting depth of groups in generated patterns
+const MAX_GROUP_DEPTH: usize = 2;
+
+/// Characters used in names, including some which change when case-folded or normalized
+const NAME_CHARS: &[char] = &['a', 'b', 'A', '.', '-', ' ', 'é', 'ß', 'İ', '\u{301}'];
+
+/// A syntactically valid pattern, to be parsed with the default options
+///
+/// Generated patterns may have a prefix (e.g. `/` or `C:\`), and be made of literals, wildcards,
+/// bracket expressions (including ranges, character classes and escapes), nested groups and `..` components.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RawPatternInput(pub String);
+
+impl<'a> Arbitrary<'a> for RawPatternInput {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let mut pattern = String::new();
+
+        let absolute = match u.int_in_range(0..=3)? {
+            0 => {
+                pattern.push('/');
+                true
+            }
+            1 => {
+                pattern.push(char::from(u.int_in_range(b'a'..=b'z')?));
+                pattern.push_str(u.choose(&[":/", ":\\"])?);
+                true
+            }
+            _ => false,
+        };
+
+        let components = u.int_in_range(0..=MAX_COMPONENTS)?;
+
+        // `..` components are only allowed at the beginning of relative patterns
+        let parent_dirs = if absolute {
+            0
+        } else {
+            u.int_in_range(0..=components.min(2))?
+        };
+
+        for i in 0..components {
+            if i > 0 {
+                pattern.push_str(u.choose(&["/", "\\"])?);
+            }
+
+            if i < parent_dirs {
+                pattern.push_str("..");
+            } else if u.ratio(1, 5)? {
+                pattern.push_str("**");
+            } else {
+                push_items(u, &mut pattern, 0)?;
+            }
+        }
+
+        if components > 0 && u.ratio(1, 5)? {
+            pattern.push('/');
+        }
+
+        Ok(Self(pattern))
+    }
+}
+
+/// Push a non-empty suite of matchers to a pattern
+fn push_items(u: &mut Unstructured, pattern: &mut String, depth: usize) -> Result<()> {
+    let mut star = false;
+
+    for _ in 0..u.int_in_range(1..=MAX_ITEMS)? {
+        // Consecutive stars are only allowed as whole components
+        let choice = u.int_in_range(0..=if depth < MAX_GROUP_DEPTH { 5 } else { 4 })?;
+
+        star = match choice {
+            0 if !star => {
+                pattern.push('*');
+                true
+            }
+            1 => {
+                pattern.push('?');
+                false
+            }
+            2 => {
+                push_brackets(u, pattern)?;
+                false
+            }
+            5 => {
+                pattern.push('{');
+
+                for i in 0..u.int_in_range(2..=3)? {
+                    if i > 0 {
+                        pattern.push(*u.choose(&['|', ','])?);
+                    }
+
+                    push_items(u, pattern, depth + 1)?;
+                }
+
+                pattern.push('}');
+                false
+            }
+            _ => {
+                for _ in 0..u.int_in_range(1..=3)? {
+                    pattern.push(*u.choose(NAME_CHARS)?);
+                }
+
+                // A component made of `..` alone would be a parent directory
+                if pattern.ends_with("..") {
+                    pattern.push('a');
+                }
+
+                false
+            }
+        };
+    }
+
+    Ok(())
+}
+
+/// Push a bracket expression to a pattern
+fn push_brackets(u: &mut Unstructured, pattern: &mut String) -> Result<()> {
+    pattern.push('[');
+
+    if u.arbitrary()? {
+        pattern.push('!');
+    }
+
+    for _ in 0..u.int_in_range(1..=3)? {
+        match u.int_in_range(0..=3)? {
+            0 => pattern.push_str(u.choose(&[
+                "[:alpha:]",
+                "[:digit:]",
+                "[:alphanumeric:]",
+                "[:uppercase:]",
+                "[:lowercase:]",
+                "[:whitespace:]",
+                "[:xdigit:]",
+                "[:punct:]",
+                "[:blank:]",
+                "[:cntrl:]",
+                "[:graph:]",
+                "[:print:]",
+            ])?),
+            1 => pattern.push_str(u.choose(&["\\[", "\\]", "\\*", "\\:", "\\|", "\\-"])?),
+            2 => pattern.push_str(u.choose(&["a-z", "A-Z", "0-9", "a-a", "é-ü"])?),
+            _ => pattern.push(*u.choose(&['a', 'b', 'A', '.', 'é'])?),
+        }
+    }
+
+    pattern.push(']');
+
+    Ok(())
+}
+
+/// A path with unusual components
+///
+/// Generated paths may have a prefix (e.g. `/` or `C:\`), use both `/` and `\` as separators,
+/// and contain empty, `.` and `..` components, as well as names that aren't valid UTF-8 on Unix.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RawPathInput(pub PathBuf);
+
+impl<'a> Arbitrary<'a> for RawPathInput {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let mut path = Vec::<u8>::new();
+
+        match u.int_in_range(0..=3)? {
+            0 => path.push(b'/'),
+            1 => path.extend_from_slice(b"C:\\"),
+            _ => {}
+        }
+
+        for i in 0..u.int_in_range(0..=MAX_COMPONENTS)? {
+            if i > 0 {
+                path.push(*u.choose(b"/\\")?);
+            }
+
+            match u.int_in_range(0..=5)? {
+                0 => {}
+                1 => path.push(b'.'),
+                2 => path.extend_from_slice(b".."),
+                3 if cfg!(unix) => path.extend_from_slice(&[b'a', 0xFF, 0xC3]),
+                _ => {
+                    for _ in 0..u.int_in_range(1..=3)? {
+                        let c = *u.choose(NAME_CHARS)?;
+                        path.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes());
+                    }
+                }
+            }
+        }
+
+        Ok(Self(bytes_to_path(path)))
+    }
+}
+
+/// Build a path from its raw bytes
+#[cfg(unix)]
+fn bytes_to_path(bytes: Vec<u8>) -> PathBuf {
+    use std::{ffi::OsString, os::unix::ffi::OsStringExt};
+
+    PathBuf::from(OsString::from_vec(bytes))
+}
+
+/// Build a path from its raw bytes, which are only invalid UTF-8 on Unix
+#[cfg(not(unix))]
+fn bytes_to_path(bytes: Vec<u8>) -> PathBuf {
+    PathBuf::from(String::from_utf8(bytes).unwrap())
+}
@@ -0,0 +1,170 @@
+use crate::{Pattern, PatternError, PatternOpts};
+
+/// A rule from a `.gitignore` file (see [`Pattern::new_gitignore`])
+#[derive(Debug, Clone)]
+pub struct GitignoreRule {
+    /// Pattern matching the paths the rule applies to
+    pattern: Pattern,
+
+    /// Does the rule re-include paths instead of ignoring them? (i.e. it starts with `!`)
+    negated: bool,
+}
+
+impl GitignoreRule {
+    /// Get the pattern matching the paths the rule applies to
+    pub fn pattern(&self) -> &Pattern {
+        &self.pattern
+    }
+
+    /// Check if the rule re-includes paths excluded by previous rules instead of ignoring them,
+    /// i.e. if it starts with `!`
+    pub fn is_negated(&self) -> bool {
+        self.negated
+    }
+
+    /// Get the pattern matching the paths the rule applies to
+    pub fn into_pattern(self) -> Pattern {
+        self.pattern
+    }
+}
+
+impl Pattern {
+    /// Parse a line of a `.gitignore` file
+    ///
+    /// Rules are interpreted like Git does:
+    /// * Rules ending with a `/` (e.g. `build/`) only match directories (see [`Pattern::is_dir_only`])
+    /// * Rules without any other `/` (e.g. `*.log`) match at any depth, while others (e.g. `/build` or `doc/*.txt`)
+    ///   are anchored, i.e. only match paths starting from the directory containing the `.gitignore` file
+    /// * A leading `**/` matches in all directories, a trailing `/**` matches everything inside a directory
+    ///   (but not the directory itself), and other `**` act like `*`
+    /// * A leading `!` negates the rule (see [`GitignoreRule::is_negated`])
+    /// * `\` escapes the character following it, e.g. in `\!important.txt` or `\#file`
+    /// * `[!abc]` and `[^abc]` both match any character except `a`, `b` and `c`, while `{` and `|` are ordinary characters
+    /// * Trailing spaces are ignored, unless escaped
+    ///
+    /// Returns [`None`] for lines that don't contain a rule, i.e. blank lines and comments (starting with `#`).
+    ///
+    /// The resulting pattern matches paths relative to the directory containing the `.gitignore` file.
+    /// Note that it only matches the paths the rule applies to: paths inside of an ignored directory
+    /// are ignored by Git as well, which is up to the caller.
+    pub fn new_gitignore(rule: &str) -> Result<Option<GitignoreRule>, PatternError> {
+        Self::new_gitignore_with_opts(rule, PatternOpts::new())
+    }
+
+    /// Parse a line of a `.gitignore` file, with additional options
+    ///
+    /// Options required to follow Git's syntax (e.g. [`PatternOpts::with_backslash_escapes`]) are always enabled.
+    /// Other ones can be used to e.g. match case-insensitively, like Git does with `core.ignoreCase`.
+    ///
+    /// See [`Pattern::new_gitignore`]
+    pub fn new_gitignore_with_opts(
+        rule: &str,
+        opts: PatternOpts,
+    ) -> Result<Option<GitignoreRule>, PatternError> {
+        let rule = trim_unescaped_trailing_spaces(rule);
+
+        if rule.is_empty() || rule.starts_with('#') {
+            return Ok(None);
+        }
+
+        let (rule, negated) = match rule.strip_prefix('!') {
+            Some(rule) => (rule, true),
+            None => (rule, false),
+        };
+
+        let opts = PatternOpts::new()
+            .with_backslash_escapes(true)
+            .with_disable_alternates(true)
+            .or(opts);
+
+        let pattern = Pattern::new_with_opts(&translate_rule(rule), opts)?;
+
+        Ok(Some(GitignoreRule { pattern, negated }))
+    }
+}
+
+/// Remove trailing spaces, except the ones escaped with a backslash
+fn trim_unescaped_trailing_spaces(rule: &str) -> &str {
+    let mut rule = rule;
+
+    while let Some(trimmed) = rule.strip_suffix(' ') {
+        if trimmed.ends_with('\\') {
+            break;
+        }
+
+        rule = trimmed;
+    }
+
+    rule
+}
+
+/// Translate a `.gitignore` rule (without its negation) into an equivalent pattern
+fn translate_rule(rule: &str) -> String {
+    let (rule, dir_only) = match rule.strip_suffix('/') {
+        Some(rule) => (rule, true),
+        None => (rule, false),
+    };
+
+    // Rules with a separator at the beginning or in the middle are relative to the `.gitignore` file
+    let anchored = rule.contains('/');
+    let rule = rule.strip_prefix('/').unwrap_or(rule);
+
+    let mut components = rule.split('/').map(translate_component).collect::<Vec<_>>();
+
+    // A trailing `**` only matches what's inside of the directory
+    if components.len() > 1 && components.last().is_some_and(|last| last == "**") {
+        components.push("*".to_owned());
+    }
+
+    if !anchored && components.first().is_none_or(|first| first != "**") {
+        components.insert(0, "**".to_owned());
+    }
+
+    let mut pattern = components.join("/");
+
+    if dir_only {
+        pattern.push('/');
+    }
+
+    pattern
+}
+
+/// Translate a single component of a `.gitignore` rule
+///
+/// `**` is only special as a whole component, and acts like `*` otherwise.
+/// Negated brackets may also be written as `[^...]`.
+fn translate_component(component: &str) -> String {
+    if component == "**" {
+        return component.to_owned();
+    }
+
+    let mut translated = String::with_capacity(component.len());
+    let mut chars = component.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                translated.push(c);
+                translated.extend(chars.next());
+            }
+
+            '*' => {
+                translated.push(c);
+
+                while chars.next_if_eq(&'*').is_some() {}
+            }
+
+            '[' => {
+                translated.push(c);
+
+                if chars.next_if_eq(&'^').is_some() {
+                    translated.push('!');
+                }
+            }
+
+            _ => translated.push(c),
+        }
+    }
+
+    translated
+}
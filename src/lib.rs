@@ -32,49 +32,88 @@
 #![forbid(unused_must_use)]
 #![warn(unused_crate_dependencies)]
 
+#[cfg(feature = "check")]
+pub mod check;
 mod compiler;
+#[cfg(feature = "arbitrary")]
+pub mod fuzzing;
+mod gitignore;
 mod opaque_os_str;
 mod parser;
 mod paths;
 mod pattern;
+mod pattern_cache;
+mod pattern_set;
+mod simplify;
+pub mod syntax;
 mod walker;
 
 use std::path::Path;
 
-use parsy::ParsingError;
+// Only used by integration tests
+#[cfg(test)]
+use {proptest as _, serde_json as _, tempfile as _};
 
 pub use self::{
-    paths::{PathPrefix, WindowsDrive, normalize_path},
-    pattern::{Pattern, PatternMatchResult, PatternOpts},
-    walker::Walker,
+    compiler::clear_regex_cache,
+    gitignore::GitignoreRule,
+    paths::{NormalizeOpts, PathPrefix, WindowsDrive, normalize_path, normalize_path_with},
+    pattern::{
+        CaseFolding, Comparison, FileNameMatcher, JoinError, MAX_EXPANSIONS, MatchCaptures,
+        MatchContext, MatchFailure, MatchFailureReason, MatchOptions, MatchTrace, ParseError,
+        Pattern, PatternComponent, PatternError, PatternMatchResult, PatternMatcher, PatternOpts,
+        PatternStats, Portability, StepResult, TargetHint, TooManyExpansions, UnicodeNormalization,
+    },
+    pattern_cache::{
+        DEFAULT_PATTERN_CACHE_CAPACITY, clear_pattern_cache, set_pattern_cache_capacity,
+    },
+    pattern_set::{PatternSet, Verdict},
+    walker::{
+        CachedRun, CachedWalker, GroupedWalker, LimitKind, MultiMatch, MultiMatchWalker, Ordering,
+        WalkError, WalkStats, Walker,
+    },
 };
 
 /// Match a pattern against the current directory
 ///
 /// Strictly equivalent to calling [`glob_in`] with the canonicalized path to the current directory
 ///
+/// The pattern is parsed first, so invalid patterns are reported even if the current directory is unavailable.
+/// Absolute patterns don't need the current directory at all (see [`Walker::new_absolute`]).
+///
+/// Uses the default options (see [`PatternOpts::new`]), so matching is case-sensitive on all platforms.
+/// To match case-insensitively on Windows and macOS, use [`glob_with`] with [`PatternOpts::platform_defaults`].
+///
 /// For details on how patterns are applied, see [`Walker`]
 pub fn glob(pattern: &str) -> Result<Walker, GlobError> {
-    let current_dir = std::env::current_dir().map_err(GlobError::FailedToGetCurrentDir)?;
-
-    glob_in(pattern, &current_dir).map_err(GlobError::InvalidPattern)
+    glob_with(pattern, PatternOpts::default())
 }
 
 /// Match a pattern against the current directory
 ///
 /// Strictly equivalent to calling [`glob_in_with`] with the canonicalized path to the current directory
 ///
+/// See [`glob`] for how the current directory is handled.
+///
 /// For details on how patterns are applied, see [`Walker`]
 pub fn glob_with(pattern: &str, opts: PatternOpts) -> Result<Walker, GlobError> {
+    let pattern = Pattern::new_with_opts(pattern, opts).map_err(GlobError::InvalidPattern)?;
+
+    if pattern.is_absolute() {
+        return Ok(Walker::new_absolute(pattern));
+    }
+
     let current_dir = std::env::current_dir().map_err(GlobError::FailedToGetCurrentDir)?;
 
-    glob_in_with(pattern, &current_dir, opts).map_err(GlobError::InvalidPattern)
+    Ok(Walker::new(pattern, &current_dir))
 }
 
 /// Match a pattern against a provided directory
 ///
+/// Uses the default options, see [`glob`] for details.
+///
 /// For details on how patterns are applied, see [`Walker::new`]
-pub fn glob_in(pattern: &str, dir: &Path) -> Result<Walker, ParsingError> {
+pub fn glob_in(pattern: &str, dir: &Path) -> Result<Walker, PatternError> {
     let pattern = Pattern::new(pattern)?;
 
     Ok(Walker::new(pattern, dir))
@@ -83,7 +122,7 @@ pub fn glob_in(pattern: &str, dir: &Path) -> Result<Walker, ParsingError> {
 /// Match a pattern against a provided directory
 ///
 /// For details on how patterns are applied, see [`Walker::new`]
-pub fn glob_in_with(pattern: &str, dir: &Path, opts: PatternOpts) -> Result<Walker, ParsingError> {
+pub fn glob_in_with(pattern: &str, dir: &Path, opts: PatternOpts) -> Result<Walker, PatternError> {
     let pattern = Pattern::new_with_opts(pattern, opts)?;
 
     Ok(Walker::new(pattern, dir))
@@ -93,7 +132,7 @@ pub fn glob_in_with(pattern: &str, dir: &Path, opts: PatternOpts) -> Result<Walk
 #[derive(Debug)]
 pub enum GlobError {
     /// The provided pattern is invalid
-    InvalidPattern(ParsingError),
+    InvalidPattern(PatternError),
 
     /// Failed to get path to the current directory
     FailedToGetCurrentDir(std::io::Error),
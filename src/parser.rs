@@ -1,27 +1,350 @@
-use std::{collections::HashSet, sync::LazyLock};
+use std::{
+    borrow::Cow,
+    collections::HashMap,
+    sync::{Mutex, OnceLock, PoisonError},
+};
 
 use parsy::{
-    Parser,
-    helpers::{char, choice, end, filter, just, not, recursive_shared, silent_choice},
+    CodeLocation, CodeRange, FileId, Parser, ParserInput, ParsingError, Span,
+    helpers::{
+        char, choice, dynamic_filter, end, filter, just, not, recursive_shared, silent_choice,
+    },
+};
+
+use crate::{
+    paths::{PathPrefix, WindowsDrive},
+    syntax::{AstComponent, ComponentKind, PatternAst, is_special},
 };
 
-use crate::paths::{PathPrefix, WindowsDrive};
+/// A pattern parser
+type PatternParser = Box<dyn Parser<RawPattern> + Send + Sync>;
+
+/// Parsers for patterns and their individual parts
+struct PatternParsers {
+    /// Parser for whole patterns
+    pattern: PatternParser,
+
+    /// Parser for an inline flag, returning whether the pattern is case-insensitive
+    inline_flag: Box<dyn Parser<bool> + Send + Sync>,
+
+    /// Parser for a prefix, along with the separator following it
+    prefix: Box<dyn Parser<PathPrefix> + Send + Sync>,
+
+    /// Parser for a single component
+    component: Box<dyn Parser<RawComponent> + Send + Sync>,
+}
+
+/// Pattern parsers for each combination of options used so far, built on first use
+///
+/// Parsers are leaked so they can be used without holding the lock, which is fine as there is a bounded number of them.
+static PATTERN_PARSERS: OnceLock<Mutex<HashMap<ParserOpts, &'static PatternParsers>>> =
+    OnceLock::new();
+
+/// Options for parsing patterns
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ParserOpts {
+    /// Make `\` escape the character following it (outside of brackets) instead of being a path separator
+    ///
+    /// See [`crate::PatternOpts::with_backslash_escapes`]
+    pub backslash_escapes: bool,
+
+    /// Make `\` an ordinary character instead of a path separator, unless it escapes characters
+    ///
+    /// See [`crate::PatternOpts::with_literal_backslashes`]
+    pub literal_backslashes: bool,
+
+    /// Parse bracket expressions like the `glob` crate, and make `]` an ordinary character outside of them
+    ///
+    /// See [`crate::PatternOpts::with_glob_compat`]
+    pub glob_compat: bool,
+
+    /// Parse group alternates (e.g. `{a|b}`), otherwise `{`, `}` and `|` are ordinary characters
+    ///
+    /// See [`crate::PatternOpts::with_disable_alternates`]
+    pub alternates: bool,
+
+    /// Parse bracket expressions (e.g. `[abc]`), otherwise `[` and `]` are ordinary characters
+    ///
+    /// See [`crate::PatternOpts::with_disable_brackets`]
+    pub brackets: bool,
+
+    /// Parse `**` components as wildcards, otherwise `**` acts like `*`
+    ///
+    /// See [`crate::PatternOpts::with_disable_globstar`]
+    pub globstar: bool,
+
+    /// Make all characters except separators ordinary, so each component is a literal
+    ///
+    /// See [`crate::PatternOpts::with_literal`]
+    pub literal: bool,
+
+    /// Allow `..` components anywhere, resolving them lexically against the preceding components
+    ///
+    /// See [`crate::PatternOpts::with_normalize_dotdot`]
+    pub normalize_dotdot: bool,
+
+    /// Recognize an inline case sensitivity flag (`(?i)` or `(?-i)`) at the start of the pattern
+    ///
+    /// See [`crate::PatternOpts::with_inline_flags`]
+    pub inline_flags: bool,
+
+    /// Parse extended glob operators (e.g. `+(a|b)` or `!(*.bak)`)
+    ///
+    /// See [`crate::PatternOpts::with_extglob`]
+    pub extglob: bool,
+}
+
+/// Maximum nesting depth of groups
+///
+/// As groups are parsed recursively, deeper patterns could overflow the stack.
+pub const MAX_GROUP_NESTING: usize = 100;
+
+/// Maximum number of values in a numeric range (e.g. `{1..15}`)
+///
+/// Ranges are expanded into group alternates, so larger ones would make huge regular expressions.
+pub const MAX_NUMERIC_RANGE_LEN: usize = 1000;
 
 /// Parse a glob (pattern) string into a [`RawPattern`]
-pub static PATTERN_PARSER: LazyLock<Box<dyn Parser<RawPattern> + Send + Sync>> = LazyLock::new(
-    || {
-        let normal_char = filter(|c| !SPECIAL_CHARS.contains(&c));
+pub fn parse_pattern(input: &str, opts: ParserOpts) -> Result<RawPattern, ParsingError> {
+    check_group_nesting(input, opts)?;
+
+    parsers(opts)
+        .pattern
+        .parse_str(input)
+        .map(|parsed| parsed.data)
+}
+
+/// Check that groups (including extended glob operators) aren't nested more than [`MAX_GROUP_NESTING`] times
+///
+/// This only looks at the characters delimiting groups, without parsing the pattern,
+/// so pathological patterns are rejected before they can overflow the stack.
+fn check_group_nesting(input: &str, opts: ParserOpts) -> Result<(), ParsingError> {
+    if !(opts.alternates || opts.extglob) || opts.literal {
+        return Ok(());
+    }
+
+    let mut depth = 0;
+    let mut in_brackets = false;
+    let mut prev = None;
+    let mut chars = input.char_indices();
+
+    while let Some((offset, c)) = chars.next() {
+        let after = prev.replace(c);
+
+        match c {
+            // Escaped characters are never delimiters
+            '\\' if opts.backslash_escapes || in_brackets => {
+                chars.next();
+            }
+
+            '[' if opts.brackets && !in_brackets => {
+                in_brackets = true;
+
+                // A leading `]` doesn't close the brackets (e.g. in `[]]` or `[!]]`)
+                if input[offset + 1..].starts_with("!]") {
+                    chars.nth(1);
+                } else if input[offset + 1..].starts_with(']') {
+                    chars.next();
+                }
+            }
+
+            ']' if in_brackets => in_brackets = false,
+
+            '{' | '('
+                if !in_brackets
+                    && if c == '{' {
+                        opts.alternates
+                    } else {
+                        opts.extglob && matches!(after, Some('?' | '*' | '+' | '@' | '!'))
+                    } =>
+            {
+                depth += 1;
+
+                if depth > MAX_GROUP_NESTING {
+                    return Err(ParsingError::custom(
+                        CodeRange::new(
+                            CodeLocation {
+                                file_id: FileId::None,
+                                offset,
+                            },
+                            1,
+                        ),
+                        "nested group",
+                    )
+                    .criticalize(format!(
+                        "Groups can't be nested more than {MAX_GROUP_NESTING} times"
+                    )));
+                }
+            }
+
+            '}' if !in_brackets && opts.alternates => depth = usize::saturating_sub(depth, 1),
+            ')' if !in_brackets && opts.extglob => depth = usize::saturating_sub(depth, 1),
+
+            _ => {}
+        }
+    }
+
+    Ok(())
+}
+
+/// Get the parsers for the provided options
+fn parsers(opts: ParserOpts) -> &'static PatternParsers {
+    PATTERN_PARSERS
+        .get_or_init(Mutex::default)
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .entry(opts)
+        .or_insert_with(|| Box::leak(Box::new(build_parsers(opts))))
+}
+
+/// Build the pattern parsers
+fn build_parsers(opts: ParserOpts) -> PatternParsers {
+    let ParserOpts {
+        backslash_escapes,
+        literal_backslashes,
+        glob_compat,
+        alternates,
+        brackets,
+        globstar,
+        literal,
+        normalize_dotdot,
+        inline_flags,
+        extglob,
+    } = opts;
+
+    let is_normal_char = move |c| {
+        if literal {
+            c != '/' && (c != '\\' || backslash_escapes || literal_backslashes)
+        } else if glob_compat {
+            !matches!(c, '/' | '*' | '?')
+                && (c != '[' || !brackets)
+                && (c != '\\' || literal_backslashes)
+        } else {
+            !is_special(c)
+                || (!alternates && matches!(c, '{' | '}' | '|'))
+                || (!brackets && matches!(c, '[' | ']'))
+                || (c == '\\' && literal_backslashes && !backslash_escapes)
+        }
+    };
+
+    let normal_char = dynamic_filter(is_normal_char);
+
+    // Inside brackets, `:` delimits character classes (e.g. `[[:alpha:]]`) and `\` always escapes characters
+    let bracket_char = dynamic_filter(move |c| c != ':' && c != '\\' && is_normal_char(c));
+
+    // Character inside brackets, which may be escaped (e.g. `\]` or `\-`)
+    // Parentheses can be escaped too, as they are special with extended glob operators
+    let bracket_single_char = choice::<char, _>((
+        bracket_char,
+        char('\\').ignore_then(
+            filter(|c| {
+                (is_special(c) || matches!(c, ':' | '-' | '(' | ')')) && c != '/' && c != '\\'
+            })
+            .critical("expected a special character to escape"),
+        ),
+    ));
+
+    // Inside brackets with the `glob` crate's syntax, all characters are ordinary except separators
+    let is_glob_bracket_char = move |c| c != '/' && (c != '\\' || literal_backslashes);
+
+    // Backslash acting as a path separator
+    let backslash_sep =
+        dynamic_filter(move |c| c == '\\' && !backslash_escapes && !literal_backslashes);
+
+    // Escaped character (e.g. `\*`)
+    let escaped_char = dynamic_filter(move |c| c == '\\' && backslash_escapes && !literal)
+        .ignore_then(filter(|_| true).critical("expected a character to escape"));
+
+    // Bound or step of a numeric range (e.g. `{1..15}`)
+    let digits = filter(|c| c.is_ascii_digit())
+        .repeated_into_container::<String>()
+        .at_least(1);
 
-        let chars_matcher = recursive_shared(|chars_matcher| -> _ {
-            choice::<CharsMatcher, _>((
+    // Item of a bracket expression (e.g. `a`, `a-z` or `[:alpha:]`)
+    let bracket_items = choice::<SingleCharMatcher, _>((
+        //
+        // Normal or escaped character, or range of characters (e.g. `a-z`)
+        //
+        bracket_item(bracket_single_char, bracket_single_char),
+        //
+        // Character class
+        //
+        just("[:")
+            .ignore_then(
+                // The whole name is parsed first, so errors point at it
+                filter(|c| c.is_ascii_alphanumeric())
+                    .repeated_into_container::<String>()
+                    .and_then_or_critical(|name| match name.as_str() {
+                        "alpha" => Ok(CharacterClass::Alpha),
+                        "digit" => Ok(CharacterClass::Digit),
+                        "alphanumeric" => Ok(CharacterClass::Alphanumeric),
+                        "uppercase" => Ok(CharacterClass::Uppercase),
+                        "lowercase" => Ok(CharacterClass::Lowercase),
+                        "whitespace" => Ok(CharacterClass::Whitespace),
+                        "xdigit" => Ok(CharacterClass::HexDigit),
+                        "punct" => Ok(CharacterClass::Punctuation),
+                        "blank" => Ok(CharacterClass::Blank),
+                        "cntrl" => Ok(CharacterClass::Control),
+                        "graph" => Ok(CharacterClass::Graphic),
+                        "print" => Ok(CharacterClass::Printable),
+                        _ => Err(Cow::Borrowed(
+                            "expected a valid character class (alpha, digit, alphanumeric, uppercase, \
+                            lowercase, whitespace, xdigit, punct, blank, cntrl, graph or print)",
+                        )),
+                    }),
+            )
+            .then_ignore(just(":]").critical_auto_msg())
+            .map(SingleCharMatcher::Class),
+    ));
+
+    // Start of an extended glob operator that could otherwise be taken for a literal character (e.g. `+(`)
+    let extglob_start =
+        dynamic_filter(move |c| extglob && matches!(c, '+' | '@' | '!')).then(char('('));
+
+    // Inside groups, `,` separates alternates like `|` does, and `)` closes extended glob operators
+    let group_char =
+        dynamic_filter(move |c| c != ',' && (c != ')' || !extglob) && is_normal_char(c));
+
+    let chars_matcher = recursive_shared(move |chars_matcher| -> _ {
+        choice::<CharsMatcher, _>((
             //
             // Literal characters
             //
-            normal_char
+            not(extglob_start)
+                .ignore_then(choice::<char, _>((group_char, escaped_char)))
                 .repeated_into_container::<String>()
                 .at_least(1)
                 .map(CharsMatcher::Literal),
             //
+            // Extended glob operator (e.g. `+(a|b)`)
+            //
+            dynamic_filter(move |c| extglob && matches!(c, '?' | '*' | '+' | '@' | '!'))
+                .then_ignore(char('('))
+                .then(
+                    chars_matcher
+                        .clone()
+                        .spanned()
+                        .repeated_into_vec()
+                        .at_least(1)
+                        .and_then(|matchers| check_negations(matchers, 0))
+                        .separated_by_into_vec(silent_choice((char('|'), char(','))))
+                        .at_least(1)
+                        .critical("expected at least one alternative matcher"),
+                )
+                .then_ignore(char(')').critical_auto_msg())
+                .map(|(op, alternates)| {
+                    let op = match op {
+                        '?' => ExtGlobOperator::ZeroOrOne,
+                        '*' => ExtGlobOperator::ZeroOrMore,
+                        '+' => ExtGlobOperator::OneOrMore,
+                        '@' => ExtGlobOperator::ExactlyOne,
+                        '!' => ExtGlobOperator::Not,
+                        _ => unreachable!(),
+                    };
+
+                    CharsMatcher::ExtGlob(op, alternates)
+                }),
+            //
             // Optional universal character (or not)
             //
             char('?').map(|_| CharsMatcher::AnyChar),
@@ -29,54 +352,64 @@ pub static PATTERN_PARSER: LazyLock<Box<dyn Parser<RawPattern> + Send + Sync>> =
             // Wildcard
             //
             char('*')
-                .followed_by(not(char('*')).critical(
-                    "Wildcard components '**' must be preceded by and followed by a path separator",
-                ))
-                .map(|_| CharsMatcher::AnyChars),
+                // Without globstar, runs of `*` act like a single one
+                .then_ignore(dynamic_filter(move |c| c == '*' && !globstar).repeated())
+                // Checked along with the first `*`, so errors point at the whole `**`
+                .then(char('*').or_not())
+                .and_then_or_critical(|(_, globstar)| match globstar {
+                    None => Ok(CharsMatcher::AnyChars),
+                    Some(_) => Err(Cow::Borrowed(MISPLACED_GLOBSTAR)),
+                }),
             //
             // Character alternates
             //
-            char('[')
+            dynamic_filter(move |c| c == '[' && brackets && !glob_compat)
+                .ignore_then(char('!').or_not())
+                .then(choice::<Vec<SingleCharMatcher>, _>((
+                    // The first character can't close the brackets, so e.g. `[]]` matches `]`
+                    bracket_item(char(']'), bracket_single_char)
+                        .then(bracket_items.repeated_into_vec())
+                        .map(|(first, mut chars)| {
+                            chars.insert(0, first);
+                            chars
+                        }),
+                    bracket_items
+                        .repeated_into_vec()
+                        .at_least(1)
+                        .critical("expected at least one character to match"),
+                )))
+                .then_ignore(char(']').critical_auto_msg())
+                .map(|(neg, chars)| {
+                    if neg.is_some() {
+                        CharsMatcher::NoneOfChars(chars)
+                    } else {
+                        CharsMatcher::OneOfChars(chars)
+                    }
+                }),
+            //
+            // Character alternates, with the `glob` crate's syntax
+            //
+            dynamic_filter(move |c| c == '[' && brackets && glob_compat)
                 .ignore_then(char('!').or_not())
                 .then(
-                    choice::<SingleCharMatcher, _>((
-                        //
-                        // Normal character
-                        //
-                        filter(|c| !SPECIAL_CHARS.contains(&c) && c != '/' && c != '\\').map(SingleCharMatcher::Literal),
-                        //
-                        // Escaped character
-                        //
-                        char('\\')
-                            .ignore_then(
-                                filter(|c| SPECIAL_CHARS.contains(&c) && c != '/' && c != '\\')
-                                    .critical("expected a special character to escape"),
-                            )
-                            .map(SingleCharMatcher::Literal),
-                        //
-                        // Character class
-                        //
-                        just("[:")
-                            .ignore_then(
-                                choice::<CharacterClass, _>((
-                                    just("alpha").to(CharacterClass::Alpha),
-                                    just("digit").to(CharacterClass::Digit),
-                                    just("alphanumeric").to(CharacterClass::Alphanumeric),
-                                    just("uppercase").to(CharacterClass::Uppercase),
-                                    just("lowercase").to(CharacterClass::Lowercase),
-                                    just("whitespace").to(CharacterClass::Whitespace),
-                                ))
-                                .critical("expected a valid character class"),
-                            )
-                            .then_ignore(just(":]").critical_auto_msg())
-                            .map(SingleCharMatcher::Class)
-                    ))
-                    .repeated_into_vec()
-                    .at_least(1)
-                    .critical("expected at least one character to match"),
+                    // The first character can't close the brackets, so e.g. `[]]` matches `]`
+                    bracket_item(
+                        dynamic_filter(is_glob_bracket_char),
+                        dynamic_filter(move |c| c != ']' && is_glob_bracket_char(c)),
+                    )
+                    .critical("expected at least one character to match")
+                    .then(
+                        bracket_item(
+                            dynamic_filter(move |c| c != ']' && is_glob_bracket_char(c)),
+                            dynamic_filter(move |c| c != ']' && is_glob_bracket_char(c)),
+                        )
+                        .repeated_into_vec(),
+                    ),
                 )
                 .then_ignore(char(']').critical_auto_msg())
-                .map(|(neg, chars)| {
+                .map(|(neg, (first, mut chars))| {
+                    chars.insert(0, first);
+
                     if neg.is_some() {
                         CharsMatcher::NoneOfChars(chars)
                     } else {
@@ -84,134 +417,507 @@ pub static PATTERN_PARSER: LazyLock<Box<dyn Parser<RawPattern> + Send + Sync>> =
                     }
                 }),
             //
+            // Numeric range (e.g. `{1..15}` or `{00..30..5}`)
+            //
+            dynamic_filter(move |c| c == '{' && alternates)
+                .ignore_then(digits)
+                .then_ignore(just(".."))
+                .then(digits)
+                .then(just("..").ignore_then(digits).or_not())
+                .then_ignore(char('}'))
+                .and_then_or_critical(|((start, end), step)| {
+                    expand_numeric_range(&start, &end, step.as_deref())
+                })
+                .map(CharsMatcher::OneOfGroups),
+            //
             // Group alternates
             //
-            char('{')
+            dynamic_filter(move |c| c == '{' && alternates)
                 .ignore_then(
                     chars_matcher
+                        .spanned()
                         .repeated_into_vec()
                         .at_least(1)
-                        .separated_by_into_vec(char('|'))
+                        .and_then(|matchers| check_negations(matchers, 0))
+                        .separated_by_into_vec(silent_choice((char('|'), char(','))))
                         .at_least(2)
                         .critical("expected at least 2 alternative matchers"),
                 )
                 .then_ignore(char('}').critical_auto_msg())
                 .map(CharsMatcher::OneOfGroups),
         ))
-        });
+    });
 
-        let dir_sep = silent_choice((char('/'), char('\\')));
+    // Outside of groups, `,` is an ordinary character
+    let chars_matcher = choice::<CharsMatcher, _>((
+        not(extglob_start)
+            .ignore_then(choice::<char, _>((normal_char, escaped_char)))
+            .repeated_into_container::<String>()
+            .at_least(1)
+            .map(CharsMatcher::Literal),
+        chars_matcher,
+    ));
 
-        let component = choice::<RawComponent, _>((
-            //
-            // Wildcard
-            //
-            just("**")
-                .followed_by(silent_choice((dir_sep, end())).critical(
-                    "Wildcard components '**' must be preceded and followed by path separators",
-                ))
-                .map(|_| RawComponent::Wildcard),
-            //
-            // Character matchers
-            //
-            chars_matcher
-                .repeated_into_vec()
-                .map(|matchers| match matchers.as_slice() {
-                    [] => RawComponent::Literal(String::new()),
-                    [CharsMatcher::Literal(lit)] => {
-                        if lit == ".." {
-                            RawComponent::ParentDir
-                        } else {
-                            RawComponent::Literal(lit.to_owned())
-                        }
+    let dir_sep = silent_choice((char('/'), backslash_sep));
+
+    let component = choice::<RawComponent, _>((
+        //
+        // Wildcard
+        //
+        just("**")
+            .validate(move |_| !literal && globstar)
+            // Checked along with the `**` itself, so errors point at it
+            .then(not(silent_choice((dir_sep, end()))).or_not())
+            .and_then_or_critical(|(_, unterminated)| match unterminated {
+                None => Ok(RawComponent::Wildcard),
+                Some(()) => Err(Cow::Borrowed(MISPLACED_GLOBSTAR)),
+            }),
+        //
+        // Character matchers
+        //
+        chars_matcher
+            .spanned()
+            .repeated_into_vec()
+            .and_then(|matchers| check_negations(matchers, 1))
+            .map(|matchers| match matchers.as_slice() {
+                [] => RawComponent::Literal(String::new()),
+                [CharsMatcher::Literal(lit)] => {
+                    if lit == ".." {
+                        RawComponent::ParentDir
+                    } else {
+                        RawComponent::Literal(lit.to_owned())
                     }
-                    _ => RawComponent::Suite(matchers),
-                }),
-        ));
+                }
+                _ => RawComponent::Suite(matchers),
+            }),
+    ));
 
-        let windows_driver_letter = filter(|c| c.is_ascii_alphabetic())
-            .map(|c| WindowsDrive::try_from(c).unwrap())
-            .then_ignore(char(':'));
+    let windows_driver_letter = filter(|c| c.is_ascii_alphabetic())
+        .map(|c| WindowsDrive::try_from(c).unwrap())
+        .then_ignore(char(':'));
 
-        // let windows_server_share = alphanumeric()
-        //     .repeated()
-        //     .then(char('\\'))
-        //     .then(alphanumeric().repeated());
+    // let windows_server_share = alphanumeric()
+    //     .repeated()
+    //     .then(char('\\'))
+    //     .then(alphanumeric().repeated());
 
-        let prefix_suffix = silent_choice((dir_sep, end())).critical(
-            "Expected either a path separator or a directory separator after path prefix",
-        );
+    let prefix_suffix = silent_choice((dir_sep, end()))
+        .critical("Expected either a path separator or a directory separator after path prefix");
 
-        let prefix =
-            choice::<PathPrefix, _>((
-                //
-                // Drive letter (e.g. `C:`)
-                //
+    let prefix = choice::<PathPrefix, _>((
+        //
+        // Drive letter (e.g. `C:`)
+        //
+        windows_driver_letter
+            .map(PathPrefix::WindowsDrive)
+            .then_ignore(prefix_suffix),
+        //
+        // Verbatim followed by drive letter (e.g. `\\?\C:`)
+        //
+        backslash_sep
+            .then(backslash_sep)
+            .then(char('?').critical("Expected '?' symbol for verbatim path after '\\\\'"))
+            .then(
+                char('\\')
+                    .critical("Expected directory separator '\\' after verbatim prefix '\\\\?'"),
+            )
+            .ignore_then(
                 windows_driver_letter
-                    .map(PathPrefix::WindowsDrive)
-                    .then_ignore(prefix_suffix),
-                //
-                // Verbatim followed by drive letter (e.g. `\\?\C:`)
-                //
-                just("\\\\")
-                    .then(char('?').critical("Expected '?' symbol for verbatim path after '\\\\'"))
-                    .then(char('\\').critical(
-                        "Expected directory separator '\\' after verbatim prefix '\\\\?'",
-                    ))
-                    .ignore_then(windows_driver_letter.critical(
-                        "Expected a Windows drive letter after verbatimc prefix '\\\\?\\'",
-                    ))
-                    .map(PathPrefix::WindowsDrive)
-                    .then_ignore(prefix_suffix),
-                //
-                // Root dir (e.g. `/` or `\`)
-                //
-                dir_sep
-                    .followed_by(not(char('\\')).critical("Invalid verbatim path"))
-                    .map(|_| PathPrefix::RootDir),
-            ));
-
-        let pattern = prefix.or_not().then(component.separated_by_into_vec(dir_sep))
-            .validate_or_dynamic_critical(|(prefix, components)| {
-                let mut passed_parent = false;
-
-                for component in components {
-                    if !matches!(component, RawComponent::ParentDir) {
-                        passed_parent = true;
-                        continue;
-                    }
+                    .critical("Expected a Windows drive letter after verbatimc prefix '\\\\?\\'"),
+            )
+            .map(PathPrefix::WindowsDrive)
+            .then_ignore(prefix_suffix),
+        //
+        // Root dir (e.g. `/` or `\`)
+        //
+        dir_sep
+            .followed_by(not(backslash_sep).critical("Invalid verbatim path"))
+            .map(|_| PathPrefix::RootDir),
+    ));
 
-                    if prefix.is_some() {
-                        return Err("Cannot use '..' components in absolute path patterns".into());
-                    }
+    // Inline case sensitivity flag (e.g. `(?i)`)
+    let inline_flag = choice::<bool, _>((just("(?i)").to(true), just("(?-i)").to(false)))
+        .validate(move |_| inline_flags);
 
-                    if passed_parent {
-                        return Err("Cannot use '..' components after the beginning of the pattern".into());
-                    }
-                }
+    let pattern = inline_flag.or_not().then(prefix.or_not()).then(component.clone().spanned().separated_by_into_vec(dir_sep))
+            .validate_or_dynamic_critical(move |((_, prefix), components)| {
+                let roles = components.iter().map(|component| DotDotRole::of(&component.data));
 
-                Ok(())
+                match misplaced_parent_dirs(prefix.is_some(), normalize_dotdot, roles).next() {
+                    Some((_, reason)) => Err(reason.into()),
+                    None => Ok(()),
+                }
             })
-            .map(|(prefix, components)| RawPattern {
-                prefix,
-                components: components.into_iter().filter(|component| !matches!(component, RawComponent::Literal(str) if str.is_empty() || str == ".")).collect(),
+            .map(move |((case_insensitive, prefix), components)| {
+                let has_separator = prefix.is_some() || components.len() > 1;
+
+                // A trailing separator results in a last empty component
+                let dir_only = components.len() > 1 && matches!(components.last(), Some(Span { data: RawComponent::Literal(str), .. }) if str.is_empty());
+
+                let components = components.into_iter().filter(|component| DotDotRole::of(&component.data) != DotDotRole::Ignored);
+
+                RawPattern {
+                    case_insensitive,
+                    has_separator,
+                    prefix,
+                    dir_only,
+                    components: if normalize_dotdot { resolve_parent_dirs(components) } else { components.collect() },
+                }
             });
 
-        Box::new(pattern.full())
-    },
-);
+    PatternParsers {
+        pattern: Box::new(pattern.full()),
+        inline_flag: Box::new(inline_flag),
+        prefix: Box::new(prefix),
+        component: Box::new(component),
+    }
+}
+
+/// Parse a character or a range of characters (e.g. `a-z`) inside brackets
+///
+/// `start` and `end` parse the characters which may respectively start and end a range.
+/// A `-` that isn't followed by a character ending a range is an ordinary character, e.g. in `[a-]`.
+fn bracket_item(
+    start: impl Parser<char> + Copy,
+    end: impl Parser<char> + Copy,
+) -> impl Parser<SingleCharMatcher> + Copy {
+    start
+        .then(char('-').ignore_then(end).or_not())
+        .validate_or_critical(
+            |(start, end)| end.is_none_or(|end| *start <= end),
+            "character ranges must not be reversed",
+        )
+        .map(|(start, end)| match end {
+            Some(end) => SingleCharMatcher::Range(start, end),
+            None => SingleCharMatcher::Literal(start),
+        })
+}
 
-/// List of special characters that must be escaped in order to be matched against
-static SPECIAL_CHARS: LazyLock<HashSet<char>> =
-    LazyLock::new(|| HashSet::from(['[', ']', '{', '}', '*', '?', '\\', '/', '|', ':']));
+/// Expand a numeric range (e.g. `{1..15}`) into group alternates
+///
+/// If either bound is zero-padded (e.g. `{01..12}`), all numbers are padded to the width of the widest bound.
+fn expand_numeric_range(
+    start: &str,
+    end: &str,
+    step: Option<&str>,
+) -> Result<Vec<Vec<CharsMatcher>>, Cow<'static, str>> {
+    let parse = |bound: &str| {
+        bound
+            .parse::<u64>()
+            .map_err(|_| Cow::Borrowed("numeric range bounds are too large"))
+    };
+
+    let (first, last) = (parse(start)?, parse(end)?);
+    let step = step.map_or(Ok(1), parse)?;
+
+    if first > last {
+        return Err("numeric ranges must not be reversed".into());
+    }
+
+    if step == 0 {
+        return Err("numeric range steps must not be zero".into());
+    }
+
+    if (last - first) / step >= MAX_NUMERIC_RANGE_LEN as u64 {
+        return Err(format!(
+            "numeric ranges can't contain more than {MAX_NUMERIC_RANGE_LEN} values"
+        )
+        .into());
+    }
+
+    let is_padded = |bound: &str| bound.len() > 1 && bound.starts_with('0');
+
+    let width = if is_padded(start) || is_padded(end) {
+        start.len().max(end.len())
+    } else {
+        0
+    };
+
+    Ok((first..=last)
+        .step_by(usize::try_from(step).unwrap_or(usize::MAX))
+        .map(|value| vec![CharsMatcher::Literal(format!("{value:0width$}"))])
+        .collect())
+}
+
+/// (Internal) Role of a component when checking and resolving `..` components
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DotDotRole {
+    /// A `..` component
+    ParentDir,
+
+    /// A `**` component
+    Wildcard,
+
+    /// An empty or `.` component, which is dropped from the pattern
+    Ignored,
+
+    /// Any other component
+    Other,
+}
+
+impl DotDotRole {
+    fn of(component: &RawComponent) -> Self {
+        match component {
+            RawComponent::ParentDir => Self::ParentDir,
+            RawComponent::Wildcard => Self::Wildcard,
+            RawComponent::Literal(lit) if lit.is_empty() || lit == "." => Self::Ignored,
+            RawComponent::Literal(_) | RawComponent::Suite(_) => Self::Other,
+        }
+    }
+}
+
+/// Find the `..` components that aren't allowed
+///
+/// Takes the role of each component, and yields the index of misplaced ones along with the reason.
+///
+/// By default, `..` components can only be used at the beginning of relative patterns.
+/// When they are resolved lexically (see [`ParserOpts::normalize_dotdot`]), they can be used anywhere,
+/// as long as they don't follow a `**` component nor go above the root directory of absolute patterns.
+fn misplaced_parent_dirs(
+    absolute: bool,
+    normalize_dotdot: bool,
+    roles: impl IntoIterator<Item = DotDotRole>,
+) -> impl Iterator<Item = (usize, &'static str)> {
+    let mut passed_parent = false;
+    let mut stack = vec![];
+
+    roles.into_iter().enumerate().filter_map(move |(i, role)| {
+        if normalize_dotdot {
+            return match (role, stack.last()) {
+                (DotDotRole::Ignored, _) => None,
+
+                (DotDotRole::ParentDir, Some(DotDotRole::Other)) => {
+                    stack.pop();
+                    None
+                }
+
+                (DotDotRole::ParentDir, Some(DotDotRole::Wildcard)) => Some((
+                    i,
+                    "Cannot use '..' components right after a wildcard component '**'",
+                )),
+
+                (DotDotRole::ParentDir, _) if absolute => Some((
+                    i,
+                    "Cannot use '..' components to go above the root directory",
+                )),
+
+                _ => {
+                    stack.push(role);
+                    None
+                }
+            };
+        }
+
+        if role != DotDotRole::ParentDir {
+            passed_parent = true;
+            None
+        } else if absolute {
+            Some((i, "Cannot use '..' components in absolute path patterns"))
+        } else if passed_parent {
+            Some((
+                i,
+                "Cannot use '..' components after the beginning of the pattern",
+            ))
+        } else {
+            None
+        }
+    })
+}
+
+/// Resolve `..` components lexically against the preceding components, e.g. `a/../b` into `b`
+///
+/// Components are expected to have been checked with [`misplaced_parent_dirs`] beforehand.
+fn resolve_parent_dirs(
+    components: impl IntoIterator<Item = Span<RawComponent>>,
+) -> Vec<Span<RawComponent>> {
+    let mut resolved: Vec<Span<RawComponent>> = vec![];
+
+    for component in components {
+        match (&component.data, resolved.last()) {
+            (RawComponent::ParentDir, Some(prev))
+                if !matches!(prev.data, RawComponent::ParentDir) =>
+            {
+                resolved.pop();
+            }
+
+            _ => resolved.push(component),
+        }
+    }
+
+    resolved
+}
+
+/// Parse a pattern, recovering from errors to find out as much of its structure as possible
+///
+/// Invalid components are skipped up to the next separator, and all errors are collected.
+/// The structure is only returned if the pattern's prefix is valid, as components can't be interpreted otherwise.
+pub fn parse_recovering(input: &str, opts: ParserOpts) -> (Option<PatternAst>, Vec<ParsingError>) {
+    if let Err(err) = check_group_nesting(input, opts) {
+        return (None, vec![err]);
+    }
+
+    let PatternParsers {
+        pattern: _,
+        inline_flag: inline_flag_parser,
+        prefix: prefix_parser,
+        component: component_parser,
+    } = parsers(opts);
+
+    let is_dir_sep =
+        |c: char| c == '/' || (c == '\\' && !opts.backslash_escapes && !opts.literal_backslashes);
+
+    // Skip everything up to the next separator (excluded)
+    let skip_component = |input: &mut ParserInput| {
+        while let Some(c) = input.inner().chars().next() {
+            if is_dir_sep(c) {
+                break;
+            }
+
+            input.try_eat_char();
+
+            // Escaped characters (e.g. `\/`) are part of the component
+            if c == '\\' && opts.backslash_escapes && !opts.literal {
+                input.try_eat_char();
+            }
+        }
+    };
+
+    let mut input = ParserInput::new(input, FileId::None);
+    let mut errors = vec![];
+
+    let case_insensitive = inline_flag_parser
+        .parse(&mut input)
+        .ok()
+        .map(|flag| flag.data);
+
+    let prefix = match prefix_parser.parse(&mut input) {
+        Ok(prefix) => Some(Some(prefix.data)),
+        Err(err) if err.is_critical() => {
+            errors.push(err);
+            skip_component(&mut input);
+
+            // Consume the separator following the invalid prefix
+            if input.try_eat_char().is_none() {
+                return (None, errors);
+            }
+
+            None
+        }
+        Err(_) => Some(None),
+    };
+
+    let mut components = vec![];
+
+    loop {
+        let start = input.offset();
+
+        let kind = match component_parser.parse(&mut input) {
+            Ok(component) => match input.inner().chars().next() {
+                // Components must be followed by a separator
+                Some(c) if !is_dir_sep(c) => {
+                    errors.push(
+                        ParsingError::custom(input.range(c.len_utf8()), "unexpected character")
+                            .criticalize(format!("Unexpected character '{c}'")),
+                    );
+
+                    skip_component(&mut input);
+                    ComponentKind::Invalid
+                }
+
+                _ => match component.data {
+                    RawComponent::Literal(lit) => ComponentKind::Literal(lit),
+                    RawComponent::Suite(matchers) => ComponentKind::Matchers(matchers),
+                    RawComponent::ParentDir => ComponentKind::ParentDir,
+                    RawComponent::Wildcard => ComponentKind::Wildcard,
+                },
+            },
+
+            Err(err) => {
+                errors.push(err);
+                skip_component(&mut input);
+                ComponentKind::Invalid
+            }
+        };
+
+        components.push(AstComponent {
+            span: start..input.offset(),
+            kind,
+        });
+
+        // Consume the separator, if any
+        if input.try_eat_char().is_none() {
+            break;
+        }
+    }
+
+    let roles = components.iter().map(|component| match &component.kind {
+        ComponentKind::ParentDir => DotDotRole::ParentDir,
+        ComponentKind::Wildcard => DotDotRole::Wildcard,
+        ComponentKind::Literal(lit) if lit.is_empty() || lit == "." => DotDotRole::Ignored,
+        ComponentKind::Literal(_) | ComponentKind::Matchers(_) | ComponentKind::Invalid => {
+            DotDotRole::Other
+        }
+    });
+
+    for (i, reason) in
+        misplaced_parent_dirs(prefix.flatten().is_some(), opts.normalize_dotdot, roles)
+            .collect::<Vec<_>>()
+    {
+        let component = &mut components[i];
+
+        errors.push(
+            ParsingError::custom(
+                CodeRange::new(
+                    CodeLocation {
+                        file_id: FileId::None,
+                        offset: component.span.start,
+                    },
+                    component.span.len(),
+                ),
+                "misplaced parent directory",
+            )
+            .criticalize(reason),
+        );
+
+        component.kind = ComponentKind::Invalid;
+    }
+
+    // A trailing separator results in a last empty component
+    let dir_only = components.len() > 1
+        && matches!(components.last(), Some(AstComponent { kind: ComponentKind::Literal(lit), .. }) if lit.is_empty());
+
+    components.retain(|component| !matches!(&component.kind, ComponentKind::Literal(lit) if lit.is_empty() || lit == "."));
+
+    errors.sort_by_key(|err| err.inner().at().start.offset);
+
+    let ast = prefix.map(|prefix| PatternAst {
+        case_insensitive,
+        prefix,
+        components,
+        dir_only,
+    });
+
+    (ast, errors)
+}
 
 /// A parsed raw pattern
 ///
 /// This is intended to be compiled using the [`crate::compiler`] module to improve performance during matching.
 #[derive(Debug)]
 pub struct RawPattern {
+    /// Case sensitivity requested by an inline flag (e.g. `(?i)`), if any
+    pub case_insensitive: Option<bool>,
+
     pub prefix: Option<PathPrefix>,
-    pub components: Vec<RawComponent>,
+
+    /// Does the pattern end with a separator, meaning it only matches directories?
+    pub dir_only: bool,
+
+    /// Does the pattern contain any separator (including in its prefix)?
+    pub has_separator: bool,
+
+    /// Components, along with their location in the pattern's source
+    pub components: Vec<Span<RawComponent>>,
 }
 
 #[derive(Debug)]
@@ -229,7 +935,156 @@ pub enum RawComponent {
     Wildcard,
 }
 
-#[derive(Debug)]
+impl RawComponent {
+    /// Get the matchers of a component that can only match a fixed set of strings
+    ///
+    /// The returned matchers are only made of literals and groups of such suites of matchers.
+    pub fn literal_matchers(&self) -> Option<Vec<CharsMatcher>> {
+        match self {
+            RawComponent::Literal(lit) => Some(vec![CharsMatcher::Literal(lit.clone())]),
+            RawComponent::ParentDir => Some(vec![CharsMatcher::Literal("..".to_owned())]),
+            RawComponent::Suite(matchers) => is_literal_suite(matchers).then(|| matchers.clone()),
+            RawComponent::Wildcard => None,
+        }
+    }
+
+    /// Get the complexity of the component, i.e. its number of matchers, including the ones nested in groups
+    ///
+    /// See [`crate::PatternOpts::with_max_complexity`]
+    pub fn complexity(&self) -> usize {
+        match self {
+            RawComponent::Literal(_) | RawComponent::ParentDir | RawComponent::Wildcard => 1,
+            RawComponent::Suite(matchers) => suite_complexity(matchers),
+        }
+    }
+
+    /// Transform the component's literals, including the ones nested in groups
+    ///
+    /// Literal strings are replaced with the result of `map_str`, and characters inside brackets
+    /// with the result of `map_char`, unless they return [`None`].
+    pub fn map_literals(
+        &mut self,
+        map_str: &impl Fn(&str) -> Option<String>,
+        map_char: &impl Fn(char) -> Option<char>,
+    ) {
+        match self {
+            RawComponent::Literal(lit) => {
+                if let Some(mapped) = map_str(lit) {
+                    *lit = mapped;
+                }
+            }
+            RawComponent::Suite(matchers) => map_suite_literals(matchers, map_str, map_char),
+            RawComponent::ParentDir | RawComponent::Wildcard => {}
+        }
+    }
+}
+
+/// Transform the literals of a suite of matchers (see [`RawComponent::map_literals`])
+fn map_suite_literals(
+    matchers: &mut [CharsMatcher],
+    map_str: &impl Fn(&str) -> Option<String>,
+    map_char: &impl Fn(char) -> Option<char>,
+) {
+    for matcher in matchers {
+        match matcher {
+            CharsMatcher::Literal(lit) => {
+                if let Some(mapped) = map_str(lit) {
+                    *lit = mapped;
+                }
+            }
+
+            CharsMatcher::OneOfChars(single_char_matchers)
+            | CharsMatcher::NoneOfChars(single_char_matchers) => {
+                for matcher in single_char_matchers {
+                    if let SingleCharMatcher::Literal(c) = matcher
+                        && let Some(mapped) = map_char(*c)
+                    {
+                        *c = mapped;
+                    }
+                }
+            }
+
+            CharsMatcher::OneOfGroups(groups) | CharsMatcher::ExtGlob(_, groups) => {
+                for group in groups {
+                    map_suite_literals(group, map_str, map_char);
+                }
+            }
+
+            CharsMatcher::AnyChar | CharsMatcher::AnyChars => {}
+        }
+    }
+}
+
+/// Get the complexity of a suite of matchers (see [`RawComponent::complexity`])
+fn suite_complexity(matchers: &[CharsMatcher]) -> usize {
+    matchers
+        .iter()
+        .map(|matcher| match matcher {
+            CharsMatcher::OneOfGroups(groups) | CharsMatcher::ExtGlob(_, groups) => {
+                1 + groups
+                    .iter()
+                    .map(|group| suite_complexity(group))
+                    .sum::<usize>()
+            }
+
+            CharsMatcher::Literal(_)
+            | CharsMatcher::AnyChar
+            | CharsMatcher::AnyChars
+            | CharsMatcher::OneOfChars(_)
+            | CharsMatcher::NoneOfChars(_) => 1,
+        })
+        .sum()
+}
+
+/// Ensure a suite of matchers contains at most `allowed` `!(...)` operators, pointing at the first extra one otherwise
+///
+/// Negations are matched outside of regular expressions, which can't express them (see [`crate::PatternOpts::with_extglob`]),
+/// so they can only be used once per component and not inside groups. Nested suites are checked when they are parsed,
+/// so only the suite's own matchers need to be checked.
+fn check_negations(
+    matchers: Vec<Span<CharsMatcher>>,
+    allowed: usize,
+) -> Result<Vec<CharsMatcher>, ParsingError> {
+    let extra = matchers
+        .iter()
+        .filter(|matcher| matches!(matcher.data, CharsMatcher::ExtGlob(ExtGlobOperator::Not, _)))
+        .nth(allowed);
+
+    match extra {
+        // Point at the `!(` itself
+        Some(matcher) => Err(ParsingError::custom(
+            CodeRange::new(matcher.at.start, 2),
+            MISPLACED_NEGATION,
+        )
+        .criticalize(MISPLACED_NEGATION)),
+
+        None => Ok(matchers.into_iter().map(|matcher| matcher.data).collect()),
+    }
+}
+
+/// Error message for `**` wildcards that aren't whole components
+const MISPLACED_GLOBSTAR: &str =
+    "Wildcard components '**' must be preceded and followed by path separators";
+
+/// Error message for misplaced `!(...)` operators (see [`check_negations`])
+const MISPLACED_NEGATION: &str =
+    "'!(...)' can only be used once per component, outside of groups and other operators";
+
+/// Check if a suite of matchers is only made of literals and groups of such suites
+fn is_literal_suite(matchers: &[CharsMatcher]) -> bool {
+    matchers.iter().all(|matcher| match matcher {
+        CharsMatcher::Literal(_) => true,
+        CharsMatcher::OneOfGroups(groups) => groups.iter().all(|group| is_literal_suite(group)),
+        CharsMatcher::AnyChar
+        | CharsMatcher::AnyChars
+        | CharsMatcher::OneOfChars(_)
+        | CharsMatcher::NoneOfChars(_)
+        | CharsMatcher::ExtGlob(_, _) => false,
+    })
+}
+
+/// Matcher of a suite of characters inside a component
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum CharsMatcher {
     /// Match any single character
     AnyChar,
@@ -248,18 +1103,47 @@ pub enum CharsMatcher {
 
     /// Match one of suites of character matchers
     OneOfGroups(Vec<Vec<CharsMatcher>>),
+
+    /// Match suites of character matchers as specified by an extended glob operator (e.g. `+(a|b)`)
+    ///
+    /// See [`crate::PatternOpts::with_extglob`]
+    ExtGlob(ExtGlobOperator, Vec<Vec<CharsMatcher>>),
+}
+
+/// Extended glob operator (see [`CharsMatcher::ExtGlob`])
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExtGlobOperator {
+    /// Match zero or one occurrence of the alternatives (`?(...)`)
+    ZeroOrOne,
+
+    /// Match zero or more occurrences of the alternatives (`*(...)`)
+    ZeroOrMore,
+
+    /// Match one or more occurrences of the alternatives (`+(...)`)
+    OneOrMore,
+
+    /// Match exactly one of the alternatives (`@(...)`)
+    ExactlyOne,
+
+    /// Match anything except the alternatives (`!(...)`)
+    Not,
 }
 
-#[derive(Debug, Clone, Copy)]
+/// Matcher of a single character inside brackets (e.g. `[a[:digit:]]`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum SingleCharMatcher {
     /// Match a specific character
     Literal(char),
 
+    /// Match a character between two others, inclusive (e.g. `a-z`)
+    Range(char, char),
+
     /// Match a character using a given character class
     Class(CharacterClass),
 }
 
-#[derive(Debug, Clone, Copy)]
+/// Character class (e.g. `[:alpha:]`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum CharacterClass {
     /// Alphabetic characters
     Alpha,
@@ -278,4 +1162,22 @@ pub enum CharacterClass {
 
     /// Whitespace characters
     Whitespace,
+
+    /// Hexadecimal digits
+    HexDigit,
+
+    /// ASCII punctuation characters (e.g. `!`, `-` or `~`)
+    Punctuation,
+
+    /// Spaces and tabs
+    Blank,
+
+    /// ASCII control characters
+    Control,
+
+    /// Visible ASCII characters, i.e. excluding spaces and control characters
+    Graphic,
+
+    /// Printable ASCII characters, i.e. visible ones and spaces
+    Printable,
 }
@@ -1,5 +1,6 @@
 use std::{
     ffi::OsString,
+    ops::Range,
     path::{MAIN_SEPARATOR_STR, Path, PathBuf},
 };
 
@@ -9,13 +10,21 @@ use crate::opaque_os_str::OpaqueOsStr;
 ///
 /// * The prefix is guaranteed to be supported by this crate
 /// * All `.` and empty components have been removed
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct NormalizedPath {
     prefix: Option<PathPrefix>,
     components: Vec<OsString>,
 }
 
 impl NormalizedPath {
+    /// Create an empty path with the provided prefix
+    pub fn new(prefix: Option<PathPrefix>) -> Self {
+        Self {
+            prefix,
+            components: vec![],
+        }
+    }
+
     pub fn prefix(&self) -> Option<PathPrefix> {
         self.prefix
     }
@@ -24,6 +33,28 @@ impl NormalizedPath {
         &self.components
     }
 
+    /// Append a component to the path
+    ///
+    /// Parent directory (`..`) components are resolved lexically by removing the last component if any.
+    /// Otherwise they are kept as-is for relative paths, and discarded for absolute ones.
+    pub fn push(&mut self, component: OsString) {
+        if component != ".." {
+            self.components.push(component);
+            return;
+        }
+
+        match self.components.last() {
+            Some(last) if last != ".." => {
+                self.components.pop();
+            }
+            _ => {
+                if self.prefix.is_none() {
+                    self.components.push(component);
+                }
+            }
+        }
+    }
+
     pub fn to_path_buf(&self) -> PathBuf {
         let Self { prefix, components } = self;
 
@@ -35,7 +66,13 @@ impl NormalizedPath {
                     windows_drive.uppercase_letter(),
                 )),
             },
-            None => PathBuf::new(),
+            None => match components.first() {
+                // Ensure a first component looking like a drive (e.g. `C:`) isn't mistaken for one
+                Some(first) if strip_windows_drive(OpaqueOsStr::new(first)).is_some() => {
+                    PathBuf::from(".")
+                }
+                _ => PathBuf::new(),
+            },
         };
 
         for component in components {
@@ -81,15 +118,74 @@ impl TryFrom<char> for WindowsDrive {
 #[derive(Debug, Clone, Copy)]
 pub struct InvalidWindowsDriveLetter;
 
+/// Options for path normalization
+///
+/// When matching, these are derived from the pattern's options (see [`crate::PatternOpts`]),
+/// so that paths are normalized consistently with the pattern itself.
+///
+/// Options are set using chained setters, e.g. `NormalizeOpts::new().with_slash_only(true)`,
+/// and can be read back with the corresponding getters (e.g. [`NormalizeOpts::slash_only`]).
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct NormalizeOpts {
+    /// Only treat `/` as a path separator (see [`NormalizeOpts::with_slash_only`])
+    slash_only: bool,
+}
+
+impl NormalizeOpts {
+    /// Create options with their default values
+    pub const fn new() -> Self {
+        Self { slash_only: false }
+    }
+
+    /// Only treat `/` as a path separator, making `\` an ordinary character
+    ///
+    /// This also disables the `\` root and `\\?\` verbatim prefixes.
+    ///
+    /// Disabled by default
+    pub const fn with_slash_only(mut self, slash_only: bool) -> Self {
+        self.slash_only = slash_only;
+        self
+    }
+
+    /// Check if [`NormalizeOpts::with_slash_only`] is enabled
+    pub fn slash_only(&self) -> bool {
+        self.slash_only
+    }
+
+    /// Check if a byte is a path separator
+    fn is_separator(&self, byte: u8) -> bool {
+        byte == b'/' || (byte == b'\\' && !self.slash_only)
+    }
+}
+
 /// Normalize a path
 ///
 /// * Extracts the prefix (root directory and `C:\`, `\\?\\C:\` syntaxes)
 /// * Detects unsupported prefixes (e.g. `\\?\server\share`, `\\?\UNC\`, `\\.\device`)
 /// * Removes empty and `.` components
+///
+/// Equivalent to [`normalize_path_with`] with the default options
 pub fn normalize_path(path: &Path) -> Result<NormalizedPath, UnsupportedWindowsPrefix> {
+    normalize_path_with(path, NormalizeOpts::default())
+}
+
+/// Normalize a path with the provided options
+///
+/// See [`normalize_path`]
+pub fn normalize_path_with(
+    path: &Path,
+    opts: NormalizeOpts,
+) -> Result<NormalizedPath, UnsupportedWindowsPrefix> {
     let path = OpaqueOsStr::new(path.as_os_str());
 
-    let (prefix, path) = if let Some(path) = path.strip_prefix(b"\\\\") {
+    let verbatim = if opts.slash_only {
+        None
+    } else {
+        path.strip_prefix(b"\\\\")
+    };
+
+    let (prefix, path) = if let Some(path) = verbatim {
         let path = path.strip_prefix(b"?\\").ok_or(UnsupportedWindowsPrefix)?;
 
         // Expect and extract drive letter
@@ -105,16 +201,9 @@ pub fn normalize_path(path: &Path) -> Result<NormalizedPath, UnsupportedWindowsP
             },
         )
     } else if let Some((windows_drive, path)) = strip_windows_drive(path.borrow()) {
-        (
-            Some(PathPrefix::WindowsDrive(windows_drive)),
-            // Check for directory separator or end of path
-            if path.is_empty() {
-                path.to_static()
-            } else {
-                path.try_strip_prefixes([b'/', b'\\']).to_static()
-            },
-        )
-    } else if let Some(path) = path.strip_prefix(b'/').or_else(|| path.strip_prefix(b'\\')) {
+        // The separator following the drive (if any) results in an empty component, which is removed below
+        (Some(PathPrefix::WindowsDrive(windows_drive)), path)
+    } else if let Some(path) = path.strip_prefix(|c| opts.is_separator(c)) {
         (Some(PathPrefix::RootDir), path)
     } else {
         (None, path)
@@ -122,7 +211,7 @@ pub fn normalize_path(path: &Path) -> Result<NormalizedPath, UnsupportedWindowsP
 
     let mut components: Vec<OsString> = vec![];
 
-    for component in path.split(|c| c == b'/' || c == b'\\') {
+    for component in path.split(|c| opts.is_separator(c)) {
         match component.to_os_string().to_str() {
             Some("" | ".") => continue,
 
@@ -135,6 +224,98 @@ pub fn normalize_path(path: &Path) -> Result<NormalizedPath, UnsupportedWindowsP
     Ok(NormalizedPath { prefix, components })
 }
 
+/// Normalize a path without allocating
+///
+/// Follows the same rules as [`normalize_path_with`], but takes the path's encoded bytes (see [`std::ffi::OsStr::as_encoded_bytes`])
+/// and writes its components to the provided buffer as ranges of these bytes, replacing its previous content.
+///
+/// Returns the path's prefix.
+pub fn normalize_path_into(
+    path: &[u8],
+    opts: NormalizeOpts,
+    components: &mut Vec<Range<usize>>,
+) -> Result<Option<PathPrefix>, UnsupportedWindowsPrefix> {
+    components.clear();
+
+    let verbatim = if opts.slash_only {
+        None
+    } else {
+        path.strip_prefix(b"\\\\")
+    };
+
+    let (prefix, start) = if let Some(path) = verbatim {
+        let path = path.strip_prefix(b"?\\").ok_or(UnsupportedWindowsPrefix)?;
+
+        // Expect and extract drive letter
+        let windows_drive = windows_drive_from_bytes(path).ok_or(UnsupportedWindowsPrefix)?;
+
+        (Some(PathPrefix::WindowsDrive(windows_drive)), 6)
+    } else if let Some(windows_drive) = windows_drive_from_bytes(path) {
+        (Some(PathPrefix::WindowsDrive(windows_drive)), 2)
+    } else if path.first().is_some_and(|c| opts.is_separator(*c)) {
+        (Some(PathPrefix::RootDir), 1)
+    } else {
+        (None, 0)
+    };
+
+    split_components(path, start, |byte| opts.is_separator(byte), components);
+
+    Ok(prefix)
+}
+
+/// Split a path provided as a string into its components, only using `/` as a separator
+///
+/// Unlike [`normalize_path_into`], a leading `/` is the only recognized prefix, so the result is the same on all platforms.
+pub fn split_str_path_into(path: &[u8], components: &mut Vec<Range<usize>>) -> Option<PathPrefix> {
+    components.clear();
+
+    let (prefix, start) = match path.first() {
+        Some(b'/') => (Some(PathPrefix::RootDir), 1),
+        _ => (None, 0),
+    };
+
+    split_components(path, start, |byte| byte == b'/', components);
+
+    prefix
+}
+
+/// Split a path into its components from the provided offset, skipping empty and `.` components
+fn split_components(
+    path: &[u8],
+    start: usize,
+    is_separator: impl Fn(u8) -> bool,
+    components: &mut Vec<Range<usize>>,
+) {
+    let mut component_start = start;
+
+    for i in start..=path.len() {
+        if i < path.len() && !is_separator(path[i]) {
+            continue;
+        }
+
+        if !matches!(&path[component_start..i], b"" | b".") {
+            components.push(component_start..i);
+        }
+
+        component_start = i + 1;
+    }
+}
+
+/// Render a relative path using `/` as the only separator, independently of the platform
+pub fn with_unix_separators(path: &Path) -> PathBuf {
+    let mut out = OsString::new();
+
+    for (i, component) in path.components().enumerate() {
+        if i > 0 {
+            out.push("/");
+        }
+
+        out.push(component.as_os_str());
+    }
+
+    PathBuf::from(out)
+}
+
 /// Match and strip the Windows drive from the provided path
 fn strip_windows_drive<'a>(path: OpaqueOsStr<'a>) -> Option<(WindowsDrive, OpaqueOsStr<'a>)> {
     let (char, path) = path.strip_ascii_char()?;
@@ -147,6 +328,14 @@ fn strip_windows_drive<'a>(path: OpaqueOsStr<'a>) -> Option<(WindowsDrive, Opaqu
     Some((windows_drive, path.to_static()))
 }
 
+/// Match a Windows drive (e.g. `C:`) at the beginning of the provided bytes
+fn windows_drive_from_bytes(path: &[u8]) -> Option<WindowsDrive> {
+    match path {
+        [letter, b':', ..] => WindowsDrive::try_from(char::from(*letter)).ok(),
+        _ => None,
+    }
+}
+
 /// The provided path contains an invalid or unsupported Windows prefix (e.g. `\\?\server\share`, `\\?\UNC\`, `\\.\device`)
 #[derive(Debug, Clone, Copy)]
 pub struct UnsupportedWindowsPrefix;
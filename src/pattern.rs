@@ -1,25 +1,769 @@
 use std::{
-    ffi::OsString,
+    collections::HashSet,
+    ffi::OsStr,
+    fmt::{self, Write as _},
+    hash::{Hash, Hasher},
+    ops::Range,
     path::{MAIN_SEPARATOR, MAIN_SEPARATOR_STR, Path, PathBuf},
+    str::FromStr,
+    sync::OnceLock,
 };
 
-use parsy::ParsingError;
+use parsy::{ParsingError, Span};
+use unicode_normalization::{UnicodeNormalization as _, is_nfc};
 
 use crate::{
-    compiler::{CaseSensitivity, Component, compile_component},
-    parser::{PATTERN_PARSER, RawPattern},
-    paths::{PathPrefix, normalize_path},
+    compiler::{
+        CaseSensitivity, CompileError, CompileOpts, Component, capture_count, caseless_eq,
+        compile_component, embeddable_regex, read_captures, strip_diacritics,
+    },
+    parser::{CharsMatcher, ParserOpts, RawComponent, RawPattern, parse_pattern, parse_recovering},
+    paths::{
+        NormalizeOpts, NormalizedPath, PathPrefix, normalize_path, normalize_path_into,
+        normalize_path_with, split_str_path_into,
+    },
+    simplify::simplify_source,
+    syntax::{PatternAst, is_special},
 };
 
 /// Options for pattern matching
-#[derive(Debug, Default, Clone, Copy)]
+///
+/// Options are set using chained setters, e.g. `PatternOpts::new().with_case_insensitive(true)`,
+/// and can be read back with the corresponding getters (e.g. [`PatternOpts::case_insensitive`]).
+///
+/// With the `serde` feature, options can be (de)serialized using the getters' names as keys,
+/// with missing keys falling back to their default value.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+#[non_exhaustive]
 pub struct PatternOpts {
+    /// Ignore case sensitivity during matching (see [`PatternOpts::with_case_insensitive`])
+    case_insensitive: bool,
+
+    /// Choose case sensitivity for each component depending on its content (see [`PatternOpts::with_smart_case`])
+    smart_case: bool,
+
+    /// Case folding to use when matching case-insensitively (see [`PatternOpts::with_case_folding`])
+    case_folding: CaseFolding,
+
+    /// Assume the filesystem is case-insensitive (see [`PatternOpts::with_case_insensitive_fs`])
+    case_insensitive_fs: bool,
+
+    /// Unicode normalization to apply before comparing strings (see [`PatternOpts::with_unicode_normalization`])
+    unicode_normalization: UnicodeNormalization,
+
+    /// Remove diacritics before comparing strings (see [`PatternOpts::with_fold_diacritics`])
+    fold_diacritics: bool,
+
+    /// Match bytes instead of characters (see [`PatternOpts::with_ascii_only`])
+    ascii_only: bool,
+
+    /// Allow `*` to match path separators (see [`PatternOpts::with_star_matches_separators`])
+    star_matches_separators: bool,
+
+    /// Fail to build patterns that can't match anything on the current platform (see [`PatternOpts::with_reject_foreign_prefixes`])
+    reject_foreign_prefixes: bool,
+
+    /// Require a leading `.` in path components to be matched by a literal `.` (see [`PatternOpts::with_require_literal_leading_dot`])
+    require_literal_leading_dot: bool,
+
+    /// Prevent `**` from matching hidden path components (see [`PatternOpts::with_globstar_skips_hidden`])
+    globstar_skips_hidden: bool,
+
+    /// Match patterns without any separator against the last component of paths (see [`PatternOpts::with_match_basename`])
+    match_basename: bool,
+
+    /// Only treat `/` as a separator in matched paths (see [`PatternOpts::with_slash_only`])
+    slash_only: bool,
+
+    /// Make `\` escape the character following it in the pattern (see [`PatternOpts::with_backslash_escapes`])
+    backslash_escapes: bool,
+
+    /// Make `\` an ordinary character in the pattern (see [`PatternOpts::with_literal_backslashes`])
+    literal_backslashes: bool,
+
+    /// Parse patterns with the `glob` crate's syntax (see [`PatternOpts::with_glob_compat`])
+    glob_compat: bool,
+
+    /// Don't parse group alternates (see [`PatternOpts::with_disable_alternates`])
+    disable_alternates: bool,
+
+    /// Don't parse bracket expressions (see [`PatternOpts::with_disable_brackets`])
+    disable_brackets: bool,
+
+    /// Make `**` act like `*` (see [`PatternOpts::with_disable_globstar`])
+    disable_globstar: bool,
+
+    /// Treat all characters except separators literally (see [`PatternOpts::with_literal`])
+    literal: bool,
+
+    /// Resolve `..` components lexically (see [`PatternOpts::with_normalize_dotdot`])
+    normalize_dotdot: bool,
+
+    /// Recognize inline case sensitivity flags (see [`PatternOpts::with_inline_flags`])
+    inline_flags: bool,
+
+    /// Parse extended glob operators (see [`PatternOpts::with_extglob`])
+    extglob: bool,
+
+    /// Maximum number of components of matched paths (see [`PatternOpts::with_max_components`])
+    max_components: Option<usize>,
+
+    /// Maximum complexity of patterns (see [`PatternOpts::with_max_complexity`])
+    max_complexity: Option<usize>,
+
+    /// Maximum size of compiled regular expressions, in bytes (see [`PatternOpts::with_regex_size_limit`])
+    regex_size_limit: Option<usize>,
+
+    /// Don't share compiled regular expressions with other patterns (see [`PatternOpts::with_bypass_regex_cache`])
+    bypass_regex_cache: bool,
+}
+
+impl PatternOpts {
+    /// Create options with their default values
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create options suited to the current platform's filesystems
+    ///
+    /// On Windows and macOS, whose filesystems are case-insensitive by default, this enables both
+    /// [`PatternOpts::with_case_insensitive`] and [`PatternOpts::with_case_insensitive_fs`], so e.g. `*.TXT` will match `readme.txt`.
+    /// On other platforms, this is the same as [`PatternOpts::new`].
+    pub fn platform_defaults() -> Self {
+        let case_insensitive = cfg!(any(windows, target_os = "macos"));
+
+        Self::new()
+            .with_case_insensitive(case_insensitive)
+            .with_case_insensitive_fs(case_insensitive)
+    }
+
+    /// Layer these options on top of other ones
+    ///
+    /// Options left to their default value are taken from `other`, while the ones that were changed take precedence.
+    /// As options are disabled by default, this means each option is enabled if it is enabled in either.
+    ///
+    /// This is useful to apply e.g. user-provided options over project-wide defaults:
+    ///
+    /// ```
+    /// use globby::PatternOpts;
+    ///
+    /// let defaults = PatternOpts::new().with_case_insensitive(true);
+    /// let opts = PatternOpts::new().with_slash_only(true).or(defaults);
+    ///
+    /// assert!(opts.case_insensitive() && opts.slash_only());
+    /// ```
+    pub fn or(self, other: Self) -> Self {
+        let Self {
+            case_insensitive,
+            smart_case,
+            case_folding,
+            case_insensitive_fs,
+            unicode_normalization,
+            fold_diacritics,
+            ascii_only,
+            star_matches_separators,
+            reject_foreign_prefixes,
+            require_literal_leading_dot,
+            globstar_skips_hidden,
+            match_basename,
+            slash_only,
+            backslash_escapes,
+            literal_backslashes,
+            glob_compat,
+            disable_alternates,
+            disable_brackets,
+            disable_globstar,
+            literal,
+            normalize_dotdot,
+            inline_flags,
+            extglob,
+            max_components,
+            max_complexity,
+            regex_size_limit,
+            bypass_regex_cache,
+        } = self;
+
+        Self {
+            case_insensitive: case_insensitive || other.case_insensitive,
+            smart_case: smart_case || other.smart_case,
+            case_folding: if case_folding == CaseFolding::default() {
+                other.case_folding
+            } else {
+                case_folding
+            },
+            case_insensitive_fs: case_insensitive_fs || other.case_insensitive_fs,
+            unicode_normalization: if unicode_normalization == UnicodeNormalization::default() {
+                other.unicode_normalization
+            } else {
+                unicode_normalization
+            },
+            fold_diacritics: fold_diacritics || other.fold_diacritics,
+            ascii_only: ascii_only || other.ascii_only,
+            star_matches_separators: star_matches_separators || other.star_matches_separators,
+            reject_foreign_prefixes: reject_foreign_prefixes || other.reject_foreign_prefixes,
+            require_literal_leading_dot: require_literal_leading_dot
+                || other.require_literal_leading_dot,
+            globstar_skips_hidden: globstar_skips_hidden || other.globstar_skips_hidden,
+            match_basename: match_basename || other.match_basename,
+            slash_only: slash_only || other.slash_only,
+            backslash_escapes: backslash_escapes || other.backslash_escapes,
+            literal_backslashes: literal_backslashes || other.literal_backslashes,
+            glob_compat: glob_compat || other.glob_compat,
+            disable_alternates: disable_alternates || other.disable_alternates,
+            disable_brackets: disable_brackets || other.disable_brackets,
+            disable_globstar: disable_globstar || other.disable_globstar,
+            literal: literal || other.literal,
+            normalize_dotdot: normalize_dotdot || other.normalize_dotdot,
+            inline_flags: inline_flags || other.inline_flags,
+            extglob: extglob || other.extglob,
+            max_components: max_components.or(other.max_components),
+            max_complexity: max_complexity.or(other.max_complexity),
+            regex_size_limit: regex_size_limit.or(other.regex_size_limit),
+            bypass_regex_cache: bypass_regex_cache || other.bypass_regex_cache,
+        }
+    }
+
     /// Ignore case sensitivity during matching
     ///
     /// This makes `a` match both lowercase `a` and uppercase `A`
     ///
     /// Disabled by default
-    pub case_insensitive: bool,
+    pub fn with_case_insensitive(mut self, case_insensitive: bool) -> Self {
+        self.case_insensitive = case_insensitive;
+        self
+    }
+
+    /// Choose case sensitivity for each component depending on its content
+    ///
+    /// Components containing at least one uppercase character are matched case-sensitively, while others
+    /// are matched case-insensitively. This includes characters inside brackets and groups.
+    ///
+    /// For instance, `src/Readme*` will match `SRC/Readme.md` but not `src/README.md`.
+    ///
+    /// Takes precedence over [`PatternOpts::with_case_insensitive`] when enabled.
+    ///
+    /// Disabled by default
+    pub fn with_smart_case(mut self, smart_case: bool) -> Self {
+        self.smart_case = smart_case;
+        self
+    }
+
+    /// Case folding to use when matching case-insensitively
+    ///
+    /// Only relevant when [`PatternOpts::with_case_insensitive`] or [`PatternOpts::with_smart_case`] is enabled.
+    /// See [`CaseFolding`] for details.
+    ///
+    /// Defaults to [`CaseFolding::Unicode`]
+    pub fn with_case_folding(mut self, case_folding: CaseFolding) -> Self {
+        self.case_folding = case_folding;
+        self
+    }
+
+    /// Assume the walked filesystem is case-insensitive
+    ///
+    /// This allows leading literal components matched case-insensitively to be part of the pattern's
+    /// common root directory (see [`Pattern::common_root_dir`]), so the [`crate::Walker`] can start from there
+    /// instead of reading the base directory to find all casings. For instance, the common root directory of
+    /// `src/**/*.rs` will be `src` instead of the base directory.
+    ///
+    /// Note that results located under these components are then rendered with the casing of the pattern.
+    /// This must not be enabled on case-sensitive filesystems, as entries with another casing would not be found.
+    ///
+    /// Only relevant when [`PatternOpts::with_case_insensitive`] or [`PatternOpts::with_smart_case`] is enabled.
+    ///
+    /// Disabled by default (see [`PatternOpts::platform_defaults`])
+    pub fn with_case_insensitive_fs(mut self, case_insensitive_fs: bool) -> Self {
+        self.case_insensitive_fs = case_insensitive_fs;
+        self
+    }
+
+    /// Unicode normalization to apply to the pattern's literals and to matched paths before comparing them
+    ///
+    /// Some characters can be written in several ways, e.g. `é` may be a single character (U+00E9)
+    /// or an `e` followed by a combining accent (U+0301). They look identical, but don't match each other by default.
+    /// This notably happens on macOS, whose filesystems may return names in a decomposed form,
+    /// while patterns are usually typed in a composed one. See [`UnicodeNormalization`] for details.
+    ///
+    /// As the walked filesystem may store names in any form, components containing non-ASCII characters
+    /// are then never part of the pattern's common root directory (see [`Pattern::common_root_dir`]).
+    ///
+    /// Defaults to [`UnicodeNormalization::None`]
+    pub fn with_unicode_normalization(
+        mut self,
+        unicode_normalization: UnicodeNormalization,
+    ) -> Self {
+        self.unicode_normalization = unicode_normalization;
+        self
+    }
+
+    /// Remove diacritics from the pattern's literals and from matched paths before comparing them
+    ///
+    /// Characters are decomposed and stripped of their combining marks, so e.g. `cafe*` will match `café_menu.pdf`
+    /// and `pina` will match `piña`, whichever way their accents are encoded. This also applies to characters
+    /// inside brackets, so `[é]` matches both `e` and `é`.
+    ///
+    /// This is independent of case sensitivity: `cafe` doesn't match `CAFÉ` unless the pattern is also case-insensitive.
+    /// Like with [`PatternOpts::with_unicode_normalization`], components containing non-ASCII characters
+    /// are never part of the pattern's common root directory, and paths that aren't valid UTF-8 are matched as they are.
+    ///
+    /// Disabled by default
+    pub fn with_fold_diacritics(mut self, fold_diacritics: bool) -> Self {
+        self.fold_diacritics = fold_diacritics;
+        self
+    }
+
+    /// Match bytes instead of characters, for paths known to only contain ASCII characters
+    ///
+    /// Components are compiled to regular expressions without Unicode support, which makes them faster to build and match:
+    /// `?` and bracket expressions match a single byte. Character classes (e.g. `[[:alpha:]]`) only match ASCII characters
+    /// in both modes, but negated ones (e.g. `[![:alpha:]]`) then match a single byte of multi-byte characters.
+    /// Case-insensitive matching uses [`CaseFolding::Ascii`], regardless of [`PatternOpts::with_case_folding`].
+    ///
+    /// **Warning:** multi-byte characters will then not match `?` or bracket expressions such as `[!a]`,
+    /// so e.g. `caf?` will not match `café`. Literal characters and `*` still match them.
+    ///
+    /// Disabled by default
+    pub fn with_ascii_only(mut self, ascii_only: bool) -> Self {
+        self.ascii_only = ascii_only;
+        self
+    }
+
+    /// Allow `*` to match path separators
+    ///
+    /// This makes components containing a `*` match any number of path components (at least one),
+    /// as some legacy matchers do. For instance, `src/*.c` will match both `src/main.c` and `src/a/b/util.c`,
+    /// and `{a|b*}` will match `b/c` as well as `a`.
+    ///
+    /// Other matchers such as `?` still never match path separators.
+    ///
+    /// Disabled by default
+    pub fn with_star_matches_separators(mut self, star_matches_separators: bool) -> Self {
+        self.star_matches_separators = star_matches_separators;
+        self
+    }
+
+    /// Fail to build patterns that can't match anything on the current platform
+    ///
+    /// For instance, `C:\**` will be rejected on Linux, as Windows drives don't exist there.
+    /// See [`Pattern::portability`] for more details.
+    ///
+    /// Disabled by default
+    pub fn with_reject_foreign_prefixes(mut self, reject_foreign_prefixes: bool) -> Self {
+        self.reject_foreign_prefixes = reject_foreign_prefixes;
+        self
+    }
+
+    /// Require a leading `.` in path components to be matched by a literal `.` in the pattern
+    ///
+    /// This makes hidden entries invisible to matchers such as `*`, `?`, `[...]` and group alternates,
+    /// as most shells do. For instance, `*` will not match `.gitignore` but `.*` will, and `**/*.rs`
+    /// will not match `.cargo/config.rs`, as `**` doesn't match hidden components either.
+    ///
+    /// The literal `.` must be at the very start of the pattern's component (or of a group alternate there),
+    /// so `*.*` will not match `.gitignore` either. Likewise, `?oo` and `[.a]oo` will not match `.oo`,
+    /// while `.?o` still matches `.ao`.
+    ///
+    /// Hidden directories are still traversed by the [`crate::Walker`] when the pattern names them explicitly,
+    /// e.g. with `.cargo/*.rs`.
+    ///
+    /// Disabled by default
+    pub fn with_require_literal_leading_dot(mut self, require_literal_leading_dot: bool) -> Self {
+        self.require_literal_leading_dot = require_literal_leading_dot;
+        self
+    }
+
+    /// Prevent `**` from matching hidden path components (starting with a `.`)
+    ///
+    /// For instance, `**/*.rs` will not match `.git/hooks/a.rs`, while `.git/**` will as `.git` is literal.
+    /// Unlike [`PatternOpts::with_require_literal_leading_dot`], other matchers are unaffected, so `*` still matches `.gitignore`.
+    ///
+    /// Disabled by default
+    pub fn with_globstar_skips_hidden(mut self, globstar_skips_hidden: bool) -> Self {
+        self.globstar_skips_hidden = globstar_skips_hidden;
+        self
+    }
+
+    /// Match patterns without any separator against the last component of paths, at any depth
+    ///
+    /// This is equivalent to an implicit `**/` prefix, as with gitignore rules. For instance, `*.log` will match
+    /// both `c.log` and `a/b/c.log`, while `dir/*.log` will only match `.log` files directly inside `dir`.
+    ///
+    /// Disabled by default
+    pub fn with_match_basename(mut self, match_basename: bool) -> Self {
+        self.match_basename = match_basename;
+        self
+    }
+
+    /// Only treat `/` as a separator in matched paths, making `\\` an ordinary character
+    ///
+    /// This is useful to match paths that aren't filesystem paths (e.g. URLs or archive entries),
+    /// or Unix paths whose names contain backslashes. For instance, `dir/*` will match `dir/a\\b`.
+    ///
+    /// Note that `\\` is still a separator in the pattern itself, unless [`PatternOpts::with_backslash_escapes`]
+    /// or [`PatternOpts::with_literal_backslashes`] is enabled (see [`Pattern::new_slash_only`] for a shorthand).
+    ///
+    /// Disabled by default
+    pub fn with_slash_only(mut self, slash_only: bool) -> Self {
+        self.slash_only = slash_only;
+        self
+    }
+
+    /// Make `\` escape the character following it in the pattern, instead of being a path separator
+    ///
+    /// For instance, `foo\*bar` will only match `foo*bar`, and `a\b` is a single `ab` component.
+    /// Only `/` separates components in the pattern, and verbatim prefixes (e.g. `\\?\C:\`) can't be used.
+    ///
+    /// Note that `\` is still a separator in matched paths, unless [`PatternOpts::with_slash_only`] is enabled.
+    ///
+    /// See [`Pattern::escape`] to escape all special characters of a string.
+    ///
+    /// Disabled by default
+    pub fn with_backslash_escapes(mut self, backslash_escapes: bool) -> Self {
+        self.backslash_escapes = backslash_escapes;
+        self
+    }
+
+    /// Make `\` an ordinary character in the pattern, instead of a path separator
+    ///
+    /// For instance, `dir/file\name` is made of the `dir` and `file\name` components.
+    /// Only `/` separates components in the pattern, and verbatim prefixes (e.g. `\\?\C:\`) can't be used.
+    /// Inside brackets, `\` still escapes special characters (e.g. `[\*]`).
+    ///
+    /// [`PatternOpts::with_backslash_escapes`] takes precedence over this option, so `\` escapes characters when both are enabled.
+    ///
+    /// Note that `\` is still a separator in matched paths, unless [`PatternOpts::with_slash_only`] is enabled
+    /// (see [`Pattern::new_slash_only`] for a shorthand).
+    ///
+    /// Disabled by default
+    pub fn with_literal_backslashes(mut self, literal_backslashes: bool) -> Self {
+        self.literal_backslashes = literal_backslashes;
+        self
+    }
+
+    /// Parse patterns with the syntax of the `glob` crate, to migrate from it without rewriting patterns
+    ///
+    /// See [`Pattern::new_glob_compat`] for the emulated semantics. This changes the syntax only:
+    /// `{`, `}`, `|` and `]` are ordinary characters, `\` never escapes characters,
+    /// and bracket expressions follow the `glob` crate's rules (e.g. `[*?]` matches `*` or `?`, and `[[:alpha:]]` isn't a character class).
+    ///
+    /// Disabled by default
+    pub fn with_glob_compat(mut self, glob_compat: bool) -> Self {
+        self.glob_compat = glob_compat;
+        self
+    }
+
+    /// Don't parse group alternates, making `{`, `}` and `|` ordinary characters
+    ///
+    /// For instance, `{a|b}` will only match a file named `{a|b}`.
+    ///
+    /// Disabled by default
+    pub fn with_disable_alternates(mut self, disable_alternates: bool) -> Self {
+        self.disable_alternates = disable_alternates;
+        self
+    }
+
+    /// Don't parse bracket expressions, making `[` and `]` ordinary characters
+    ///
+    /// For instance, `track [1].mp3` will only match a file named `track [1].mp3`.
+    /// This is useful for paths which often contain brackets, e.g. in music libraries, without having to escape them.
+    ///
+    /// Disabled by default
+    pub fn with_disable_brackets(mut self, disable_brackets: bool) -> Self {
+        self.disable_brackets = disable_brackets;
+        self
+    }
+
+    /// Don't parse `**` components as wildcards, making `**` act like `*`
+    ///
+    /// For instance, `src/**` will match `src/main.rs` but not `src/app/main.rs`, and `a**b` is the same as `a*b`.
+    /// Patterns then never contain any wildcard (see [`Pattern::has_wildcard`]), so the [`crate::Walker`] only descends
+    /// as deep as the pattern's number of components.
+    ///
+    /// This is useful for legacy patterns written for tools in which `**` is just another `*`,
+    /// which would otherwise walk entire directory trees.
+    ///
+    /// Disabled by default
+    pub fn with_disable_globstar(mut self, disable_globstar: bool) -> Self {
+        self.disable_globstar = disable_globstar;
+        self
+    }
+
+    /// Treat all characters literally, except path separators
+    ///
+    /// This is useful to find paths provided by users without interpreting them as patterns.
+    /// For instance, `report[final]*.txt` will only match a file with this exact name, and `**` is a literal component.
+    ///
+    /// Prefixes (e.g. `/` or `C:\`) are still recognized, so absolute paths can be walked as usual.
+    /// With [`PatternOpts::with_backslash_escapes`] or [`PatternOpts::with_literal_backslashes`], `\` is an ordinary character instead of a path separator.
+    ///
+    /// See [`Pattern::literal`] for a shorthand.
+    ///
+    /// Disabled by default
+    pub fn with_literal(mut self, literal: bool) -> Self {
+        self.literal = literal;
+        self
+    }
+
+    /// Allow `..` components anywhere in the pattern, resolving them lexically
+    ///
+    /// For instance, `a/../b/*.txt` is interpreted as `b/*.txt`, and `a/b/../../c` as `c`.
+    /// This is useful to accept paths produced by other tools without having to normalize them first.
+    ///
+    /// Leading `..` components of relative patterns are kept as usual, while the pattern fails to build if
+    /// a `..` component goes above the root directory of an absolute pattern (e.g. `/../x`)
+    /// or directly follows a `**` component, as the latter may match any number of components.
+    ///
+    /// Disabled by default
+    pub fn with_normalize_dotdot(mut self, normalize_dotdot: bool) -> Self {
+        self.normalize_dotdot = normalize_dotdot;
+        self
+    }
+
+    /// Recognize an inline case sensitivity flag at the very start of the pattern
+    ///
+    /// `(?i)` makes the pattern case-insensitive, while `(?-i)` makes it case-sensitive, overriding both
+    /// [`PatternOpts::with_case_insensitive`] and [`PatternOpts::with_smart_case`]. For instance, `(?i)**/*.md` will match `README.MD`.
+    /// This is useful when patterns are provided as plain strings, e.g. in configuration files.
+    ///
+    /// Flags are only recognized before the first component (and the prefix, e.g. `(?i)/**`).
+    /// As `(?i)` is otherwise a valid component, enabling this changes the meaning of patterns starting with it;
+    /// with [`PatternOpts::with_backslash_escapes`], `\(` can be used to match a literal `(` instead.
+    ///
+    /// Disabled by default
+    pub fn with_inline_flags(mut self, inline_flags: bool) -> Self {
+        self.inline_flags = inline_flags;
+        self
+    }
+
+    /// Parse the extended glob operators of ksh and bash (with `shopt -s extglob`)
+    ///
+    /// Each operator is followed by alternatives in parentheses, separated with `|` like in groups:
+    /// * `?(a|b)` matches zero or one occurrence of the alternatives
+    /// * `*(a|b)` matches zero or more occurrences of the alternatives
+    /// * `+(a|b)` matches one or more occurrences of the alternatives
+    /// * `@(a|b)` matches exactly one of the alternatives
+    /// * `!(a|b)` matches anything except the alternatives, e.g. `!(*.bak)` matches all names not ending with `.bak`
+    ///
+    /// Inside groups and operators, `)` must then be escaped (e.g. `[)]`) to be matched.
+    /// As regular expressions can't express negations, `!(...)` can only be used once per component and outside of other
+    /// groups and operators, e.g. `!(a)!(b)` and `{!(a)|b}` are invalid. Components containing it are always matched
+    /// against a single path component, even with [`PatternOpts::with_star_matches_separators`], and don't support captures.
+    ///
+    /// Disabled by default
+    pub fn with_extglob(mut self, extglob: bool) -> Self {
+        self.extglob = extglob;
+        self
+    }
+
+    /// Maximum number of components of the paths the pattern matches
+    ///
+    /// Deeper paths never match, even through wildcards: with a limit of 2, `**/*.rs` matches `a/b.rs` but not `a/b/c.rs`.
+    /// Paths that reach the limit are also never [`PatternMatchResult::Starved`], so the [`crate::Walker`] doesn't descend past it.
+    ///
+    /// Unlike limiting the walker's depth, this also applies when matching paths directly, e.g. with [`Pattern::is_match`].
+    ///
+    /// Unlimited if not set
+    pub fn with_max_components(mut self, max: usize) -> Self {
+        self.max_components = Some(max);
+        self
+    }
+
+    /// Maximum complexity of the pattern, i.e. its total number of matchers, including the ones nested in groups
+    ///
+    /// For instance, `src/*.{rs|toml}` has a complexity of 6: `src`, `*`, `.`, the group, `rs` and `toml`.
+    /// Patterns exceeding it fail to build with [`PatternError::TooComplex`], before any regular expression is compiled.
+    ///
+    /// This is useful to reject pathological patterns provided by users (e.g. with thousands of alternatives) early.
+    /// Unlike [`PatternOpts::with_regex_size_limit`], it doesn't depend on how the pattern is compiled.
+    ///
+    /// Unlimited if not set
+    pub fn with_max_complexity(mut self, max: usize) -> Self {
+        self.max_complexity = Some(max);
+        self
+    }
+
+    /// Maximum size of the regular expressions compiled from the pattern's components, in bytes
+    ///
+    /// Patterns producing bigger expressions (e.g. with huge alternations) will fail to build.
+    ///
+    /// Uses the [`regex`](https://docs.rs/regex) crate's default limit if not set
+    pub fn with_regex_size_limit(mut self, limit: usize) -> Self {
+        self.regex_size_limit = Some(limit);
+        self
+    }
+
+    /// Don't share compiled regular expressions with other patterns
+    ///
+    /// By default, regular expressions are stored in a process-wide cache keyed by their source,
+    /// so patterns with identical components (e.g. loaded from many similar rules) only compile them once.
+    /// The cache is never trimmed automatically; it can be emptied with [`crate::clear_regex_cache`].
+    ///
+    /// Disabled by default
+    pub fn with_bypass_regex_cache(mut self, bypass_regex_cache: bool) -> Self {
+        self.bypass_regex_cache = bypass_regex_cache;
+        self
+    }
+
+    /// Check if [`PatternOpts::with_case_insensitive`] is enabled
+    pub fn case_insensitive(&self) -> bool {
+        self.case_insensitive
+    }
+
+    /// Check if [`PatternOpts::with_smart_case`] is enabled
+    pub fn smart_case(&self) -> bool {
+        self.smart_case
+    }
+
+    /// Get the case folding set with [`PatternOpts::with_case_folding`]
+    pub fn case_folding(&self) -> CaseFolding {
+        self.case_folding
+    }
+
+    /// Check if [`PatternOpts::with_case_insensitive_fs`] is enabled
+    pub fn case_insensitive_fs(&self) -> bool {
+        self.case_insensitive_fs
+    }
+
+    /// Get the Unicode normalization set with [`PatternOpts::with_unicode_normalization`]
+    pub fn unicode_normalization(&self) -> UnicodeNormalization {
+        self.unicode_normalization
+    }
+
+    /// Check if [`PatternOpts::with_fold_diacritics`] is enabled
+    pub fn fold_diacritics(&self) -> bool {
+        self.fold_diacritics
+    }
+
+    /// Check if [`PatternOpts::with_ascii_only`] is enabled
+    pub fn ascii_only(&self) -> bool {
+        self.ascii_only
+    }
+
+    /// Check if [`PatternOpts::with_star_matches_separators`] is enabled
+    pub fn star_matches_separators(&self) -> bool {
+        self.star_matches_separators
+    }
+
+    /// Check if [`PatternOpts::with_reject_foreign_prefixes`] is enabled
+    pub fn reject_foreign_prefixes(&self) -> bool {
+        self.reject_foreign_prefixes
+    }
+
+    /// Check if [`PatternOpts::with_require_literal_leading_dot`] is enabled
+    pub fn require_literal_leading_dot(&self) -> bool {
+        self.require_literal_leading_dot
+    }
+
+    /// Check if [`PatternOpts::with_globstar_skips_hidden`] is enabled
+    pub fn globstar_skips_hidden(&self) -> bool {
+        self.globstar_skips_hidden
+    }
+
+    /// Check if [`PatternOpts::with_match_basename`] is enabled
+    pub fn match_basename(&self) -> bool {
+        self.match_basename
+    }
+
+    /// Check if [`PatternOpts::with_slash_only`] is enabled
+    pub fn slash_only(&self) -> bool {
+        self.slash_only
+    }
+
+    /// Check if [`PatternOpts::with_backslash_escapes`] is enabled
+    pub fn backslash_escapes(&self) -> bool {
+        self.backslash_escapes
+    }
+
+    /// Check if [`PatternOpts::with_literal_backslashes`] is enabled
+    pub fn literal_backslashes(&self) -> bool {
+        self.literal_backslashes
+    }
+
+    /// Check if [`PatternOpts::with_glob_compat`] is enabled
+    pub fn glob_compat(&self) -> bool {
+        self.glob_compat
+    }
+
+    /// Check if [`PatternOpts::with_disable_alternates`] is enabled
+    pub fn disable_alternates(&self) -> bool {
+        self.disable_alternates
+    }
+
+    /// Check if [`PatternOpts::with_disable_brackets`] is enabled
+    pub fn disable_brackets(&self) -> bool {
+        self.disable_brackets
+    }
+
+    /// Check if [`PatternOpts::with_disable_globstar`] is enabled
+    pub fn disable_globstar(&self) -> bool {
+        self.disable_globstar
+    }
+
+    /// Check if [`PatternOpts::with_literal`] is enabled
+    pub fn literal(&self) -> bool {
+        self.literal
+    }
+
+    /// Check if [`PatternOpts::with_normalize_dotdot`] is enabled
+    pub fn normalize_dotdot(&self) -> bool {
+        self.normalize_dotdot
+    }
+
+    /// Check if [`PatternOpts::with_inline_flags`] is enabled
+    pub fn inline_flags(&self) -> bool {
+        self.inline_flags
+    }
+
+    /// Check if [`PatternOpts::with_extglob`] is enabled
+    pub fn extglob(&self) -> bool {
+        self.extglob
+    }
+
+    /// Get the limit set with [`PatternOpts::with_max_components`], if any
+    pub fn max_components(&self) -> Option<usize> {
+        self.max_components
+    }
+
+    /// Get the limit set with [`PatternOpts::with_max_complexity`], if any
+    pub fn max_complexity(&self) -> Option<usize> {
+        self.max_complexity
+    }
+
+    /// Get the limit set with [`PatternOpts::with_regex_size_limit`], if any
+    pub fn regex_size_limit(&self) -> Option<usize> {
+        self.regex_size_limit
+    }
+
+    /// Check if [`PatternOpts::with_bypass_regex_cache`] is enabled
+    pub fn bypass_regex_cache(&self) -> bool {
+        self.bypass_regex_cache
+    }
+
+    /// (Internal) Get the options to parse patterns with
+    fn parser_opts(&self) -> ParserOpts {
+        ParserOpts {
+            backslash_escapes: self.backslash_escapes && !self.glob_compat,
+            literal_backslashes: self.literal_backslashes,
+            glob_compat: self.glob_compat,
+            alternates: !self.disable_alternates && !self.glob_compat,
+            brackets: !self.disable_brackets,
+            globstar: !self.disable_globstar,
+            normalize_dotdot: self.normalize_dotdot,
+            inline_flags: self.inline_flags,
+            extglob: self.extglob && !self.glob_compat,
+            literal: self.literal,
+        }
+    }
+
+    /// Get the options paths should be normalized with before being matched
+    pub fn normalize_opts(&self) -> NormalizeOpts {
+        NormalizeOpts::new().with_slash_only(self.slash_only)
+    }
 }
 
 /// A pattern that can be matched against filesystem paths
@@ -33,7 +777,11 @@ pub struct PatternOpts {
 /// * `*` matches any suite of characters, or no character at all
 /// * `[abc]` matches any of `a`, `b` or `c`
 /// * `[!abc]` matches any character except `a`, `b` and `c`
-/// * `[\[]` matches `[`. The list of escapable characters is `[`, `]`, `{`, `}`, `*`, `?`, `\`, `/`, `|` and ':'
+///     - A `]` right after the opening `[` or `[!` doesn't close the brackets, so e.g. `[]]` matches `]` and `[!]]` matches any character except `]`
+/// * `[a-z]` matches any character between `a` and `z` (inclusive), e.g. `[0-9A-Fa-f]` matches hexadecimal digits
+///     - A `-` at the start or at the end of the brackets is a normal character, e.g. `[-a]` matches `-` or `a`, and so is an escaped one (`\-`)
+///     - Ranges can't be reversed, e.g. `[z-a]` is invalid
+/// * `[\[]` matches `[`. The list of escapable characters is `[`, `]`, `{`, `}`, `*`, `?`, `\`, `/`, `|`, `:` and `-`
 ///     - `[abc\[]` matches any of `a`, `b`, `c` or `[`
 /// * `[[:alpha:]]` will match any alphabetic character. The list of character classes are:
 ///     - `:alpha:` for any alphabetic character
@@ -42,277 +790,4186 @@ pub struct PatternOpts {
 ///     - `:uppercase:` for any uppercase character
 ///     - `:lowercase:` for any lowercase character
 ///     - `:whitespace:` for any whitespace character
+///     - `:xdigit:` for any hexadecimal digit
+///     - `:punct:` for any ASCII punctuation character
+///     - `:blank:` for a space or a tab
+///     - `:cntrl:` for any ASCII control character
+///     - `:graph:` for any visible ASCII character
+///     - `:print:` for any visible ASCII character or a space
 /// * `[![:alpha:]]` will match any non-alphabetic character
+///     - Bracket expressions can be disabled with [`PatternOpts::with_disable_brackets`]
 /// * `{a|bc}` will match any of `a` or `bc`
+///     - Alternates can also be separated with commas like in shells, e.g. `*.{rs,toml}`. Inside groups, a literal comma must then be escaped (`[,]`, or `\,` with [`PatternOpts::with_backslash_escapes`])
+///     - Alternates can't be empty, so e.g. `{a,}` and `{|a}` are invalid
 ///     - This can be combined with other matchers, e.g. `{[[:alpha:]][![:digit]]|[[:digit:]]*}` will match any alphabetic character followed by a non-digit character, OR a digit followed by anything
+///     - Groups can be nested up to 100 times, and group alternates can be disabled with [`PatternOpts::with_disable_alternates`]
+/// * `{1..15}` will match any number from `1` to `15`, e.g. `shard-{0..31}/**` matches the content of `shard-0` through `shard-31`
+///     - A step can be provided, e.g. `{0..30..10}` matches `0`, `10`, `20` or `30`
+///     - If either bound is zero-padded, all numbers are padded to the same width, e.g. `{01..12}` matches `01` to `12` but not `1`
+///     - Ranges can't be reversed (e.g. `{5..1}`) and can't contain more than 1000 numbers
+/// * With [`PatternOpts::with_extglob`], the extended glob operators of ksh and bash are supported: `?(a|b)`, `*(a|b)`, `+(a|b)` and `@(a|b)` match zero or one, zero or more, one or more and exactly one occurrence of the alternatives, while `!(a|b)` matches anything except them, e.g. `!(*.bak)`
+/// * A trailing separator, e.g. in `build/` or `**/target/`, restricts matches to directories (see [`Pattern::is_dir_only`])
+///
+/// To match a path without interpreting any of its characters, see [`Pattern::literal`] and [`Pattern::escape`].
 ///
 /// Matches are performed against path components, e.g. in `/path/to/item` components are `path`, `to` and `item`.
-/// Matchers **cannot** match path separators.
+/// Matchers **cannot** match path separators (unless [`PatternOpts::with_star_matches_separators`] is enabled).
 ///
 /// In addition, note that `**` will match any possible combination of directories. For instance, `/**/*.txt` will match any of `/file.txt`, `/dir/file.txt`, `/dir/dir2/file.txt`, and so on.
+/// This includes hidden directories, unless [`PatternOpts::with_globstar_skips_hidden`] is enabled.
+/// Globstars can be disabled with [`PatternOpts::with_disable_globstar`], in which case `**` acts like `*`.
+///
+/// # Parent directories
+///
+/// Relative patterns may start with any number of parent directory (`..`) components, e.g. `../../shared/**/*.proto`.
+/// They are not allowed anywhere else, nor in absolute patterns, unless [`PatternOpts::with_normalize_dotdot`] is enabled.
+///
+/// When matching against a path, these are compared against literal `..` components of the path.
+/// When walking, the [`crate::Walker`] starts from the corresponding ancestor of the base directory,
+/// and yields paths starting with the same `..` components.
+///
+/// # Filesystem access
+///
+/// Building patterns and matching them against paths is purely lexical, and is guaranteed to never access
+/// the filesystem: paths are never canonicalized, symbolic links are never resolved, and `..` components
+/// are compared literally. This makes matching safe to use in sandboxed environments.
+///
+/// Only walking-related methods (e.g. [`Pattern::any_match_in`]) and [`crate::Walker`] access the filesystem.
 ///
 /// # Platform-specific support
 ///
-/// * `/` and `\` are treated as path separators independently of the platform
+/// * `/` and `\` are treated as path separators independently of the platform (see [`PatternOpts::with_backslash_escapes`] to escape characters with `\` instead)
 /// * Absolute patterns can only be matched against absolute paths. e.g. `/dir` will not match `dir`. Note that using a [`crate::Walker`] will not cause this problem as a base directory is used.
 /// * Absolute patterns can be matched against named drives in Windows, e.g. `\dir` will match against `C:\dir` (but not the opposite)
 /// * Supported syntaxes for Windows drives are `C:\` and `\\?\C:\`
 /// * Other verbatim paths such as `\\?\server\share` or `\\.\device` are unsupported
 /// * Paths starting with `\\?\C:\` are normalized like any other path
+///
+/// # Equality
+///
+/// Patterns are equal if they were built from the same string (see [`Pattern::as_str`]) with the same options,
+/// which makes them usable as keys in e.g. a [`std::collections::HashSet`].
+/// Patterns built with different options are never equal, even if they match the same paths, and so are
+/// patterns written differently, e.g. `a/b` and `a//b`.
+///
+/// As equality and hashing only depend on these, Clippy's `mutable_key_type` lint (caused by the compiled
+/// regular expressions' internal caches) can safely be ignored.
+///
+/// # Serialization
+///
+/// With the `serde` feature, patterns are serialized as the string they were built from (see [`Pattern::as_str`]),
+/// and deserialized from a string with [`Pattern::new`], so invalid patterns are rejected while deserializing.
+/// Options are **not** preserved: deserialized patterns always use the default ones.
+/// To use other options, deserialize the string and the [`PatternOpts`] separately and call [`Pattern::new_with_opts`].
+
+#[derive(Debug, Clone)]
+pub struct Pattern {
+    /// Does the pattern start with a prefix component?
+    prefix: Option<PathPrefix>,
+
+    /// Root directory that's common to all possible matches of a pattern
+    ///
+    /// e.g. The root directory of `/a/{b, c}` will be `/a`
+    ///
+    /// This is useful to determine where to start directory traversal from
+    common_root_dir: PathBuf,
+
+    /// The components that make up the pattern
+    ///
+    /// Each of them match a single path component (except the wildcard matcher)
+    components: Vec<Component>,
+
+    /// Matchers of the leading components that can only match a fixed set of strings
+    ///
+    /// These are only made of literals and groups of literals, see [`Pattern::literal_roots`]
+    literal_skeleton: Vec<Vec<CharsMatcher>>,
+
+    /// For each component index (and the end of the pattern), is there a wildcard at or after it?
+    /// For more informations, see [`Pattern::wildcard_at_or_after`]
+    wildcard_at_or_after: Vec<bool>,
+
+    /// Does the pattern only match directories? (see [`Pattern::is_dir_only`])
+    dir_only: bool,
+
+    /// Structural statistics (see [`Pattern::stats`])
+    stats: PatternStats,
+
+    /// Options to normalize matched paths with
+    normalize_opts: NormalizeOpts,
+
+    /// Prevent wildcard components from matching hidden path components
+    wildcard_skips_hidden: bool,
+
+    /// Should the filesystem be assumed to be case-insensitive? (see [`PatternOpts::with_case_insensitive_fs`])
+    case_insensitive_fs: bool,
+
+    /// Maximum number of components of matched paths (see [`PatternOpts::with_max_components`])
+    max_components: Option<usize>,
+
+    /// Unicode normalization to apply to matched paths (see [`PatternOpts::with_unicode_normalization`])
+    unicode_normalization: UnicodeNormalization,
+
+    /// Remove diacritics from matched paths (see [`PatternOpts::with_fold_diacritics`])
+    fold_diacritics: bool,
+
+    /// Source of the pattern, as provided by the user (see [`Pattern::as_str`])
+    source: Box<str>,
+
+    /// Options the pattern was built with
+    opts: PatternOpts,
+
+    /// Case sensitivity of all components, unless it was chosen for each component (see [`PatternOpts::with_smart_case`])
+    uniform_case_insensitive: Option<bool>,
+
+    /// Case-sensitive and case-insensitive variants of the pattern, compiled on first use (see [`Pattern::case_variant`])
+    case_variants: [OnceLock<Option<Box<Pattern>>>; 2],
+
+    /// Number of capturing matchers in each component, or [`None`] for the implicit `**` added by [`PatternOpts::with_match_basename`]
+    capture_counts: Vec<Option<usize>>,
+
+    /// Variant of the pattern compiled with capture groups, compiled on first use (see [`Pattern::match_captures`])
+    capturing_variant: OnceLock<Option<Box<Pattern>>>,
+
+    /// Location of each component in the source, or [`None`] for the implicit `**` added by [`PatternOpts::with_match_basename`]
+    component_spans: Vec<Option<Range<usize>>>,
+
+    /// Literal text found at the edges of each component (see [`Pattern::literal_prefix`] and [`Pattern::literal_suffix`])
+    component_literals: Vec<ComponentLiterals>,
+
+    /// Text all matched paths start with (see [`Pattern::literal_prefix`])
+    literal_prefix: String,
+
+    /// Text all matched paths end with, if any (see [`Pattern::literal_suffix`])
+    literal_suffix: Option<String>,
+}
+
+impl Pattern {
+    /// Parse a pattern with the default options
+    pub fn new(input: &str) -> Result<Self, PatternError> {
+        Self::new_with_opts(input, PatternOpts::default())
+    }
+
+    /// Parse a pattern in which only `/` is a separator, both in the pattern and in matched paths
+    ///
+    /// This is useful to match strings in which `\` is an ordinary character, e.g. URLs or archive entry names.
+    /// For instance, `dir/file\name` matches the `file\name` entry of `dir`.
+    ///
+    /// This is a shorthand for [`PatternOpts::with_slash_only`] with [`PatternOpts::with_literal_backslashes`].
+    pub fn new_slash_only(input: &str) -> Result<Self, PatternError> {
+        Self::new_with_opts(
+            input,
+            PatternOpts::new()
+                .with_slash_only(true)
+                .with_literal_backslashes(true),
+        )
+    }
+
+    /// Parse a pattern written for the `glob` crate, to migrate from it without rewriting patterns
+    ///
+    /// The pattern is parsed with [`PatternOpts::with_glob_compat`], and matches paths like `glob::glob` would:
+    /// * `?` matches any character and `*` any sequence of characters, both inside a single component
+    /// * `**` matches any number of components (including none), and must be a whole component
+    /// * `[...]` matches any of the provided characters or ranges (e.g. `[a-z0-9]`), and `[!...]` any other character.
+    ///   A `]` or `-` is matched literally when it comes first (e.g. `[]-]`), and `^` is an ordinary character (`[^a]` isn't negated)
+    /// * Special characters are escaped using brackets (e.g. `[*]` or `[[]`), as there are no escape sequences
+    /// * `{`, `}` and `|` are ordinary characters, as there are no group alternates
+    /// * `\` is an ordinary character, unless on Windows where it's a separator (both in the pattern and in matched paths)
+    /// * Matching is case-sensitive, and wildcards match leading dots (as with `glob`'s default `MatchOptions`)
+    ///
+    /// Some semantics are not emulated:
+    /// * With `glob::Pattern::matches`, `*` matches separators unless `require_literal_separator` is set,
+    ///   use [`PatternOpts::with_star_matches_separators`] to get the same behaviour
+    /// * Reversed ranges (e.g. `[z-a]`) and separators inside brackets are rejected, instead of never matching
+    /// * Patterns are still normalized like any other (see [`Pattern`]), so e.g. `..` can only start relative patterns
+    pub fn new_glob_compat(input: &str) -> Result<Self, PatternError> {
+        Self::new_with_opts(
+            input,
+            PatternOpts::new()
+                .with_glob_compat(true)
+                .with_slash_only(!cfg!(windows))
+                .with_literal_backslashes(!cfg!(windows)),
+        )
+    }
+
+    /// Build a pattern matching the provided path literally
+    ///
+    /// Only path separators and prefixes are interpreted, see [`PatternOpts::with_literal`].
+    /// A first component that looks like a drive without being followed by a separator (e.g. `a:b`) is relative,
+    /// while e.g. `C:` and `C:/x` are absolute.
+    ///
+    /// Fails if the path contains `..` components that aren't allowed in patterns (see [`Pattern`]).
+    pub fn literal(path: &str) -> Result<Self, PatternError> {
+        let opts = PatternOpts::new().with_literal(true);
+
+        match path.as_bytes() {
+            // Ensure a first component looking like a drive (e.g. `a:b`) isn't mistaken for one
+            [letter, b':', next, ..]
+                if letter.is_ascii_alphabetic() && !matches!(next, b'/' | b'\\') =>
+            {
+                Self::new_with_opts(&format!("./{path}"), opts)
+            }
+
+            _ => Self::new_with_opts(path, opts),
+        }
+    }
+
+    /// Build a pattern matching exactly the provided path
+    ///
+    /// The path is normalized first (see [`crate::normalize_path`]), and its prefix (e.g. `/` or `C:\`) is preserved.
+    /// Its components are then matched literally (see [`PatternOpts::with_literal`]), so the resulting pattern
+    /// is fully literal (see [`Pattern::is_literal`]), e.g. `weird [1] {copy}` only matches a component with this exact name.
+    ///
+    /// Parent directory (`..`) components are resolved lexically, e.g. `./a/../b` gives `b`, while leading ones
+    /// are kept for relative paths (e.g. `../a`) and discarded for absolute ones. Note that this doesn't follow symbolic links.
+    ///
+    /// Fails with [`PatternError::UnsupportedPath`] if the path has an unsupported prefix (e.g. `\\server\share`)
+    /// or isn't valid UTF-8.
+    pub fn from_literal_path(path: &Path) -> Result<Self, PatternError> {
+        let unsupported = || PatternError::UnsupportedPath(path.to_owned());
+
+        let components = normalize_path(path).map_err(|_| unsupported())?;
+
+        let mut normalized = NormalizedPath::new(components.prefix());
+
+        for component in components.components() {
+            normalized.push(component.clone());
+        }
+
+        let mut source = match normalized.prefix() {
+            None => String::new(),
+            Some(PathPrefix::RootDir) => "/".to_owned(),
+            Some(PathPrefix::WindowsDrive(drive)) => format!("{}:/", drive.uppercase_letter()),
+        };
+
+        for (i, component) in normalized.components().iter().enumerate() {
+            let component = component.to_str().ok_or_else(unsupported)?;
+
+            if i > 0 {
+                source.push('/');
+            } else if normalized.prefix().is_none() && component.as_bytes().get(1) == Some(&b':') {
+                // Ensure a first component looking like a drive (e.g. `C:`) isn't mistaken for one
+                source.push_str("./");
+            }
+
+            source.push_str(component);
+        }
+
+        Self::literal(&source)
+    }
+
+    /// Escape all special characters of a string, except `/`
+    ///
+    /// The result matches the string literally, e.g. to embed user-provided names in larger patterns:
+    /// for instance, `what?.txt` is escaped into `what[\?].txt`, which only matches `what?.txt`.
+    /// Special characters are escaped inside brackets, so the result can be parsed with or without
+    /// [`PatternOpts::with_backslash_escapes`] (but not with [`PatternOpts::with_disable_brackets`]).
+    /// `:` is escaped too, so strings such as `c:` aren't mistaken for Windows drives,
+    /// and so are `(` and `)`, which delimit operators with [`PatternOpts::with_extglob`].
+    ///
+    /// `/` is kept as a separator, so paths can be escaped as a whole.
+    /// `\` can't be escaped inside brackets, so it's escaped as `\\` instead, which means it's only matched
+    /// as an ordinary character with [`PatternOpts::with_backslash_escapes`] (and [`PatternOpts::with_slash_only`] in matched paths).
+    /// For the same reason, strings containing `\` can't be escaped for patterns using [`PatternOpts::with_literal_backslashes`] alone.
+    ///
+    /// The result can't be parsed with [`PatternOpts::with_glob_compat`] either, as the `glob` crate's syntax has no escape
+    /// sequences inside brackets: use [`Pattern::escape_glob_compat`] instead.
+    pub fn escape(str: &str) -> String {
+        let mut escaped = String::with_capacity(str.len());
+
+        for c in str.chars() {
+            match c {
+                '/' => escaped.push(c),
+                '\\' => escaped.push_str("\\\\"),
+                _ if is_special(c) || matches!(c, ':' | '(' | ')') => {
+                    escaped.push_str("[\\");
+                    escaped.push(c);
+                    escaped.push(']');
+                }
+                _ => escaped.push(c),
+            }
+        }
+
+        escaped
+    }
+
+    /// Escape all special characters of a string for the `glob` crate's syntax, except `/`
+    /// (see [`Pattern::new_glob_compat`])
+    ///
+    /// Like with the `glob` crate, special characters are escaped using brackets, e.g. `what?.txt` is escaped into `what[?].txt`.
+    /// `:` is escaped too, so strings such as `c:` aren't mistaken for Windows drives.
+    /// `\` is kept as-is, as it's either an ordinary character or a separator (see [`Pattern::new_glob_compat`]).
+    ///
+    /// The result must be parsed with [`PatternOpts::with_glob_compat`], see [`Pattern::escape`] otherwise.
+    pub fn escape_glob_compat(str: &str) -> String {
+        let mut escaped = String::with_capacity(str.len());
+
+        for c in str.chars() {
+            match c {
+                '*' | '?' | '[' | ':' => {
+                    escaped.push('[');
+                    escaped.push(c);
+                    escaped.push(']');
+                }
+                _ => escaped.push(c),
+            }
+        }
+
+        escaped
+    }
+
+    /// Parse a pattern as much as possible, recovering from syntax errors
+    ///
+    /// This is intended for e.g. editors, to show which parts of a partially-written pattern are understood.
+    /// Components that fail to parse are skipped up to the next separator and marked as [`crate::syntax::ComponentKind::Invalid`],
+    /// while all errors are collected in order instead of stopping at the first one.
+    ///
+    /// The structure is [`None`] if the pattern's prefix is invalid (e.g. `\\?\`), as it can't be interpreted then,
+    /// or if groups are nested too deeply (see [`Pattern`]).
+    /// Only options related to the syntax are used (e.g. [`PatternOpts::with_backslash_escapes`]), and the pattern isn't compiled,
+    /// so only [`PatternError::Parsing`] errors are returned.
+    pub fn parse_partial(
+        input: &str,
+        opts: PatternOpts,
+    ) -> (Option<PatternAst>, Vec<PatternError>) {
+        let (ast, errors) = parse_recovering(input, opts.parser_opts());
+
+        (
+            ast,
+            errors
+                .iter()
+                .map(|err| PatternError::Parsing(ParseError::from_parsing(err)))
+                .collect(),
+        )
+    }
+
+    /// Parse a pattern
+    pub fn new_with_opts(input: &str, opts: PatternOpts) -> Result<Self, PatternError> {
+        Self::build(input, opts, None, BuildPurpose::Matching)
+    }
+
+    /// (Internal) Parse a pattern, optionally forcing the case sensitivity of all its components
+    ///
+    /// Components are compiled differently depending on what the pattern is built for (see [`BuildPurpose`])
+    fn build(
+        input: &str,
+        opts: PatternOpts,
+        force_case_insensitive: Option<bool>,
+        purpose: BuildPurpose,
+    ) -> Result<Self, PatternError> {
+        let PatternOpts {
+            case_insensitive,
+            smart_case,
+            case_folding,
+            case_insensitive_fs,
+            unicode_normalization,
+            fold_diacritics,
+            ascii_only,
+            star_matches_separators,
+            reject_foreign_prefixes,
+            require_literal_leading_dot,
+            globstar_skips_hidden,
+            match_basename,
+            slash_only: _,
+            backslash_escapes: _,
+            literal_backslashes: _,
+            glob_compat: _,
+            disable_alternates: _,
+            disable_brackets: _,
+            disable_globstar: _,
+            literal: _,
+            normalize_dotdot: _,
+            inline_flags: _,
+            extglob: _,
+            max_components,
+            max_complexity,
+            regex_size_limit,
+            bypass_regex_cache,
+        } = opts;
+
+        let RawPattern {
+            case_insensitive: inline_case_insensitive,
+            components,
+            prefix,
+            dir_only,
+            has_separator,
+        } = parse_pattern(input, opts.parser_opts())
+            .map_err(|err| PatternError::Parsing(ParseError::from_parsing(&err)))?;
+
+        // Inline flags take precedence over the provided options
+        let (case_insensitive, smart_case) =
+            match force_case_insensitive.or(inline_case_insensitive) {
+                Some(case_insensitive) => (case_insensitive, false),
+                None => (case_insensitive, smart_case),
+            };
+
+        if let Some(max) = max_complexity {
+            let complexity = components
+                .iter()
+                .map(|component| component.data.complexity())
+                .sum();
+
+            if complexity > max {
+                return Err(PatternError::TooComplex { complexity, max });
+            }
+        }
+
+        let portability = Portability::of_prefix(prefix);
+
+        if reject_foreign_prefixes && !portability.supports_current_platform() {
+            return Err(PatternError::ForeignPrefix(portability));
+        }
+
+        let mut stats = PatternStats {
+            components: components.len(),
+            absolute: prefix.is_some(),
+            ..Default::default()
+        };
+
+        let mut capture_counts = components
+            .iter()
+            .map(|component| Some(capture_count(&component.data)))
+            .collect::<Vec<_>>();
+
+        let mut component_spans = components
+            .iter()
+            .map(|Span { at, data: _ }| Some(at.start.offset..at.start.offset + at.len))
+            .collect::<Vec<_>>();
+
+        // Compile each individual comopnent
+        let (mut components, (mut literal_matchers, mut component_literals)): (
+            Vec<_>,
+            (Vec<_>, Vec<_>),
+        ) = components
+            .into_iter()
+            .map(|Span { at, mut data }| {
+                let source = &input[at.start.offset..at.start.offset + at.len];
+
+                if fold_diacritics {
+                    data.map_literals(
+                        &|lit| (!lit.is_ascii()).then(|| strip_diacritics(lit.chars()).collect()),
+                        &|c| {
+                            let mut stripped = strip_diacritics([c].into_iter());
+
+                            // Characters inside brackets must remain single characters
+                            match (stripped.next(), stripped.next()) {
+                                (Some(stripped), None) => Some(stripped),
+                                _ => None,
+                            }
+                        },
+                    );
+                } else if unicode_normalization == UnicodeNormalization::Nfc {
+                    data.map_literals(&|lit| (!is_nfc(lit)).then(|| lit.nfc().collect()), &|_| {
+                        None
+                    });
+                }
+
+                let case_insensitive = if smart_case {
+                    !source.chars().any(char::is_uppercase)
+                } else {
+                    case_insensitive
+                };
+
+                stats.add_component(&data, case_insensitive);
+
+                // Case-insensitive components may match any casing of their literals,
+                // which can only be found by reading directories on case-sensitive filesystems.
+                // The same goes for normalized components, which may match names stored in any form.
+                let literal_matchers = if (case_insensitive
+                    && !case_insensitive_fs
+                    && !matches!(data, RawComponent::ParentDir))
+                    || ((unicode_normalization != UnicodeNormalization::None || fold_diacritics)
+                        && !source.is_ascii())
+                {
+                    None
+                } else {
+                    data.literal_matchers()
+                };
+
+                // Transformed paths may not contain the pattern's literals as they are
+                let literals =
+                    if unicode_normalization != UnicodeNormalization::None || fold_diacritics {
+                        ComponentLiterals::Unknown
+                    } else {
+                        ComponentLiterals::of(&data, case_insensitive)
+                    };
+
+                let component = compile_component(
+                    data,
+                    CompileOpts {
+                        case_sensitivity: if case_insensitive {
+                            CaseSensitivity::Insensitive(if ascii_only {
+                                CaseFolding::Ascii
+                            } else {
+                                case_folding
+                            })
+                        } else {
+                            CaseSensitivity::Sensitive
+                        },
+                        regex_size_limit: match purpose {
+                            BuildPurpose::Matching | BuildPurpose::Capturing => regex_size_limit,
+                            BuildPurpose::Exporting => None,
+                        },
+                        star_matches_separators,
+                        require_literal_leading_dot,
+                        ascii_only,
+                        bypass_regex_cache: bypass_regex_cache
+                            || purpose == BuildPurpose::Exporting,
+                        captures: purpose == BuildPurpose::Capturing,
+                        exportable: purpose == BuildPurpose::Exporting,
+                    },
+                )
+                .map_err(|CompileError(err)| PatternError::Compilation {
+                    component: source.to_owned(),
+                    offset: at.start.offset,
+                    err,
+                })?;
+
+                Ok((component, (literal_matchers, literals)))
+            })
+            .collect::<Result<Vec<_>, _>>()?
+            .into_iter()
+            .unzip();
+
+        // Match basenames at any depth through an implicit `**/` prefix
+        if match_basename
+            && !has_separator
+            && matches!(
+                components.first(),
+                Some(
+                    Component::Literal(_)
+                        | Component::CaselessLiteral(_, _)
+                        | Component::Regex(_)
+                        | Component::Spanning(_)
+                        | Component::Negated(_)
+                )
+            )
+        {
+            components.insert(0, Component::Wildcard);
+            literal_matchers.insert(0, None);
+            capture_counts.insert(0, None);
+            component_spans.insert(0, None);
+            component_literals.insert(0, ComponentLiterals::Wildcard);
+        }
+
+        let (literal_prefix, literal_suffix) = build_literal_affixes(prefix, &component_literals);
+
+        Ok(Self {
+            common_root_dir: build_common_root_dir(prefix, &components, &literal_matchers),
+            prefix,
+            literal_skeleton: literal_matchers
+                .into_iter()
+                .map_while(|matchers| matchers)
+                .collect(),
+            wildcard_at_or_after: wildcard_at_or_after(&components),
+            components,
+            dir_only,
+            stats,
+            normalize_opts: opts.normalize_opts(),
+            wildcard_skips_hidden: require_literal_leading_dot || globstar_skips_hidden,
+            case_insensitive_fs,
+            max_components,
+            unicode_normalization,
+            fold_diacritics,
+            source: input.into(),
+            opts,
+            uniform_case_insensitive: (!smart_case).then_some(case_insensitive),
+            case_variants: Default::default(),
+            capture_counts,
+            capturing_variant: OnceLock::new(),
+            component_spans,
+            component_literals,
+            literal_prefix,
+            literal_suffix,
+        })
+    }
+
+    /// Get the string the pattern was parsed from, exactly as it was provided
+    ///
+    /// This is also what the pattern's [`fmt::Display`] implementation prints.
+    pub fn as_str(&self) -> &str {
+        &self.source
+    }
+
+    /// Get the options the pattern was built with
+    ///
+    /// Building a pattern from its source with these options results in the same pattern (see [`Pattern::as_str`]).
+    pub fn opts(&self) -> PatternOpts {
+        self.opts
+    }
+
+    /// Build the pattern again from its source, with other options
+    ///
+    /// This is useful to change a single option of an existing pattern:
+    ///
+    /// ```
+    /// use std::path::Path;
+    ///
+    /// use globby::Pattern;
+    ///
+    /// let pattern = Pattern::new("src/*.rs").unwrap();
+    /// let pattern = pattern.with_opts(pattern.opts().with_case_insensitive(true)).unwrap();
+    ///
+    /// assert!(pattern.is_match(Path::new("SRC/main.rs")));
+    /// ```
+    ///
+    /// Fails if the source isn't a valid pattern with the new options (e.g. when disabling [`PatternOpts::with_normalize_dotdot`]).
+    pub fn with_opts(&self, opts: PatternOpts) -> Result<Pattern, PatternError> {
+        Self::new_with_opts(&self.source, opts)
+    }
+
+    /// Check if the pattern is absolute (only matches absolute paths)
+    pub fn is_absolute(&self) -> bool {
+        self.prefix.is_some()
+    }
+
+    /// Get the path prefix
+    pub fn prefix(&self) -> Option<PathPrefix> {
+        self.prefix
+    }
+
+    /// Get the platforms this pattern can match paths on
+    ///
+    /// Patterns starting with a Windows drive (e.g. `C:\**`) can only match on Windows,
+    /// while other patterns can match on any platform.
+    pub fn portability(&self) -> Portability {
+        Portability::of_prefix(self.prefix)
+    }
+
+    /// (Internal) Get the pattern's first component if it only matches a single name, byte for byte
+    ///
+    /// Patterns transforming the paths they match (see [`PatternOpts::with_unicode_normalization`]
+    /// and [`PatternOpts::with_fold_diacritics`]) never have one, as names can't be compared directly.
+    pub(crate) fn leading_literal(&self) -> Option<&str> {
+        if self.unicode_normalization != UnicodeNormalization::None || self.fold_diacritics {
+            return None;
+        }
+
+        match self.components.first() {
+            Some(Component::Literal(lit)) => Some(lit),
+            _ => None,
+        }
+    }
+
+    /// Check if the pattern is fully literal, meaning it can only match a single path (see [`Pattern::as_literal_path`])
+    ///
+    /// This is the case of patterns without any wildcard, brackets or group, e.g. `/etc/hosts` or `../src`.
+    /// Case-insensitive components are not literal, as they match any casing of their name.
+    pub fn is_literal(&self) -> bool {
+        self.components
+            .iter()
+            .all(|component| matches!(component, Component::Literal(_) | Component::ParentDir))
+    }
+
+    /// Get the path a fully literal pattern matches (see [`Pattern::is_literal`]), including its prefix
+    ///
+    /// This allows e.g. checking if the path exists instead of walking directories.
+    /// Unlike [`Pattern::common_root_dir`], the last component is included, so `/etc/hosts` gives `/etc/hosts`.
+    ///
+    /// Note that patterns ending with a separator still only match directories (see [`Pattern::is_dir_only`]).
+    /// Returns [`None`] if the pattern isn't literal, or if it can't match anything because of [`PatternOpts::with_max_components`].
+    pub fn as_literal_path(&self) -> Option<PathBuf> {
+        let within_limit = self
+            .max_components
+            .is_none_or(|max| self.components.len() <= max);
+
+        if !self.is_literal() || !within_limit {
+            return None;
+        }
+
+        let components = self
+            .components
+            .iter()
+            .map(|component| match component {
+                Component::Literal(lit) => lit.as_str(),
+                Component::ParentDir => "..",
+                Component::CaselessLiteral(_, _)
+                | Component::Regex(_)
+                | Component::Spanning(_)
+                | Component::Negated(_)
+                | Component::Wildcard => unreachable!(),
+            })
+            .collect::<Vec<_>>();
+
+        Some(join_root_dir(self.prefix, &components))
+    }
+
+    /// (Internal) Get the path this pattern matches, if it only matches a single one that may be walked to
+    pub(crate) fn literal_path(&self) -> Option<PathBuf> {
+        // Patterns made only of `..` components never yield anything when walking
+        if !matches!(self.components.last(), Some(Component::Literal(_))) {
+            return None;
+        }
+
+        self.as_literal_path()
+    }
+
+    /// Rewrite the pattern without its redundant constructs, returning [`None`] if it has none
+    ///
+    /// The simplified pattern matches exactly the same paths, and is built with the same options. Simplifications are:
+    /// * Consecutive `**` components are collapsed, e.g. `**/**/*.rs` into `**/*.rs`
+    /// * `.` and empty components are removed, e.g. `a/./b` and `a//b` into `a/b`
+    /// * `..` components resolved with [`PatternOpts::with_normalize_dotdot`] are removed along with the preceding component
+    /// * Duplicate alternates are removed from groups, and groups left with a single alternate are inlined,
+    ///   e.g. `{a|a}.rs` into `a.rs` and `{a|b|a}` into `{a|b}`
+    /// * Adjacent literals and `*` wildcards are merged
+    ///
+    /// Parts of the pattern that can't be simplified are kept as they are written.
+    /// As groups may be inlined or lose alternates, captures of the simplified pattern may differ (see [`Pattern::match_captures`]).
+    ///
+    /// Getting [`Some`] indicates that the pattern contains redundant constructs, e.g. for linters to report them.
+    pub fn simplify(&self) -> Option<Pattern> {
+        let source = simplify_source(
+            &self.source,
+            self.opts.parser_opts(),
+            self.opts.match_basename,
+        )?;
+
+        Some(
+            Self::new_with_opts(&source, self.opts)
+                .expect("simplified patterns should be valid with the same options"),
+        )
+    }
+
+    /// Check if all paths matched by this pattern are also matched by another one
+    ///
+    /// This is a conservative check over the compiled components of both patterns, which may not be able to conclude.
+    /// [`Comparison::Yes`] is only returned when this can be proven structurally (e.g. a literal is matched by a `*` wildcard,
+    /// or a sequence of components by a `**` one), and [`Comparison::No`] when a path matched by this pattern
+    /// but not by the other one was found.
+    ///
+    /// Examples:
+    /// * `src/*.rs` is a subset of `src/**`
+    /// * `a/*/b` is a subset of `a/**/b`, but `a/**/b` isn't a subset of `a/*/b` as it matches `a/b`
+    /// * `[ab].rs` is a subset of `[abc].rs`, but this can't be proven so [`Comparison::Unknown`] is returned
+    pub fn matches_subset_of(&self, other: &Pattern) -> Comparison {
+        if self == other || self.is_structural_subset_of(other) {
+            Comparison::Yes
+        } else if self.find_witness_against(other) {
+            Comparison::No
+        } else {
+            Comparison::Unknown
+        }
+    }
+
+    /// Check if this pattern matches exactly the same paths as another one
+    ///
+    /// Both patterns must match a subset of each other's paths (see [`Pattern::matches_subset_of`]).
+    pub fn is_equivalent_to(&self, other: &Pattern) -> Comparison {
+        match (self.matches_subset_of(other), other.matches_subset_of(self)) {
+            (Comparison::Yes, Comparison::Yes) => Comparison::Yes,
+            (Comparison::No, _) | (_, Comparison::No) => Comparison::No,
+            _ => Comparison::Unknown,
+        }
+    }
+
+    /// Prove that all paths matched by this pattern are matched by another one, by comparing their components
+    fn is_structural_subset_of(&self, other: &Pattern) -> bool {
+        // Paths must be transformed the same way, and components can't span multiple path components
+        if self.prefix != other.prefix
+            || self.normalize_opts != other.normalize_opts
+            || self.unicode_normalization != other.unicode_normalization
+            || self.fold_diacritics != other.fold_diacritics
+            || self.opts.star_matches_separators
+            || other.opts.star_matches_separators
+            || (other.dir_only && !self.dir_only)
+        {
+            return false;
+        }
+
+        if let Some(max) = other.max_components
+            && self.depth_bounds().1.is_none_or(|depth| depth > max)
+        {
+            return false;
+        }
+
+        let (len, other_len) = (self.components.len(), other.components.len());
+
+        // Does the pattern's components from index `i` match a subset of the other's components from index `j`?
+        let mut subsets = vec![false; (len + 1) * (other_len + 1)];
+        let at = |i: usize, j: usize| i * (other_len + 1) + j;
+
+        subsets[at(len, other_len)] = true;
+
+        for i in (0..=len).rev() {
+            for j in (0..=other_len).rev() {
+                if i == len && j == other_len {
+                    continue;
+                }
+
+                subsets[at(i, j)] = match other.components.get(j) {
+                    // `**` may match no component at all, or also match the first component of the rest
+                    Some(Component::Wildcard) => {
+                        subsets[at(i, j + 1)]
+                            || (i < len
+                                && self.is_absorbed_by_wildcard(i, other)
+                                && subsets[at(i + 1, j)])
+                    }
+
+                    Some(_) if i < len => {
+                        self.is_component_subset(i, other, j) && subsets[at(i + 1, j + 1)]
+                    }
+
+                    _ => false,
+                };
+            }
+        }
+
+        subsets[at(0, 0)]
+    }
+
+    /// Check if all path components matched by the component at index `i` are matched by a `**` of another pattern
+    fn is_absorbed_by_wildcard(&self, i: usize, other: &Pattern) -> bool {
+        if !other.wildcard_skips_hidden {
+            return true;
+        }
+
+        match &self.components[i] {
+            Component::Wildcard => self.wildcard_skips_hidden,
+            Component::Literal(lit) | Component::CaselessLiteral(lit, _) => !lit.starts_with('.'),
+            Component::ParentDir => false,
+            Component::Regex(_) | Component::Spanning(_) | Component::Negated(_) => {
+                match &self.component_literals[i] {
+                    ComponentLiterals::Exact(start)
+                    | ComponentLiterals::Edges { start, end: _ }
+                    | ComponentLiterals::Glob { start, end: _ } => start.starts_with(|c| c != '.'),
+                    ComponentLiterals::Wildcard | ComponentLiterals::Unknown => false,
+                }
+            }
+        }
+    }
+
+    /// Check if all path components matched by the component at index `i` are matched by the one of another pattern at index `j`
+    fn is_component_subset(&self, i: usize, other: &Pattern, j: usize) -> bool {
+        let other_component = &other.components[j];
+
+        match (&self.components[i], other_component) {
+            (Component::Wildcard, _) | (_, Component::Wildcard) => false,
+            (Component::ParentDir, _) => other.component_matches(other_component, b".."),
+            (Component::Literal(lit), _) => {
+                other.component_matches(other_component, lit.as_bytes())
+            }
+
+            (
+                Component::CaselessLiteral(lit, folding),
+                Component::CaselessLiteral(other_lit, other_folding),
+            ) => lit == other_lit && folding == other_folding,
+
+            (Component::Regex(regex), Component::Regex(other_regex))
+                if regex.as_str() == other_regex.as_str() =>
+            {
+                true
+            }
+
+            (Component::Regex(_), Component::Regex(_)) => {
+                let ComponentLiterals::Glob {
+                    start: other_start,
+                    end: other_end,
+                } = &other.component_literals[j]
+                else {
+                    return false;
+                };
+
+                // The literals of `self` surround a non-literal matcher, so they can't overlap
+                let (start, end) = match &self.component_literals[i] {
+                    ComponentLiterals::Exact(lit) => {
+                        if lit.len() < other_start.len() + other_end.len() {
+                            return false;
+                        }
+
+                        (lit, lit)
+                    }
+                    ComponentLiterals::Edges { start, end }
+                    | ComponentLiterals::Glob { start, end } => (start, end),
+                    ComponentLiterals::Wildcard | ComponentLiterals::Unknown => return false,
+                };
+
+                // A leading `*` may not match a leading dot
+                let leading_dot = other_start.is_empty()
+                    && other.opts.require_literal_leading_dot
+                    && !start.starts_with(|c| c != '.');
+
+                start.starts_with(other_start.as_str())
+                    && end.ends_with(other_end.as_str())
+                    && !leading_dot
+            }
+
+            _ => false,
+        }
+    }
+
+    /// Look for a path matched by this pattern but not by another one
+    ///
+    /// Candidate paths are built from the literals of each component, with `**` wildcards matching up to two components.
+    fn find_witness_against(&self, other: &Pattern) -> bool {
+        /// Maximum number of candidate paths
+        const MAX_CANDIDATES: usize = 64;
+
+        let mut candidates = vec![Vec::<String>::new()];
+
+        for (i, component) in self.components.iter().enumerate() {
+            let names = match component {
+                Component::Wildcard => {
+                    let mut extended = vec![];
+
+                    for candidate in &candidates {
+                        for count in 0..=2 {
+                            let mut candidate = candidate.clone();
+                            candidate.extend(std::iter::repeat_n("x".to_owned(), count));
+                            extended.push(candidate);
+                        }
+                    }
+
+                    candidates = extended;
+                    candidates.truncate(MAX_CANDIDATES);
+                    continue;
+                }
+
+                Component::Literal(lit) | Component::CaselessLiteral(lit, _) => vec![lit.clone()],
+                Component::ParentDir => vec!["..".to_owned()],
+
+                Component::Regex(_) | Component::Spanning(_) | Component::Negated(_) => {
+                    let (start, end) = match &self.component_literals[i] {
+                        ComponentLiterals::Exact(lit) => (lit.as_str(), ""),
+                        ComponentLiterals::Edges { start, end }
+                        | ComponentLiterals::Glob { start, end } => (start.as_str(), end.as_str()),
+                        ComponentLiterals::Wildcard | ComponentLiterals::Unknown => return false,
+                    };
+
+                    ["", "x", "0", "."]
+                        .into_iter()
+                        .map(|middle| format!("{start}{middle}{end}"))
+                        .filter(|name| self.component_matches(component, name.as_bytes()))
+                        .collect()
+                }
+            };
+
+            if names.is_empty() {
+                return false;
+            }
+
+            candidates = candidates
+                .iter()
+                .flat_map(|candidate| {
+                    names.iter().map(|name| {
+                        let mut candidate = candidate.clone();
+                        candidate.push(name.clone());
+                        candidate
+                    })
+                })
+                .take(MAX_CANDIDATES)
+                .collect();
+        }
+
+        candidates.iter().any(|candidate| {
+            let path = join_root_dir(self.prefix, candidate);
+
+            self.is_match_dir_aware(&path, self.dir_only)
+                && !other.is_match_dir_aware(&path, self.dir_only)
+        })
+    }
+
+    /// Append a relative pattern to this one, e.g. `crates/*/src` and `**/*.rs` into `crates/*/src/**/*.rs`
+    ///
+    /// Compiled components of both patterns are reused, so nothing is parsed again. The resulting pattern
+    /// is equal to the one built from both sources joined with a separator (see [`Pattern::as_str`]),
+    /// and only matches directories if `other` does (see [`Pattern::is_dir_only`]).
+    ///
+    /// Fails if `other` is absolute, or starts with `..` components while this pattern isn't only made of them.
+    /// Both patterns must also be built with the same options, and neither may depend on being a whole pattern,
+    /// i.e. start with an inline flag (see [`PatternOpts::with_inline_flags`]) or match basenames at any depth
+    /// (see [`PatternOpts::with_match_basename`]).
+    ///
+    /// [`PatternOpts::with_max_complexity`] only applies to each pattern on its own.
+    pub fn join(&self, other: &Pattern) -> Result<Pattern, JoinError> {
+        if other.prefix.is_some() {
+            return Err(JoinError::Absolute);
+        }
+
+        if self.opts != other.opts
+            || [self, other].into_iter().any(|pattern| {
+                pattern.capture_counts.first() == Some(&None) || pattern.inline_flag().is_some()
+            })
+        {
+            return Err(JoinError::IncompatibleOptions);
+        }
+
+        if matches!(other.components.first(), Some(Component::ParentDir))
+            && (self.prefix.is_some()
+                || !self
+                    .components
+                    .iter()
+                    .all(|component| matches!(component, Component::ParentDir)))
+        {
+            return Err(JoinError::ParentDir);
+        }
+
+        // Joining to e.g. `.` doesn't change anything
+        if self.prefix.is_none() && self.components.is_empty() {
+            return Ok(other.clone());
+        }
+
+        if other.components.is_empty() {
+            return Ok(self.clone());
+        }
+
+        let mut source = self.source.to_string();
+
+        let ends_with_separator = source.ends_with('/')
+            || (source.ends_with('\\')
+                && !self.opts.slash_only
+                && (!self.opts.backslash_escapes || self.opts.glob_compat)
+                && !self.opts.literal_backslashes);
+
+        if !ends_with_separator {
+            source.push('/');
+        }
+
+        let offset = source.len();
+        source.push_str(&other.source);
+
+        let components = self
+            .components
+            .iter()
+            .chain(&other.components)
+            .cloned()
+            .collect::<Vec<_>>();
+
+        let mut literal_skeleton = self.literal_skeleton.clone();
+
+        if literal_skeleton.len() == self.components.len() {
+            literal_skeleton.extend(other.literal_skeleton.iter().cloned());
+        }
+
+        let literal_matchers = literal_skeleton
+            .iter()
+            .cloned()
+            .map(Some)
+            .collect::<Vec<_>>();
+
+        let mut stats = self.stats.clone();
+        stats.merge(&other.stats);
+
+        let component_literals = self
+            .component_literals
+            .iter()
+            .chain(&other.component_literals)
+            .cloned()
+            .collect::<Vec<_>>();
+
+        let (literal_prefix, literal_suffix) =
+            build_literal_affixes(self.prefix, &component_literals);
+
+        Ok(Self {
+            common_root_dir: build_common_root_dir(self.prefix, &components, &literal_matchers),
+            prefix: self.prefix,
+            literal_skeleton,
+            wildcard_at_or_after: wildcard_at_or_after(&components),
+            components,
+            dir_only: other.dir_only,
+            stats,
+            normalize_opts: self.normalize_opts,
+            wildcard_skips_hidden: self.wildcard_skips_hidden,
+            case_insensitive_fs: self.case_insensitive_fs,
+            max_components: self.max_components,
+            unicode_normalization: self.unicode_normalization,
+            fold_diacritics: self.fold_diacritics,
+            source: source.into(),
+            opts: self.opts,
+            uniform_case_insensitive: self.uniform_case_insensitive,
+            case_variants: Default::default(),
+            capture_counts: self
+                .capture_counts
+                .iter()
+                .chain(&other.capture_counts)
+                .copied()
+                .collect(),
+            capturing_variant: OnceLock::new(),
+            component_spans: self
+                .component_spans
+                .iter()
+                .cloned()
+                .chain(other.component_spans.iter().map(|span| {
+                    span.as_ref()
+                        .map(|span| span.start + offset..span.end + offset)
+                }))
+                .collect(),
+            component_literals,
+            literal_prefix,
+            literal_suffix,
+        })
+    }
+
+    /// Anchor a relative pattern to a base directory, e.g. `src/**/*.rs` and `/home/me/project` into `/home/me/project/src/**/*.rs`
+    ///
+    /// The base directory is normalized first, with its `..` components resolved lexically (e.g. `/a/b/../c` becomes `/a/c`),
+    /// and so are the pattern's leading `..` components, e.g. `../shared/*.proto` based in `/a/b` becomes `/a/shared/*.proto`.
+    /// The base's components are then matched literally, and using the same options as the pattern
+    /// (e.g. case-insensitively with [`PatternOpts::with_case_insensitive`]). See [`Pattern::join`] for the other constraints.
+    ///
+    /// With an absolute base (e.g. `/dir` or `C:\dir`), the resulting pattern is absolute and its [`Pattern::common_root_dir`]
+    /// includes the base, up to its first component containing special characters (which are escaped like with [`Pattern::escape`]).
+    /// Relative bases are accepted as well, resulting in a relative pattern.
+    ///
+    /// Fails with [`JoinError::Absolute`] if the pattern is already absolute, as it can't be anchored somewhere else,
+    /// with [`JoinError::IncompatibleOptions`] if it starts with an inline flag (see [`PatternOpts::with_inline_flags`]),
+    /// and with [`JoinError::InvalidBase`] if the base can't be matched literally (see its documentation).
+    pub fn rebase(&self, base: &Path) -> Result<Pattern, JoinError> {
+        if self.prefix.is_some() {
+            return Err(JoinError::Absolute);
+        }
+
+        // Inline flags only apply to whole patterns, like with [`Pattern::join`]
+        if self.inline_flag().is_some() {
+            return Err(JoinError::IncompatibleOptions);
+        }
+
+        let normalized =
+            normalize_path_with(base, self.normalize_opts).map_err(|_| JoinError::InvalidBase)?;
+
+        let mut resolved = NormalizedPath::new(normalized.prefix());
+
+        for component in normalized.components() {
+            resolved.push(component.clone());
+        }
+
+        // Resolve the pattern's leading `..` components against the base
+        let parent_dirs = self.parent_dirs();
+
+        for _ in 0..parent_dirs {
+            resolved.push("..".into());
+        }
+
+        let mut source = match resolved.prefix() {
+            None => String::new(),
+            Some(PathPrefix::RootDir) => "/".to_owned(),
+            Some(PathPrefix::WindowsDrive(drive)) => format!("{}:/", drive.uppercase_letter()),
+        };
+
+        for (i, component) in resolved.components().iter().enumerate() {
+            let component = component.to_str().ok_or(JoinError::InvalidBase)?;
+
+            if i > 0 {
+                source.push('/');
+            }
+
+            if self.opts.literal {
+                source.push_str(component);
+                continue;
+            }
+
+            for c in component.chars() {
+                match c {
+                    // The `glob` crate's syntax only has brackets to escape characters
+                    '*' | '?' | '[' | ':' if self.opts.glob_compat => {
+                        if self.opts.disable_brackets {
+                            return Err(JoinError::InvalidBase);
+                        }
+
+                        source.push('[');
+                        source.push(c);
+                        source.push(']');
+                    }
+                    _ if self.opts.glob_compat && c != '\\' => source.push(c),
+                    '\\' if self.opts.backslash_escapes && !self.opts.glob_compat => {
+                        source.push_str("\\\\")
+                    }
+                    '\\' if self.opts.literal_backslashes => source.push(c),
+                    // Names may only contain it with [`PatternOpts::with_slash_only`], but it's still a separator in patterns
+                    '\\' => return Err(JoinError::InvalidBase),
+                    // Parentheses could otherwise form extended glob operators (e.g. `+(`)
+                    _ if is_special(c)
+                        || c == ':'
+                        || (self.opts.extglob && matches!(c, '(' | ')')) =>
+                    {
+                        if self.opts.disable_brackets {
+                            return Err(JoinError::InvalidBase);
+                        }
+
+                        source.push_str("[\\");
+                        source.push(c);
+                        source.push(']');
+                    }
+                    _ => source.push(c),
+                }
+            }
+        }
+
+        let base = Self::new_with_opts(&source, self.opts).map_err(|_| JoinError::InvalidBase)?;
+
+        if parent_dirs == 0 {
+            return base.join(self);
+        }
+
+        let rest = match self.component_spans.get(parent_dirs) {
+            Some(span) => &self.source[span.clone().unwrap().start..],
+            None => "",
+        };
+
+        let rest =
+            Self::new_with_opts(rest, self.opts).map_err(|_| JoinError::IncompatibleOptions)?;
+
+        base.join(&rest)
+    }
+
+    /// Split the pattern after its first `components` components, e.g. `crates/*/src/**/*.rs` after 2 components
+    /// into `crates/*` and `src/**/*.rs`
+    ///
+    /// A path matches the pattern if its first `components` components match the first returned pattern
+    /// and the remaining ones match the second one, which is relative. This allows finding directories with the former
+    /// and then matching their content with the latter. Both patterns are built again with the same options,
+    /// so e.g. their [`Pattern::common_root_dir`] is computed for each of them, and only the second one may be directory-only.
+    /// The only exception is [`PatternOpts::with_max_components`], which is capped to `components` for the first pattern
+    /// and reduced by `components` for the second one.
+    ///
+    /// Returns [`None`] if either part would be empty, or if the first part may match a variable number of path components,
+    /// i.e. if it contains `**` (including the implicit one of [`PatternOpts::with_match_basename`])
+    /// or `*` wildcards with [`PatternOpts::with_star_matches_separators`].
+    /// Also returns [`None`] if either part can't be built again on its own.
+    pub fn split_prefix(&self, components: usize) -> Option<(Pattern, Pattern)> {
+        if components == 0
+            || components >= self.components.len()
+            || self.components[..components]
+                .iter()
+                .any(|component| matches!(component, Component::Wildcard | Component::Spanning(_)))
+        {
+            return None;
+        }
+
+        let head_end = self.component_spans[components - 1].clone()?.end;
+        let tail_start = self.component_spans[components].clone()?.start;
+
+        // Inline flags apply to the whole pattern, so both parts need them
+        let flag = self.inline_flag().unwrap_or("");
+
+        let mut head = self.source[..head_end].to_owned();
+        let mut tail = format!("{flag}{}", &self.source[tail_start..]);
+
+        // Patterns without separators are matched against basenames, so one must be added
+        // Parent directory components can't follow a `.` one, and are never matched against basenames anyway
+        if self.opts.match_basename {
+            if components == 1
+                && self.prefix.is_none()
+                && !matches!(self.components[0], Component::ParentDir)
+            {
+                head.insert_str(flag.len(), "./");
+            }
+
+            if self.components.len() - components == 1
+                && !self.dir_only
+                && !matches!(self.components[components], Component::ParentDir)
+            {
+                tail.insert_str(flag.len(), "./");
+            }
+        }
+
+        let (mut head_opts, mut tail_opts) = (self.opts, self.opts);
+
+        if let Some(max) = self.opts.max_components {
+            head_opts.max_components = Some(max.min(components));
+            tail_opts.max_components = Some(max.saturating_sub(components));
+        }
+
+        Some((
+            Self::new_with_opts(&head, head_opts).ok()?,
+            Self::new_with_opts(&tail, tail_opts).ok()?,
+        ))
+    }
+
+    /// Check if the pattern only matches directories, i.e. if it ends with a path separator
+    ///
+    /// As matching a path doesn't access the filesystem, this is ignored by e.g. [`Pattern::is_match`].
+    /// Use [`Pattern::is_match_dir_aware`] to take it into account, while the [`crate::Walker`] does it automatically.
+    pub fn is_dir_only(&self) -> bool {
+        self.dir_only
+    }
+
+    /// Get statistics on the pattern's structure
+    ///
+    /// These are computed when building the pattern and only describe its syntax, not the paths it matches.
+    pub fn stats(&self) -> &PatternStats {
+        &self.stats
+    }
+
+    /// (Internal) Check if the filesystem should be assumed to be case-insensitive
+    pub(crate) fn is_case_insensitive_fs(&self) -> bool {
+        self.case_insensitive_fs
+    }
+
+    /// (Internal) Get the inline flag the pattern starts with, if any (see [`PatternOpts::with_inline_flags`])
+    fn inline_flag(&self) -> Option<&'static str> {
+        ["(?i)", "(?-i)"]
+            .into_iter()
+            .find(|flag| self.opts.inline_flags && self.source.starts_with(flag))
+    }
+
+    /// (Internal) Get the number of parent directory (`..`) components the pattern starts with
+    pub(crate) fn parent_dirs(&self) -> usize {
+        self.components
+            .iter()
+            .take_while(|component| matches!(component, Component::ParentDir))
+            .count()
+    }
+
+    /// Get the number of components in the pattern, not counting its prefix
+    ///
+    /// For instance, `/src/**/*.rs` has 3 components: `src`, `**` and `*.rs`.
+    pub fn component_count(&self) -> usize {
+        self.components.len()
+    }
+
+    /// Get the pattern's components, in order, without their prefix (see [`Pattern::prefix`])
+    ///
+    /// This describes how each path component is matched, e.g. for `/a/**/b*.{rs|toml}`:
+    /// * `a` is a [`PatternComponent::Literal`]
+    /// * `**` is a [`PatternComponent::AnyRecursive`]
+    /// * `b*.{rs|toml}` is a [`PatternComponent::Matcher`]
+    ///
+    /// For the full structure of each component, see [`Pattern::parse_partial`].
+    pub fn components(&self) -> impl Iterator<Item = PatternComponent<'_>> {
+        self.components
+            .iter()
+            .zip(&self.component_spans)
+            .map(|(component, span)| {
+                let source = span.clone().map(|span| &self.source[span]);
+
+                match component {
+                    Component::Literal(name) | Component::CaselessLiteral(name, _) => {
+                        PatternComponent::Literal {
+                            name,
+                            source: source.unwrap(),
+                        }
+                    }
+
+                    Component::ParentDir => PatternComponent::ParentDir,
+
+                    Component::Wildcard => PatternComponent::AnyRecursive {
+                        implicit: source.is_none(),
+                    },
+
+                    Component::Regex(_) | Component::Spanning(_) | Component::Negated(_) => {
+                        PatternComponent::Matcher {
+                            source: source.unwrap(),
+                        }
+                    }
+                }
+            })
+    }
+
+    /// Describe how the pattern matches paths, in a human-readable form
+    ///
+    /// The description is built from the compiled pattern, one line per item:
+    /// * The pattern's source, e.g. ``pattern `src/**/*.rs` ``
+    /// * Its prefix: `absolute, from the root directory`, ``absolute, from drive `C:` `` or `relative`
+    /// * Each component, numbered from 1:
+    ///     - ``literal `src` ``, followed by `, ignoring case` for case-insensitive literals
+    ///     - `parent directory`
+    ///     - `recursive wildcard`, followed by ` (implicit)` when added by [`PatternOpts::with_match_basename`]
+    ///     - `any single directory` (or `any single name` for the last component) for `*`
+    ///     - ``directory matching `a*` `` (or ``name matching `*.rs` `` for the last component),
+    ///       followed by `, possibly across separators` with [`PatternOpts::with_star_matches_separators`]
+    /// * `only matches directories` if the pattern ends with a separator (see [`Pattern::is_dir_only`])
+    /// * Whether descendants of matched directories may match too (see [`Pattern::has_wildcard`])
+    ///
+    /// Each item after the source is on its own line starting with `- `, e.g. for `src/**/*.rs`:
+    ///
+    /// ```
+    /// use globby::Pattern;
+    ///
+    /// assert_eq!(
+    ///     Pattern::new("src/**/*.rs").unwrap().explain(),
+    ///     "pattern `src/**/*.rs`
+    /// - relative
+    /// - component 1: literal `src`
+    /// - component 2: recursive wildcard
+    /// - component 3: name matching `*.rs`
+    /// - descendants of matches may match"
+    /// );
+    /// ```
+    pub fn explain(&self) -> String {
+        let mut explanation = format!("pattern `{}`", self.source);
+
+        match self.prefix {
+            None => explanation.push_str("\n- relative"),
+            Some(PathPrefix::RootDir) => {
+                explanation.push_str("\n- absolute, from the root directory")
+            }
+            Some(PathPrefix::WindowsDrive(drive)) => write!(
+                explanation,
+                "\n- absolute, from drive `{}:`",
+                drive.uppercase_letter()
+            )
+            .unwrap(),
+        }
+
+        let last = self.components.len().saturating_sub(1);
+
+        for (i, (component, compiled)) in self.components().zip(&self.components).enumerate() {
+            write!(explanation, "\n- component {}: ", i + 1).unwrap();
+
+            let (kind, any) = if i == last {
+                ("name", "any single name")
+            } else {
+                ("directory", "any single directory")
+            };
+
+            match component {
+                PatternComponent::Literal { name, source: _ } => {
+                    write!(explanation, "literal `{name}`").unwrap();
+
+                    if matches!(compiled, Component::CaselessLiteral(_, _)) {
+                        explanation.push_str(", ignoring case");
+                    }
+                }
+
+                PatternComponent::ParentDir => explanation.push_str("parent directory"),
+
+                PatternComponent::AnyRecursive { implicit } => {
+                    explanation.push_str("recursive wildcard");
+
+                    if implicit {
+                        explanation.push_str(" (implicit)");
+                    }
+                }
+
+                PatternComponent::Matcher { source } => {
+                    if source == "*" && matches!(compiled, Component::Regex(_)) {
+                        explanation.push_str(any);
+                    } else {
+                        write!(explanation, "{kind} matching `{source}`").unwrap();
+
+                        if matches!(compiled, Component::Spanning(_)) {
+                            explanation.push_str(", possibly across separators");
+                        }
+                    }
+                }
+            }
+        }
+
+        if self.dir_only {
+            explanation.push_str("\n- only matches directories");
+        }
+
+        explanation.push_str(if self.has_wildcard() {
+            "\n- descendants of matches may match"
+        } else {
+            "\n- descendants of matches can't match"
+        });
+
+        explanation
+    }
+
+    /// Check if a single path component (e.g. a file name) matches the pattern's component at the provided index
+    ///
+    /// This allows matching entries one by one when walking directories manually, e.g. with `entry.file_name()`.
+    /// The name goes through the same transformations as paths (see e.g. [`PatternOpts::with_unicode_normalization`]).
+    ///
+    /// A `**` component matches any name, except hidden ones with [`PatternOpts::with_globstar_skips_hidden`].
+    /// With [`PatternOpts::with_star_matches_separators`], a component containing a `*` is matched against the name alone,
+    /// although it may also match several components when matching full paths.
+    ///
+    /// Returns `false` if the index is past the last component (see [`Pattern::component_count`]).
+    ///
+    /// Example, for `src/**/*.rs`:
+    /// * `matches_component(0, "src")` returns `true`
+    /// * `matches_component(1, ...)` returns `true` for any name
+    /// * `matches_component(2, "lib.rs")` returns `true`, while `matches_component(2, "lib.rson")` returns `false`
+    pub fn matches_component(&self, index: usize, name: &OsStr) -> bool {
+        let Some(component) = self.components.get(index) else {
+            return false;
+        };
+
+        let mut buffer = String::new();
+        let bytes = self.path_bytes(Path::new(name), &mut buffer);
+
+        self.component_matches(component, bytes)
+    }
+
+    /// (Internal) Check if a single path component, after transformation, matches one of the pattern's components
+    fn component_matches(&self, component: &Component, bytes: &[u8]) -> bool {
+        component_matches(component, self.wildcard_skips_hidden, bytes)
+    }
+
+    /// Check if a file name matches the pattern's last component
+    ///
+    /// Equivalent to calling [`Pattern::matches_component`] with the index of the last component.
+    /// Note that this doesn't check if the rest of the path matches, so e.g. `src/*.rs` matches the file name `main.rs`
+    /// even if the file isn't located in `src`.
+    ///
+    /// Returns `false` if the pattern has no component (e.g. `/`).
+    pub fn matches_file_name(&self, name: &OsStr) -> bool {
+        self.component_count()
+            .checked_sub(1)
+            .is_some_and(|last| self.matches_component(last, name))
+    }
+
+    /// Get the extension all file names matched by the pattern's last component have, if any
+    ///
+    /// This is the literal text following the last `.` of the component, e.g. `rs` for `src/**/*.rs`, `gz` for `*.tar.gz` or `toml` for `Cargo.toml`.
+    /// It allows filtering paths by extension before matching them. Note that a name made only of the extension and its dot
+    /// (e.g. `.rs`) may match, although it has no extension according to [`Path::extension`].
+    ///
+    /// Returns [`None`] if the extension isn't fixed (e.g. `*.{rs|toml}` or `Makefile`), if the last component is a `**` wildcard,
+    /// or if it's matched case-insensitively or after transformations (see e.g. [`PatternOpts::with_unicode_normalization`]).
+    pub fn required_extension(&self) -> Option<&str> {
+        let end = match self.component_literals.last()? {
+            ComponentLiterals::Exact(lit) => lit,
+            ComponentLiterals::Edges { start: _, end }
+            | ComponentLiterals::Glob { start: _, end } => end,
+            ComponentLiterals::Wildcard | ComponentLiterals::Unknown => return None,
+        };
+
+        end.rsplit_once('.')
+            .map(|(_, extension)| extension)
+            .filter(|extension| !extension.is_empty())
+    }
+
+    /// Get a standalone matcher for the file names matched by the pattern's last component
+    ///
+    /// The matcher behaves like [`Pattern::matches_file_name`], and doesn't borrow the pattern. This is useful to filter
+    /// names cheaply while walking directories, before matching full paths.
+    ///
+    /// Returns [`None`] if the pattern has no component, or if its last component doesn't only match names:
+    /// `**`, `..` and components matching separators (see [`PatternOpts::with_star_matches_separators`]).
+    pub fn file_name_pattern(&self) -> Option<FileNameMatcher> {
+        let component = self.components.last()?;
+
+        if matches!(
+            component,
+            Component::Wildcard | Component::ParentDir | Component::Spanning(_)
+        ) {
+            return None;
+        }
+
+        Some(FileNameMatcher {
+            component: component.clone(),
+            unicode_normalization: self.unicode_normalization,
+            fold_diacritics: self.fold_diacritics,
+        })
+    }
+
+    /// Create an incremental matcher, consuming a path one component at a time
+    ///
+    /// This is useful to match paths while walking a tree manually, without matching each full path again
+    /// from its first component. See [`PatternMatcher`] for more details.
+    pub fn matcher(&self) -> PatternMatcher<'_> {
+        let mut initial = vec![MatcherState::At(0)];
+        self.close_matcher_states(&mut initial);
+
+        PatternMatcher {
+            pattern: self,
+            states: vec![initial],
+            buffer: String::new(),
+        }
+    }
+
+    /// (Internal) Add the states reachable without consuming any path component, i.e. by skipping `**` components
+    fn close_matcher_states(&self, states: &mut Vec<MatcherState>) {
+        let mut i = 0;
+
+        while i < states.len() {
+            if let MatcherState::At(index) = states[i]
+                && matches!(self.components.get(index), Some(Component::Wildcard))
+            {
+                push_matcher_state(states, MatcherState::At(index + 1));
+            }
+
+            i += 1;
+        }
+    }
+
+    /// Match the pattern against a path
+    ///
+    /// Note that the path should be normalized.
+    /// For instance, '..' components in the pattern will be matched against literal '..' in the path.
+    ///
+    /// Relative patterns only match relative paths, so e.g. `src/*.rs` never matches `/home/me/project/src/main.rs`.
+    /// To match paths relative to a base directory the same way the [`crate::Walker`] does, use [`Pattern::is_match_under`].
+    pub fn is_match(&self, path: &Path) -> bool {
+        self.is_match_with_ctx(path, &mut MatchContext::new())
+    }
+
+    /// Match the pattern against a path, reusing the provided context's allocations
+    ///
+    /// Equivalent to [`Pattern::is_match`], but faster when matching lots of paths (see [`MatchContext`])
+    pub fn is_match_with_ctx(&self, path: &Path, ctx: &mut MatchContext) -> bool {
+        matches!(
+            self.match_against_with_ctx(path, ctx),
+            PatternMatchResult::Matched
+        )
+    }
+
+    /// Match the pattern against a path, knowing if it's a directory or not
+    ///
+    /// Equivalent to [`Pattern::is_match`], except patterns that only match directories (see [`Pattern::is_dir_only`])
+    /// don't match paths that aren't directories.
+    pub fn is_match_dir_aware(&self, path: &Path, is_dir: bool) -> bool {
+        (is_dir || !self.dir_only) && self.is_match(path)
+    }
+
+    /// Match the pattern against a path, with options overriding the pattern's ones for this call only
+    ///
+    /// Equivalent to [`Pattern::is_match`] when no option is overridden (see [`MatchOptions`])
+    pub fn is_match_with(&self, path: &Path, opts: MatchOptions) -> bool {
+        matches!(
+            self.match_against_with(path, opts),
+            PatternMatchResult::Matched
+        )
+    }
+
+    /// Match the pattern against a path, with options overriding the pattern's ones for this call only
+    ///
+    /// Equivalent to [`Pattern::match_against`] when no option is overridden (see [`MatchOptions`])
+    pub fn match_against_with(&self, path: &Path, opts: MatchOptions) -> PatternMatchResult {
+        let MatchOptions { case_insensitive } = opts;
+
+        match case_insensitive {
+            Some(case_insensitive) => match self.case_variant(case_insensitive) {
+                Some(pattern) => pattern.match_against(path),
+                None => PatternMatchResult::NotMatched,
+            },
+
+            None => self.match_against(path),
+        }
+    }
+
+    /// Check if descendants of a directory may match the pattern
+    ///
+    /// This is useful to prune directories when walking them manually (e.g. with `walkdir`),
+    /// and uses the same logic as the [`crate::Walker`]:
+    /// * If the pattern doesn't match the directory but may match a longer path (see [`PatternMatchResult::Starved`]), returns `true`
+    /// * If the pattern matches the directory, returns `true` only if it has a wildcard (see [`Pattern::has_wildcard`])
+    ///   which may still match more components
+    /// * Otherwise, returns `false`
+    ///
+    /// Examples:
+    /// * `a/**/b` may match descendants of `a`, `a/b` and `a/c`, but not of `b`
+    /// * `a/b` may match descendants of `a`, but not of `a/b` which it matches
+    /// * `**` may match descendants of any relative path
+    pub fn can_match_descendants(&self, dir: &Path) -> bool {
+        self.can_match_descendants_with_ctx(dir, &mut MatchContext::new())
+    }
+
+    /// Check if descendants of a directory may match the pattern, reusing the provided context's allocations
+    ///
+    /// Equivalent to [`Pattern::can_match_descendants`], but faster when checking lots of paths (see [`MatchContext`])
+    pub fn can_match_descendants_with_ctx(&self, dir: &Path, ctx: &mut MatchContext) -> bool {
+        match self.match_against_with_ctx(dir, ctx) {
+            PatternMatchResult::Starved => true,
+            PatternMatchResult::Matched => self.descendants_may_match(dir, ctx),
+            PatternMatchResult::NotMatched
+            | PatternMatchResult::PathNotAbsolute
+            | PatternMatchResult::PathIsAbsolute
+            | PatternMatchResult::IncompatiblePrefix => false,
+        }
+    }
+
+    /// Check if a path is matched by the pattern or is an ancestor of a possible match
+    ///
+    /// This is useful e.g. to know whether a change to a directory may affect the paths matched by a pattern.
+    /// Only the path's components are compared to the pattern's ones, so the filesystem is never accessed.
+    ///
+    /// Examples:
+    /// * `a/b/**/*.toml` may match descendants of `a`, `a/b` and `a/b/c`, but not of `a/c`
+    /// * `src/*.rs` may match descendants of `src` and `src/main.rs` is a match, but not `tests`
+    /// * `**` may match descendants of any relative path
+    pub fn matches_any_ancestor(&self, path: &Path) -> bool {
+        matches!(
+            self.match_against(path),
+            PatternMatchResult::Matched | PatternMatchResult::Starved
+        )
+    }
+
+    /// (Internal) Get a variant of the pattern with the provided case sensitivity for all components
+    ///
+    /// Variants are compiled on first use and kept afterwards, so each one is only compiled once.
+    /// Returns [`None`] if it can't be compiled, e.g. if case-insensitive components exceed [`PatternOpts::with_regex_size_limit`].
+    fn case_variant(&self, case_insensitive: bool) -> Option<&Pattern> {
+        if self.uniform_case_insensitive == Some(case_insensitive) {
+            return Some(self);
+        }
+
+        self.case_variants[usize::from(case_insensitive)]
+            .get_or_init(|| {
+                Self::build(
+                    &self.source,
+                    self.opts,
+                    Some(case_insensitive),
+                    BuildPurpose::Matching,
+                )
+                .ok()
+                .map(Box::new)
+            })
+            .as_deref()
+    }
+
+    pub fn match_against(&self, path: &Path) -> PatternMatchResult {
+        self.match_against_with_ctx(path, &mut MatchContext::new())
+    }
+
+    /// Match the pattern against a path, reusing the provided context's allocations
+    ///
+    /// Equivalent to [`Pattern::match_against`], but faster when matching lots of paths (see [`MatchContext`])
+    pub fn match_against_with_ctx(
+        &self,
+        path: &Path,
+        ctx: &mut MatchContext,
+    ) -> PatternMatchResult {
+        self.match_recording(path, ctx, &mut ())
+    }
+
+    /// Match the pattern against a `/`-separated path provided as a string, e.g. an object-store key or an archive entry name
+    ///
+    /// Unlike with [`Pattern::is_match`], only `/` is a separator and a leading `/` is the only prefix,
+    /// so e.g. `C:\a` is a relative path made of a single component. Paths are interpreted identically on all platforms.
+    /// Empty and `.` components are ignored like in paths, and characters are still transformed like when matching paths
+    /// (see e.g. [`PatternOpts::with_unicode_normalization`]).
+    ///
+    /// Patterns using `\` as a separator are matched the same way, while patterns starting with a Windows drive never match.
+    pub fn is_match_str(&self, path: &str) -> bool {
+        matches!(self.match_against_str(path), PatternMatchResult::Matched)
+    }
+
+    /// Match the pattern against a `/`-separated path provided as a string
+    ///
+    /// See [`Pattern::is_match_str`]
+    pub fn match_against_str(&self, path: &str) -> PatternMatchResult {
+        self.match_against_str_with_ctx(path, &mut MatchContext::new())
+    }
+
+    /// Match the pattern against a `/`-separated path provided as a string, reusing the provided context's allocations
+    ///
+    /// Equivalent to [`Pattern::match_against_str`], but faster when matching lots of paths (see [`MatchContext`])
+    pub fn match_against_str_with_ctx(
+        &self,
+        path: &str,
+        ctx: &mut MatchContext,
+    ) -> PatternMatchResult {
+        self.match_against_bytes_with_ctx(path.as_bytes(), ctx)
+    }
+
+    /// Match the pattern against a `/`-separated path provided as raw bytes
+    ///
+    /// This is a fast path for Unix-style paths, e.g. read from a file listing or received over the network:
+    /// no [`Path`] is built, literals are compared byte by byte and regular expressions run directly on the bytes.
+    /// Bytes are usually UTF-8, but don't need to be (non-UTF-8 bytes are matched like on Unix).
+    ///
+    /// Paths are split exactly like with [`Pattern::is_match_str`]: only `/` is a separator
+    /// and a leading `/` is the only prefix, so Windows drives and UNC prefixes aren't recognized.
+    pub fn is_match_bytes(&self, path: &[u8]) -> bool {
+        matches!(self.match_against_bytes(path), PatternMatchResult::Matched)
+    }
+
+    /// Match the pattern against a `/`-separated path provided as raw bytes
+    ///
+    /// See [`Pattern::is_match_bytes`]
+    pub fn match_against_bytes(&self, path: &[u8]) -> PatternMatchResult {
+        self.match_against_bytes_with_ctx(path, &mut MatchContext::new())
+    }
+
+    /// Match the pattern against a `/`-separated path provided as raw bytes, reusing the provided context's allocations
+    ///
+    /// Equivalent to [`Pattern::match_against_bytes`], but faster when matching lots of paths (see [`MatchContext`])
+    pub fn match_against_bytes_with_ctx(
+        &self,
+        path: &[u8],
+        ctx: &mut MatchContext,
+    ) -> PatternMatchResult {
+        let bytes = self.transform_bytes(path, &mut ctx.normalized);
+        let path_prefix = split_str_path_into(bytes, &mut ctx.components);
+
+        self.match_split(bytes, path_prefix, &ctx.components, &mut ())
+    }
+
+    /// Match the pattern against a path, and explain why it didn't match
+    ///
+    /// This is useful to debug complex patterns. The result is the same as with [`Pattern::match_against`],
+    /// and comes with the location where matching failed, if it did (see [`MatchTrace::failure`]).
+    ///
+    /// As `**` may consume any number of components, a pattern can fail to match a path in several ways.
+    /// The reported failure is the one located the furthest in the pattern, then in the path,
+    /// i.e. the one where the most of the pattern was matched.
+    ///
+    /// For instance, `a/b*/c` fails to match `a/bx/d` because its component at index 2 (`c`)
+    /// doesn't match the path component at index 2 (`d`).
+    pub fn explain_match(&self, path: &Path) -> MatchTrace {
+        let mut ctx = MatchContext::new();
+        let mut tracer = FailureTracer::default();
+
+        let result = self.match_recording(path, &mut ctx, &mut tracer);
+
+        MatchTrace {
+            result,
+            failure: match result {
+                PatternMatchResult::Matched => None,
+                _ => tracer.furthest,
+            },
+        }
+    }
+
+    /// Match the pattern against a path provided as its prefix and components, without building a [`Path`]
+    ///
+    /// This is equivalent to [`Pattern::match_against`] with the path made of the provided prefix and components,
+    /// which are expected to be normalized (see [`crate::normalize_path_with`] and [`Pattern::normalize_opts`]).
+    /// Empty and `.` components are ignored like in paths, while components containing separators are matched as a single component.
+    ///
+    /// Components go through the same transformations as paths (see e.g. [`PatternOpts::with_unicode_normalization`]).
+    pub fn match_components<'a>(
+        &self,
+        prefix: Option<PathPrefix>,
+        components: impl IntoIterator<Item = &'a OsStr>,
+    ) -> PatternMatchResult {
+        self.match_components_with_ctx(prefix, components, &mut MatchContext::new())
+    }
+
+    /// Match the pattern against a path provided as its prefix and components, reusing the provided context's allocations
+    ///
+    /// Equivalent to [`Pattern::match_components`], but faster when matching lots of paths (see [`MatchContext`])
+    pub fn match_components_with_ctx<'a>(
+        &self,
+        prefix: Option<PathPrefix>,
+        components: impl IntoIterator<Item = &'a OsStr>,
+        ctx: &mut MatchContext,
+    ) -> PatternMatchResult {
+        ctx.joined.clear();
+        ctx.components.clear();
+
+        for component in components {
+            let bytes = component.as_encoded_bytes();
+
+            if matches!(bytes, b"" | b".") {
+                continue;
+            }
+
+            let start = ctx.joined.len();
+            ctx.joined
+                .extend_from_slice(self.transform_bytes(bytes, &mut ctx.normalized));
+            ctx.components.push(start..ctx.joined.len());
+        }
+
+        self.match_split(&ctx.joined, prefix, &ctx.components, &mut ())
+    }
+
+    /// Match the pattern against a path and get the text captured by its wildcards
+    ///
+    /// This allows e.g. mirroring the structure of matched paths: when `src/*/tests/*.rs` matches
+    /// `src/parser/tests/basic.rs`, the first `*` captures `parser` and the second one captures `basic`.
+    /// See [`MatchCaptures`] for what is captured.
+    ///
+    /// Captured texts come from the path after it was transformed like when matching
+    /// (see e.g. [`PatternOpts::with_unicode_normalization`]).
+    ///
+    /// The pattern is compiled again with capture groups the first time it's used, and the result
+    /// is kept inside of the pattern. Returns [`None`] if the path doesn't match, or if that compilation fails
+    /// (e.g. because of [`PatternOpts::with_regex_size_limit`]).
+    pub fn match_captures(&self, path: &Path) -> Option<MatchCaptures> {
+        // Patterns without capturing matchers don't need to be compiled again
+        let pattern = if self
+            .capture_counts
+            .iter()
+            .flatten()
+            .all(|count| *count == 0)
+        {
+            self
+        } else {
+            self.capturing_variant
+                .get_or_init(|| {
+                    Self::build(&self.source, self.opts, None, BuildPurpose::Capturing)
+                        .ok()
+                        .map(Box::new)
+                })
+                .as_deref()?
+        };
+
+        let mut ctx = MatchContext::new();
+        let mut consumed = vec![];
+
+        if !matches!(
+            pattern.match_recording(path, &mut ctx, &mut consumed),
+            PatternMatchResult::Matched
+        ) {
+            return None;
+        }
+
+        let parts = PathParts {
+            bytes: pattern.path_bytes(path, &mut ctx.normalized),
+            ranges: &ctx.components,
+        };
+
+        let mut captures = MatchCaptures {
+            texts: vec![],
+            globstars: vec![],
+        };
+
+        let mut offset = 0;
+
+        for ((component, count), capture_count) in pattern
+            .components
+            .iter()
+            .zip(consumed)
+            .zip(&pattern.capture_counts)
+        {
+            let consumed_parts = (offset..offset + count).map(|i| parts.get(i));
+            offset += count;
+
+            let (regex, text) = match component {
+                Component::Wildcard => {
+                    // Don't report the implicit `**` prefix
+                    if capture_count.is_some() {
+                        captures.globstars.push(
+                            consumed_parts
+                                .map(|part| String::from_utf8_lossy(part).into_owned())
+                                .collect(),
+                        );
+                    }
+
+                    continue;
+                }
+
+                Component::Regex(regex) => (regex, parts.get(offset - 1).to_vec()),
+
+                Component::Spanning(regex) => {
+                    (regex, consumed_parts.collect::<Vec<_>>().join(&b'/'))
+                }
+
+                // Components containing a `!(...)` operator don't support captures
+                Component::Literal(_)
+                | Component::CaselessLiteral(_, _)
+                | Component::ParentDir
+                | Component::Negated(_) => {
+                    continue;
+                }
+            };
+
+            let ranges = read_captures(regex, &text, capture_count.unwrap_or(0))?;
+
+            captures.texts.extend(ranges.into_iter().map(|range| {
+                range.map(|range| String::from_utf8_lossy(&text[range]).into_owned())
+            }));
+        }
+
+        Some(captures)
+    }
+
+    /// (Internal) Match the pattern against a path, recording the number of path components consumed by each component
+    fn match_recording(
+        &self,
+        path: &Path,
+        ctx: &mut MatchContext,
+        consumed: &mut impl ConsumedComponents,
+    ) -> PatternMatchResult {
+        let bytes = self.path_bytes(path, &mut ctx.normalized);
+
+        let Ok(path_prefix) = normalize_path_into(bytes, self.normalize_opts, &mut ctx.components)
+        else {
+            return PatternMatchResult::IncompatiblePrefix;
+        };
+
+        self.match_split(bytes, path_prefix, &ctx.components, consumed)
+    }
+
+    /// (Internal) Match the pattern against a path split into its prefix and components
+    fn match_split(
+        &self,
+        bytes: &[u8],
+        path_prefix: Option<PathPrefix>,
+        components: &[Range<usize>],
+        consumed: &mut impl ConsumedComponents,
+    ) -> PatternMatchResult {
+        let is_absolute = path_prefix.is_some();
+
+        match &self.prefix {
+            Some(PathPrefix::RootDir) => {
+                if !is_absolute {
+                    return PatternMatchResult::PathNotAbsolute;
+                }
+            }
+
+            Some(PathPrefix::WindowsDrive(windows_drive)) => match path_prefix {
+                Some(prefix) => match prefix {
+                    PathPrefix::RootDir => return PatternMatchResult::IncompatiblePrefix,
+
+                    PathPrefix::WindowsDrive(path_windows_drive) => {
+                        if *windows_drive != path_windows_drive {
+                            consumed.fail(0, 0, MatchFailureReason::PrefixMismatch);
+                            return PatternMatchResult::NotMatched;
+                        }
+                    }
+                },
+
+                None => return PatternMatchResult::PathNotAbsolute,
+            },
+
+            None => {
+                if is_absolute {
+                    return PatternMatchResult::PathIsAbsolute;
+                }
+            }
+        }
+
+        if self
+            .max_components
+            .is_some_and(|max| components.len() > max)
+        {
+            let max = self.max_components.unwrap();
+            consumed.fail(0, max, MatchFailureReason::TooManyComponents);
+            return PatternMatchResult::NotMatched;
+        }
+
+        let result = match_components(
+            &self.components,
+            PathParts {
+                bytes,
+                ranges: components,
+            },
+            self.wildcard_skips_hidden,
+            consumed,
+        );
+
+        // Paths at the maximum depth can't be completed with more components
+        match result {
+            PatternMatchResult::Starved if self.max_components == Some(components.len()) => {
+                PatternMatchResult::NotMatched
+            }
+
+            _ => result,
+        }
+    }
+
+    /// (Internal) Get the encoded bytes of a path to match, after transforming its characters like the pattern's literals
+    ///
+    /// The provided buffer is only used if the path needs to be changed.
+    fn path_bytes<'a>(&self, path: &'a Path, buffer: &'a mut String) -> &'a [u8] {
+        self.transform_bytes(path.as_os_str().as_encoded_bytes(), buffer)
+    }
+
+    /// (Internal) Transform the characters of a path to match like the pattern's literals
+    ///
+    /// The provided buffer is only used if the path needs to be changed.
+    fn transform_bytes<'a>(&self, bytes: &'a [u8], buffer: &'a mut String) -> &'a [u8] {
+        transform_bytes(
+            bytes,
+            buffer,
+            self.unicode_normalization,
+            self.fold_diacritics,
+        )
+    }
+
+    /// Check if descendants of a path may match the pattern
+    ///
+    /// The path is expected to have a prefix compatible with the pattern, e.g. after it was matched.
+    /// This is used by the [`crate::Walker`] to avoid traversing matching directories whose content can't match.
+    pub(crate) fn descendants_may_match(&self, path: &Path, ctx: &mut MatchContext) -> bool {
+        if !self.has_wildcard() {
+            return false;
+        }
+
+        let bytes = self.path_bytes(path, &mut ctx.normalized);
+
+        if normalize_path_into(bytes, self.normalize_opts, &mut ctx.components).is_err() {
+            return false;
+        }
+
+        if self
+            .max_components
+            .is_some_and(|max| ctx.components.len() >= max)
+        {
+            return false;
+        }
+
+        descendants_may_match(
+            &self.components,
+            &self.wildcard_at_or_after,
+            PathParts {
+                bytes,
+                ranges: &ctx.components,
+            },
+            self.wildcard_skips_hidden,
+        )
+    }
+
+    /// Get the sources of the regular expressions compiled from the pattern's components, in order
+    ///
+    /// This is mostly useful for debugging purposes.
+    pub fn regex_sources(&self) -> impl Iterator<Item = &str> {
+        self.components
+            .iter()
+            .filter_map(|component| match component {
+                Component::Regex(regex) | Component::Spanning(regex) => Some(regex.as_str()),
+                Component::Literal(_)
+                | Component::CaselessLiteral(_, _)
+                | Component::Negated(_)
+                | Component::Wildcard
+                | Component::ParentDir => None,
+            })
+    }
+
+    /// Export the pattern as a single regular expression matching whole paths
+    ///
+    /// This allows matching paths in other systems that only understand regular expressions.
+    /// The expression uses the syntax of the [`regex`](https://docs.rs/regex) crate, and is anchored at both ends.
+    ///
+    /// Paths are expected to be normalized (see [`Pattern::normalize_opts`]), with components joined by `/`
+    /// regardless of the platform, and absolute paths starting with `/` or a drive followed by `/` (e.g. `C:/`).
+    /// For instance, `a/**/b*.rs` is exported as `^a/(?:[^/]+/)*(?:b[^/\n]*\.rs)$`.
+    ///
+    /// For such paths, the expression matches the same paths as [`Pattern::is_match`], with the following exceptions:
+    /// * Case-insensitive components use inline flags (e.g. `(?i:readme)`), which apply simple case folding
+    ///   instead of the full one used with [`CaseFolding::Unicode`] (e.g. `ß` doesn't match `ss`)
+    /// * Transformations of matched paths aren't included (see [`PatternOpts::with_unicode_normalization`]
+    ///   and [`PatternOpts::with_fold_diacritics`]), so paths must be transformed beforehand
+    /// * [`PatternOpts::with_max_components`] and [`Pattern::is_dir_only`] aren't taken into account
+    /// * Expressions of relative patterns may match absolute paths (e.g. `*/b` matches `/b` and `C:/b`),
+    ///   as well as the empty path (e.g. with `*`), so they should only be matched against non-empty relative paths
+    /// * Components containing a `!(...)` operator (see [`PatternOpts::with_extglob`]) can't be expressed,
+    ///   so they match any path component instead
+    ///
+    /// With [`PatternOpts::with_ascii_only`], the expression starts with a `(?-u)` flag, so it must be compiled
+    /// for bytes (e.g. with `regex::bytes::Regex`).
+    pub fn to_regex_string(&self) -> String {
+        // Compiled components match single path components, so they may match separators
+        let exported = Self::build(&self.source, self.opts, None, BuildPurpose::Exporting)
+            .expect("pattern was already built with the same options");
+
+        let mut out = String::new();
+
+        if self.opts.ascii_only() {
+            out.push_str("(?-u)");
+        }
+
+        out.push('^');
+
+        match self.prefix {
+            // Patterns starting with a separator match paths starting with any prefix
+            Some(PathPrefix::RootDir) => out.push_str("(?:[A-Za-z]:)?/"),
+            Some(PathPrefix::WindowsDrive(drive)) => {
+                let letter = drive.uppercase_letter();
+                write!(out, "[{letter}{}]:/", letter.to_ascii_lowercase()).unwrap();
+            }
+            None => {}
+        }
+
+        // Wildcards can't consume hidden components in some cases
+        let wildcard_component = if self.wildcard_skips_hidden {
+            "[^/.][^/]*"
+        } else {
+            "[^/]+"
+        };
+
+        // Was a component written yet?
+        let mut any_written = false;
+
+        // Is a wildcard waiting to be written before the next component?
+        let mut pending_wildcard = false;
+
+        for component in &exported.components {
+            let regex = match component {
+                // Negations can't be expressed, so any component is matched instead
+                Component::Negated(_) => "[^/]+".to_owned(),
+
+                _ => {
+                    let Some(regex) = embeddable_regex(component) else {
+                        pending_wildcard = true;
+                        continue;
+                    };
+
+                    regex
+                }
+            };
+
+            if any_written {
+                out.push('/');
+            }
+
+            // Consume any number of components, each followed by a separator
+            if pending_wildcard {
+                write!(out, "(?:{wildcard_component}/)*").unwrap();
+                pending_wildcard = false;
+            }
+
+            out.push_str(&regex);
+            any_written = true;
+        }
+
+        if pending_wildcard {
+            if any_written {
+                write!(out, "(?:/{wildcard_component})*").unwrap();
+            } else {
+                write!(out, "(?:{wildcard_component}(?:/{wildcard_component})*)?").unwrap();
+            }
+        }
+
+        out.push('$');
+        out
+    }
+
+    /// Get the options matched paths are normalized with
+    ///
+    /// Normalizing paths with [`crate::normalize_path_with`] and these options splits them
+    /// into the same components as when matching.
+    pub fn normalize_opts(&self) -> NormalizeOpts {
+        self.normalize_opts
+    }
+
+    /// Get the common root directory for all possible matches of this pattern
+    pub fn common_root_dir(&self) -> &Path {
+        &self.common_root_dir
+    }
+
+    /// Get the longest literal text all matched paths start with
+    ///
+    /// This is useful to pre-filter candidate strings before matching them, e.g. in a database query.
+    /// Unlike [`Pattern::common_root_dir`], the text may end in the middle of a component, so `src/foo*.rs` gives `src/foo`.
+    ///
+    /// Paths are considered in their normalized form (see [`crate::normalize_path_with`]) with `/` as the separator,
+    /// e.g. as provided to [`Pattern::is_match_str`]. The text ends before the first wildcard, case-insensitive component,
+    /// or group of alternates, and is empty for patterns starting with a Windows drive (whose letter may have any casing)
+    /// or transforming the paths they match (see [`PatternOpts::with_unicode_normalization`] and [`PatternOpts::with_fold_diacritics`]).
+    pub fn literal_prefix(&self) -> &str {
+        &self.literal_prefix
+    }
+
+    /// Get the longest literal text all matched paths end with, if any
+    ///
+    /// For instance, `**/*.rs` gives `.rs` and `**/docs/*.md` gives `.md`, while `src/*` doesn't have any.
+    ///
+    /// Paths are considered the same way as with [`Pattern::literal_prefix`].
+    pub fn literal_suffix(&self) -> Option<&str> {
+        self.literal_suffix.as_deref()
+    }
+
+    /// Get the directories under which all possible matches of this pattern are located
+    ///
+    /// This is a more precise version of [`Pattern::common_root_dir`], which expands the groups
+    /// of literal alternates found in the leading components. For instance, the roots of
+    /// `src/{app|lib}/**/*.rs` are `src/app` and `src/lib`, while its common root directory is `src`.
+    ///
+    /// Expansion stops at the first component that isn't made only of literals and groups of literals,
+    /// or before the first component whose expansion would produce more than `limit` roots.
+    /// The returned list is never empty, and only contains [`Pattern::common_root_dir`] if no group was expanded.
+    pub fn literal_roots(&self, limit: usize) -> Vec<PathBuf> {
+        let mut roots = vec![vec![]];
+
+        for (i, matchers) in self.literal_skeleton.iter().enumerate() {
+            // Follow the same rules as for the common root directory
+            let rest = &self.components[i + 1..];
+
+            if rest.is_empty()
+                || (rest.iter().all(|c| matches!(c, Component::Wildcard))
+                    && !matches!(self.components[i], Component::ParentDir))
+            {
+                break;
+            }
+
+            if count_expansions(matchers).saturating_mul(roots.len()) > limit {
+                break;
+            }
+
+            let expansions = expand_literal_matchers(matchers);
+
+            roots = roots
+                .iter()
+                .flat_map(|root| {
+                    expansions.iter().map(|expansion| {
+                        let mut root = root.clone();
+                        root.push(expansion.clone());
+                        root
+                    })
+                })
+                .collect();
+        }
+
+        roots
+            .into_iter()
+            .map(|root| join_root_dir(self.prefix, &root))
+            .collect()
+    }
+
+    /// Get all the paths matched by this pattern, if there is a finite number of them
+    ///
+    /// This is the case when the pattern is only made of literals and groups of literals, e.g. `{src|tests}/{lib|main}.rs`
+    /// matches 4 paths. This is useful to e.g. create the matched directories beforehand, or give explicit lists of files to other tools.
+    ///
+    /// Returns [`None`] if the pattern may match an infinite number of paths (e.g. with `*`, `?`, brackets or `**`),
+    /// or if its components may match names with other forms than their literals
+    /// (e.g. when they are case-insensitive, see [`Pattern::literal_roots`]).
+    ///
+    /// Fails if the pattern matches more than [`MAX_EXPANSIONS`] paths.
+    pub fn expand(&self) -> Result<Option<Vec<PathBuf>>, TooManyExpansions> {
+        if self.literal_skeleton.len() != self.components.len() {
+            return Ok(None);
+        }
+
+        let count = self
+            .literal_skeleton
+            .iter()
+            .map(|matchers| count_expansions(matchers))
+            .fold(1, usize::saturating_mul);
+
+        if count > MAX_EXPANSIONS {
+            return Err(TooManyExpansions { count });
+        }
+
+        // Paths with too many components are never matched
+        if self
+            .max_components
+            .is_some_and(|max| self.components.len() > max)
+        {
+            return Ok(Some(vec![]));
+        }
+
+        let mut paths = vec![vec![]];
+
+        for matchers in &self.literal_skeleton {
+            let expansions = expand_literal_matchers(matchers);
+
+            paths = paths
+                .iter()
+                .flat_map(|path| {
+                    expansions.iter().map(|expansion| {
+                        let mut path = path.clone();
+                        path.push(expansion.clone());
+                        path
+                    })
+                })
+                .collect();
+        }
+
+        // Groups may contain the same alternate several times
+        let mut seen = HashSet::new();
+
+        Ok(Some(
+            paths
+                .into_iter()
+                .map(|path| join_root_dir(self.prefix, &path))
+                .filter(|path| seen.insert(path.clone()))
+                .collect(),
+        ))
+    }
+
+    /// Check if the component contains a wildcard
+    ///
+    /// Can be useful for e.g. determining if a matching directory should be traversed or not,
+    /// as the presence or absence of a wildcard indicates whether descendants may match
+    ///
+    /// Example:
+    /// * `/a/b` matches `/a/b` but cannot match any descendant
+    /// * `/a/**/b` matches `/a/b` and may match some descendants
+    /// * `/a/b/**` matches `/a/b` and may match some descendants
+    pub fn has_wildcard(&self) -> bool {
+        self.wildcard_at_or_after(0)
+    }
+
+    /// Check if a wildcard is located at or after the component at the provided index
+    ///
+    /// Wildcards are `**` components, as well as components containing a `*` when
+    /// [`PatternOpts::with_star_matches_separators`] is enabled. Indices past the last component return `false`.
+    ///
+    /// Once a path has been matched up to a component with no wildcard at or after it, the rest of the pattern
+    /// can only match a fixed number of components.
+    ///
+    /// Example, for `a/**/b/c`:
+    /// * `wildcard_at_or_after(0)` and `wildcard_at_or_after(1)` return `true`
+    /// * `wildcard_at_or_after(2)` returns `false`, as only `b/c` remains
+    pub fn wildcard_at_or_after(&self, idx: usize) -> bool {
+        self.wildcard_at_or_after.get(idx).copied().unwrap_or(false)
+    }
+
+    /// Get the minimum and maximum number of components of the paths this pattern can match
+    ///
+    /// Components are counted after normalization, without the prefix (e.g. `/a/b` has 2 components).
+    /// Each component of the pattern counts as one, except `**` which may match any number of components,
+    /// and components containing a `*` with [`PatternOpts::with_star_matches_separators`], which match at least one.
+    /// The maximum is [`None`] if the pattern contains any of these, unless [`PatternOpts::with_max_components`] is set.
+    ///
+    /// Example:
+    /// * `a/b/c` returns `(3, Some(3))`
+    /// * `a/**/b` returns `(2, None)`
+    /// * `*/*` returns `(2, Some(2))`
+    pub fn depth_bounds(&self) -> (usize, Option<usize>) {
+        let min = self
+            .components
+            .iter()
+            .filter(|component| !matches!(component, Component::Wildcard))
+            .count();
+
+        let max = if self.has_wildcard() {
+            self.max_components
+        } else {
+            Some(self.max_components.map_or(min, |max| max.min(min)))
+        };
+
+        (min, max)
+    }
+
+    /// Get a hint on the kind of entries this pattern matches
+    ///
+    /// This is derived from the pattern's structure only, so it is a hint and not a guarantee.
+    /// For instance, `a/*` may well only match directories on a given filesystem.
+    ///
+    /// Examples:
+    /// * `src/*.rs` may match any entry
+    /// * `src/**` is likely to be used for traversal, so it's likely to match directories
+    /// * `../..` and `build/` can only match directories
+    pub fn match_target_hint(&self) -> TargetHint {
+        if self.dir_only {
+            return TargetHint::RequiresDirectory;
+        }
+
+        match self.components.last() {
+            Some(Component::ParentDir) => TargetHint::RequiresDirectory,
+            Some(Component::Wildcard) => TargetHint::DirectoriesLikely,
+            Some(
+                Component::Regex(_)
+                | Component::Spanning(_)
+                | Component::Negated(_)
+                | Component::Literal(_)
+                | Component::CaselessLiteral(_, _),
+            )
+            | None => TargetHint::AnyEntry,
+        }
+    }
+}
+
+impl PartialEq for Pattern {
+    fn eq(&self, other: &Self) -> bool {
+        self.source == other.source && self.opts == other.opts
+    }
+}
+
+impl Eq for Pattern {}
+
+impl Hash for Pattern {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.source.hash(state);
+        self.opts.hash(state);
+    }
+}
+
+impl fmt::Display for Pattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.source)
+    }
+}
+
+/// Parse a pattern with the default options, like [`Pattern::new`]
+///
+/// This allows parsing patterns with [`str::parse`], e.g. from command-line arguments:
+///
+/// ```
+/// use std::{path::Path, str::FromStr};
+///
+/// use globby::{Pattern, PatternError};
+///
+/// /// Parse an argument with any type implementing `FromStr`, as argument parsers do
+/// fn parse_arg<T: FromStr>(arg: &str) -> Result<T, T::Err> {
+///     arg.parse()
+/// }
+///
+/// let pattern: Pattern = parse_arg("**/*.rs")?;
+/// assert!(pattern.is_match(Path::new("src/main.rs")));
+///
+/// assert!(parse_arg::<Pattern>("src/[").is_err());
+/// # Ok::<(), PatternError>(())
+/// ```
+impl FromStr for Pattern {
+    type Err = PatternError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        Self::new(input)
+    }
+}
+
+impl TryFrom<&str> for Pattern {
+    type Error = PatternError;
+
+    fn try_from(input: &str) -> Result<Self, Self::Error> {
+        Self::new(input)
+    }
+}
+
+impl TryFrom<&Path> for Pattern {
+    type Error = PatternError;
+
+    /// Build a pattern matching exactly the provided path, see [`Pattern::from_literal_path`]
+    fn try_from(path: &Path) -> Result<Self, Self::Error> {
+        Self::from_literal_path(path)
+    }
+}
+
+impl TryFrom<String> for Pattern {
+    type Error = PatternError;
+
+    fn try_from(input: String) -> Result<Self, Self::Error> {
+        Self::new(&input)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for Pattern {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.source)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Pattern {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let source = String::deserialize(deserializer)?;
+
+        Self::new(&source).map_err(serde::de::Error::custom)
+    }
+}
+
+/// (Internal) What a pattern is built for (see [`Pattern::build`])
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum BuildPurpose {
+    /// Matching paths
+    Matching,
+
+    /// Reading the text captured by wildcards (see [`Pattern::match_captures`])
+    Capturing,
+
+    /// Exporting the pattern as a single regular expression (see [`Pattern::to_regex_string`])
+    ///
+    /// Expressions don't use the cache or the size limit, as they are only used to get their source.
+    Exporting,
+}
+
+/// Build the common root directory of a pattern
+///
+/// Uses the matchers of components that can only match a fixed set of strings (see [`RawComponent::literal_matchers`])
+fn build_common_root_dir(
+    prefix: Option<PathPrefix>,
+    components: &[Component],
+    literal_matchers: &[Option<Vec<CharsMatcher>>],
+) -> PathBuf {
+    // Get all deterministic components at the beginning of the pattern
+    // These will be used to compute the common root directory
+    let mut common_root_dir_components = literal_matchers
+        .iter()
+        .map_while(|matchers| match matchers.as_deref() {
+            // Only get literal components, as these will always match the exact same path components
+            Some([CharsMatcher::Literal(lit)]) => Some(lit.as_str()),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+
+    // If the entire pattern is deterministic, match from the parent directory to allow yielding
+    // that specific child item
+    //
+    // The same goes if only wildcards follow, as they may match zero component
+    // (e.g. `a/**` matches `a` itself)
+    let rest = &components[common_root_dir_components.len()..];
+
+    if rest.is_empty()
+        || (rest.iter().all(|c| matches!(c, Component::Wildcard))
+            && matches!(
+                components[..common_root_dir_components.len()].last(),
+                Some(Component::Literal(_) | Component::CaselessLiteral(_, _))
+            ))
+    {
+        common_root_dir_components.pop();
+    }
+
+    join_root_dir(prefix, &common_root_dir_components)
+}
+
+/// Literal text found at the edges of a pattern component
+#[derive(Debug, Clone)]
+enum ComponentLiterals {
+    /// The component only matches this text
+    Exact(String),
+
+    /// The component matches text starting and ending with these (possibly empty) literals
+    Edges { start: String, end: String },
+
+    /// The component matches all text starting and ending with these (possibly empty) literals, e.g. `a*.rs`
+    Glob { start: String, end: String },
+
+    /// The component is a `**` wildcard, which may match any number of path components
+    Wildcard,
+
+    /// The component's text can't be known in advance, e.g. as it is case-insensitive
+    Unknown,
+}
+
+impl ComponentLiterals {
+    /// Get the literal text at the edges of a parsed component
+    fn of(component: &RawComponent, case_insensitive: bool) -> Self {
+        match component {
+            RawComponent::ParentDir => Self::Exact("..".to_owned()),
+            RawComponent::Wildcard => Self::Wildcard,
+            _ if case_insensitive => Self::Unknown,
+            RawComponent::Literal(lit) => Self::Exact(lit.clone()),
+            RawComponent::Suite(matchers) => {
+                let start = literal_run(matchers.iter());
+
+                if start.len() == matchers.len() {
+                    return Self::Exact(start.concat());
+                }
+
+                let mut end = literal_run(matchers.iter().rev());
+                end.reverse();
+
+                let is_glob = start.len() + end.len() + 1 == matchers.len()
+                    && matchers[start.len()] == CharsMatcher::AnyChars;
+
+                let (start, end) = (start.concat(), end.concat());
+
+                if is_glob {
+                    Self::Glob { start, end }
+                } else {
+                    Self::Edges { start, end }
+                }
+            }
+        }
+    }
+}
+
+/// Get the run of literals a suite of matchers starts with
+fn literal_run<'a>(matchers: impl Iterator<Item = &'a CharsMatcher>) -> Vec<&'a str> {
+    matchers
+        .map_while(|matcher| match matcher {
+            CharsMatcher::Literal(lit) => Some(lit.as_str()),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Build the literal texts all paths matched by a pattern start and end with
+///
+/// See [`Pattern::literal_prefix`] and [`Pattern::literal_suffix`]
+fn build_literal_affixes(
+    prefix: Option<PathPrefix>,
+    components: &[ComponentLiterals],
+) -> (String, Option<String>) {
+    // Drive letters may have any casing in matched paths
+    let root = match prefix {
+        None => Some(""),
+        Some(PathPrefix::RootDir) => Some("/"),
+        Some(PathPrefix::WindowsDrive(_)) => None,
+    };
+
+    let mut literal_prefix = String::new();
+
+    if let Some(root) = root {
+        literal_prefix.push_str(root);
+
+        for (i, component) in components.iter().enumerate() {
+            // `**` may match no component at all, so the separator preceding it isn't guaranteed
+            if matches!(component, ComponentLiterals::Wildcard) {
+                break;
+            }
+
+            if i > 0 {
+                literal_prefix.push('/');
+            }
+
+            match component {
+                ComponentLiterals::Exact(lit) => literal_prefix.push_str(lit),
+                ComponentLiterals::Edges { start, end: _ }
+                | ComponentLiterals::Glob { start, end: _ } => {
+                    literal_prefix.push_str(start);
+                    break;
+                }
+                ComponentLiterals::Wildcard | ComponentLiterals::Unknown => break,
+            }
+        }
+    }
+
+    // Collect the suffix's parts in reverse order
+    let mut parts = vec![];
+
+    for (i, component) in components.iter().enumerate().rev() {
+        match component {
+            ComponentLiterals::Exact(lit) => parts.push(lit.as_str()),
+            ComponentLiterals::Edges { start: _, end }
+            | ComponentLiterals::Glob { start: _, end } => {
+                parts.push(end);
+                break;
+            }
+            ComponentLiterals::Wildcard | ComponentLiterals::Unknown => break,
+        }
+
+        match i.checked_sub(1).map(|prev| &components[prev]) {
+            Some(ComponentLiterals::Wildcard) => break,
+            Some(_) => parts.push("/"),
+            None => parts.extend(root),
+        }
+    }
+
+    let literal_suffix = parts.into_iter().rev().collect::<String>();
+
+    (
+        literal_prefix,
+        (!literal_suffix.is_empty()).then_some(literal_suffix),
+    )
+}
+
+/// Build a root directory from a prefix and its components
+fn join_root_dir(prefix: Option<PathPrefix>, components: &[impl AsRef<str>]) -> PathBuf {
+    let mut root_dir = match prefix {
+        Some(prefix) => match prefix {
+            PathPrefix::RootDir => MAIN_SEPARATOR_STR.to_owned(),
+            // Without a separator, the path would be relative to the drive's current directory
+            PathPrefix::WindowsDrive(drive_letter) => {
+                format!("{}:{MAIN_SEPARATOR}", drive_letter.uppercase_letter())
+            }
+        },
+
+        None => String::new(),
+    };
+
+    for (i, component) in components.iter().enumerate() {
+        if i > 0 {
+            root_dir.push(MAIN_SEPARATOR);
+        }
+
+        root_dir.push_str(component.as_ref());
+    }
+
+    PathBuf::from(root_dir)
+}
+
+/// Count the strings matched by a suite of literal matchers (see [`expand_literal_matchers`])
+fn count_expansions(matchers: &[CharsMatcher]) -> usize {
+    matchers
+        .iter()
+        .map(|matcher| match matcher {
+            CharsMatcher::OneOfGroups(groups) => groups
+                .iter()
+                .map(|group| count_expansions(group))
+                .fold(0, usize::saturating_add),
+            _ => 1,
+        })
+        .fold(1, usize::saturating_mul)
+}
+
+/// Get all the strings matched by a suite of matchers only made of literals and groups of literals
+fn expand_literal_matchers(matchers: &[CharsMatcher]) -> Vec<String> {
+    let mut expansions = vec![String::new()];
+
+    for matcher in matchers {
+        expansions = match matcher {
+            CharsMatcher::Literal(lit) => expansions
+                .into_iter()
+                .map(|expansion| expansion + lit)
+                .collect(),
+
+            CharsMatcher::OneOfGroups(groups) => {
+                let alternates = groups
+                    .iter()
+                    .flat_map(|group| expand_literal_matchers(group))
+                    .collect::<Vec<_>>();
+
+                expansions
+                    .iter()
+                    .flat_map(|expansion| {
+                        alternates
+                            .iter()
+                            .map(move |alternate| format!("{expansion}{alternate}"))
+                    })
+                    .collect()
+            }
+
+            CharsMatcher::AnyChar
+            | CharsMatcher::AnyChars
+            | CharsMatcher::OneOfChars(_)
+            | CharsMatcher::NoneOfChars(_)
+            | CharsMatcher::ExtGlob(_, _) => unreachable!(),
+        };
+    }
+
+    expansions
+}
+
+/// Reusable scratch space for matching patterns against paths
+///
+/// Matching a path requires splitting it into components first, which allocates.
+/// Providing the same context to e.g. [`Pattern::is_match_with_ctx`] across calls makes these allocations
+/// only happen once, which makes a difference when matching millions of paths.
+///
+/// A single context can be used with any number of patterns.
+#[derive(Debug, Default, Clone)]
+pub struct MatchContext {
+    /// Components of the path being matched, as ranges of its encoded bytes
+    components: Vec<Range<usize>>,
+
+    /// Path being matched, if it had to be normalized (see [`PatternOpts::with_unicode_normalization`] and [`PatternOpts::with_fold_diacritics`])
+    normalized: String,
+
+    /// Components of the path being matched, concatenated, when they are provided separately (see [`Pattern::match_components`])
+    joined: Vec<u8>,
+}
+
+impl MatchContext {
+    /// Create a new (empty) context
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+/// Text captured by the wildcards of a pattern when matching a path (see [`Pattern::match_captures`])
+///
+/// Captures are split into two lists:
+/// * Texts, captured by each `*`, `?`, brackets and group (e.g. `{a|b}`) of the pattern
+/// * Globstars, the path components captured by each `**` of the pattern
+///
+/// Both lists are in order of appearance in the pattern, groups coming before the matchers they contain.
+/// For instance, when `src/**/{*_test|test_*}.rs` matches `src/a/b/foo_test.rs`:
+/// * Texts are `foo_test` for the group, `foo` for the first `*`, and nothing for the second one
+///   (as the alternate containing it wasn't used)
+/// * The only globstar contains the `a` and `b` components
+///
+/// Invalid UTF-8 sequences in captured texts are replaced with `U+FFFD REPLACEMENT CHARACTER`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MatchCaptures {
+    /// Text captured by each `*`, `?`, brackets and group, if they were used
+    texts: Vec<Option<String>>,
+
+    /// Path components captured by each `**`
+    globstars: Vec<Vec<String>>,
+}
+
+impl MatchCaptures {
+    /// Get the text captured by the `*`, `?`, brackets or group at the provided index
+    ///
+    /// Returns [`None`] if the index is out of bounds, or if the matcher is inside of a group's alternate
+    /// that wasn't used (see [`MatchCaptures::texts`] to distinguish these cases).
+    pub fn text(&self, index: usize) -> Option<&str> {
+        self.texts.get(index)?.as_deref()
+    }
+
+    /// Get the texts captured by each `*`, `?`, brackets and group, in order of appearance
+    pub fn texts(&self) -> &[Option<String>] {
+        &self.texts
+    }
+
+    /// Get the path components captured by the `**` at the provided index
+    ///
+    /// Returns [`None`] if the index is out of bounds. Note that `**` may capture no component at all.
+    pub fn globstar(&self, index: usize) -> Option<&[String]> {
+        self.globstars.get(index).map(Vec::as_slice)
+    }
+
+    /// Get the path components captured by each `**`, in order of appearance
+    pub fn globstars(&self) -> &[Vec<String>] {
+        &self.globstars
+    }
+}
+
+/// Incremental matcher, consuming a path one component at a time (see [`Pattern::matcher`])
+///
+/// Components are pushed (e.g. when entering a directory) and popped (e.g. when leaving it), and each step
+/// tells if the path made of the components pushed so far matches, and if its descendants may match (see [`StepResult`]).
+/// Each step only depends on the number of ways the pattern can match the path so far, not on its length,
+/// so walking a tree this way avoids matching each path from its first component.
+/// The exception is components that may span several path components (with [`PatternOpts::with_star_matches_separators`]):
+/// they are matched again against all the path components they may span, so their steps grow with the path's length.
+///
+/// Results are the same as with [`Pattern::is_match`] and [`Pattern::can_match_descendants`] for the path made of
+/// the pushed components, except the pattern's prefix is ignored: components are matched starting from the pattern's
+/// first one, so absolute patterns are matched against the components following the path's prefix.
+/// Pushed components are expected to be normalized, i.e. be single components and not `.`.
+#[derive(Debug, Clone)]
+pub struct PatternMatcher<'a> {
+    /// The pattern being matched
+    pattern: &'a Pattern,
+
+    /// States reached for the empty path, then after each pushed component
+    states: Vec<Vec<MatcherState>>,
+
+    /// Buffer to transform pushed components (see [`Pattern::path_bytes`])
+    buffer: String,
+}
+
+impl PatternMatcher<'_> {
+    /// Push a path component (e.g. a file name), and get the result for the path made of all pushed components
+    pub fn push(&mut self, name: &OsStr) -> StepResult {
+        let pattern = self.pattern;
+        let mut buffer = std::mem::take(&mut self.buffer);
+        let bytes = pattern.path_bytes(Path::new(name), &mut buffer);
+
+        let mut next = vec![];
+
+        for state in self.states.last().unwrap() {
+            match state {
+                MatcherState::At(index) => match pattern.components.get(*index) {
+                    // The component may also span the following ones
+                    Some(Component::Spanning(regex)) => {
+                        if regex.is_match(bytes) {
+                            push_matcher_state(&mut next, MatcherState::At(index + 1));
+                        }
+
+                        push_matcher_state(
+                            &mut next,
+                            MatcherState::Spanning(*index, bytes.to_vec()),
+                        );
+                    }
+
+                    // The wildcard consumes the component and may consume more
+                    Some(component @ Component::Wildcard)
+                        if pattern.component_matches(component, bytes) =>
+                    {
+                        push_matcher_state(&mut next, MatcherState::At(*index));
+                    }
+
+                    Some(component)
+                        if !matches!(component, Component::Wildcard)
+                            && pattern.component_matches(component, bytes) =>
+                    {
+                        push_matcher_state(&mut next, MatcherState::At(index + 1));
+                    }
+
+                    _ => {}
+                },
+
+                MatcherState::Spanning(index, joined) => {
+                    let Some(Component::Spanning(regex)) = pattern.components.get(*index) else {
+                        unreachable!()
+                    };
+
+                    let mut joined = joined.clone();
+                    joined.push(b'/');
+                    joined.extend_from_slice(bytes);
+
+                    if regex.is_match(&joined) {
+                        push_matcher_state(&mut next, MatcherState::At(index + 1));
+                    }
+
+                    push_matcher_state(&mut next, MatcherState::Spanning(*index, joined));
+                }
+            }
+        }
+
+        self.buffer = buffer;
+
+        pattern.close_matcher_states(&mut next);
+        self.states.push(next);
+
+        self.result()
+    }
+
+    /// Remove the last pushed component, if any
+    pub fn pop(&mut self) {
+        if self.states.len() > 1 {
+            self.states.pop();
+        }
+    }
+
+    /// Get the number of pushed components
+    pub fn depth(&self) -> usize {
+        self.states.len() - 1
+    }
+
+    /// Get the result for the path made of all pushed components
+    ///
+    /// Before any component is pushed, this is the result for the empty path (e.g. `**` matches it).
+    pub fn result(&self) -> StepResult {
+        let pattern = self.pattern;
+        let depth = self.depth();
+        let states = self.states.last().unwrap();
+
+        if states.is_empty() {
+            return StepResult::Dead;
+        }
+
+        let matched = states.contains(&MatcherState::At(pattern.components.len()))
+            && pattern.max_components.is_none_or(|max| depth <= max);
+
+        let may_match_descendants = states.iter().any(|state| match state {
+            MatcherState::At(index) => *index < pattern.components.len(),
+            MatcherState::Spanning(_, _) => true,
+        }) && pattern.max_components.is_none_or(|max| depth < max);
+
+        match (matched, may_match_descendants) {
+            (true, _) => StepResult::Matched {
+                may_match_descendants,
+            },
+            (false, true) => StepResult::Starved,
+            (false, false) => StepResult::Dead,
+        }
+    }
+}
+
+/// (Internal) State of a [`PatternMatcher`], i.e. a way the pattern can consume the components pushed so far
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum MatcherState {
+    /// Components were consumed by the pattern's components before this index
+    At(usize),
+
+    /// Components were consumed by the pattern's components before this index, then by the spanning component at this index
+    /// (see [`PatternOpts::with_star_matches_separators`]), which consumed the provided components joined with `/`
+    Spanning(usize, Vec<u8>),
+}
+
+/// (Internal) Add a state to a list, unless it's already in it
+fn push_matcher_state(states: &mut Vec<MatcherState>, state: MatcherState) {
+    if !states.contains(&state) {
+        states.push(state);
+    }
+}
+
+/// Result of a step of a [`PatternMatcher`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StepResult {
+    /// The path matches the pattern
+    Matched {
+        /// May descendants of the path match as well? (see [`Pattern::can_match_descendants`])
+        may_match_descendants: bool,
+    },
+
+    /// The path doesn't match the pattern, but its descendants may
+    Starved,
+
+    /// Neither the path nor its descendants can match the pattern
+    Dead,
+}
+
+impl StepResult {
+    /// Check if the path matches the pattern
+    pub fn is_match(&self) -> bool {
+        matches!(self, StepResult::Matched { .. })
+    }
+
+    /// Check if descendants of the path may match the pattern, i.e. if they should be visited
+    pub fn may_match_descendants(&self) -> bool {
+        match self {
+            StepResult::Matched {
+                may_match_descendants,
+            } => *may_match_descendants,
+            StepResult::Starved => true,
+            StepResult::Dead => false,
+        }
+    }
+}
+
+/// Result of a semantic comparison between patterns (see [`Pattern::matches_subset_of`])
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Comparison {
+    /// The comparison holds for all paths
+    Yes,
+
+    /// A path for which the comparison doesn't hold was found
+    No,
+
+    /// The comparison couldn't be proven nor disproven
+    Unknown,
+}
+
+/// Options overriding the ones of a pattern for a single match (see [`Pattern::is_match_with`])
+///
+/// Options are set using chained setters, e.g. `MatchOptions::new().with_case_insensitive(true)`.
+/// Options left unset use the pattern's ones.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct MatchOptions {
+    /// Override the case sensitivity of all components (see [`MatchOptions::with_case_insensitive`])
+    case_insensitive: Option<bool>,
+}
+
+impl MatchOptions {
+    /// Create options which don't override anything
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Match all components case-insensitively or case-sensitively, regardless of the pattern's options
+    ///
+    /// This takes precedence over [`PatternOpts::with_case_insensitive`], [`PatternOpts::with_smart_case`] and inline flags
+    /// (see [`PatternOpts::with_inline_flags`]), while [`PatternOpts::with_case_folding`] is still used.
+    ///
+    /// The pattern is compiled again with the requested case sensitivity the first time it's used,
+    /// and the result is kept inside of the pattern, so later matches are as fast as with [`Pattern::is_match`].
+    /// If that compilation fails (e.g. because of [`PatternOpts::with_regex_size_limit`]), nothing is matched.
+    pub fn with_case_insensitive(mut self, case_insensitive: bool) -> Self {
+        self.case_insensitive = Some(case_insensitive);
+        self
+    }
+
+    /// Get the case sensitivity set with [`MatchOptions::with_case_insensitive`], if any
+    pub fn case_insensitive(&self) -> Option<bool> {
+        self.case_insensitive
+    }
+}
+
+/// (Internal) Components of a path being matched
+#[derive(Clone, Copy)]
+struct PathParts<'a> {
+    /// The path's encoded bytes
+    bytes: &'a [u8],
+
+    /// Range of each component in the path's bytes
+    ranges: &'a [Range<usize>],
+}
+
+impl<'a> PathParts<'a> {
+    fn len(&self) -> usize {
+        self.ranges.len()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    fn get(&self, index: usize) -> &'a [u8] {
+        &self.bytes[self.ranges[index].clone()]
+    }
+
+    fn first(&self) -> Option<&'a [u8]> {
+        (!self.is_empty()).then(|| self.get(0))
+    }
+
+    /// Skip the provided number of components
+    fn skip(&self, count: usize) -> Self {
+        Self {
+            bytes: self.bytes,
+            ranges: &self.ranges[count..],
+        }
+    }
+}
+
+/// Compute, for each component index (and the end of the pattern), if a wildcard is located at or after it
+fn wildcard_at_or_after(components: &[Component]) -> Vec<bool> {
+    let mut out = vec![false; components.len() + 1];
+
+    for (i, component) in components.iter().enumerate().rev() {
+        out[i] = out[i + 1] || matches!(component, Component::Wildcard | Component::Spanning(_));
+    }
+
+    out
+}
+
+/// Check if a path followed by any number of additional components may match the provided components
+///
+/// This explores all the ways the path can be consumed, and succeeds if the pattern may still consume
+/// more components afterwards: either because some components remain, or because the last one
+/// consuming the path is a wildcard that could keep consuming.
+fn descendants_may_match(
+    components: &[Component],
+    wildcard_at_or_after: &[bool],
+    path: PathParts,
+    wildcard_skips_hidden: bool,
+) -> bool {
+    if path.is_empty() {
+        return !components.is_empty();
+    }
+
+    // Without any wildcard left, components are consumed one by one
+    if !wildcard_at_or_after[0] && components.len() <= path.len() {
+        return false;
+    }
+
+    let Some(component) = components.first() else {
+        return false;
+    };
+
+    let rest = |consumed: usize| {
+        descendants_may_match(
+            &components[1..],
+            &wildcard_at_or_after[1..],
+            path.skip(consumed),
+            wildcard_skips_hidden,
+        )
+    };
+
+    match component {
+        Component::Wildcard => {
+            for j in 0..path.len() {
+                if wildcard_skips_hidden && j > 0 && is_hidden(path.get(j - 1)) {
+                    return false;
+                }
+
+                if rest(j) {
+                    return true;
+                }
+            }
+
+            // The wildcard consumes the whole path, and may consume more
+            !(wildcard_skips_hidden && is_hidden(path.get(path.len() - 1)))
+        }
+
+        Component::Spanning(regex) => {
+            let mut joined = Vec::new();
+
+            for j in 0..path.len() {
+                if j > 0 {
+                    joined.push(b'/');
+                }
+
+                joined.extend_from_slice(path.get(j));
+
+                // The component may span additional components if it consumes the whole path
+                if regex.is_match(&joined) && (j + 1 == path.len() || rest(j + 1)) {
+                    return true;
+                }
+            }
+
+            // The component may also only match once joined with additional components
+            true
+        }
+
+        Component::Literal(lit) => path.get(0) == lit.as_bytes() && rest(1),
+
+        Component::CaselessLiteral(lit, folding) => {
+            caseless_eq(lit, *folding, path.get(0)) && rest(1)
+        }
+
+        Component::Regex(regex) => regex.is_match(path.get(0)) && rest(1),
+
+        Component::Negated(negated) => negated.is_match(path.get(0)) && rest(1),
+
+        Component::ParentDir => path.get(0) == b".." && rest(1),
+    }
+}
+
+/// Check if a path component is hidden, i.e. starts with a `.`
+fn is_hidden(component: &[u8]) -> bool {
+    component.first() == Some(&b'.')
+}
+
+/// Check if a single path component, after transformation, matches a compiled component
+fn component_matches(component: &Component, wildcard_skips_hidden: bool, bytes: &[u8]) -> bool {
+    match component {
+        Component::Wildcard => !(wildcard_skips_hidden && is_hidden(bytes)),
+        Component::Regex(regex) | Component::Spanning(regex) => regex.is_match(bytes),
+        Component::Negated(negated) => negated.is_match(bytes),
+        Component::Literal(lit) => bytes == lit.as_bytes(),
+        Component::CaselessLiteral(lit, folding) => caseless_eq(lit, *folding, bytes),
+        Component::ParentDir => bytes == b"..",
+    }
+}
+
+/// Transform the characters of a path to match like the literals of a pattern built with the provided options
+///
+/// The provided buffer is only used if the path needs to be changed.
+fn transform_bytes<'a>(
+    bytes: &'a [u8],
+    buffer: &'a mut String,
+    unicode_normalization: UnicodeNormalization,
+    fold_diacritics: bool,
+) -> &'a [u8] {
+    if !fold_diacritics {
+        return unicode_normalization.normalize(bytes, buffer);
+    }
+
+    match std::str::from_utf8(bytes) {
+        Ok(str) if !str.is_ascii() => {
+            buffer.clear();
+            buffer.extend(strip_diacritics(str.chars()));
+            buffer.as_bytes()
+        }
+
+        _ => bytes,
+    }
+}
+
+/// Match a path against components, recording the number of path components consumed by each one
+///
+/// The record is only complete if the path matched.
+fn match_components(
+    components: &[Component],
+    mut path: PathParts,
+    wildcard_skips_hidden: bool,
+    consumed: &mut impl ConsumedComponents,
+) -> PatternMatchResult {
+    for i in 0..components.len() {
+        match &components[i] {
+            Component::Wildcard => {
+                if components[i + 1..].is_empty() {
+                    return if wildcard_skips_hidden
+                        && let Some(j) = (0..path.len()).find(|j| is_hidden(path.get(*j)))
+                    {
+                        consumed.fail(0, j, MatchFailureReason::HiddenComponent);
+                        PatternMatchResult::NotMatched
+                    } else {
+                        consumed.push(path.len());
+                        PatternMatchResult::Matched
+                    };
+                }
+
+                if path.is_empty() {
+                    return if components[i + 1..].iter().any(|component| match component {
+                        Component::Regex(_)
+                        | Component::Spanning(_)
+                        | Component::Negated(_)
+                        | Component::Literal(_)
+                        | Component::CaselessLiteral(_, _)
+                        | Component::ParentDir => true,
+                        Component::Wildcard => false,
+                    }) {
+                        consumed.fail(1, 0, MatchFailureReason::Starved);
+                        PatternMatchResult::Starved
+                    } else {
+                        for _ in i..components.len() {
+                            consumed.push(0);
+                        }
+
+                        PatternMatchResult::Matched
+                    };
+                }
+
+                let mut starved = false;
+                let consumed_len = consumed.len();
+
+                for j in 0..path.len() {
+                    // The wildcard can't consume hidden components, so the rest of the pattern must match from there
+                    if wildcard_skips_hidden && j > 0 && is_hidden(path.get(j - 1)) {
+                        consumed.fail(0, j - 1, MatchFailureReason::HiddenComponent);
+
+                        return if starved {
+                            PatternMatchResult::Starved
+                        } else {
+                            PatternMatchResult::NotMatched
+                        };
+                    }
+
+                    consumed.push(j);
+
+                    match match_components(
+                        &components[i + 1..],
+                        path.skip(j),
+                        wildcard_skips_hidden,
+                        consumed,
+                    ) {
+                        PatternMatchResult::PathNotAbsolute
+                        | PatternMatchResult::PathIsAbsolute
+                        | PatternMatchResult::IncompatiblePrefix => unreachable!(),
+
+                        PatternMatchResult::Matched => return PatternMatchResult::Matched,
+
+                        PatternMatchResult::Starved => starved = true,
+
+                        PatternMatchResult::NotMatched => {}
+                    }
+
+                    consumed.truncate(consumed_len);
+                }
+
+                if wildcard_skips_hidden && is_hidden(path.get(path.len() - 1)) && !starved {
+                    consumed.fail(0, path.len() - 1, MatchFailureReason::HiddenComponent);
+                    return PatternMatchResult::NotMatched;
+                }
+
+                consumed.fail(1, path.len(), MatchFailureReason::Starved);
+                return PatternMatchResult::Starved;
+            }
+
+            Component::Literal(lit) => {
+                let Some(part) = path.first() else {
+                    consumed.fail(0, 0, MatchFailureReason::Starved);
+                    return PatternMatchResult::Starved;
+                };
+
+                if part != lit.as_bytes() {
+                    consumed.fail(0, 0, MatchFailureReason::LiteralMismatch);
+                    return PatternMatchResult::NotMatched;
+                }
+
+                path = path.skip(1);
+                consumed.push(1);
+            }
+
+            Component::CaselessLiteral(lit, folding) => {
+                let Some(part) = path.first() else {
+                    consumed.fail(0, 0, MatchFailureReason::Starved);
+                    return PatternMatchResult::Starved;
+                };
+
+                if !caseless_eq(lit, *folding, part) {
+                    consumed.fail(0, 0, MatchFailureReason::LiteralMismatch);
+                    return PatternMatchResult::NotMatched;
+                }
+
+                path = path.skip(1);
+                consumed.push(1);
+            }
+
+            Component::ParentDir => {
+                // Descendants of the path can't have `..` components
+                let Some(part) = path.first() else {
+                    consumed.fail(0, 0, MatchFailureReason::MissingParentDir);
+                    return PatternMatchResult::NotMatched;
+                };
+
+                if part != b".." {
+                    consumed.fail(0, 0, MatchFailureReason::LiteralMismatch);
+                    return PatternMatchResult::NotMatched;
+                }
+
+                path = path.skip(1);
+                consumed.push(1);
+            }
+
+            Component::Spanning(regex) => {
+                if path.is_empty() {
+                    consumed.fail(0, 0, MatchFailureReason::Starved);
+                    return PatternMatchResult::Starved;
+                }
+
+                // Try to match every possible suite of components, starting from the first one
+                let mut joined = Vec::new();
+                let consumed_len = consumed.len();
+
+                for j in 0..path.len() {
+                    if j > 0 {
+                        joined.push(b'/');
+                    }
+
+                    joined.extend_from_slice(path.get(j));
+
+                    if !regex.is_match(&joined) {
+                        continue;
+                    }
+
+                    consumed.push(j + 1);
+
+                    match match_components(
+                        &components[i + 1..],
+                        path.skip(j + 1),
+                        wildcard_skips_hidden,
+                        consumed,
+                    ) {
+                        PatternMatchResult::PathNotAbsolute
+                        | PatternMatchResult::PathIsAbsolute
+                        | PatternMatchResult::IncompatiblePrefix => unreachable!(),
+
+                        PatternMatchResult::Matched => return PatternMatchResult::Matched,
+
+                        PatternMatchResult::NotMatched | PatternMatchResult::Starved => {}
+                    }
+
+                    consumed.truncate(consumed_len);
+                }
+
+                // The component may span additional components the path doesn't have yet
+                consumed.fail(0, path.len(), MatchFailureReason::Starved);
+                return PatternMatchResult::Starved;
+            }
+
+            Component::Regex(regex) => {
+                let Some(part) = path.first() else {
+                    consumed.fail(0, 0, MatchFailureReason::Starved);
+                    return PatternMatchResult::Starved;
+                };
+
+                if !regex.is_match(part) {
+                    consumed.fail(0, 0, MatchFailureReason::RegexMismatch);
+                    return PatternMatchResult::NotMatched;
+                }
+
+                path = path.skip(1);
+                consumed.push(1);
+            }
+
+            Component::Negated(negated) => {
+                let Some(part) = path.first() else {
+                    consumed.fail(0, 0, MatchFailureReason::Starved);
+                    return PatternMatchResult::Starved;
+                };
+
+                if !negated.is_match(part) {
+                    consumed.fail(0, 0, MatchFailureReason::RegexMismatch);
+                    return PatternMatchResult::NotMatched;
+                }
+
+                path = path.skip(1);
+                consumed.push(1);
+            }
+        }
+    }
+
+    if path.is_empty() {
+        PatternMatchResult::Matched
+    } else {
+        consumed.fail(0, 0, MatchFailureReason::ExtraComponents);
+        PatternMatchResult::NotMatched
+    }
+}
+
+/// (Internal) Record of the number of path components consumed by each pattern component (see [`match_components`])
+///
+/// Matching without recording anything uses `()`, for which recording is a no-op.
+trait ConsumedComponents {
+    fn push(&mut self, count: usize);
+
+    fn len(&self) -> usize;
+
+    fn truncate(&mut self, len: usize);
+
+    /// Record a failure located after the components recorded so far, with the provided offsets
+    fn fail(&mut self, _pattern_offset: usize, _path_offset: usize, _reason: MatchFailureReason) {}
+}
+
+impl ConsumedComponents for () {
+    fn push(&mut self, _: usize) {}
+
+    fn len(&self) -> usize {
+        0
+    }
+
+    fn truncate(&mut self, _: usize) {}
+}
+
+/// (Internal) Record of the consumed components, keeping the failure located the furthest in the pattern (see [`Pattern::explain_match`])
+#[derive(Default)]
+struct FailureTracer {
+    consumed: Vec<usize>,
+    furthest: Option<MatchFailure>,
+}
+
+impl ConsumedComponents for FailureTracer {
+    fn push(&mut self, count: usize) {
+        self.consumed.push(count);
+    }
+
+    fn len(&self) -> usize {
+        self.consumed.len()
+    }
+
+    fn truncate(&mut self, len: usize) {
+        self.consumed.truncate(len);
+    }
+
+    fn fail(&mut self, pattern_offset: usize, path_offset: usize, reason: MatchFailureReason) {
+        let failure = MatchFailure {
+            pattern_component: self.consumed.len() + pattern_offset,
+            path_component: self.consumed.iter().sum::<usize>() + path_offset,
+            reason,
+        };
+
+        // Prefer the failure the furthest in the pattern, then in the path
+        let key = |failure: &MatchFailure| (failure.pattern_component, failure.path_component);
+
+        if self
+            .furthest
+            .as_ref()
+            .is_none_or(|furthest| key(&failure) > key(furthest))
+        {
+            self.furthest = Some(failure);
+        }
+    }
+}
+
+impl ConsumedComponents for Vec<usize> {
+    fn push(&mut self, count: usize) {
+        Vec::push(self, count);
+    }
+
+    fn len(&self) -> usize {
+        Vec::len(self)
+    }
+
+    fn truncate(&mut self, len: usize) {
+        Vec::truncate(self, len);
+    }
+}
+
+/// Error occuring while building a pattern
+#[derive(Debug)]
+pub enum PatternError {
+    /// The pattern's syntax is invalid
+    Parsing(ParseError),
+
+    /// A component of the pattern could not be compiled,
+    /// e.g. because it exceeds [`PatternOpts::with_regex_size_limit`]
+    Compilation {
+        /// Source of the component
+        component: String,
+
+        /// Offset of the component in the pattern, in bytes
+        offset: usize,
+
+        /// Reason of the failure
+        err: regex::Error,
+    },
+
+    /// The pattern can't match anything on the current platform,
+    /// and [`PatternOpts::with_reject_foreign_prefixes`] is enabled
+    ForeignPrefix(Portability),
+
+    /// The pattern exceeds [`PatternOpts::with_max_complexity`]
+    TooComplex {
+        /// Complexity of the pattern
+        complexity: usize,
+
+        /// Maximum allowed complexity
+        max: usize,
+    },
+
+    /// The path can't be matched by a pattern (see [`Pattern::from_literal_path`]),
+    /// because it has an unsupported prefix (e.g. `\\server\share`) or isn't valid UTF-8
+    UnsupportedPath(PathBuf),
+}
+
+impl fmt::Display for PatternError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PatternError::Parsing(err) => write!(f, "{err}"),
+
+            PatternError::Compilation {
+                component,
+                offset,
+                err,
+            } => write!(
+                f,
+                "failed to compile component '{component}' at offset {offset}: {err}"
+            ),
 
-#[derive(Debug, Clone)]
-pub struct Pattern {
-    /// Does the pattern start with a prefix component?
-    prefix: Option<PathPrefix>,
+            PatternError::ForeignPrefix(portability) => {
+                let platform = match portability {
+                    Portability::Universal => "any platform",
+                    Portability::WindowsOnly => "Windows",
+                };
 
-    /// Root directory that's common to all possible matches of a pattern
+                write!(
+                    f,
+                    "pattern can only match paths on {platform}, which is not the current platform"
+                )
+            }
+
+            PatternError::TooComplex { complexity, max } => write!(
+                f,
+                "pattern is too complex ({complexity} matchers, maximum is {max})"
+            ),
+
+            PatternError::UnsupportedPath(path) => write!(
+                f,
+                "path '{}' cannot be matched by a pattern, as it has an unsupported prefix or isn't valid UTF-8",
+                path.display()
+            ),
+        }
+    }
+}
+
+impl std::error::Error for PatternError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            PatternError::Compilation { err, .. } => Some(err),
+            PatternError::Parsing(_)
+            | PatternError::ForeignPrefix(_)
+            | PatternError::TooComplex { .. }
+            | PatternError::UnsupportedPath(_) => None,
+        }
+    }
+}
+
+impl PatternError {
+    /// Render the error below the pattern it occurred in, pointing at the offending part of the pattern
     ///
-    /// e.g. The root directory of `/a/{b, c}` will be `/a`
+    /// For instance, `src/[abc` fails with:
     ///
-    /// This is useful to determine where to start directory traversal from
-    common_root_dir: PathBuf,
-
-    /// The components that make up the pattern
+    /// ```text
+    /// src/[abc
+    ///         ^ unexpected end of input
+    /// ```
     ///
-    /// Each of them match a single path component (except the wildcard matcher)
-    components: Vec<Component>,
+    /// Errors that aren't related to a specific part of the pattern are rendered after it, without pointing at anything.
+    pub fn render(&self, pattern: &str) -> String {
+        match self {
+            PatternError::Parsing(err) => err.render(pattern),
 
-    /// Does the pattern contain a wildcard?
-    /// For more informations, see [`Pattern::has_wildcard`]
-    has_wildcard: bool,
-}
+            PatternError::Compilation {
+                component,
+                offset,
+                err,
+            } => render_error_snippet(pattern, *offset, component.len(), &err.to_string()),
 
-impl Pattern {
-    /// Parse a pattern with the default options
-    pub fn new(input: &str) -> Result<Self, ParsingError> {
-        Self::new_with_opts(input, PatternOpts::default())
+            PatternError::ForeignPrefix(_)
+            | PatternError::TooComplex { .. }
+            | PatternError::UnsupportedPath(_) => format!("{pattern}\n{self}"),
+        }
     }
+}
 
-    /// Parse a pattern
-    pub fn new_with_opts(input: &str, opts: PatternOpts) -> Result<Self, ParsingError> {
-        let PatternOpts { case_insensitive } = opts;
+/// Syntax error in a pattern (see [`PatternError::Parsing`])
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    /// Offset of the offending part of the pattern, in bytes
+    pub offset: usize,
 
-        let RawPattern { components, prefix } =
-            PATTERN_PARSER.parse_str(input).map(|parsed| parsed.data)?;
+    /// Length of the offending part of the pattern, in bytes
+    ///
+    /// This is `0` when the error is located between two characters, e.g. for an unexpected end of input.
+    pub len: usize,
 
-        // Compile each individual comopnent
-        let components: Vec<_> = components
-            .into_iter()
-            .map(|component| {
-                compile_component(
-                    component,
-                    // Provide compilation options
-                    if case_insensitive {
-                        CaseSensitivity::Insensitive
-                    } else {
-                        CaseSensitivity::Sensitive
-                    },
-                )
-            })
-            .collect();
+    /// Description of the error
+    pub message: String,
+}
 
-        Ok(Self {
-            common_root_dir: build_common_root_dir(prefix, &components),
-            prefix,
-            has_wildcard: components.iter().any(|c| matches!(c, Component::Wildcard)),
-            components,
-        })
+impl ParseError {
+    /// Convert an error from the parser
+    fn from_parsing(err: &ParsingError) -> Self {
+        let at = err.inner().at();
+
+        Self {
+            offset: at.start.offset,
+            len: at.len,
+            message: err
+                .critical_message()
+                .map(str::to_owned)
+                .unwrap_or_else(|| err.inner().expected().to_string()),
+        }
     }
 
-    /// Check if the pattern is absolute (only matches absolute paths)
-    pub fn is_absolute(&self) -> bool {
-        self.prefix.is_some()
+    /// Render the error below the pattern it occurred in, pointing at the offending part of the pattern
+    ///
+    /// See [`PatternError::render`]
+    pub fn render(&self, pattern: &str) -> String {
+        render_error_snippet(pattern, self.offset, self.len, &self.message)
     }
+}
 
-    /// Get the path prefix
-    pub fn prefix(&self) -> Option<PathPrefix> {
-        self.prefix
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "invalid pattern at offset {}: {}",
+            self.offset, self.message
+        )
     }
+}
 
-    /// Match the pattern against a path
-    ///
-    /// Note that the path should be normalized.
-    /// For instance, '..' components in the pattern will be matched against literal '..' in the path.
-    pub fn is_match(&self, path: &Path) -> bool {
-        matches!(self.match_against(path), PatternMatchResult::Matched)
+impl std::error::Error for ParseError {}
+
+/// Render a message below a pattern, with carets pointing at the provided span of bytes
+///
+/// Carets are aligned on characters rather than bytes, and at least one is rendered
+/// so errors located between two characters can be pointed at.
+fn render_error_snippet(pattern: &str, offset: usize, len: usize, message: &str) -> String {
+    let chars_until = |offset: usize| {
+        pattern
+            .char_indices()
+            .take_while(|(i, _)| *i < offset)
+            .count()
+    };
+
+    let start = chars_until(offset);
+    let carets = (chars_until(offset + len) - start).max(1);
+
+    format!(
+        "{pattern}\n{}{} {message}",
+        " ".repeat(start),
+        "^".repeat(carets)
+    )
+}
+
+/// Maximum number of paths a pattern can be expanded into (see [`Pattern::expand`])
+pub const MAX_EXPANSIONS: usize = 10_000;
+
+/// Error occuring when a pattern matches too many paths to be expanded (see [`Pattern::expand`])
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TooManyExpansions {
+    /// Number of paths the pattern's groups expand into, some of which may be identical (saturated at [`usize::MAX`])
+    pub count: usize,
+}
+
+impl fmt::Display for TooManyExpansions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "pattern matches too many paths to be expanded ({}, maximum is {MAX_EXPANSIONS})",
+            self.count
+        )
     }
+}
 
-    pub fn match_against(&self, path: &Path) -> PatternMatchResult {
-        let Ok(path) = normalize_path(path) else {
-            return PatternMatchResult::IncompatiblePrefix;
-        };
+impl std::error::Error for TooManyExpansions {}
 
-        let is_absolute = path.prefix().is_some();
+/// Error occuring while joining two patterns (see [`Pattern::join`])
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JoinError {
+    /// The appended pattern is absolute
+    Absolute,
 
-        match &self.prefix {
-            Some(PathPrefix::RootDir) => {
-                if !is_absolute {
-                    return PatternMatchResult::PathNotAbsolute;
-                }
+    /// The appended pattern starts with `..` components, which can't follow other components
+    ParentDir,
+
+    /// The patterns were built with different options, or with options that only apply to whole patterns
+    IncompatibleOptions,
+
+    /// The base directory can't be matched literally by a pattern built with the same options (see [`Pattern::rebase`])
+    ///
+    /// This happens when its prefix is unsupported (e.g. `\\?\server\share`), when it isn't valid UTF-8,
+    /// or when it contains characters that can't be escaped, i.e. special characters with
+    /// [`PatternOpts::with_disable_brackets`], or `\` (with [`PatternOpts::with_slash_only`]) without [`PatternOpts::with_backslash_escapes`]
+    /// or [`PatternOpts::with_literal_backslashes`].
+    InvalidBase,
+}
+
+impl fmt::Display for JoinError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            JoinError::Absolute => write!(f, "cannot append an absolute pattern"),
+            JoinError::ParentDir => write!(
+                f,
+                "cannot append a pattern starting with '..' after other components"
+            ),
+            JoinError::IncompatibleOptions => {
+                write!(f, "cannot join patterns built with incompatible options")
             }
+            JoinError::InvalidBase => write!(f, "base directory cannot be matched literally"),
+        }
+    }
+}
 
-            Some(PathPrefix::WindowsDrive(windows_drive)) => match path.prefix() {
-                Some(prefix) => match prefix {
-                    PathPrefix::RootDir => return PatternMatchResult::IncompatiblePrefix,
+impl std::error::Error for JoinError {}
 
-                    PathPrefix::WindowsDrive(path_windows_drive) => {
-                        if *windows_drive != path_windows_drive {
-                            return PatternMatchResult::NotMatched;
-                        }
-                    }
-                },
+/// Statistics on a pattern's structure
+///
+/// See [`Pattern::stats`]
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct PatternStats {
+    /// Number of path components (`.` components and empty ones, e.g. with `a//b`, are not counted)
+    pub components: usize,
 
-                None => return PatternMatchResult::PathNotAbsolute,
-            },
+    /// Number of literal suites of characters (`..` components included)
+    ///
+    /// For instance, `a{b|c}d` contains 4 literals
+    pub literals: usize,
 
-            None => {
-                if is_absolute {
-                    return PatternMatchResult::PathIsAbsolute;
+    /// Number of `*` and `?` matchers
+    pub wildcards: usize,
+
+    /// Number of `**` components
+    pub globstars: usize,
+
+    /// Number of character alternates (e.g. `[abc]` or `[![:digit:]]`)
+    pub classes: usize,
+
+    /// Number of group alternates (e.g. `{a|b}`) and extended glob operators (e.g. `+(a|b)`)
+    pub groups: usize,
+
+    /// Maximum nesting depth of group alternates and extended glob operators
+    ///
+    /// For instance, this is `0` for `*.rs`, `1` for `{a|b}/{c|d}` and `2` for `{a|{b|c}}`
+    pub max_nesting_depth: usize,
+
+    /// Is the pattern absolute? (see [`Pattern::is_absolute`])
+    pub absolute: bool,
+
+    /// Is any component matched case-insensitively?
+    ///
+    /// See [`PatternOpts::with_case_insensitive`] and [`PatternOpts::with_smart_case`]
+    pub case_insensitive: bool,
+}
+
+impl PatternStats {
+    /// Account for a parsed component
+    fn add_component(&mut self, component: &RawComponent, case_insensitive: bool) {
+        match component {
+            RawComponent::Literal(_) | RawComponent::ParentDir => self.literals += 1,
+            RawComponent::Suite(matchers) => self.add_matchers(matchers, 0),
+            RawComponent::Wildcard => self.globstars += 1,
+        }
+
+        self.case_insensitive |= case_insensitive;
+    }
+
+    /// Account for the components of another pattern appended to this one
+    fn merge(&mut self, other: &PatternStats) {
+        self.components += other.components;
+        self.literals += other.literals;
+        self.wildcards += other.wildcards;
+        self.globstars += other.globstars;
+        self.classes += other.classes;
+        self.groups += other.groups;
+        self.max_nesting_depth = self.max_nesting_depth.max(other.max_nesting_depth);
+        self.case_insensitive |= other.case_insensitive;
+    }
+
+    /// Account for a suite of matchers, nested in `depth` groups
+    fn add_matchers(&mut self, matchers: &[CharsMatcher], depth: usize) {
+        for matcher in matchers {
+            match matcher {
+                CharsMatcher::Literal(_) => self.literals += 1,
+                CharsMatcher::AnyChar | CharsMatcher::AnyChars => self.wildcards += 1,
+                CharsMatcher::OneOfChars(_) | CharsMatcher::NoneOfChars(_) => self.classes += 1,
+                CharsMatcher::OneOfGroups(groups) | CharsMatcher::ExtGlob(_, groups) => {
+                    self.groups += 1;
+                    self.max_nesting_depth = self.max_nesting_depth.max(depth + 1);
+
+                    for group in groups {
+                        self.add_matchers(group, depth + 1);
+                    }
                 }
             }
         }
+    }
+}
+
+/// Platforms a pattern can match paths on
+///
+/// See [`Pattern::portability`]. Paths that can be matched on Unix platforms (e.g. `/abs`) can also be matched
+/// on Windows, so no pattern is restricted to Unix platforms.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Portability {
+    /// The pattern can match paths on any platform
+    Universal,
+
+    /// The pattern can only match paths on Windows (e.g. because of a drive prefix)
+    WindowsOnly,
+}
 
-        match_components(&self.components, path.components())
+impl Portability {
+    fn of_prefix(prefix: Option<PathPrefix>) -> Self {
+        match prefix {
+            Some(PathPrefix::WindowsDrive(_)) => Self::WindowsOnly,
+            Some(PathPrefix::RootDir) | None => Self::Universal,
+        }
     }
 
-    /// Get the common root directory for all possible matches of this pattern
-    pub fn common_root_dir(&self) -> &Path {
-        &self.common_root_dir
+    /// Check if patterns with this portability can match paths on the current platform
+    pub fn supports_current_platform(self) -> bool {
+        match self {
+            Self::Universal => true,
+            Self::WindowsOnly => cfg!(windows),
+        }
     }
+}
 
-    /// Check if the component contains a wildcard
+/// Case folding used to match patterns case-insensitively
+///
+/// See [`PatternOpts::with_case_folding`]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "lowercase")
+)]
+pub enum CaseFolding {
+    /// Only fold ASCII letters, so e.g. `é` will not match `É`
+    Ascii,
+
+    /// Fold all Unicode characters, so e.g. `é` will match `É`
     ///
-    /// Can be useful for e.g. determining if a matching directory should be traversed or not,
-    /// as the presence or absence of a wildcard indicates whether descendants may match
+    /// Literal components use full case folding, which may map a character to several ones:
+    /// for instance, `straße` will match `STRASSE`. Turkic-specific mappings are not used, so `I` matches `i`,
+    /// while dotless `ı` only matches itself and dotted `İ` matches `i̇` (`i` followed by U+0307).
     ///
-    /// Example:
-    /// * `/a/b` matches `/a/b` but cannot match any descendant
-    /// * `/a/**/b` matches `/a/b` and may match some descendants
-    /// * `/a/b/**` matches `/a/b` and may match some descendants
-    pub fn has_wildcard(&self) -> bool {
-        self.has_wildcard
-    }
+    /// Components containing matchers use the simple case folding of regular expressions instead,
+    /// in which each character is folded to a single one (e.g. `ß` matches `ẞ` but not `ss`).
+    #[default]
+    Unicode,
 }
 
-fn build_common_root_dir(prefix: Option<PathPrefix>, components: &[Component]) -> PathBuf {
-    // Get all deterministic components at the beginning of the pattern
-    // These will be used to compute the common root directory
-    let mut common_root_dir_components = components
-        .iter()
-        .map_while(|component| match component {
-            // Only get literal components, as these will always match the exact same path components
-            Component::Literal(lit) => Some(lit.as_str()),
-            Component::ParentDir => Some(".."),
-            _ => None,
-        })
-        .collect::<Vec<_>>();
+/// Unicode normalization applied before comparing strings
+///
+/// See [`PatternOpts::with_unicode_normalization`]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "lowercase")
+)]
+pub enum UnicodeNormalization {
+    /// Compare strings as they are, so e.g. `é` (U+00E9) will not match `é` (`e` followed by U+0301)
+    #[default]
+    None,
 
-    // If the entire pattern is deterministic, match from the parent directory to allow yielding
-    // that specific child item
-    if common_root_dir_components.len() == components.len() {
-        common_root_dir_components.pop();
-    }
+    /// Normalize strings to the canonical composition form (NFC) first, so both forms of `é` match each other
+    ///
+    /// Paths that aren't valid UTF-8 are matched without being normalized.
+    /// Characters inside brackets (e.g. `[é]`) are not normalized either, as each of them must match a single character.
+    Nfc,
+}
 
-    // Build the common root directory
-    let mut common_root_dir = match prefix {
-        Some(prefix) => match prefix {
-            PathPrefix::RootDir => MAIN_SEPARATOR_STR.to_owned(),
-            PathPrefix::WindowsDrive(drive_letter) => {
-                format!("{}:", drive_letter.uppercase_letter())
-            }
-        },
+impl UnicodeNormalization {
+    /// (Internal) Normalize a path's encoded bytes, using the provided buffer if they need to be changed
+    fn normalize<'a>(self, bytes: &'a [u8], buffer: &'a mut String) -> &'a [u8] {
+        match self {
+            UnicodeNormalization::None => bytes,
 
-        None => String::new(),
-    };
+            UnicodeNormalization::Nfc => match std::str::from_utf8(bytes) {
+                Ok(str) if !is_nfc(str) => {
+                    buffer.clear();
+                    buffer.extend(str.nfc());
+                    buffer.as_bytes()
+                }
 
-    for (i, common_root_dir_component) in common_root_dir_components.iter().enumerate() {
-        if i > 0 {
-            common_root_dir.push(MAIN_SEPARATOR);
+                _ => bytes,
+            },
         }
-
-        common_root_dir.push_str(common_root_dir_component);
     }
+}
+
+/// Component of a pattern (see [`Pattern::components`])
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum PatternComponent<'a> {
+    /// Component matching a single name (e.g. `src`)
+    Literal {
+        /// Name matched by the component
+        ///
+        /// This is the name paths are compared against, so escapes are resolved and transformations
+        /// are applied (see e.g. [`PatternOpts::with_unicode_normalization`]). Components matched case-insensitively
+        /// with [`CaseFolding::Unicode`] provide their name in folded form (e.g. `readme` for `README`).
+        name: &'a str,
+
+        /// Source of the component in the pattern
+        source: &'a str,
+    },
 
-    PathBuf::from(common_root_dir)
+    /// Parent directory (`..`)
+    ParentDir,
+
+    /// Any number of components (`**`)
+    AnyRecursive {
+        /// Was the component added by [`PatternOpts::with_match_basename`] instead of being part of the pattern?
+        implicit: bool,
+    },
+
+    /// Component matched using wildcards, brackets or groups (e.g. `*.{rs|toml}`)
+    Matcher {
+        /// Source of the component in the pattern
+        source: &'a str,
+    },
 }
 
-fn match_components(components: &[Component], mut path: &[OsString]) -> PatternMatchResult {
-    for i in 0..components.len() {
-        match &components[i] {
-            Component::Wildcard => {
-                if components[i + 1..].is_empty() {
-                    return PatternMatchResult::Matched;
-                }
+/// Matcher for the file names matched by a pattern's last component (see [`Pattern::file_name_pattern`])
+#[derive(Debug, Clone)]
+pub struct FileNameMatcher {
+    /// The pattern's last component
+    component: Component,
 
-                if path.is_empty() {
-                    return if components[i + 1..].iter().any(|component| match component {
-                        Component::Regex(_) | Component::Literal(_) | Component::ParentDir => true,
-                        Component::Wildcard => false,
-                    }) {
-                        PatternMatchResult::Starved
-                    } else {
-                        PatternMatchResult::Matched
-                    };
-                }
+    /// Unicode normalization to apply to matched names (see [`PatternOpts::with_unicode_normalization`])
+    unicode_normalization: UnicodeNormalization,
 
-                for j in 0..path.len() {
-                    match match_components(&components[i + 1..], &path[j..]) {
-                        PatternMatchResult::PathNotAbsolute
-                        | PatternMatchResult::PathIsAbsolute
-                        | PatternMatchResult::IncompatiblePrefix => unreachable!(),
+    /// Remove diacritics from matched names (see [`PatternOpts::with_fold_diacritics`])
+    fold_diacritics: bool,
+}
 
-                        PatternMatchResult::Matched => return PatternMatchResult::Matched,
+impl FileNameMatcher {
+    /// Check if a file name matches
+    pub fn is_match(&self, name: &OsStr) -> bool {
+        self.is_match_bytes(name.as_encoded_bytes())
+    }
 
-                        PatternMatchResult::NotMatched | PatternMatchResult::Starved => {}
-                    }
-                }
+    /// Check if a file name provided as a string matches
+    pub fn is_match_str(&self, name: &str) -> bool {
+        self.is_match_bytes(name.as_bytes())
+    }
 
-                return PatternMatchResult::Starved;
-            }
+    fn is_match_bytes(&self, name: &[u8]) -> bool {
+        let mut buffer = String::new();
+        let bytes = transform_bytes(
+            name,
+            &mut buffer,
+            self.unicode_normalization,
+            self.fold_diacritics,
+        );
 
-            Component::Literal(lit) => {
-                let Some(part) = path.first() else {
-                    return PatternMatchResult::Starved;
-                };
+        // `**` components are never used, so hidden names don't need to be skipped
+        component_matches(&self.component, false, bytes)
+    }
+}
 
-                path = &path[1..];
+/// Hint on the kind of entries a pattern matches
+///
+/// See [`Pattern::match_target_hint`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TargetHint {
+    /// The pattern may match any kind of entry
+    AnyEntry,
 
-                if part.as_encoded_bytes() != lit.as_bytes() {
-                    return PatternMatchResult::NotMatched;
-                }
-            }
+    /// The pattern may match any kind of entry, but is mostly useful for matching directories
+    DirectoriesLikely,
 
-            Component::ParentDir => {
-                let Some(part) = path.first() else {
-                    return PatternMatchResult::NotMatched;
-                };
+    /// The pattern can only match directories
+    RequiresDirectory,
+}
 
-                path = &path[1..];
+/// Result of a match, explaining why it failed (see [`Pattern::explain_match`])
+#[derive(Debug, Clone)]
+pub struct MatchTrace {
+    /// Result of the match
+    result: PatternMatchResult,
 
-                if part.as_encoded_bytes() != "..".as_bytes() {
-                    return PatternMatchResult::NotMatched;
-                }
-            }
+    /// Location of the failure, if any
+    failure: Option<MatchFailure>,
+}
 
-            Component::Regex(regex) => {
-                let Some(part) = path.first() else {
-                    return PatternMatchResult::Starved;
-                };
+impl MatchTrace {
+    /// Get the result of the match, as returned by [`Pattern::match_against`]
+    pub fn result(&self) -> PatternMatchResult {
+        self.result
+    }
 
-                path = &path[1..];
+    /// Get the location and reason of the failure
+    ///
+    /// Returns [`None`] if the path matched, or if it was rejected before matching any component
+    /// (i.e. [`PatternMatchResult::PathNotAbsolute`], [`PatternMatchResult::PathIsAbsolute`] and
+    /// [`PatternMatchResult::IncompatiblePrefix`]).
+    pub fn failure(&self) -> Option<&MatchFailure> {
+        self.failure.as_ref()
+    }
+}
 
-                if !regex.is_match(part.as_encoded_bytes()) {
-                    return PatternMatchResult::NotMatched;
-                }
-            }
-        }
+/// Location where matching a path failed (see [`MatchTrace::failure`])
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MatchFailure {
+    /// Index of the pattern's component (see [`Pattern::components`])
+    pattern_component: usize,
+
+    /// Index of the path's component, after normalization
+    path_component: usize,
+
+    /// Reason of the failure
+    reason: MatchFailureReason,
+}
+
+impl MatchFailure {
+    /// Get the index of the pattern's component that failed (see [`Pattern::components`])
+    ///
+    /// This is the number of components if the path has more components than the pattern
+    /// (see [`MatchFailureReason::ExtraComponents`]).
+    pub fn pattern_component(&self) -> usize {
+        self.pattern_component
     }
 
-    if path.is_empty() {
-        PatternMatchResult::Matched
-    } else {
-        PatternMatchResult::NotMatched
+    /// Get the index of the path's component, after normalization (see [`crate::normalize_path`]), where matching failed
+    ///
+    /// This is the number of components if the path ended before the pattern
+    /// (see [`MatchFailureReason::Starved`] and [`MatchFailureReason::MissingParentDir`]).
+    pub fn path_component(&self) -> usize {
+        self.path_component
     }
+
+    /// Get the reason of the failure
+    pub fn reason(&self) -> MatchFailureReason {
+        self.reason
+    }
+}
+
+/// Reason why matching a path failed (see [`MatchFailure`])
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MatchFailureReason {
+    /// The path's component is different from the pattern's literal (or `..`) component
+    LiteralMismatch,
+
+    /// The path's component doesn't match the pattern's component
+    RegexMismatch,
+
+    /// The path ended before the pattern's component, which may still match a descendant
+    Starved,
+
+    /// The path ended before the pattern's `..` component, which can't match any descendant
+    MissingParentDir,
+
+    /// The pattern ended before the path's component
+    ExtraComponents,
+
+    /// The path's component is hidden, and can't be matched by the pattern's `**` component
+    /// (see [`PatternOpts::with_globstar_skips_hidden`] and [`PatternOpts::with_require_literal_leading_dot`])
+    HiddenComponent,
+
+    /// The path's Windows drive is different from the pattern's one
+    PrefixMismatch,
+
+    /// The path has more components than [`PatternOpts::with_max_components`], the first extra one being reported
+    TooManyComponents,
 }
 
 /// Result of a pattern matching against a path
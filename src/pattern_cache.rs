@@ -0,0 +1,238 @@
+//! Process-wide cache of compiled patterns (see [`Pattern::cached`])
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex, MutexGuard, OnceLock, PoisonError},
+};
+
+use crate::{Pattern, PatternError, PatternOpts};
+
+/// Number of patterns kept in the cache, unless changed with [`set_pattern_cache_capacity`]
+pub const DEFAULT_PATTERN_CACHE_CAPACITY: usize = 256;
+
+/// Process-wide cache of compiled patterns
+static PATTERN_CACHE: OnceLock<Mutex<PatternCache>> = OnceLock::new();
+
+/// Compiled patterns, keyed by their source and options, evicting the least recently used ones
+///
+/// Patterns are stored in a doubly-linked list ordered by recency, whose nodes are indexed by options then source,
+/// so patterns can be looked up without allocating and both lookups and evictions take constant time.
+struct PatternCache {
+    /// Index of each cached pattern's node, by options then source
+    index: HashMap<PatternOpts, HashMap<Box<str>, usize>>,
+
+    /// Nodes of the list, [`None`] for free ones
+    nodes: Vec<Option<CacheNode>>,
+
+    /// Indices of the free nodes, to be reused
+    free: Vec<usize>,
+
+    /// Most recently used node
+    head: Option<usize>,
+
+    /// Least recently used node
+    tail: Option<usize>,
+
+    /// Number of cached patterns
+    len: usize,
+
+    /// Maximum number of cached patterns
+    capacity: usize,
+}
+
+/// Node of the [`PatternCache`]'s recency list
+struct CacheNode {
+    source: Box<str>,
+    opts: PatternOpts,
+    pattern: Arc<Pattern>,
+
+    /// More recently used node
+    prev: Option<usize>,
+
+    /// Less recently used node
+    next: Option<usize>,
+}
+
+impl PatternCache {
+    fn new() -> Self {
+        Self {
+            index: HashMap::new(),
+            nodes: vec![],
+            free: vec![],
+            head: None,
+            tail: None,
+            len: 0,
+            capacity: DEFAULT_PATTERN_CACHE_CAPACITY,
+        }
+    }
+
+    fn get(&mut self, input: &str, opts: PatternOpts) -> Option<Arc<Pattern>> {
+        let index = *self.index.get(&opts)?.get(input)?;
+
+        self.unlink(index);
+        self.push_front(index);
+
+        Some(Arc::clone(&self.node(index).pattern))
+    }
+
+    fn insert(&mut self, input: &str, opts: PatternOpts, pattern: Arc<Pattern>) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        // The pattern may have been inserted while it was being compiled
+        if let Some(&index) = self.index.get(&opts).and_then(|sources| sources.get(input)) {
+            self.node_mut(index).pattern = pattern;
+            self.unlink(index);
+            self.push_front(index);
+            return;
+        }
+
+        self.shrink_to(self.capacity - 1);
+
+        let node = CacheNode {
+            source: input.into(),
+            opts,
+            pattern,
+            prev: None,
+            next: None,
+        };
+
+        let index = match self.free.pop() {
+            Some(index) => {
+                self.nodes[index] = Some(node);
+                index
+            }
+
+            None => {
+                self.nodes.push(Some(node));
+                self.nodes.len() - 1
+            }
+        };
+
+        self.index
+            .entry(opts)
+            .or_default()
+            .insert(input.into(), index);
+
+        self.push_front(index);
+        self.len += 1;
+    }
+
+    /// Evict the least recently used patterns until at most `len` remain
+    fn shrink_to(&mut self, len: usize) {
+        while self.len > len {
+            let index = self.tail.unwrap();
+            self.unlink(index);
+
+            let node = self.nodes[index].take().unwrap();
+
+            if let Some(sources) = self.index.get_mut(&node.opts) {
+                sources.remove(&node.source);
+
+                if sources.is_empty() {
+                    self.index.remove(&node.opts);
+                }
+            }
+
+            self.free.push(index);
+            self.len -= 1;
+        }
+    }
+
+    /// Remove all patterns
+    fn clear(&mut self) {
+        let capacity = self.capacity;
+
+        *self = Self::new();
+        self.capacity = capacity;
+    }
+
+    /// Remove a node from the list, keeping it allocated
+    fn unlink(&mut self, index: usize) {
+        let CacheNode { prev, next, .. } = *self.node(index);
+
+        match prev {
+            Some(prev) => self.node_mut(prev).next = next,
+            None => self.head = next,
+        }
+
+        match next {
+            Some(next) => self.node_mut(next).prev = prev,
+            None => self.tail = prev,
+        }
+    }
+
+    /// Insert a node at the start of the list, i.e. as the most recently used one
+    fn push_front(&mut self, index: usize) {
+        let head = self.head;
+
+        let node = self.node_mut(index);
+        node.prev = None;
+        node.next = head;
+
+        match head {
+            Some(head) => self.node_mut(head).prev = Some(index),
+            None => self.tail = Some(index),
+        }
+
+        self.head = Some(index);
+    }
+
+    fn node(&self, index: usize) -> &CacheNode {
+        self.nodes[index].as_ref().unwrap()
+    }
+
+    fn node_mut(&mut self, index: usize) -> &mut CacheNode {
+        self.nodes[index].as_mut().unwrap()
+    }
+}
+
+/// Get the process-wide cache, locked
+fn lock_cache() -> MutexGuard<'static, PatternCache> {
+    PATTERN_CACHE
+        .get_or_init(|| Mutex::new(PatternCache::new()))
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Remove all patterns from the process-wide cache (see [`Pattern::cached`])
+///
+/// Patterns that were already fetched are unaffected.
+pub fn clear_pattern_cache() {
+    if let Some(cache) = PATTERN_CACHE.get() {
+        cache.lock().unwrap_or_else(PoisonError::into_inner).clear();
+    }
+}
+
+/// Set the maximum number of patterns kept in the process-wide cache (see [`Pattern::cached`])
+///
+/// Least recently used patterns are evicted if there are more of them. A capacity of 0 disables caching.
+///
+/// Defaults to [`DEFAULT_PATTERN_CACHE_CAPACITY`].
+pub fn set_pattern_cache_capacity(capacity: usize) {
+    let mut cache = lock_cache();
+
+    cache.capacity = capacity;
+    cache.shrink_to(capacity);
+}
+
+impl Pattern {
+    /// Build a pattern with the provided options, or get it from the process-wide cache if it was built recently
+    ///
+    /// This is useful when the same patterns are built over and over, e.g. when they are received with each request of a server.
+    /// Patterns are shared through an [`Arc`], and the least recently used ones are evicted when the cache is full
+    /// (see [`set_pattern_cache_capacity`] and [`clear_pattern_cache`]). Errors are not cached.
+    pub fn cached(input: &str, opts: PatternOpts) -> Result<Arc<Pattern>, PatternError> {
+        if let Some(pattern) = lock_cache().get(input, opts) {
+            return Ok(pattern);
+        }
+
+        // Don't hold the lock while compiling, as it may take a while
+        let pattern = Arc::new(Pattern::new_with_opts(input, opts)?);
+
+        lock_cache().insert(input, opts, Arc::clone(&pattern));
+
+        Ok(pattern)
+    }
+}
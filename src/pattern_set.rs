@@ -0,0 +1,305 @@
+use std::{
+    collections::HashMap,
+    ops::Range,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    MatchContext, Pattern, PatternError, PatternOpts,
+    paths::{NormalizeOpts, normalize_path_into},
+};
+
+/// Indices of patterns, indexed by their leading literal component
+type LiteralIndex = HashMap<Box<[u8]>, Vec<usize>>;
+
+/// A set of patterns matched all at once
+///
+/// This is useful to find which patterns among many match a given path, e.g. a list of include patterns.
+///
+/// Patterns can be negated to exclude paths matched by previous patterns (see [`PatternSet::add_negated`]).
+/// Like in `.gitignore` files, the last pattern matching a path decides whether it is included or excluded
+/// (see [`PatternSet::verdict`]).
+///
+/// Patterns starting with a literal component (e.g. `src/**/*.rs`) are indexed by that component,
+/// so they are only matched against paths starting with it. Other patterns (e.g. `**/*.rs`) are matched
+/// against every path.
+#[derive(Debug, Clone, Default)]
+pub struct PatternSet {
+    /// Patterns of the set, in order
+    patterns: Vec<Pattern>,
+
+    /// Is each pattern negated? (see [`PatternSet::add_negated`])
+    negated: Vec<bool>,
+
+    /// Indices of the patterns starting with a literal component, indexed by that component
+    ///
+    /// Patterns are grouped by the options paths are normalized with, as they determine the paths' first component.
+    by_leading_literal: Vec<(NormalizeOpts, LiteralIndex)>,
+
+    /// Indices of the other patterns, which must be matched against every path
+    unindexed: Vec<usize>,
+
+    /// Deepest directory common to all patterns (see [`PatternSet::common_root_dir`])
+    common_root_dir: Option<PathBuf>,
+}
+
+/// Outcome of matching a path against a [`PatternSet`] (see [`PatternSet::verdict`])
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Verdict {
+    /// The last pattern matching the path isn't negated
+    Include,
+
+    /// The last pattern matching the path is negated
+    Exclude,
+
+    /// No pattern matches the path
+    None,
+}
+
+impl PatternSet {
+    /// Create a set from a list of patterns
+    ///
+    /// Patterns are identified by their index in the list.
+    pub fn new(patterns: impl IntoIterator<Item = Pattern>) -> Self {
+        let mut set = Self::default();
+
+        for pattern in patterns {
+            set.push(pattern, false);
+        }
+
+        set.common_root_dir = set.compute_common_root_dir();
+        set
+    }
+
+    /// Create a set from a list of rules, e.g. lines of a configuration file
+    ///
+    /// Rules starting with a `!` are negated (see [`PatternSet::add_negated`]), while other ones are parsed as-is.
+    /// Patterns starting with a literal `!` can be added with [`PatternSet::add`] instead.
+    ///
+    /// Fails on the first rule that isn't a valid pattern.
+    pub fn from_rules<S: AsRef<str>>(
+        rules: impl IntoIterator<Item = S>,
+    ) -> Result<Self, PatternError> {
+        Self::from_rules_with_opts(rules, PatternOpts::new())
+    }
+
+    /// Create a set from a list of rules, with additional options
+    ///
+    /// See [`PatternSet::from_rules`]
+    pub fn from_rules_with_opts<S: AsRef<str>>(
+        rules: impl IntoIterator<Item = S>,
+        opts: PatternOpts,
+    ) -> Result<Self, PatternError> {
+        let mut set = Self::default();
+
+        for rule in rules {
+            let rule = rule.as_ref();
+
+            let (rule, negated) = match rule.strip_prefix('!') {
+                Some(rule) => (rule, true),
+                None => (rule, false),
+            };
+
+            set.push(Pattern::new_with_opts(rule, opts)?, negated);
+        }
+
+        set.common_root_dir = set.compute_common_root_dir();
+        Ok(set)
+    }
+
+    /// Add a pattern at the end of the set
+    pub fn add(&mut self, pattern: Pattern) {
+        self.push(pattern, false);
+        self.common_root_dir = self.compute_common_root_dir();
+    }
+
+    /// Add a negated pattern at the end of the set
+    ///
+    /// Paths it matches are excluded, unless a later pattern matches them as well.
+    /// For instance, in `**/*.rs`, `!target/**`, `target/keep.rs`, the last pattern
+    /// re-includes a file from the directory excluded by the second one.
+    pub fn add_negated(&mut self, pattern: Pattern) {
+        self.push(pattern, true);
+    }
+
+    /// Get the patterns of the set, in order
+    pub fn patterns(&self) -> &[Pattern] {
+        &self.patterns
+    }
+
+    /// Get the number of patterns in the set
+    pub fn len(&self) -> usize {
+        self.patterns.len()
+    }
+
+    /// Check if the set doesn't contain any pattern
+    pub fn is_empty(&self) -> bool {
+        self.patterns.is_empty()
+    }
+
+    /// Check if a pattern of the set is negated (see [`PatternSet::add_negated`])
+    ///
+    /// Panics if the index is out of bounds.
+    pub fn is_negated(&self, index: usize) -> bool {
+        self.negated[index]
+    }
+
+    /// Check if the set includes a path, i.e. if its verdict is [`Verdict::Include`]
+    ///
+    /// Without negated patterns, this checks if any pattern of the set matches the path.
+    pub fn is_match(&self, path: &Path) -> bool {
+        self.verdict(path) == Verdict::Include
+    }
+
+    /// Check if a path is included or excluded by the set
+    ///
+    /// The last pattern matching the path decides, see [`PatternSet::deciding_pattern`].
+    pub fn verdict(&self, path: &Path) -> Verdict {
+        match self.deciding_pattern(path) {
+            Some(index) if self.negated[index] => Verdict::Exclude,
+            Some(_) => Verdict::Include,
+            None => Verdict::None,
+        }
+    }
+
+    /// Get the index of the last pattern matching a path, which decides its verdict
+    ///
+    /// This is useful to report why a path was included or excluded.
+    /// Paths are matched like with [`Pattern::is_match`].
+    pub fn deciding_pattern(&self, path: &Path) -> Option<usize> {
+        let mut ctx = MatchContext::new();
+
+        let mut candidates = self.candidates(path);
+        candidates.sort_unstable();
+
+        candidates
+            .into_iter()
+            .rev()
+            .find(|&index| self.patterns[index].is_match_with_ctx(path, &mut ctx))
+    }
+
+    /// Get the indices of the patterns matching a path, in ascending order
+    ///
+    /// Negated patterns are included. Paths are matched like with [`Pattern::is_match`].
+    pub fn matches(&self, path: &Path) -> Vec<usize> {
+        let mut ctx = MatchContext::new();
+
+        let mut matches = self.candidates(path);
+        matches.retain(|&index| self.patterns[index].is_match_with_ctx(path, &mut ctx));
+        matches.sort_unstable();
+        matches
+    }
+
+    /// Get the deepest directory under which all possible matches of all patterns are located
+    ///
+    /// This is the longest common path of the patterns' own root directories (see [`Pattern::common_root_dir`]).
+    /// For instance, the root directory of `src/a/*.rs` and `src/b/**` is `src`.
+    /// Negated patterns are ignored, as they don't include any path.
+    ///
+    /// Returns [`None`] if the set doesn't have any pattern which isn't negated, or if they have different prefixes (see [`Pattern::prefix`]),
+    /// e.g. when relative and absolute patterns are mixed.
+    pub fn common_root_dir(&self) -> Option<&Path> {
+        self.common_root_dir.as_deref()
+    }
+
+    /// (Internal) Add a pattern to the set, without updating its common root directory
+    fn push(&mut self, pattern: Pattern, negated: bool) {
+        let index = self.patterns.len();
+
+        match pattern.leading_literal() {
+            Some(literal) => {
+                let normalize_opts = pattern.normalize_opts();
+
+                let group = match self
+                    .by_leading_literal
+                    .iter()
+                    .position(|(opts, _)| *opts == normalize_opts)
+                {
+                    Some(pos) => &mut self.by_leading_literal[pos].1,
+                    None => {
+                        self.by_leading_literal
+                            .push((normalize_opts, HashMap::new()));
+                        &mut self.by_leading_literal.last_mut().unwrap().1
+                    }
+                };
+
+                group
+                    .entry(literal.as_bytes().into())
+                    .or_default()
+                    .push(index);
+            }
+
+            None => self.unindexed.push(index),
+        }
+
+        self.patterns.push(pattern);
+        self.negated.push(negated);
+    }
+
+    /// (Internal) Compute the deepest directory common to all patterns which aren't negated
+    fn compute_common_root_dir(&self) -> Option<PathBuf> {
+        let mut patterns = self
+            .patterns
+            .iter()
+            .zip(&self.negated)
+            .filter(|(_, negated)| !**negated)
+            .map(|(pattern, _)| pattern);
+
+        let first = patterns.next()?;
+        let mut common = first.common_root_dir().components().collect::<Vec<_>>();
+
+        for pattern in patterns {
+            if pattern.prefix() != first.prefix() {
+                return None;
+            }
+
+            let len = common
+                .iter()
+                .zip(pattern.common_root_dir().components())
+                .take_while(|(a, b)| *a == b)
+                .count();
+
+            common.truncate(len);
+        }
+
+        Some(
+            common
+                .iter()
+                .map(|component| component.as_os_str())
+                .collect(),
+        )
+    }
+
+    /// (Internal) Get the indices of the patterns that may match a path
+    fn candidates(&self, path: &Path) -> Vec<usize> {
+        let mut candidates = self.unindexed.clone();
+
+        if self.by_leading_literal.is_empty() {
+            return candidates;
+        }
+
+        let bytes = path.as_os_str().as_encoded_bytes();
+        let mut components = Vec::<Range<usize>>::new();
+
+        for (normalize_opts, by_literal) in &self.by_leading_literal {
+            if normalize_path_into(bytes, *normalize_opts, &mut components).is_err() {
+                continue;
+            }
+
+            if let Some(indices) = components
+                .first()
+                .and_then(|first| by_literal.get(&bytes[first.clone()]))
+            {
+                candidates.extend(indices);
+            }
+        }
+
+        candidates
+    }
+}
+
+impl FromIterator<Pattern> for PatternSet {
+    fn from_iter<T: IntoIterator<Item = Pattern>>(iter: T) -> Self {
+        Self::new(iter)
+    }
+}
@@ -0,0 +1,336 @@
+//! Simplification of redundant constructs in patterns (see [`crate::Pattern::simplify`])
+
+use parsy::Span;
+
+use crate::{
+    parser::{
+        CharacterClass, CharsMatcher, ExtGlobOperator, ParserOpts, RawComponent, RawPattern,
+        SingleCharMatcher, parse_pattern,
+    },
+    paths::PathPrefix,
+    syntax::is_special,
+};
+
+/// Simplify the source of a valid pattern, returning [`None`] if there is nothing to simplify
+///
+/// Only the parts of the source that can be simplified are rewritten, the rest being kept as is.
+pub fn simplify_source(source: &str, opts: ParserOpts, match_basename: bool) -> Option<String> {
+    let RawPattern {
+        case_insensitive,
+        prefix,
+        dir_only,
+        has_separator,
+        components,
+    } = parse_pattern(source, opts).ok()?;
+
+    // Source of the kept components, along with the separator preceding them
+    let mut kept = Vec::<(&str, String)>::with_capacity(components.len());
+    let mut prev_end = None;
+
+    for (i, Span { at, data }) in components.iter().enumerate() {
+        let start = at.start.offset;
+        let end = start + at.len;
+
+        // Separators are single characters, so a longer gap contains dropped components (e.g. `.`)
+        let sep = match prev_end {
+            Some(prev_end) => &source[prev_end..prev_end + 1],
+            None => "",
+        };
+
+        prev_end = Some(end);
+
+        // Consecutive `**` components are equivalent to a single one
+        if i > 0
+            && matches!(data, RawComponent::Wildcard)
+            && matches!(components[i - 1].data, RawComponent::Wildcard)
+        {
+            continue;
+        }
+
+        let simplified = match data {
+            RawComponent::Suite(matchers) => {
+                let simplified = simplify_matchers(matchers);
+
+                if simplified == *matchers {
+                    None
+                } else {
+                    render_component(&simplified, opts, kept.is_empty() && prefix.is_none())
+                }
+            }
+
+            RawComponent::Literal(_) | RawComponent::ParentDir | RawComponent::Wildcard => None,
+        };
+
+        kept.push((
+            sep,
+            simplified.unwrap_or_else(|| source[start..end].to_owned()),
+        ));
+    }
+
+    let (Some(first), Some(last)) = (components.first(), components.last()) else {
+        return None;
+    };
+
+    // Keep the inline flag and the prefix, but not the dropped components following them
+    let flag_len = match case_insensitive {
+        None => 0,
+        Some(true) => "(?i)".len(),
+        Some(false) => "(?-i)".len(),
+    };
+
+    let prefix_len = match prefix {
+        None => 0,
+        Some(PathPrefix::RootDir) => 1,
+        Some(PathPrefix::WindowsDrive(_)) if source[flag_len..].starts_with(r"\\?\") => 7,
+        Some(PathPrefix::WindowsDrive(_)) => 3,
+    };
+
+    let mut simplified = source[..(flag_len + prefix_len).min(first.at.start.offset)].to_owned();
+
+    // Patterns without separators are matched against basenames, so one must be kept
+    if match_basename && has_separator && prefix.is_none() && kept.len() == 1 && !dir_only {
+        simplified.push_str("./");
+    }
+
+    for (sep, component) in kept {
+        simplified.push_str(sep);
+        simplified.push_str(&component);
+    }
+
+    if dir_only {
+        let last_end = last.at.start.offset + last.at.len;
+        simplified.push_str(&source[last_end..last_end + 1]);
+    }
+
+    (simplified != source).then_some(simplified)
+}
+
+/// Simplify a suite of matchers
+///
+/// Duplicate alternates are removed from groups and operators, groups with a single remaining alternate are inlined,
+/// and adjacent literals (or `*` wildcards) are merged.
+fn simplify_matchers(matchers: &[CharsMatcher]) -> Vec<CharsMatcher> {
+    let mut simplified = Vec::<CharsMatcher>::with_capacity(matchers.len());
+
+    for matcher in matchers {
+        let inlined = match matcher {
+            CharsMatcher::OneOfGroups(groups) => {
+                let mut unique = Vec::<Vec<CharsMatcher>>::with_capacity(groups.len());
+
+                for group in groups {
+                    let group = simplify_matchers(group);
+
+                    if !unique.contains(&group) {
+                        unique.push(group);
+                    }
+                }
+
+                if unique.len() == 1 {
+                    unique.pop().unwrap()
+                } else {
+                    vec![CharsMatcher::OneOfGroups(unique)]
+                }
+            }
+
+            CharsMatcher::ExtGlob(op, groups) => {
+                let mut unique = Vec::<Vec<CharsMatcher>>::with_capacity(groups.len());
+
+                for group in groups {
+                    let group = simplify_matchers(group);
+
+                    if !unique.contains(&group) {
+                        unique.push(group);
+                    }
+                }
+
+                vec![CharsMatcher::ExtGlob(*op, unique)]
+            }
+
+            _ => vec![matcher.clone()],
+        };
+
+        for matcher in inlined {
+            match (simplified.last_mut(), matcher) {
+                (Some(CharsMatcher::Literal(prev)), CharsMatcher::Literal(lit)) => {
+                    prev.push_str(&lit)
+                }
+                (Some(CharsMatcher::AnyChars), CharsMatcher::AnyChars) => {}
+                (_, matcher) => simplified.push(matcher),
+            }
+        }
+    }
+
+    simplified
+}
+
+/// Render a component's matchers as a pattern, if they can be written with the provided options
+///
+/// The rendered component must not be mistaken for something else, e.g. a `..` component or a Windows drive.
+fn render_component(matchers: &[CharsMatcher], opts: ParserOpts, first: bool) -> Option<String> {
+    if opts.glob_compat {
+        return None;
+    }
+
+    let mut rendered = String::new();
+    render_matchers(matchers, opts, false, &mut rendered)?;
+
+    let looks_like_drive = first
+        && rendered.starts_with(|c: char| c.is_ascii_alphabetic())
+        && rendered[1..].starts_with(':');
+
+    if matches!(rendered.as_str(), "" | "." | "..") || looks_like_drive {
+        return None;
+    }
+
+    Some(rendered)
+}
+
+/// Render a suite of matchers, if they can be written with the provided options
+///
+/// Inside groups, `,` separates alternates so it must be escaped as well.
+fn render_matchers(
+    matchers: &[CharsMatcher],
+    opts: ParserOpts,
+    in_group: bool,
+    out: &mut String,
+) -> Option<()> {
+    for matcher in matchers {
+        match matcher {
+            CharsMatcher::AnyChar => out.push('?'),
+            CharsMatcher::AnyChars => out.push('*'),
+            CharsMatcher::Literal(lit) => {
+                for c in lit.chars() {
+                    render_char(c, opts, in_group, out)?;
+                }
+            }
+            CharsMatcher::OneOfChars(chars) => render_brackets(chars, false, out)?,
+            CharsMatcher::NoneOfChars(chars) => render_brackets(chars, true, out)?,
+            CharsMatcher::OneOfGroups(groups) => {
+                out.push('{');
+
+                for (i, group) in groups.iter().enumerate() {
+                    if i > 0 {
+                        out.push('|');
+                    }
+
+                    render_matchers(group, opts, true, out)?;
+                }
+
+                out.push('}');
+            }
+            CharsMatcher::ExtGlob(op, groups) => {
+                out.push(match op {
+                    ExtGlobOperator::ZeroOrOne => '?',
+                    ExtGlobOperator::ZeroOrMore => '*',
+                    ExtGlobOperator::OneOrMore => '+',
+                    ExtGlobOperator::ExactlyOne => '@',
+                    ExtGlobOperator::Not => '!',
+                });
+                out.push('(');
+
+                for (i, group) in groups.iter().enumerate() {
+                    if i > 0 {
+                        out.push('|');
+                    }
+
+                    render_matchers(group, opts, true, out)?;
+                }
+
+                out.push(')');
+            }
+        }
+    }
+
+    Some(())
+}
+
+/// Render a character outside of brackets, escaping it if required
+fn render_char(c: char, opts: ParserOpts, in_group: bool, out: &mut String) -> Option<()> {
+    let special = match c {
+        '/' => return None,
+        '*' | '?' => true,
+        ',' => in_group,
+        '{' | '}' | '|' => opts.alternates,
+        '[' | ']' => opts.brackets,
+        // Escaping `(` prevents e.g. a literal `+` from being followed by one
+        '(' | ')' => opts.extglob,
+        '\\' => opts.backslash_escapes || !opts.literal_backslashes,
+        _ => false,
+    };
+
+    if !special {
+        out.push(c);
+    } else if opts.backslash_escapes {
+        out.push('\\');
+        out.push(c);
+    } else if opts.brackets && c == ',' {
+        out.push_str("[,]");
+    } else if opts.brackets && c != '\\' {
+        out.push_str("[\\");
+        out.push(c);
+        out.push(']');
+    } else {
+        return None;
+    }
+
+    Some(())
+}
+
+/// Render a bracket expression, if all of its characters can be written
+fn render_brackets(chars: &[SingleCharMatcher], negated: bool, out: &mut String) -> Option<()> {
+    out.push('[');
+
+    if negated {
+        out.push('!');
+    }
+
+    for (i, matcher) in chars.iter().enumerate() {
+        match *matcher {
+            // A leading `!` would negate them
+            SingleCharMatcher::Literal('!') if i == 0 && !negated => return None,
+
+            SingleCharMatcher::Literal(c) => render_bracket_char(c, out)?,
+
+            SingleCharMatcher::Range(start, end) => {
+                render_bracket_char(start, out)?;
+                out.push('-');
+                render_bracket_char(end, out)?;
+            }
+
+            SingleCharMatcher::Class(class) => out.push_str(match class {
+                CharacterClass::Alpha => "[:alpha:]",
+                CharacterClass::Digit => "[:digit:]",
+                CharacterClass::Alphanumeric => "[:alphanumeric:]",
+                CharacterClass::Uppercase => "[:uppercase:]",
+                CharacterClass::Lowercase => "[:lowercase:]",
+                CharacterClass::Whitespace => "[:whitespace:]",
+                CharacterClass::HexDigit => "[:xdigit:]",
+                CharacterClass::Punctuation => "[:punct:]",
+                CharacterClass::Blank => "[:blank:]",
+                CharacterClass::Control => "[:cntrl:]",
+                CharacterClass::Graphic => "[:graph:]",
+                CharacterClass::Printable => "[:print:]",
+            }),
+        }
+    }
+
+    out.push(']');
+
+    Some(())
+}
+
+/// Render a character inside brackets, escaping it if required
+fn render_bracket_char(c: char, out: &mut String) -> Option<()> {
+    // `\` can't be escaped inside brackets
+    if matches!(c, '\\' | '/') {
+        return None;
+    }
+
+    if is_special(c) || c == ':' || c == '-' {
+        out.push('\\');
+    }
+
+    out.push(c);
+
+    Some(())
+}
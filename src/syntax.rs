@@ -0,0 +1,138 @@
+//! Informations about the patterns' syntax
+//!
+//! This is useful for e.g. highlighting patterns or building them from arbitrary strings.
+//!
+//! See [`crate::Pattern`] for a description of the syntax.
+
+use std::ops::Range;
+
+pub use crate::parser::{CharacterClass, CharsMatcher, ExtGlobOperator, SingleCharMatcher};
+use crate::paths::PathPrefix;
+
+/// List of special characters that must be escaped in order to be matched against
+///
+/// Escaping is only possible inside brackets (e.g. `[\*]`), except for path separators which can't be matched at all.
+/// With [`crate::PatternOpts::with_backslash_escapes`], any character can also be escaped outside of brackets (e.g. `\*`).
+///
+/// Note that `{`, `}` and `|` are ordinary characters when [`crate::PatternOpts::with_disable_alternates`] is enabled,
+/// and so are `[` and `]` when [`crate::PatternOpts::with_disable_brackets`] is enabled.
+///
+/// `:` is only special inside brackets, where it delimits character classes (see [`classify_in_brackets`]),
+/// while `(` and `)` are only special with [`crate::PatternOpts::with_extglob`] (see [`EXTGLOB_SPECIAL_CHARS`]).
+pub const SPECIAL_CHARS: &[char] = &['[', ']', '{', '}', '*', '?', '\\', '/', '|'];
+
+/// Role of a character in a pattern
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CharRole {
+    /// Matches itself
+    Literal,
+
+    /// Separates path components (`/` and `\`)
+    Separator,
+
+    /// Matches any character or suite of characters (`*` and `?`)
+    Wildcard,
+
+    /// Delimits a bracket expression or a character class (`[`, `]`, and `:` inside brackets)
+    ClassDelim,
+
+    /// Delimits a group of alternates or separates them (`{`, `}` and `|`),
+    /// or delimits an extended glob operator (`(` and `)`, see [`EXTGLOB_SPECIAL_CHARS`])
+    GroupDelim,
+
+    /// Escapes a special character
+    ///
+    /// Only returned for `\` inside brackets, see [`classify_in_brackets`]
+    Escape,
+}
+
+/// List of characters that are special with [`crate::PatternOpts::with_extglob`], in addition to [`SPECIAL_CHARS`]
+///
+/// They delimit extended glob operators (e.g. `+(a|b)`), and can be escaped inside brackets like other special characters (e.g. `[\(]`).
+pub const EXTGLOB_SPECIAL_CHARS: &[char] = &['(', ')'];
+
+/// Check if a character is special, meaning it must be escaped in order to be matched against
+pub fn is_special(c: char) -> bool {
+    SPECIAL_CHARS.contains(&c)
+}
+
+/// Check if a string contains any character that must be escaped in order to be matched against
+pub fn needs_escaping(str: &str) -> bool {
+    str.chars().any(is_special)
+}
+
+/// Get the role of a character outside of brackets
+///
+/// Note that `:` is an ordinary character there (e.g. in `log_12:30.txt`), except in Windows drive prefixes (e.g. `C:\`).
+/// `(` and `)` are classified as group delimiters, but they are only special with [`crate::PatternOpts::with_extglob`].
+pub fn classify(c: char) -> CharRole {
+    match c {
+        '/' | '\\' => CharRole::Separator,
+        '*' | '?' => CharRole::Wildcard,
+        '[' | ']' => CharRole::ClassDelim,
+        '{' | '}' | '|' | '(' | ')' => CharRole::GroupDelim,
+        _ => CharRole::Literal,
+    }
+}
+
+/// Get the role of a character inside brackets (e.g. `[abc]`)
+///
+/// Special characters other than the ones returned here can't be used in brackets without being escaped,
+/// and neither can `:`, which delimits character classes (e.g. `[[:alpha:]]`).
+pub fn classify_in_brackets(c: char) -> CharRole {
+    match c {
+        '\\' => CharRole::Escape,
+        '/' => CharRole::Separator,
+        '[' | ']' | ':' => CharRole::ClassDelim,
+        _ => classify(c),
+    }
+}
+
+/// Structure of a pattern, as understood by the parser
+///
+/// See [`crate::Pattern::parse_partial`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PatternAst {
+    /// Case sensitivity requested by an inline flag (e.g. `(?i)`), if any (see [`crate::PatternOpts::with_inline_flags`])
+    pub case_insensitive: Option<bool>,
+
+    /// Prefix of the pattern (e.g. `/` or `C:\`), if any
+    pub prefix: Option<PathPrefix>,
+
+    /// Components of the pattern, in order
+    ///
+    /// Empty and `.` components are not included, as they don't match anything.
+    pub components: Vec<AstComponent>,
+
+    /// Does the pattern end with a separator? (see [`crate::Pattern::is_dir_only`])
+    pub dir_only: bool,
+}
+
+/// Component of a pattern, as understood by the parser
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AstComponent {
+    /// Location of the component in the pattern, in bytes
+    pub span: Range<usize>,
+
+    /// Kind of component
+    pub kind: ComponentKind,
+}
+
+/// Kind of a pattern's component
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ComponentKind {
+    /// Literal name (e.g. `src`)
+    Literal(String),
+
+    /// Suite of matchers (e.g. `*.{rs|toml}`)
+    Matchers(Vec<CharsMatcher>),
+
+    /// Parent directory (`..`)
+    ParentDir,
+
+    /// Any suite of directories (`**`)
+    Wildcard,
+
+    /// Component that couldn't be parsed, which is reported as an error
+    Invalid,
+}
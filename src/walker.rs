@@ -1,10 +1,23 @@
 use std::{
-    ffi::OsStr,
-    fs::{ReadDir, canonicalize},
+    cmp,
+    collections::{BinaryHeap, HashMap, HashSet, VecDeque},
+    ffi::{OsStr, OsString},
+    fmt,
+    fs::{DirEntry, File, OpenOptions, ReadDir, canonicalize},
+    io::{self, ErrorKind, Read, Seek, SeekFrom, Write},
+    mem,
     path::{Path, PathBuf},
+    sync::atomic::{AtomicUsize, Ordering as AtomicOrdering},
+    time::{Duration, SystemTime},
 };
 
-use crate::{Pattern, normalize_path, paths::NormalizedPath, pattern::PatternMatchResult};
+use smallvec::SmallVec;
+
+use crate::{
+    MatchContext, Pattern,
+    paths::{NormalizeOpts, NormalizedPath, PathPrefix, normalize_path_with, with_unix_separators},
+    pattern::{PatternMatchResult, TargetHint},
+};
 
 /// Walker implementation, yielding filesystem entries that match the provided pattern
 ///
@@ -12,7 +25,8 @@ use crate::{Pattern, normalize_path, paths::NormalizedPath, pattern::PatternMatc
 /// root point otherwise.
 ///
 /// For instance, a pattern written as `/**/*` will make the walker from `/` no matter what the provided
-/// base directory is.
+/// base directory is. Patterns starting with a Windows drive must be on the same drive as the base directory though
+/// (see [Inapplicable patterns](#inapplicable-patterns)).
 ///
 /// Yielded results may be [`Err`] variants in case something goes wrong while fetching informations about
 /// the related path.
@@ -23,13 +37,49 @@ use crate::{Pattern, normalize_path, paths::NormalizedPath, pattern::PatternMatc
 ///
 /// * If the pattern is absolute (starts with a `/`), all yielded results will be absolute. Otherwise, yielded results will be relative to the provided base directory.
 /// * If the pattern starts with an ancestor (`../`), yielded results will not be simplified. e.g. starting from directory `/a/b` and matching `../*` will yield `../b` results instead of `.`
+/// * Leading `..` components are resolved lexically against the canonicalized base directory, e.g. starting from `/a/b/c` and matching `../../x/**` will walk from `/a/x` and yield results such as `../../x/y`. Ascending above the filesystem's root stays at the root.
+/// * Patterns made only of `..` components don't yield anything, as the directories they denote are never children of the walked directory
 ///
 /// # Ordering and traversal rules
 ///
-/// - Directories are always yielded before their content
+/// - Directories are yielded before their content, unless [`Ordering::Unspecified`] is requested
 /// - Symbolic links are always followed
 /// - The base directory is not yielded in the results
-/// - No guarantee is given as for the order the results are yielded in
+/// - Directories are only read if the pattern may match some of their descendants, so patterns without `**` (e.g. `*/*/*.lock`) never make the walker read deeper than their number of components
+/// - The order of entries inside a directory is the one of the filesystem, unless [`Ordering::ParentsFirstSorted`] is requested
+///
+/// See [`Walker::ordering`] for more details.
+///
+/// # Concurrent modifications
+///
+/// The filesystem may change while walking. Entries are trusted to exist once listed when the listing provides
+/// their type, which most platforms do, so they may still be yielded if they vanish afterwards. Other entries that vanish
+/// between being listed and being inspected (or descended into) are skipped silently, as are directories that vanish
+/// before being read. An entry is never yielded twice for the same directory listing, even if it gets recreated in the meantime.
+///
+/// # Limits
+///
+/// The amount of work performed by the walker can be capped using [`Walker::max_results`] and
+/// [`Walker::max_entries_scanned`]. Once a limit is hit, all open directories are released and
+/// a final [`WalkError::LimitReached`] is yielded (see [`Walker::error_on_limit`]).
+///
+/// # Inapplicable patterns
+///
+/// Patterns that can't match any entry because of their path prefix are detected when creating the walker,
+/// and reported once with a [`WalkError::PatternInapplicable`] before any other result:
+///
+/// * Absolute patterns whose prefix doesn't exist on the current platform (e.g. `C:\**` on Linux)
+/// * Absolute patterns on another Windows drive than the base directory (e.g. `D:\**` from `C:\dir`)
+/// * Relative patterns when no base directory was provided (see [`Walker::new_absolute`])
+///
+/// # Multiple patterns
+///
+/// A single walker can apply several patterns at once (see [`Walker::new_multi`]), in which case each entry
+/// is only yielded once, if it matches at least one of the patterns. Directories are traversed as long as
+/// any of the patterns may match their content.
+///
+/// Results follow the relativity rules of the first pattern they match.
+/// To know which patterns matched each result, see [`Walker::with_pattern_indices`].
 pub struct Walker {
     /// Set to [`None`] if the walker cannot apply, e.g. if the base directory does not exist
     state: Option<WalkerState>,
@@ -37,12 +87,72 @@ pub struct Walker {
 
 /// (Internal) Walker state
 struct WalkerState {
-    /// The pattern to apply to all entries
+    /// The patterns to apply to all entries
+    patterns: Vec<WalkedPattern>,
+
+    /// Are we going into a directory?
+    going_into_dir: Option<PathBuf>,
+
+    /// Directory readers, recursively
+    open_dirs: Vec<OpenDir>,
+
+    /// Should relative results be rendered with `/` separators only?
+    unix_separators: bool,
+
+    /// Should subdirectories only be traversed once their parent has been fully read?
+    deferred_descent: bool,
+
+    /// Should directories be traversed level by level? (requires deferred descent)
+    breadth_first: bool,
+
+    /// Order to yield results in
+    ordering: Ordering,
+
+    /// Directories waiting to be traversed (when descent is deferred, or when walking from multiple roots)
+    queued_dirs: VecDeque<PathBuf>,
+
+    /// Maximum number of results to yield
+    max_results: Option<usize>,
+
+    /// Maximum number of entries to examine
+    max_entries_scanned: Option<usize>,
+
+    /// Should an error be yielded when a limit is reached?
+    error_on_limit: bool,
+
+    /// Has a limit been reached?
+    limit_reached: bool,
+
+    /// Limit that was reached and still needs to be reported
+    pending_limit: Option<(LimitKind, usize)>,
+
+    /// Errors to yield before walking, e.g. inapplicable patterns
+    pending_errors: VecDeque<WalkError>,
+
+    /// Statistics about the walk so far
+    stats: WalkStats,
+
+    /// Scratch space for matching entries
+    match_ctx: MatchContext,
+
+    /// Cache of directory listings (see [`CachedWalker`])
+    cache: Option<DirCache>,
+}
+
+/// (Internal) A pattern applied by the walker
+struct WalkedPattern {
+    /// The pattern to apply
     pattern: Pattern,
 
-    /// Directory we're walking from (canonicalized)
+    /// Directory the pattern's matches are located in (canonicalized)
     walk_from: NormalizedPath,
 
+    /// Hint on the kind of entries the pattern matches
+    target_hint: TargetHint,
+
+    /// Reason the pattern can't match any entry, if any (see [`WalkError::PatternInapplicable`])
+    inapplicable: Option<PatternMatchResult>,
+
     /// Prefix to add to all paths before pattern matching
     ///
     /// The reason this exists is as follows:
@@ -56,36 +166,121 @@ struct WalkerState {
     /// In our example, the prefix would be equal to `..` and the path provided to the pattern matcher
     /// would be `../c/d`
     parent_prefix: PathBuf,
-
-    /// Directory readers, recursively
-    open_dirs: Vec<ReadDir>,
-
-    /// Are we going into a directory?
-    going_into_dir: Option<PathBuf>,
 }
 
 impl Walker {
     /// Create a walker that will yield filesystem entries that match the provided pattern
     pub fn new(pattern: Pattern, base_dir: &Path) -> Self {
-        Self::new_inner(pattern, base_dir).unwrap_or(Self { state: None })
+        Self::new_multi([pattern], base_dir)
     }
 
-    fn new_inner(pattern: Pattern, base_dir: &Path) -> Option<Self> {
-        let base_dir = canonicalize(base_dir).ok()?;
+    /// Create a walker that will yield filesystem entries that match any of the provided patterns
+    ///
+    /// Pattern indices (see [`Walker::with_pattern_indices`]) follow the order the patterns are provided in.
+    pub fn new_multi(patterns: impl IntoIterator<Item = Pattern>, base_dir: &Path) -> Self {
+        Self::new_inner(patterns, Some(base_dir)).unwrap_or(Self { state: None })
+    }
 
-        let walk_from = base_dir.join(pattern.common_root_dir());
+    /// Create a walker that will yield filesystem entries that match the provided absolute pattern
+    ///
+    /// As no base directory is needed, this never accesses the current directory.
+    /// If the pattern is relative, a [`WalkError::PatternInapplicable`] is yielded.
+    pub fn new_absolute(pattern: Pattern) -> Self {
+        Self::new_inner([pattern], None).unwrap_or(Self { state: None })
+    }
 
+    /// Create a walker, with a base directory that is only required for relative patterns
+    fn new_inner(
+        patterns: impl IntoIterator<Item = Pattern>,
+        base_dir: Option<&Path>,
+    ) -> Option<Self> {
         // Simplify the base directory, as to have an absolute path,
         // and avoid components like `.` or `..`
-        let walk_from = normalize_path(&walk_from).ok()?;
+        let base_dir = match base_dir {
+            Some(base_dir) => Some(
+                normalize_path_with(&canonicalize(base_dir).ok()?, PLATFORM_NORMALIZE_OPTS).ok()?,
+            ),
+            None => None,
+        };
+
+        let patterns = patterns
+            .into_iter()
+            .map(|pattern| {
+                let inapplicable = if !pattern.is_absolute() {
+                    base_dir
+                        .is_none()
+                        .then_some(PatternMatchResult::PathNotAbsolute)
+                } else if !pattern.portability().supports_current_platform() {
+                    Some(PatternMatchResult::IncompatiblePrefix)
+                } else if let Some(PathPrefix::WindowsDrive(drive)) = pattern.prefix()
+                    && let Some(PathPrefix::WindowsDrive(base_drive)) =
+                        base_dir.as_ref().and_then(NormalizedPath::prefix)
+                    && drive != base_drive
+                {
+                    Some(PatternMatchResult::PathIsAbsolute)
+                } else {
+                    None
+                };
+
+                Some(WalkedPattern {
+                    target_hint: pattern.match_target_hint(),
+                    inapplicable,
+                    parent_prefix: pattern.common_root_dir().to_owned(),
+                    // Inapplicable patterns are never walked
+                    walk_from: walk_from(&pattern, base_dir.as_ref())?,
+                    pattern,
+                })
+            })
+            .collect::<Option<Vec<_>>>()?;
+
+        // Walk from the closest common ancestor of all patterns' directories
+        // (one for each path prefix, as they can't share one)
+        let mut roots = Vec::<NormalizedPath>::new();
+
+        for walked in patterns
+            .iter()
+            .filter(|walked| walked.inapplicable.is_none())
+        {
+            match roots
+                .iter_mut()
+                .find(|root| root.prefix() == walked.walk_from.prefix())
+            {
+                Some(root) => *root = common_ancestor(root, &walked.walk_from),
+                None => roots.push(walked.walk_from.clone()),
+            }
+        }
+
+        let mut roots = roots.iter().map(NormalizedPath::to_path_buf);
+
+        let pending_errors = patterns
+            .iter()
+            .enumerate()
+            .filter_map(|(pattern, walked)| {
+                walked
+                    .inapplicable
+                    .map(|reason| WalkError::PatternInapplicable { pattern, reason })
+            })
+            .collect();
 
         Some(Walker {
             state: Some(WalkerState {
-                parent_prefix: diff_path(&walk_from, &normalize_path(&base_dir).unwrap()),
-                going_into_dir: Some(walk_from.to_path_buf()),
-                pattern,
-                walk_from,
+                going_into_dir: roots.next(),
+                queued_dirs: roots.collect(),
+                unix_separators: false,
+                deferred_descent: false,
+                breadth_first: false,
+                ordering: Ordering::default(),
+                patterns,
                 open_dirs: vec![],
+                max_results: None,
+                max_entries_scanned: None,
+                error_on_limit: true,
+                limit_reached: false,
+                pending_limit: None,
+                pending_errors,
+                stats: WalkStats::default(),
+                match_ctx: MatchContext::new(),
+                cache: None,
             }),
         })
     }
@@ -93,128 +288,1792 @@ impl Walker {
     pub fn is_invalid(&self) -> bool {
         self.state.is_none()
     }
-}
 
-impl Iterator for Walker {
-    type Item = Result<PathBuf, std::io::Error>;
+    /// Stop walking after `limit` results have been yielded
+    pub fn max_results(mut self, limit: usize) -> Self {
+        if let Some(state) = &mut self.state {
+            state.max_results = Some(limit);
+        }
 
-    fn next(&mut self) -> Option<Self::Item> {
-        let state = self.state.as_mut()?;
+        self
+    }
 
-        loop {
-            // Check if we're going into a directory
-            if let Some(going_into_dir) = state.going_into_dir.take() {
-                match std::fs::read_dir(&going_into_dir) {
-                    Err(err) => return Some(Err(err)),
-                    Ok(reader) => {
-                        state.open_dirs.push(reader);
-                        continue;
-                    }
+    /// Stop walking after `limit` filesystem entries have been examined
+    ///
+    /// This caps the total amount of work performed by the walker, independently of how many entries match
+    pub fn max_entries_scanned(mut self, limit: usize) -> Self {
+        if let Some(state) = &mut self.state {
+            state.max_entries_scanned = Some(limit);
+        }
+
+        self
+    }
+
+    /// Choose if a final [`WalkError::LimitReached`] should be yielded when a limit is reached
+    ///
+    /// When disabled, the walker simply ends. Enabled by default.
+    pub fn error_on_limit(mut self, error_on_limit: bool) -> Self {
+        if let Some(state) = &mut self.state {
+            state.error_on_limit = error_on_limit;
+        }
+
+        self
+    }
+
+    /// Render relative results with `/` separators, independently of the platform
+    ///
+    /// By default, results use the platform's separator (e.g. `\` on Windows).
+    /// Results of absolute patterns are left untouched.
+    pub fn unix_separators(mut self, unix_separators: bool) -> Self {
+        if let Some(state) = &mut self.state {
+            state.unix_separators = unix_separators;
+        }
+
+        self
+    }
+
+    /// Choose the order results are yielded in
+    ///
+    /// Defaults to [`Ordering::ParentsFirst`]. See [`Ordering`] for the guarantees provided by each variant.
+    pub fn ordering(mut self, ordering: Ordering) -> Self {
+        if let Some(state) = &mut self.state {
+            state.ordering = ordering;
+        }
+
+        self
+    }
+
+    /// Group matches by their parent directory
+    ///
+    /// Each group is made of the directory's path and all the matches it directly contains.
+    /// A group is yielded as soon as its directory has been fully read, and groups with no match are skipped.
+    ///
+    /// Matches directly inside the walking root are grouped under the root directory's path.
+    /// Directory paths are always absolute, while matches follow the same rules as with [`Walker`].
+    ///
+    /// To make this possible, subdirectories are only traversed once their parent has been fully read,
+    /// which keeps the guarantee that parent directories are yielded before their content.
+    pub fn grouped(mut self) -> GroupedWalker {
+        if let Some(state) = &mut self.state {
+            state.deferred_descent = true;
+        }
+
+        GroupedWalker {
+            walker: self,
+            current: None,
+            pending_err: None,
+        }
+    }
+
+    /// Traverse directories level by level, yielding shallower results first
+    ///
+    /// All entries of a directory are examined before any of its subdirectories is traversed,
+    /// and subdirectories are only traversed once all directories of the previous level have been.
+    ///
+    /// This is useful when looking for a few results that are likely to be shallow.
+    pub fn breadth_first(mut self) -> Self {
+        if let Some(state) = &mut self.state {
+            state.deferred_descent = true;
+            state.breadth_first = true;
+        }
+
+        self
+    }
+
+    /// Yield the indices of all the patterns each result matches
+    ///
+    /// Indices refer to the order patterns were provided in to [`Walker::new_multi`].
+    /// When the walker was created from a single pattern, the only index is `0`.
+    pub fn with_pattern_indices(self) -> MultiMatchWalker {
+        MultiMatchWalker { walker: self }
+    }
+
+    /// Check which of the provided candidates match the pattern and exist
+    ///
+    /// Returns each candidate along with `true` if it both matches the pattern and exists, in the same order.
+    ///
+    /// Candidates are first matched against the pattern, so non-matching ones never touch the filesystem.
+    /// The remaining ones are grouped by parent directory, and directories containing many candidates are
+    /// listed once instead of checking each candidate individually.
+    ///
+    /// Candidates are interpreted relatively to the provided base directory, unless they are absolute.
+    /// Like with walking, broken symbolic links are considered as existing.
+    pub fn filter_existing<'a>(
+        pattern: &Pattern,
+        base_dir: &Path,
+        candidates: impl IntoIterator<Item = &'a Path>,
+    ) -> Vec<(PathBuf, bool)> {
+        Self::filter_existing_with_stats(pattern, base_dir, candidates).0
+    }
+
+    /// Same as [`Walker::filter_existing`], but also returns statistics about the performed filesystem accesses
+    ///
+    /// Listed directories are counted in [`WalkStats::dirs_read`], while candidates checked individually
+    /// are counted in [`WalkStats::metadata_queries`]
+    pub fn filter_existing_with_stats<'a>(
+        pattern: &Pattern,
+        base_dir: &Path,
+        candidates: impl IntoIterator<Item = &'a Path>,
+    ) -> (Vec<(PathBuf, bool)>, WalkStats) {
+        let mut stats = WalkStats::default();
+
+        let mut results = candidates
+            .into_iter()
+            .map(|candidate| (candidate.to_owned(), false))
+            .collect::<Vec<_>>();
+
+        // Group matching candidates by parent directory
+        let mut groups = HashMap::<PathBuf, Vec<(usize, OsString)>>::new();
+        let mut match_ctx = MatchContext::new();
+
+        for (i, (candidate, exists)) in results.iter_mut().enumerate() {
+            stats.entries_scanned += 1;
+
+            if !pattern.is_match_with_ctx(candidate, &mut match_ctx) {
+                continue;
+            }
+
+            let path = base_dir.join(&candidate);
+
+            match (path.parent(), path.file_name()) {
+                (Some(parent), Some(name)) => groups
+                    .entry(parent.to_owned())
+                    .or_default()
+                    .push((i, name.to_owned())),
+
+                // Paths ending with e.g. `..` can't be looked up in their parent directory
+                _ => {
+                    stats.metadata_queries += 1;
+                    *exists = entry_exists(&path);
                 }
             }
+        }
 
-            // Otherwise, get the currently handled directory's reader
-            let queue = state.open_dirs.last_mut()?;
+        for (dir, candidates) in groups {
+            let listed = if candidates.len() >= MIN_CANDIDATES_PER_LISTING {
+                stats.dirs_read += 1;
 
-            let Some(entry) = queue.next() else {
-                // If the reader is empty, remove it from the last
-                state.open_dirs.pop();
-                // then get to use the next reader
-                continue;
-            };
+                match std::fs::read_dir(&dir) {
+                    Ok(reader) => Some(
+                        reader
+                            .filter_map(Result::ok)
+                            .map(|entry| entry.file_name())
+                            .collect::<HashSet<_>>(),
+                    ),
 
-            // Get the successful entry or return the error
-            let entry = match entry {
-                Ok(entry) => entry,
-                Err(err) => return Some(Err(err)),
+                    Err(err) if err.kind() == ErrorKind::NotFound => Some(HashSet::new()),
+
+                    // Fall back to checking candidates individually
+                    Err(_) => None,
+                }
+            } else {
+                None
             };
 
-            // Compute the real entry path, as the walker only provides something relative to the base *walking* directory
-            let entry_path = normalize_path(&entry.path()).unwrap();
+            for (i, name) in candidates {
+                results[i].1 = match &listed {
+                    Some(listed) => listed.contains(&name),
+                    None => {
+                        stats.metadata_queries += 1;
+                        entry_exists(&dir.join(name))
+                    }
+                };
+            }
+        }
+
+        stats.results = results.iter().filter(|(_, exists)| *exists).count();
+
+        (results, stats)
+    }
+
+    /// Get statistics about the walk so far
+    pub fn stats(&self) -> WalkStats {
+        self.state
+            .as_ref()
+            .map(|state| state.stats)
+            .unwrap_or_default()
+    }
+}
+
+impl WalkerState {
+    /// Check if a limit was reached, and stop walking if so
+    fn check_limits(&mut self) {
+        if self.limit_reached {
+            return;
+        }
+
+        let reached = match (self.max_results, self.max_entries_scanned) {
+            (Some(limit), _) if self.stats.results >= limit => (LimitKind::Results, limit),
+            (_, Some(limit)) if self.stats.entries_scanned >= limit => {
+                (LimitKind::EntriesScanned, limit)
+            }
+            _ => return,
+        };
+
+        self.limit_reached = true;
+
+        // Release all directory handles immediately
+        self.open_dirs.clear();
+        self.going_into_dir = None;
+        self.queued_dirs.clear();
+
+        if self.error_on_limit {
+            self.pending_limit = Some(reached);
+        }
+    }
+}
 
-            // Compute the path relative to the base directory (if the pattern is not absolute)
-            let entry_path = if state.pattern.is_absolute() {
-                entry_path.to_path_buf()
+// Walking-related methods of patterns live here, so the pattern module never touches the filesystem
+impl Pattern {
+    /// Check if at least one entry of the provided directory (or its descendants) matches this pattern
+    ///
+    /// This is equivalent to checking if a [`Walker`] yields at least one result, but optimized for that purpose:
+    /// * Patterns made only of literal components are checked directly, without traversing anything
+    /// * Otherwise, directories are traversed breadth-first (see [`Walker::breadth_first`]),
+    ///   and traversal stops as soon as a match is found
+    ///
+    /// Fails if an error occurs before any match is found.
+    pub fn any_match_in(&self, dir: &Path) -> Result<bool, WalkError> {
+        if let Some(path) = self.literal_path() {
+            let path = if self.is_absolute() {
+                normalize_path_with(&path, PLATFORM_NORMALIZE_OPTS)
+                    .map(|path| path.to_path_buf())
+                    .unwrap_or(path)
             } else {
-                state
-                    .parent_prefix
-                    .join(diff_path(&entry_path, &state.walk_from))
+                dir.join(path)
             };
 
-            // Check if the path matches the provided globbing pattern
-            match state.pattern.match_against(&entry_path) {
-                // Absolute path conflict should not happen as it's been taken care of ahead of matching
-                PatternMatchResult::PathNotAbsolute
-                | PatternMatchResult::PathIsAbsolute
-                | PatternMatchResult::IncompatiblePrefix => {
-                    unreachable!()
-                }
+            // Broken symbolic links are considered as matches, like when walking
+            let exists = if self.is_dir_only() {
+                path.is_dir()
+            } else {
+                path.symlink_metadata().is_ok()
+            };
 
-                // Success!
-                PatternMatchResult::Matched => {
-                    // If the pattern contains no wildcard, no descendant of this path may be matched
-                    // by the pattern, so if it's a directory, we can skip it
-                    // Otherwise, we'll need to traverse it
-                    if entry.path().is_dir() && state.pattern.has_wildcard() {
-                        state.going_into_dir = Some(entry.path());
-                    }
+            return Ok(dir.is_dir() && exists);
+        }
 
-                    return Some(Ok(entry_path));
-                }
+        Walker::new(self.clone(), dir)
+            .breadth_first()
+            .max_results(1)
+            .error_on_limit(false)
+            .next()
+            .transpose()
+            .map(|result| result.is_some())
+    }
 
-                // May have matched if the path was more complete, so we just do nothing
-                PatternMatchResult::Starved => {
-                    if entry.path().is_dir() {
-                        state.going_into_dir = Some(entry.path());
-                    }
-                }
+    /// Match the pattern against a path located under the provided base directory
+    ///
+    /// This matches paths exactly like a [`Walker`] started from `base` would:
+    /// * Relative patterns are matched against the path relative to `base` (ascending for each leading `..` component),
+    ///   and never match paths located outside of it
+    /// * Absolute patterns are matched against the path itself, regardless of `base`
+    ///
+    /// Both paths are normalized but, unlike when walking, the filesystem is never accessed:
+    /// symbolic links are not resolved and whether the pattern only matches directories is ignored (see [`Pattern::is_dir_only`]).
+    pub fn is_match_under(&self, base: &Path, candidate: &Path) -> bool {
+        matches!(
+            self.match_against_under(base, candidate),
+            PatternMatchResult::Matched
+        )
+    }
+
+    /// Match the pattern against an entry listed with [`std::fs::read_dir`], located under the provided base directory
+    ///
+    /// The entry's path is matched like with [`Pattern::is_match_under`], so `base` must be relative if the directory
+    /// was read from a relative path (and vice-versa). Unlike [`Pattern::is_match_under`], patterns that only match directories
+    /// (see [`Pattern::is_dir_only`]) are taken into account using [`DirEntry::file_type`], so the entry is usually not `stat`-ed
+    /// (except for symbolic links, which are resolved like when walking).
+    ///
+    /// This is useful to filter entries when reading directories manually:
+    ///
+    /// ```
+    /// use std::{fs, path::Path};
+    ///
+    /// use globby::{Pattern, PatternMatchResult};
+    ///
+    /// let pattern = Pattern::new("*.toml")?;
+    /// let base = Path::new(".");
+    ///
+    /// let manifests = fs::read_dir(base)?
+    ///     .filter_map(Result::ok)
+    ///     .filter(|entry| matches!(pattern.matches_dir_entry(base, entry), PatternMatchResult::Matched))
+    ///     .map(|entry| entry.file_name())
+    ///     .collect::<Vec<_>>();
+    ///
+    /// assert!(manifests.iter().any(|name| name == "Cargo.toml"));
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn matches_dir_entry(&self, base: &Path, entry: &DirEntry) -> PatternMatchResult {
+        let path = entry.path();
+        let result = self.match_against_under(base, &path);
+
+        if !self.is_dir_only() || !matches!(result, PatternMatchResult::Matched) {
+            return result;
+        }
+
+        let is_dir = match entry.file_type() {
+            Ok(file_type) if file_type.is_symlink() => entry_is_dir(&path) == Some(true),
+            Ok(file_type) => file_type.is_dir(),
+            Err(_) => false,
+        };
+
+        if is_dir {
+            PatternMatchResult::Matched
+        } else {
+            PatternMatchResult::NotMatched
+        }
+    }
+
+    /// Match the pattern against a path located under the provided base directory (see [`Pattern::is_match_under`])
+    fn match_against_under(&self, base: &Path, candidate: &Path) -> PatternMatchResult {
+        if self.is_absolute() && !self.portability().supports_current_platform() {
+            return PatternMatchResult::IncompatiblePrefix;
+        }
+
+        let (Ok(base), Ok(candidate)) = (
+            normalize_path_with(base, PLATFORM_NORMALIZE_OPTS),
+            normalize_path_with(candidate, PLATFORM_NORMALIZE_OPTS),
+        ) else {
+            return PatternMatchResult::NotMatched;
+        };
+
+        let Some(walk_from) = walk_from(self, Some(&base)) else {
+            return PatternMatchResult::NotMatched;
+        };
+
+        match match_entry(
+            self,
+            &walk_from,
+            self.common_root_dir(),
+            &candidate,
+            false,
+            &mut MatchContext::new(),
+        ) {
+            EntryMatch::Matched { .. } => PatternMatchResult::Matched,
+            EntryMatch::Starved => PatternMatchResult::Starved,
+            EntryMatch::NotMatched => PatternMatchResult::NotMatched,
+        }
+    }
+}
+
+/// (Internal) A directory being read by the walker
+struct OpenDir {
+    /// Path to the directory
+    path: PathBuf,
+
+    /// Reader for the directory's entries
+    reader: DirReader,
+
+    /// Names of the entries that have already been handled in this directory
+    ///
+    /// Used to avoid yielding or descending into the same entry twice,
+    /// e.g. if it's removed and recreated while the directory is being read
+    seen: HashSet<OsString>,
+
+    /// Subdirectories to descend into once this directory has been fully read
+    ///
+    /// Only used when descent is deferred
+    subdirs: Vec<PathBuf>,
+
+    /// Listing being recorded for the cache, if any
+    recording: Option<CachedDir>,
+}
+
+impl OpenDir {
+    fn new(path: PathBuf, reader: DirReader, recording: Option<CachedDir>) -> Self {
+        Self {
+            path,
+            reader,
+            seen: HashSet::new(),
+            subdirs: vec![],
+            recording,
+        }
+    }
+
+    /// Read all remaining entries of the directory, and replay them sorted by name
+    ///
+    /// Errors are replayed first, as they can't be sorted
+    fn sort_entries(&mut self) {
+        let mut entries = std::iter::from_fn(|| self.next_entry()).collect::<Vec<_>>();
+
+        entries.sort_by(|a, b| match (a, b) {
+            (Ok(a), Ok(b)) => a.name.cmp(&b.name),
+            (Ok(_), Err(_)) => cmp::Ordering::Greater,
+            (Err(_), Ok(_)) => cmp::Ordering::Less,
+            (Err(_), Err(_)) => cmp::Ordering::Equal,
+        });
+
+        self.reader = DirReader::Sorted(entries.into_iter());
+    }
+
+    /// Replay the directory's entries sorted by name, only keeping `chunk` of them in memory at once
+    ///
+    /// Listings from the cache are already in memory, so they are simply sorted (see [`OpenDir::sort_entries`])
+    fn sort_entries_chunked(&mut self, chunk: usize) {
+        let reader = mem::replace(&mut self.reader, DirReader::Sorted(vec![].into_iter()));
 
-                // Failed to match and not starved, so we simply ignore this entry
-                PatternMatchResult::NotMatched => {}
+        match reader {
+            DirReader::Fs(reader) => {
+                self.reader = DirReader::Chunked(ChunkedReader::new(reader, chunk.max(1)));
             }
+
+            DirReader::Cached(_) | DirReader::Sorted(_) | DirReader::Chunked(_) => {
+                self.reader = reader;
+                self.sort_entries();
+            }
+        }
+    }
+
+    /// Get the next entry of the directory
+    fn next_entry(&mut self) -> Option<io::Result<ListedEntry>> {
+        match &mut self.reader {
+            DirReader::Fs(reader) => reader
+                .next()
+                .map(|entry| entry.map(ListedEntry::from_dir_entry)),
+
+            DirReader::Cached(entries) => entries.next().map(|CachedEntry { name, is_dir }| {
+                Ok(ListedEntry {
+                    path: self.path.join(&name),
+                    name,
+                    listed: true,
+                    is_dir,
+                })
+            }),
+
+            DirReader::Sorted(entries) => entries.next(),
+
+            DirReader::Chunked(reader) => reader.next(&self.path),
         }
     }
 }
 
-fn diff_path(path: &NormalizedPath, base: &NormalizedPath) -> PathBuf {
-    assert!(path.prefix().is_some());
-    assert!(base.prefix().is_some());
-    assert_eq!(path.prefix(), base.prefix());
+/// (Internal) Reader yielding a directory's entries sorted by name, with bounded memory
+///
+/// The directory is read once, in chunks of entries which are sorted and spilled to a temporary file
+/// (see [`SpillFile`]). Chunks are then merged while yielding entries, so only the last chunk
+/// and a small buffer for each spilled one are kept in memory.
+///
+/// Errors are replayed first, as they can't be sorted.
+struct ChunkedReader {
+    /// Errors to yield before the entries
+    errors: VecDeque<io::Error>,
+
+    /// File holding the spilled chunks, if any
+    spill: Option<SpillFile>,
+
+    /// Chunks spilled to the file
+    runs: Vec<SpilledRun>,
+
+    /// Last chunk, which was kept in memory
+    last_chunk: std::vec::IntoIter<ListedName>,
+
+    /// Next name of each chunk, along with the chunk's index (the last chunk having index `runs.len()`)
+    heads: BinaryHeap<cmp::Reverse<(ListedName, usize)>>,
+
+    /// Last yielded name
+    last: Option<OsString>,
+}
+
+impl ChunkedReader {
+    /// Read a directory, spilling sorted chunks of `chunk` entries to a temporary file
+    fn new(reader: ReadDir, chunk: usize) -> Self {
+        let mut errors = VecDeque::new();
+        let mut spill = None::<SpillFile>;
+        let mut runs = vec![];
+        let mut names = Vec::with_capacity(chunk.min(MAX_PREALLOCATED_CHUNK));
 
-    let mut ita = path.components().iter();
-    let mut itb = base.components().iter();
+        // Entries are kept in memory if they can't be spilled
+        let mut can_spill = true;
 
-    let mut comps = PathBuf::new();
+        for entry in reader {
+            match entry {
+                Ok(entry) => {
+                    let ListedEntry {
+                        name,
+                        path: _,
+                        listed,
+                        is_dir,
+                    } = ListedEntry::from_dir_entry(entry);
 
-    loop {
-        match (ita.next(), itb.next()) {
-            (None, None) => break,
+                    names.push(ListedName {
+                        name,
+                        listed,
+                        is_dir,
+                    });
+                }
+                Err(err) => errors.push_back(err),
+            }
 
-            (Some(a), None) => {
-                comps.push(a);
-                comps.extend(ita.by_ref());
-                break;
+            if !can_spill || names.len() < chunk {
+                continue;
             }
 
-            (None, _) => comps.push(OsStr::new("..")),
+            names.sort_unstable();
 
-            (Some(a), Some(b)) if comps.components().count() == 0 && a == b => (),
+            let spilled = match &mut spill {
+                Some(spill) => spill.write_run(&names),
+                None => SpillFile::create().and_then(|created| {
+                    let spill = spill.insert(created);
+                    spill.write_run(&names)
+                }),
+            };
 
-            (Some(a), Some(_)) => {
-                comps.push(OsStr::new(".."));
+            match spilled {
+                Ok(run) => {
+                    runs.push(run);
+                    names.clear();
+                }
 
-                for _ in itb {
-                    comps.push(OsStr::new(".."));
+                Err(err) => {
+                    errors.push_back(err);
+                    can_spill = false;
                 }
+            }
+        }
+
+        names.sort_unstable();
+
+        let mut reader = Self {
+            errors,
+            spill,
+            runs,
+            last_chunk: names.into_iter(),
+            heads: BinaryHeap::new(),
+            last: None,
+        };
+
+        for index in 0..=reader.runs.len() {
+            reader.advance_run(index);
+        }
+
+        reader
+    }
 
-                comps.push(a);
+    fn next(&mut self, dir: &Path) -> Option<io::Result<ListedEntry>> {
+        if let Some(err) = self.errors.pop_front() {
+            return Some(Err(err));
+        }
 
-                comps.extend(ita.by_ref());
+        loop {
+            let cmp::Reverse((
+                ListedName {
+                    name,
+                    listed,
+                    is_dir,
+                },
+                index,
+            )) = self.heads.pop()?;
+            self.advance_run(index);
 
-                break;
+            // The same name may be listed twice if the entry was recreated while the directory was read
+            if self.last.as_ref().is_some_and(|last| name <= *last) {
+                continue;
             }
+
+            self.last = Some(name.clone());
+
+            return Some(Ok(ListedEntry {
+                path: dir.join(&name),
+                name,
+                listed,
+                is_dir,
+            }));
+        }
+    }
+
+    /// Push the next name of a chunk to the merged ones
+    ///
+    /// Failing to read a spilled chunk is reported as an error, and the rest of the chunk is dropped
+    fn advance_run(&mut self, index: usize) {
+        let next = match (self.runs.get_mut(index), &mut self.spill) {
+            (Some(run), Some(spill)) => run.next_name(&mut spill.file),
+            _ => Ok(self.last_chunk.next()),
+        };
+
+        match next {
+            Ok(Some(name)) => self.heads.push(cmp::Reverse((name, index))),
+            Ok(None) => {}
+            Err(err) => self.errors.push_back(err),
         }
     }
+}
+
+/// (Internal) Name of an entry read by a [`ChunkedReader`], along with what its listing provided (see [`ListedEntry`])
+///
+/// Names are ordered first, so chunks of these are sorted by name.
+#[derive(PartialEq, Eq, PartialOrd, Ord)]
+struct ListedName {
+    name: OsString,
+    listed: bool,
+    is_dir: Option<bool>,
+}
+
+/// Maximum number of entries to allocate room for in advance in a chunk (see [`ChunkedReader`])
+const MAX_PREALLOCATED_CHUNK: usize = 4096;
+
+/// Size of the buffer used to read each spilled chunk back (see [`SpilledRun`])
+const RUN_BUFFER_SIZE: usize = 4096;
+
+/// (Internal) Temporary file holding chunks of sorted entry names, removed when dropped
+///
+/// Each name is stored as its length (as a little-endian `u32`) followed by its encoded form (see [`encode_name`]),
+/// then a byte holding what the listing provided about the entry (see [`ListedName`])
+struct SpillFile {
+    path: PathBuf,
+    file: File,
+}
+
+impl SpillFile {
+    /// Create a new file in the temporary directory
+    fn create() -> io::Result<Self> {
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+        loop {
+            let path = std::env::temp_dir().join(format!(
+                "globby-walk-{}-{}",
+                std::process::id(),
+                COUNTER.fetch_add(1, AtomicOrdering::Relaxed)
+            ));
+
+            let mut options = OpenOptions::new();
+            options.read(true).write(true).create_new(true);
+
+            // Entry names shouldn't be readable by other users of the shared temporary directory
+            #[cfg(unix)]
+            std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+
+            match options.open(&path) {
+                Ok(file) => return Ok(Self { path, file }),
+                // Left by a previous process with the same identifier
+                Err(err) if err.kind() == ErrorKind::AlreadyExists => continue,
+                Err(err) => return Err(err),
+            }
+        }
+    }
+
+    /// Append a sorted chunk of names to the file
+    fn write_run(&mut self, names: &[ListedName]) -> io::Result<SpilledRun> {
+        let mut bytes = vec![];
+
+        for ListedName {
+            name,
+            listed,
+            is_dir,
+        } in names
+        {
+            let encoded = encode_name(name);
+            let len = u32::try_from(encoded.len()).map_err(io::Error::other)?;
+
+            bytes.extend_from_slice(&len.to_le_bytes());
+            bytes.extend_from_slice(&encoded);
+            bytes.push(
+                u8::from(*listed)
+                    | (u8::from(is_dir.is_some()) << 1)
+                    | (u8::from(*is_dir == Some(true)) << 2),
+            );
+        }
+
+        let offset = self.file.seek(SeekFrom::End(0))?;
+        self.file.write_all(&bytes)?;
+
+        Ok(SpilledRun {
+            offset,
+            end: offset + bytes.len() as u64,
+            buffer: vec![],
+            pos: 0,
+        })
+    }
+}
+
+impl Drop for SpillFile {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+/// (Internal) Chunk of sorted names stored in a [`SpillFile`], read back through a small buffer
+struct SpilledRun {
+    /// Offset of the chunk's data that wasn't buffered yet
+    offset: u64,
+
+    /// Offset of the end of the chunk
+    end: u64,
+
+    /// Buffered data
+    buffer: Vec<u8>,
+
+    /// Position of the next name in the buffer
+    pos: usize,
+}
+
+impl SpilledRun {
+    /// Read the next name of the chunk, if any
+    fn next_name(&mut self, file: &mut File) -> io::Result<Option<ListedName>> {
+        if self.pos == self.buffer.len() && self.offset == self.end {
+            return Ok(None);
+        }
+
+        let len = self.take(file, 4)?;
+        let len = u32::from_le_bytes(len.try_into().unwrap()) as usize;
+
+        let name = decode_name(self.take(file, len)?);
+        let flags = self.take(file, 1)?[0];
+
+        Ok(Some(ListedName {
+            name,
+            listed: flags & 1 != 0,
+            is_dir: (flags & 2 != 0).then_some(flags & 4 != 0),
+        }))
+    }
+
+    /// Take the provided number of bytes from the chunk, refilling the buffer if required
+    fn take(&mut self, file: &mut File, len: usize) -> io::Result<&[u8]> {
+        if self.buffer.len() - self.pos < len {
+            self.buffer.drain(..self.pos);
+            self.pos = 0;
+
+            let missing = len - self.buffer.len();
+            let remaining = usize::try_from(self.end - self.offset).unwrap_or(usize::MAX);
+
+            if missing > remaining {
+                return Err(ErrorKind::UnexpectedEof.into());
+            }
+
+            let wanted = missing.max(RUN_BUFFER_SIZE).min(remaining);
+            let filled = self.buffer.len();
+            self.buffer.resize(filled + wanted, 0);
+
+            file.seek(SeekFrom::Start(self.offset))?;
+            file.read_exact(&mut self.buffer[filled..])?;
+            self.offset += wanted as u64;
+        }
+
+        let taken = &self.buffer[self.pos..self.pos + len];
+        self.pos += len;
+
+        Ok(taken)
+    }
+}
+
+/// Encode an entry name to store it in a [`SpillFile`]
+fn encode_name(name: &OsStr) -> Vec<u8> {
+    #[cfg(target_family = "windows")]
+    {
+        use std::os::windows::ffi::OsStrExt;
+
+        name.encode_wide().flat_map(u16::to_le_bytes).collect()
+    }
+
+    #[cfg(target_family = "unix")]
+    {
+        use std::os::unix::ffi::OsStrExt;
+
+        name.as_bytes().to_vec()
+    }
+}
+
+/// Decode an entry name stored in a [`SpillFile`] (see [`encode_name`])
+fn decode_name(bytes: &[u8]) -> OsString {
+    #[cfg(target_family = "windows")]
+    {
+        use std::os::windows::ffi::OsStringExt;
+
+        let wide = bytes
+            .chunks_exact(2)
+            .map(|pair| u16::from_le_bytes([pair[0], pair[1]]))
+            .collect::<Vec<_>>();
+
+        OsString::from_wide(&wide)
+    }
+
+    #[cfg(target_family = "unix")]
+    {
+        use std::os::unix::ffi::OsStringExt;
+
+        OsString::from_vec(bytes.to_vec())
+    }
+}
+
+/// (Internal) Source of a directory's entries
+enum DirReader {
+    /// Entries are read from the filesystem
+    Fs(ReadDir),
+
+    /// Entries are replayed from the cache
+    Cached(std::vec::IntoIter<CachedEntry>),
+
+    /// Entries were read beforehand, and are replayed in sorted order
+    Sorted(std::vec::IntoIter<io::Result<ListedEntry>>),
+
+    /// Entries are read in sorted chunks, see [`ChunkedReader`]
+    Chunked(ChunkedReader),
+}
+
+/// (Internal) An entry listed from a directory
+struct ListedEntry {
+    /// Name of the entry
+    name: OsString,
+
+    /// Full path to the entry
+    path: PathBuf,
+
+    /// Is the entry known to exist, i.e. does it come from the cache or did its listing provide its type?
+    listed: bool,
+
+    /// Is the entry a directory, if known
+    is_dir: Option<bool>,
+}
+
+impl ListedEntry {
+    /// Describe an entry read from a directory, using the type provided by the listing if any
+    ///
+    /// This type is often known without querying the entry's metadata. Symbolic links are
+    /// followed to know if they point to a directory, so their type isn't enough for that.
+    fn from_dir_entry(entry: DirEntry) -> Self {
+        let file_type = entry.file_type().ok();
+
+        Self {
+            name: entry.file_name(),
+            path: entry.path(),
+            listed: file_type.is_some(),
+            is_dir: file_type
+                .filter(|file_type| !file_type.is_symlink())
+                .map(|file_type| file_type.is_dir()),
+        }
+    }
+
+    /// Check if the entry is a directory
+    ///
+    /// Returns [`None`] if the entry doesn't exist anymore
+    fn is_dir(&self, stats: &mut WalkStats) -> Option<bool> {
+        self.is_dir.or_else(|| {
+            stats.metadata_queries += 1;
+            entry_is_dir(&self.path)
+        })
+    }
+
+    /// Check if the entry still exists
+    ///
+    /// Entries are assumed to exist if they come from the cache, as their parent directory didn't change,
+    /// or if their listing provided their type
+    fn exists(&self, stats: &mut WalkStats) -> bool {
+        self.listed || {
+            stats.metadata_queries += 1;
+            entry_exists(&self.path)
+        }
+    }
+}
+
+/// (Internal) Event emitted while walking
+enum WalkEvent {
+    /// Started reading a directory
+    DirStart(PathBuf),
+
+    /// A directory was fully read
+    DirDone,
+
+    /// Found a matching entry, along with the indices of the patterns it matches
+    Matched(PathBuf, SmallVec<[usize; 2]>),
+
+    /// An error occurred
+    Error(WalkError),
+}
+
+impl WalkerState {
+    /// Schedule traversal of a directory
+    fn descend_into(&mut self, dir: PathBuf) {
+        match self.open_dirs.last_mut() {
+            Some(open_dir) if self.deferred_descent => open_dir.subdirs.push(dir),
+            _ => self.going_into_dir = Some(dir),
+        }
+    }
+
+    /// Open a directory for reading, from the cache if possible
+    fn open_dir(&mut self, path: PathBuf) -> io::Result<OpenDir> {
+        let Some(cache) = &mut self.cache else {
+            self.stats.dirs_read += 1;
+
+            let reader = std::fs::read_dir(&path)?;
+            return Ok(OpenDir::new(path, DirReader::Fs(reader), None));
+        };
+
+        cache.visited.insert(path.clone());
+
+        let listed_at = SystemTime::now();
+
+        self.stats.metadata_queries += 1;
+        let mtime = std::fs::metadata(&path)?.modified().ok();
+
+        if cache.trust_dir_mtime
+            && let Some(mtime) = mtime
+            && let Some(cached) = cache.dirs.get(&path)
+            && cached.mtime == mtime
+        {
+            self.stats.dirs_cached += 1;
+
+            let entries = cached.entries.clone().into_iter();
+            return Ok(OpenDir::new(path, DirReader::Cached(entries), None));
+        }
+
+        cache.dirs.remove(&path);
+
+        self.stats.dirs_read += 1;
+
+        let reader = std::fs::read_dir(&path)?;
+
+        // Don't record listings of directories modified too recently, as further modifications
+        // may not change their modification time
+        let recording = mtime
+            .filter(|mtime| {
+                cache.trust_dir_mtime
+                    && listed_at
+                        .duration_since(*mtime)
+                        .is_ok_and(|age| age >= MTIME_GRANULARITY)
+            })
+            .map(|mtime| CachedDir {
+                mtime,
+                entries: vec![],
+            });
+
+        Ok(OpenDir::new(path, DirReader::Fs(reader), recording))
+    }
+
+    fn next_event(&mut self) -> Option<WalkEvent> {
+        if let Some(err) = self.pending_errors.pop_front() {
+            return Some(WalkEvent::Error(err));
+        }
+
+        self.check_limits();
+
+        if let Some((kind, limit)) = self.pending_limit.take() {
+            return Some(WalkEvent::Error(WalkError::LimitReached { kind, limit }));
+        }
+
+        loop {
+            // When descent is deferred, only go into a new directory once the current one has been fully read
+            if self.going_into_dir.is_none() && self.open_dirs.is_empty() {
+                self.going_into_dir = self.queued_dirs.pop_front();
+            }
+
+            // Check if we're going into a directory
+            if let Some(going_into_dir) = self.going_into_dir.take() {
+                match self.open_dir(going_into_dir.clone()) {
+                    // The directory was removed after being listed (or the walking root
+                    // isn't a directory), so there is nothing to traverse
+                    Err(err)
+                        if matches!(err.kind(), ErrorKind::NotFound | ErrorKind::NotADirectory) =>
+                    {
+                        continue;
+                    }
+                    Err(err) => {
+                        return Some(WalkEvent::Error(WalkError::Io {
+                            path: going_into_dir,
+                            err,
+                        }));
+                    }
+                    Ok(mut open_dir) => {
+                        match self.ordering {
+                            Ordering::ParentsFirstSorted => open_dir.sort_entries(),
+                            Ordering::ParentsFirstSortedChunked { chunk } => {
+                                open_dir.sort_entries_chunked(chunk)
+                            }
+                            Ordering::Unspecified | Ordering::ParentsFirst => {}
+                        }
+
+                        self.open_dirs.push(open_dir);
+
+                        return Some(WalkEvent::DirStart(going_into_dir));
+                    }
+                }
+            }
+
+            // Otherwise, get the currently handled directory's reader
+            let queue = self.open_dirs.last_mut()?;
+
+            let Some(entry) = queue.next_entry() else {
+                // If the reader is empty, remove it from the last
+                let done = self.open_dirs.pop().unwrap();
+
+                // Cache its listing if it was recorded
+                if let Some(cache) = &mut self.cache
+                    && let Some(recording) = done.recording
+                {
+                    cache.dirs.insert(done.path, recording);
+                }
+
+                // then queue its deferred subdirectories, in order
+                if self.breadth_first {
+                    self.queued_dirs.extend(done.subdirs);
+                } else {
+                    for subdir in done.subdirs.into_iter().rev() {
+                        self.queued_dirs.push_front(subdir);
+                    }
+                }
+
+                return Some(WalkEvent::DirDone);
+            };
+
+            // Get the successful entry or return the error
+            let entry = match entry {
+                Ok(entry) => entry,
+                Err(err) => {
+                    // The listing is incomplete, so it can't be cached
+                    queue.recording = None;
+
+                    return Some(WalkEvent::Error(WalkError::Io {
+                        path: queue.path.clone(),
+                        err,
+                    }));
+                }
+            };
+
+            // Don't handle the same entry twice in a single directory
+            // Chunked readers yield strictly increasing names, so they don't need to remember them
+            if !matches!(queue.reader, DirReader::Chunked(_))
+                && !queue.seen.insert(entry.name.clone())
+            {
+                continue;
+            }
+
+            // Compute the real entry path, as the walker only provides something relative to the base *walking* directory
+            let entry_path = normalize_path_with(&entry.path, PLATFORM_NORMALIZE_OPTS).unwrap();
+
+            self.stats.entries_scanned += 1;
+
+            // Patterns this entry matches, along with the entry's path rendered for each of them
+            let mut matched = SmallVec::<[(usize, PathBuf); 2]>::new();
+
+            // Should we traverse this entry if it's a directory?
+            let mut descend = false;
+
+            // Do we need to know if this entry is a directory?
+            let mut needs_is_dir = false;
+
+            for (index, walked) in self.patterns.iter().enumerate() {
+                match walked.match_entry(&entry_path, self.unix_separators, &mut self.match_ctx) {
+                    EntryMatch::Matched {
+                        path,
+                        descend: descend_matched,
+                    } => {
+                        // If no descendant of this path may be matched by the pattern (e.g. it contains
+                        // no wildcard), we can skip it if it's a directory
+                        // Otherwise, we'll need to traverse it
+                        if descend_matched {
+                            descend = true;
+                        }
+
+                        if walked.target_hint == TargetHint::RequiresDirectory {
+                            needs_is_dir = true;
+                        }
+
+                        matched.push((index, path));
+                    }
+
+                    // May have matched if the path was more complete, so we'll need to traverse it
+                    EntryMatch::Starved => descend = true,
+
+                    // Failed to match and not starved, so we simply ignore this entry
+                    EntryMatch::NotMatched => {}
+                }
+            }
+
+            // Did the entry vanish since it was listed?
+            let mut vanished = false;
+
+            // Is the entry a directory, if known
+            let mut is_dir = entry.is_dir;
+
+            let result = if matched.is_empty() && !descend {
+                None
+            } else if !descend && !needs_is_dir {
+                // Knowing if the entry is a directory is irrelevant, so only check it still exists
+                vanished = !entry.exists(&mut self.stats);
+
+                (!vanished).then_some(matched)
+            } else {
+                match entry.is_dir(&mut self.stats) {
+                    None => {
+                        vanished = true;
+                        None
+                    }
+
+                    Some(entry_is_dir) => {
+                        is_dir = Some(entry_is_dir);
+
+                        if entry_is_dir && descend {
+                            self.descend_into(entry.path.clone());
+                        }
+
+                        if !entry_is_dir {
+                            matched.retain(|(index, _)| {
+                                self.patterns[*index].target_hint != TargetHint::RequiresDirectory
+                            });
+                        }
+
+                        Some(matched)
+                    }
+                }
+            };
+
+            // Record the entry for the cache
+            if !vanished
+                && let Some(recording) = self
+                    .open_dirs
+                    .last_mut()
+                    .and_then(|open_dir| open_dir.recording.as_mut())
+            {
+                recording.entries.push(CachedEntry {
+                    name: entry.name,
+                    is_dir,
+                });
+            }
+
+            // Render the entry for the first pattern it matches
+            let result = result.and_then(|matched| {
+                let indices = matched.iter().map(|(index, _)| *index).collect();
+                let (_, path) = matched.into_iter().next()?;
+
+                Some((path, indices))
+            });
+
+            if result.is_some() {
+                self.stats.results += 1;
+            }
+
+            self.check_limits();
+
+            if let Some((entry_path, matched)) = result {
+                return Some(WalkEvent::Matched(entry_path, matched));
+            }
+
+            if let Some((kind, limit)) = self.pending_limit.take() {
+                return Some(WalkEvent::Error(WalkError::LimitReached { kind, limit }));
+            }
+        }
+    }
+}
+
+impl Iterator for Walker {
+    type Item = Result<PathBuf, WalkError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let state = self.state.as_mut()?;
+
+        loop {
+            match state.next_event()? {
+                WalkEvent::Matched(path, _) => return Some(Ok(path)),
+                WalkEvent::Error(err) => return Some(Err(err)),
+                WalkEvent::DirStart(_) | WalkEvent::DirDone => {}
+            }
+        }
+    }
+}
+
+impl WalkedPattern {
+    /// Match a filesystem entry against the pattern
+    fn match_entry(
+        &self,
+        entry_path: &NormalizedPath,
+        unix_separators: bool,
+        match_ctx: &mut MatchContext,
+    ) -> EntryMatch {
+        let Self {
+            pattern,
+            walk_from,
+            target_hint: _,
+            inapplicable,
+            parent_prefix,
+        } = self;
+
+        if inapplicable.is_some() {
+            return EntryMatch::NotMatched;
+        }
+
+        match_entry(
+            pattern,
+            walk_from,
+            parent_prefix,
+            entry_path,
+            unix_separators,
+            match_ctx,
+        )
+    }
+}
+
+/// Compute the directory to walk from for a pattern, ascending for each leading `..` component
+///
+/// Relative patterns are walked from the provided base directory, if any.
+fn walk_from(pattern: &Pattern, base_dir: Option<&NormalizedPath>) -> Option<NormalizedPath> {
+    let common_root_dir =
+        normalize_path_with(pattern.common_root_dir(), PLATFORM_NORMALIZE_OPTS).ok()?;
+
+    match base_dir {
+        Some(base_dir) if !pattern.is_absolute() => {
+            let mut walk_from = base_dir.clone();
+
+            for component in common_root_dir.components() {
+                walk_from.push(component.clone());
+            }
+
+            Some(walk_from)
+        }
+
+        _ => Some(common_root_dir),
+    }
+}
+
+/// Match a filesystem entry against a pattern walked from the provided directory
+///
+/// Relative patterns are matched against the entry's path relative to the base directory,
+/// which is made of the provided prefix (see [`WalkedPattern::parent_prefix`]) and the entry's path from the walking directory.
+fn match_entry(
+    pattern: &Pattern,
+    walk_from: &NormalizedPath,
+    parent_prefix: &Path,
+    entry_path: &NormalizedPath,
+    unix_separators: bool,
+    match_ctx: &mut MatchContext,
+) -> EntryMatch {
+    // Only entries located below the pattern's walking directory may match it
+    if entry_path.prefix() != walk_from.prefix() {
+        return EntryMatch::NotMatched;
+    }
+
+    let (entry_comps, walk_from_comps) = (entry_path.components(), walk_from.components());
+
+    // Compare components the same way the filesystem does
+    let case_insensitive_fs = pattern.is_case_insensitive_fs();
+    let same_component =
+        |(a, b): (&OsString, &OsString)| a == b || (case_insensitive_fs && eq_ignore_case(a, b));
+
+    // Entries leading to the walking directory need to be traversed
+    if entry_comps.len() <= walk_from_comps.len()
+        && entry_comps.iter().zip(walk_from_comps).all(same_component)
+    {
+        return EntryMatch::Starved;
+    }
+
+    if entry_comps.len() < walk_from_comps.len()
+        || !entry_comps.iter().zip(walk_from_comps).all(same_component)
+    {
+        return EntryMatch::NotMatched;
+    }
+
+    // Compute the path relative to the base directory (if the pattern is not absolute)
+    let entry_path = if pattern.is_absolute() {
+        entry_path.to_path_buf()
+    } else {
+        let mut relative = parent_prefix.to_owned();
+        relative.extend(&entry_comps[walk_from_comps.len()..]);
+
+        if unix_separators {
+            with_unix_separators(&relative)
+        } else {
+            relative
+        }
+    };
+
+    // Check if the path matches the provided globbing pattern
+    match pattern.match_against_with_ctx(&entry_path, match_ctx) {
+        // Absolute path conflict should not happen as it's been taken care of ahead of matching
+        PatternMatchResult::PathNotAbsolute
+        | PatternMatchResult::PathIsAbsolute
+        | PatternMatchResult::IncompatiblePrefix => {
+            unreachable!()
+        }
+
+        PatternMatchResult::Matched => EntryMatch::Matched {
+            descend: pattern.descendants_may_match(&entry_path, match_ctx),
+            path: entry_path,
+        },
+        PatternMatchResult::Starved => EntryMatch::Starved,
+        PatternMatchResult::NotMatched => EntryMatch::NotMatched,
+    }
+}
+
+/// Check if two path components are equal, ignoring case
+fn eq_ignore_case(a: &OsStr, b: &OsStr) -> bool {
+    match (a.to_str(), b.to_str()) {
+        (Some(a), Some(b)) => a.to_lowercase() == b.to_lowercase(),
+        _ => a.eq_ignore_ascii_case(b),
+    }
+}
+
+/// (Internal) Result of matching a filesystem entry against a single pattern
+enum EntryMatch {
+    /// The entry matches, rendered as provided
+    Matched {
+        path: PathBuf,
+
+        /// May the entry's descendants match too?
+        descend: bool,
+    },
+
+    /// The entry's descendants may match
+    Starved,
+
+    /// Neither the entry nor its descendants can match
+    NotMatched,
+}
+
+/// A walker's result, along with the patterns it matches
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MultiMatch {
+    /// Path of the result, rendered as with [`Walker`]
+    pub path: PathBuf,
+
+    /// Indices of all the patterns matching the result, in ascending order
+    pub pattern_indices: SmallVec<[usize; 2]>,
+}
+
+/// Walker yielding results along with the indices of the patterns they match
+///
+/// Created using [`Walker::with_pattern_indices`]
+pub struct MultiMatchWalker {
+    walker: Walker,
+}
+
+impl Iterator for MultiMatchWalker {
+    type Item = Result<MultiMatch, WalkError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let state = self.walker.state.as_mut()?;
+
+        loop {
+            match state.next_event()? {
+                WalkEvent::Matched(path, pattern_indices) => {
+                    return Some(Ok(MultiMatch {
+                        path,
+                        pattern_indices,
+                    }));
+                }
+                WalkEvent::Error(err) => return Some(Err(err)),
+                WalkEvent::DirStart(_) | WalkEvent::DirDone => {}
+            }
+        }
+    }
+}
+
+/// Walker yielding matches grouped by their parent directory
+///
+/// Created using [`Walker::grouped`]
+pub struct GroupedWalker {
+    walker: Walker,
+
+    /// Group being built
+    current: Option<(PathBuf, Vec<PathBuf>)>,
+
+    /// Error to yield after the current group
+    pending_err: Option<WalkError>,
+}
+
+impl GroupedWalker {
+    /// Take the current group if it contains at least one match
+    fn take_group(&mut self) -> Option<(PathBuf, Vec<PathBuf>)> {
+        self.current
+            .take()
+            .filter(|(_, matches)| !matches.is_empty())
+    }
+}
+
+impl Iterator for GroupedWalker {
+    type Item = Result<(PathBuf, Vec<PathBuf>), WalkError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(err) = self.pending_err.take() {
+            return Some(Err(err));
+        }
+
+        loop {
+            let event = self.walker.state.as_mut().and_then(WalkerState::next_event);
+
+            let Some(event) = event else {
+                return self.take_group().map(Ok);
+            };
+
+            match event {
+                WalkEvent::DirStart(dir) => self.current = Some((dir, vec![])),
+
+                WalkEvent::Matched(path, _) => {
+                    if let Some((_, matches)) = &mut self.current {
+                        matches.push(path);
+                    }
+                }
+
+                WalkEvent::DirDone => {
+                    if let Some(group) = self.take_group() {
+                        return Some(Ok(group));
+                    }
+                }
+
+                WalkEvent::Error(err) => {
+                    // Limits stop the walk, so flush the group that was being built beforehand
+                    if matches!(err, WalkError::LimitReached { .. })
+                        && let Some(group) = self.take_group()
+                    {
+                        self.pending_err = Some(err);
+                        return Some(Ok(group));
+                    }
+
+                    return Some(Err(err));
+                }
+            }
+        }
+    }
+}
+
+/// Walker keeping directory listings in cache between runs
+///
+/// This is useful when walking the same tree repeatedly (e.g. from a long-running process), while only a few
+/// directories change between runs. On each run, the directories' modification time is compared to the one they
+/// had when they were last listed, and only directories that changed since are read again.
+/// The others' entries are taken from the cache, which saves reading them and checking their type.
+///
+/// A run yields the same results as a [`Walker`] would, with the following caveats:
+/// * Changing the target of a symbolic link isn't detected, as it doesn't change the modification time of the
+///   directory containing the link
+/// * Some filesystems (e.g. FAT or some network filesystems) don't update the modification time of directories
+///   reliably. Use [`CachedWalker::trust_dir_mtime`] to disable caching on these.
+///
+/// Directories modified in the last couple of seconds before being listed are not cached, as the modification time's
+/// granularity may be too coarse to detect further changes.
+pub struct CachedWalker {
+    pattern: Pattern,
+    base_dir: PathBuf,
+
+    /// Cached directory listings
+    cache: DirCache,
+
+    /// Statistics about the last run
+    stats: WalkStats,
+}
+
+impl CachedWalker {
+    /// Create a cached walker that will yield filesystem entries that match the provided pattern
+    ///
+    /// The cache starts empty, so the first run reads all directories like a [`Walker`] would.
+    pub fn new(pattern: Pattern, base_dir: &Path) -> Self {
+        Self {
+            pattern,
+            base_dir: base_dir.to_owned(),
+            cache: DirCache {
+                trust_dir_mtime: true,
+                ..DirCache::default()
+            },
+            stats: WalkStats::default(),
+        }
+    }
+
+    /// Choose if directories' modification time can be relied on to detect changes
+    ///
+    /// When disabled, all directories are read again on each run. Enabled by default.
+    pub fn trust_dir_mtime(mut self, trust_dir_mtime: bool) -> Self {
+        self.cache.trust_dir_mtime = trust_dir_mtime;
+
+        if !trust_dir_mtime {
+            self.cache.dirs.clear();
+        }
+
+        self
+    }
+
+    /// Walk the directory, reusing listings from previous runs
+    ///
+    /// The cache is updated once the returned iterator is dropped.
+    pub fn run(&mut self) -> CachedRun<'_> {
+        let mut walker = Walker::new(self.pattern.clone(), &self.base_dir);
+
+        if let Some(state) = &mut walker.state {
+            state.cache = Some(mem::take(&mut self.cache));
+        }
+
+        CachedRun {
+            walker,
+            owner: self,
+            finished: false,
+        }
+    }
+
+    /// Get statistics about the last run
+    pub fn stats(&self) -> WalkStats {
+        self.stats
+    }
+}
+
+/// A single run of a [`CachedWalker`]
+///
+/// Created using [`CachedWalker::run`]
+pub struct CachedRun<'a> {
+    walker: Walker,
+    owner: &'a mut CachedWalker,
+
+    /// Did the walk complete?
+    finished: bool,
+}
+
+impl Iterator for CachedRun<'_> {
+    type Item = Result<PathBuf, WalkError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let next = self.walker.next();
+        self.finished = next.is_none();
+        next
+    }
+}
+
+impl Drop for CachedRun<'_> {
+    fn drop(&mut self) {
+        self.owner.stats = self.walker.stats();
+
+        let Some(state) = &mut self.walker.state else {
+            return;
+        };
+
+        let Some(mut cache) = state.cache.take() else {
+            return;
+        };
+
+        // Forget about directories that don't exist or aren't traversed anymore
+        if self.finished && !state.limit_reached {
+            let DirCache { dirs, visited, .. } = &mut cache;
+            dirs.retain(|path, _| visited.contains(path));
+        }
+
+        cache.visited.clear();
+
+        self.owner.cache = cache;
+    }
+}
+
+/// (Internal) Cache of directory listings
+#[derive(Default)]
+struct DirCache {
+    /// Listing of each cached directory
+    dirs: HashMap<PathBuf, CachedDir>,
+
+    /// Directories opened during the current run
+    visited: HashSet<PathBuf>,
+
+    /// Can directories' modification time be relied on?
+    trust_dir_mtime: bool,
+}
+
+/// (Internal) Cached listing of a directory
+struct CachedDir {
+    /// Modification time of the directory when it was listed
+    mtime: SystemTime,
+
+    /// Entries of the directory
+    entries: Vec<CachedEntry>,
+}
+
+/// (Internal) Cached directory entry
+#[derive(Clone)]
+struct CachedEntry {
+    /// Name of the entry
+    name: OsString,
+
+    /// Is the entry a directory, if known
+    is_dir: Option<bool>,
+}
+
+/// Options to normalize actual filesystem paths with
+///
+/// Backslashes are ordinary characters in Unix paths, so they must not be treated as separators.
+/// The pattern's own options are used when matching the resulting paths.
+const PLATFORM_NORMALIZE_OPTS: NormalizeOpts = NormalizeOpts::new().with_slash_only(cfg!(unix));
+
+/// Minimum number of candidates in a single directory for [`Walker::filter_existing`] to list it
+/// instead of checking each candidate individually
+const MIN_CANDIDATES_PER_LISTING: usize = 4;
+
+/// Modification times more recent than this (compared to the listing time) may not reflect further changes
+const MTIME_GRANULARITY: Duration = Duration::from_secs(2);
+
+/// Order in which a [`Walker`] yields its results
+///
+/// Whatever the ordering, results are always the same: only the order they are yielded in may change.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Ordering {
+    /// No guarantee is given as for the order results are yielded in
+    ///
+    /// This leaves room for the walker to yield results in whichever order is the most efficient.
+    Unspecified,
+
+    /// Directories are always yielded before their content
+    ///
+    /// This also applies to symbolic links to directories, as well as to results of patterns starting
+    /// with `..` components or absolute ones. Entries of a single directory are yielded in the order
+    /// the filesystem lists them in, which may differ between runs.
+    #[default]
+    ParentsFirst,
+
+    /// Same as [`Ordering::ParentsFirst`], with entries of each directory yielded sorted by name
+    ///
+    /// This makes the order of results deterministic for a given filesystem tree, at the cost
+    /// of reading each directory fully before handling its entries.
+    ParentsFirstSorted,
+
+    /// Same as [`Ordering::ParentsFirstSorted`], while only keeping up to `chunk` entries of each directory in memory
+    ///
+    /// Each directory is read in chunks of entries, which are sorted and spilled to a temporary file,
+    /// then merged while yielding entries. This trades file writes and reads for memory,
+    /// which is useful for directories with millions of entries. On Unix platforms, the temporary file
+    /// is only readable by the current user.
+    ///
+    /// All chunks are spilled even when combined with [`Walker::max_results`], as entries that don't match
+    /// (or whose content doesn't) don't count towards the limit, so any of a directory's entries may be needed
+    /// before it is reached.
+    ///
+    /// Entries are never yielded twice. If the temporary file can't be written, an error is yielded
+    /// and the directory's remaining entries are kept in memory instead.
+    ParentsFirstSortedChunked {
+        /// Maximum number of entries of a single directory to keep in memory (at least 1)
+        chunk: usize,
+    },
+}
+
+/// Statistics about a walk
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct WalkStats {
+    /// Number of directories the walker tried to read
+    pub dirs_read: usize,
+
+    /// Number of directories whose entries were taken from the cache instead of being read (see [`CachedWalker`])
+    pub dirs_cached: usize,
+
+    /// Number of filesystem entries examined
+    pub entries_scanned: usize,
+
+    /// Number of metadata queries performed on individual entries,
+    /// e.g. to check if they are directories or still exist
+    pub metadata_queries: usize,
+
+    /// Number of results yielded
+    pub results: usize,
+}
+
+/// Error occuring while walking
+#[derive(Debug)]
+pub enum WalkError {
+    /// Failed to read a directory or one of its entries
+    Io {
+        /// Path of the directory, as provided to the filesystem (i.e. joined to the base directory)
+        path: PathBuf,
+
+        /// The underlying error
+        err: std::io::Error,
+    },
+
+    /// A limit set on the walker was reached, so the walk was stopped
+    LimitReached { kind: LimitKind, limit: usize },
+
+    /// A pattern can't match any entry, so it is ignored
+    ///
+    /// The reason is one of:
+    ///
+    /// * [`PatternMatchResult::PathNotAbsolute`] if the pattern is relative while no base directory was provided
+    /// * [`PatternMatchResult::PathIsAbsolute`] if the pattern is absolute on another Windows drive than the base directory
+    /// * [`PatternMatchResult::IncompatiblePrefix`] if the pattern's prefix doesn't exist on the current platform
+    PatternInapplicable {
+        /// Index of the pattern (see [`Walker::with_pattern_indices`])
+        pattern: usize,
+
+        /// Result of matching any entry against the pattern
+        reason: PatternMatchResult,
+    },
+}
+
+impl fmt::Display for WalkError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WalkError::Io { path, err } => {
+                write!(f, "failed to read directory '{}': {err}", path.display())
+            }
+            WalkError::LimitReached { kind, limit } => match kind {
+                LimitKind::Results => write!(f, "limit of {limit} results reached"),
+                LimitKind::EntriesScanned => write!(f, "limit of {limit} scanned entries reached"),
+            },
+            WalkError::PatternInapplicable { pattern, reason } => {
+                let reason = match reason {
+                    PatternMatchResult::PathNotAbsolute => {
+                        "it is relative while no base directory was provided"
+                    }
+                    PatternMatchResult::PathIsAbsolute => {
+                        "it is absolute on another drive than the base directory"
+                    }
+                    PatternMatchResult::IncompatiblePrefix => {
+                        "its path prefix doesn't exist on this platform"
+                    }
+                    PatternMatchResult::Matched
+                    | PatternMatchResult::NotMatched
+                    | PatternMatchResult::Starved => "it doesn't match any entry",
+                };
+
+                write!(f, "pattern {pattern} can't match any entry: {reason}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for WalkError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            WalkError::Io { err, .. } => Some(err),
+            WalkError::LimitReached { .. } | WalkError::PatternInapplicable { .. } => None,
+        }
+    }
+}
+
+/// Kind of limit that can be set on a [`Walker`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LimitKind {
+    /// See [`Walker::max_results`]
+    Results,
+
+    /// See [`Walker::max_entries_scanned`]
+    EntriesScanned,
+}
+
+/// Check if an entry is a directory, following symbolic links
+///
+/// Returns [`None`] if the entry doesn't exist anymore.
+/// Broken symbolic links are considered as non-directory entries.
+fn entry_is_dir(path: &Path) -> Option<bool> {
+    match std::fs::metadata(path) {
+        Ok(metadata) => Some(metadata.is_dir()),
+        Err(err) if err.kind() == ErrorKind::NotFound => {
+            // Distinguish between broken symbolic links and vanished entries
+            std::fs::symlink_metadata(path).ok().map(|_| false)
+        }
+        Err(_) => Some(false),
+    }
+}
+
+/// Compute the deepest directory both paths are located in
+///
+/// Both paths must have the same prefix
+fn common_ancestor(a: &NormalizedPath, b: &NormalizedPath) -> NormalizedPath {
+    assert_eq!(a.prefix(), b.prefix());
+
+    let mut ancestor = a.clone();
+
+    let common = a
+        .components()
+        .iter()
+        .zip(b.components())
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    for _ in common..a.components().len() {
+        ancestor.push(OsString::from(".."));
+    }
+
+    ancestor
+}
 
-    comps
+/// Check if an entry still exists, without following symbolic links
+fn entry_exists(path: &Path) -> bool {
+    std::fs::symlink_metadata(path).is_ok()
 }
@@ -0,0 +1,62 @@
+#![allow(dead_code)]
+
+use std::{
+    collections::BTreeSet,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use globby::Walker;
+use tempfile::TempDir;
+
+/// A temporary directory tree to run walkers against
+pub struct Fixture {
+    dir: TempDir,
+}
+
+impl Fixture {
+    /// Create a fixture from a list of paths
+    ///
+    /// Paths ending with a `/` are created as directories, others as empty files
+    pub fn new(entries: &[&str]) -> Self {
+        let dir = tempfile::tempdir().unwrap();
+
+        for entry in entries {
+            let path = dir.path().join(entry);
+
+            if entry.ends_with('/') {
+                fs::create_dir_all(&path).unwrap();
+            } else {
+                fs::create_dir_all(path.parent().unwrap()).unwrap();
+                fs::write(&path, "").unwrap();
+            }
+        }
+
+        Self { dir }
+    }
+
+    /// Get the fixture's root directory
+    pub fn path(&self) -> &Path {
+        self.dir.path()
+    }
+
+    /// Get the absolute path to an item of the fixture
+    pub fn join(&self, path: &str) -> PathBuf {
+        self.dir.path().join(path)
+    }
+}
+
+/// Collect all successful results of a walker, as `/`-separated strings
+///
+/// Panics if the walker yields an error
+pub fn collect_sorted(walker: Walker) -> BTreeSet<String> {
+    walker.map(|path| to_slash_string(&path.unwrap())).collect()
+}
+
+/// Render a path with `/` separators
+pub fn to_slash_string(path: &Path) -> String {
+    path.components()
+        .map(|component| component.as_os_str().to_string_lossy().into_owned())
+        .collect::<Vec<_>>()
+        .join("/")
+}
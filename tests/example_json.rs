@@ -0,0 +1,113 @@
+mod common;
+
+#[allow(dead_code)]
+#[path = "../examples/walker.rs"]
+mod walker_example;
+
+use serde_json::Value;
+
+use self::common::Fixture;
+
+/// Run the walker example and parse each line of its output as JSON
+fn run_json(args: &[&str]) -> Vec<Value> {
+    let mut out = vec![];
+
+    walker_example::run(args.iter().map(|arg| arg.to_string()), &mut out).unwrap();
+
+    String::from_utf8(out)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect()
+}
+
+#[test]
+fn json_output() {
+    let fixture = Fixture::new(&["a/b/c.txt", "a/d/", "e.txt"]);
+
+    #[cfg(unix)]
+    std::os::unix::fs::symlink(fixture.join("e.txt"), fixture.join("a/link")).unwrap();
+
+    #[cfg(windows)]
+    std::os::windows::fs::symlink_file(fixture.join("e.txt"), fixture.join("a/link")).unwrap();
+
+    let mut records = run_json(&["--json", "**", fixture.path().to_str().unwrap()]);
+
+    records.sort_by_key(|record| record["path"].as_str().unwrap().replace('\\', "/"));
+
+    let records = records
+        .into_iter()
+        .map(|record| {
+            let object = record.as_object().unwrap();
+            assert_eq!(object.len(), 3, "Unexpected record: {record}");
+
+            (
+                object["path"].as_str().unwrap().replace('\\', "/"),
+                object["type"].as_str().unwrap().to_owned(),
+                object["depth"].as_u64().unwrap(),
+            )
+        })
+        .collect::<Vec<_>>();
+
+    let expected = [
+        ("a", "dir", 1),
+        ("a/b", "dir", 2),
+        ("a/b/c.txt", "file", 3),
+        ("a/d", "dir", 2),
+        ("a/link", "symlink", 2),
+        ("e.txt", "file", 1),
+    ]
+    .map(|(path, ty, depth)| (path.to_owned(), ty.to_owned(), depth));
+
+    assert_eq!(records, expected);
+}
+
+#[test]
+fn json_errors() {
+    let fixture = Fixture::new(&["a/", "b/", "c/"]);
+
+    // Make the walk fail on an unreadable directory
+    #[cfg(unix)]
+    {
+        use std::{fs, os::unix::fs::PermissionsExt, path::Path};
+
+        fs::set_permissions(fixture.join("b"), fs::Permissions::from_mode(0o000)).unwrap();
+
+        // Permissions are not enforced for privileged users
+        let readable = fs::read_dir(fixture.join("b")).is_ok();
+
+        let records = run_json(&[
+            "--json",
+            "--json-errors",
+            "*/*",
+            fixture.path().to_str().unwrap(),
+        ]);
+
+        fs::set_permissions(fixture.join("b"), fs::Permissions::from_mode(0o755)).unwrap();
+
+        if !readable {
+            assert_eq!(records.len(), 1, "Unexpected records: {records:?}");
+
+            let object = records[0].as_object().unwrap();
+
+            assert!(object["error"].is_string());
+            assert_eq!(
+                object["path"].as_str().map(Path::new),
+                Some(fixture.join("b").as_path())
+            );
+        } else {
+            assert!(records.is_empty(), "Unexpected records: {records:?}");
+        }
+    }
+
+    // Without errors, only results are emitted
+    let records = run_json(&[
+        "--json",
+        "--json-errors",
+        "*",
+        fixture.path().to_str().unwrap(),
+    ]);
+
+    assert_eq!(records.len(), 3);
+    assert!(records.iter().all(|record| record.get("error").is_none()));
+}
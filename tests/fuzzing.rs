@@ -0,0 +1,54 @@
+use arbitrary::{Arbitrary, Unstructured};
+use globby::{
+    Pattern, PatternMatchResult,
+    fuzzing::{RawPathInput, RawPatternInput},
+    normalize_path_with,
+};
+
+/// Generate pseudo-random bytes to build inputs from
+fn random_bytes(seed: u64, len: usize) -> Vec<u8> {
+    let mut state = seed.wrapping_mul(0x9E37_79B9_7F4A_7C15) | 1;
+
+    (0..len)
+        .map(|_| {
+            // xorshift64
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state as u8
+        })
+        .collect()
+}
+
+#[test]
+fn generated_patterns_are_valid() {
+    for seed in 0..2000 {
+        let bytes = random_bytes(seed, 256);
+        let mut u = Unstructured::new(&bytes);
+
+        let RawPatternInput(pattern) = RawPatternInput::arbitrary(&mut u).unwrap();
+        let RawPathInput(path) = RawPathInput::arbitrary(&mut u).unwrap();
+
+        let pattern = Pattern::new(&pattern)
+            .unwrap_or_else(|err| panic!("Generated pattern '{pattern}' is invalid: {err:?}"));
+
+        if let Some(simplified) = pattern.simplify() {
+            assert_eq!(
+                simplified.is_match(&path),
+                pattern.is_match(&path),
+                "Simplifying '{pattern}' into '{simplified}' changed the result for {path:?}"
+            );
+        }
+
+        // Starved patterns need more components than the path has (see the `match_against` fuzz target)
+        if let PatternMatchResult::Starved = pattern.match_against(&path) {
+            let depth = normalize_path_with(&path, pattern.normalize_opts())
+                .unwrap()
+                .components()
+                .len();
+
+            let (_, max) = pattern.depth_bounds();
+            assert!(max.is_none_or(|max| depth < max));
+        }
+    }
+}
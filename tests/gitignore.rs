@@ -0,0 +1,161 @@
+use std::path::Path;
+
+use globby::{GitignoreRule, Pattern, PatternOpts};
+
+fn rule(rule: &str) -> GitignoreRule {
+    Pattern::new_gitignore(rule).unwrap().unwrap()
+}
+
+/// Check which paths a rule applies to, as `(path, is_dir)` pairs
+fn assert_rule(rule_str: &str, should_match: &[(&str, bool)], should_not_match: &[(&str, bool)]) {
+    let rule = rule(rule_str);
+
+    for (path, is_dir) in should_match {
+        assert!(
+            rule.pattern().is_match_dir_aware(Path::new(path), *is_dir),
+            "Rule '{rule_str}' did not match path '{path}' (is_dir: {is_dir})"
+        );
+    }
+
+    for (path, is_dir) in should_not_match {
+        assert!(
+            !rule.pattern().is_match_dir_aware(Path::new(path), *is_dir),
+            "Rule '{rule_str}' unexpectedly matched path '{path}' (is_dir: {is_dir})"
+        );
+    }
+}
+
+#[test]
+fn documented_examples() {
+    // "The pattern hello.* matches any file or directory whose name begins with hello."
+    assert_rule(
+        "hello.*",
+        &[
+            ("hello.txt", false),
+            ("a/b/hello.c", false),
+            ("hello.d", true),
+        ],
+        &[("ahello.txt", false), ("hello", false)],
+    );
+
+    // "The pattern doc/frotz/ matches doc/frotz directory, but not a/doc/frotz directory"
+    assert_rule(
+        "doc/frotz/",
+        &[("doc/frotz", true)],
+        &[("a/doc/frotz", true), ("doc/frotz", false)],
+    );
+
+    // "frotz/ matches frotz and a/frotz that is a directory"
+    assert_rule(
+        "frotz/",
+        &[("frotz", true), ("a/frotz", true)],
+        &[("frotz", false), ("a/frotz", false)],
+    );
+
+    // "The pattern foo/*, matches foo/test.json (a regular file), foo/bar (a directory),
+    // but it does not match foo/bar/hello.c (a regular file)"
+    assert_rule(
+        "foo/*",
+        &[("foo/test.json", false), ("foo/bar", true)],
+        &[("foo/bar/hello.c", false), ("a/foo/test.json", false)],
+    );
+
+    // "A leading slash matches the beginning of the pathname. For example, /*.c matches cat-file.c but not mozilla-sha1/sha1.c"
+    assert_rule(
+        "/*.c",
+        &[("cat-file.c", false)],
+        &[("mozilla-sha1/sha1.c", false)],
+    );
+
+    // "**/foo matches file or directory foo anywhere"
+    assert_rule(
+        "**/foo",
+        &[("foo", false), ("a/foo", true), ("a/b/foo", false)],
+        &[("foo/a", false), ("afoo", false)],
+    );
+
+    // "**/foo/bar matches file or directory bar anywhere that is directly under directory foo"
+    assert_rule(
+        "**/foo/bar",
+        &[("foo/bar", false), ("a/foo/bar", true)],
+        &[("foo/a/bar", false), ("bar", false)],
+    );
+
+    // "abc/** matches all files inside directory abc"
+    assert_rule(
+        "abc/**",
+        &[("abc/a", false), ("abc/a/b/c", false)],
+        &[("abc", true), ("a/abc/b", false)],
+    );
+
+    // "a/**/b matches a/b, a/x/b, a/x/y/b and so on"
+    assert_rule(
+        "a/**/b",
+        &[("a/b", false), ("a/x/b", false), ("a/x/y/b", false)],
+        &[("b", false), ("x/a/b", false)],
+    );
+}
+
+#[test]
+fn anchoring() {
+    // Rules without separators match at any depth
+    assert_rule(
+        "bar",
+        &[("bar", false), ("a/bar", false)],
+        &[("a/bar/c", false)],
+    );
+
+    // Leading and middle separators anchor the rule
+    assert_rule("/bar", &[("bar", false)], &[("a/bar", false)]);
+    assert_rule("a/bar", &[("a/bar", false)], &[("b/a/bar", false)]);
+}
+
+#[test]
+fn negation_and_escaping() {
+    let negated = rule("!important.txt");
+    assert!(negated.is_negated());
+    assert!(negated.pattern().is_match(Path::new("a/important.txt")));
+
+    assert!(!rule("important.txt").is_negated());
+
+    // Escaped leading characters are literal
+    let escaped = rule("\\!important!.txt");
+    assert!(!escaped.is_negated());
+    assert!(escaped.pattern().is_match(Path::new("!important!.txt")));
+
+    assert!(rule("\\#file").pattern().is_match(Path::new("#file")));
+    assert!(rule("what\\?").pattern().is_match(Path::new("what?")));
+    assert!(!rule("what\\?").pattern().is_match(Path::new("whatt")));
+
+    // Blank lines and comments don't contain rules
+    assert!(Pattern::new_gitignore("").unwrap().is_none());
+    assert!(Pattern::new_gitignore("   ").unwrap().is_none());
+    assert!(Pattern::new_gitignore("# comment").unwrap().is_none());
+}
+
+#[test]
+fn gitignore_specific_syntax() {
+    // Trailing spaces are ignored unless escaped
+    assert_rule("foo  ", &[("foo", false)], &[("foo  ", false)]);
+    assert_rule("foo\\ ", &[("foo ", false)], &[("foo", false)]);
+
+    // Both negated brackets syntaxes are supported
+    assert_rule("[^a]*", &[("bc", false)], &[("abc", false)]);
+    assert_rule("[!a]*", &[("bc", false)], &[("abc", false)]);
+
+    // Groups aren't supported
+    assert_rule("{a|b}", &[("{a|b}", false)], &[("a", false)]);
+
+    // `**` acts like `*` when it's not a whole component
+    assert_rule("a**b", &[("ab", false), ("axxb", false)], &[("a/b", false)]);
+}
+
+#[test]
+fn gitignore_with_opts() {
+    let rule =
+        Pattern::new_gitignore_with_opts("*.LOG", PatternOpts::new().with_case_insensitive(true))
+            .unwrap()
+            .unwrap();
+
+    assert!(rule.pattern().is_match(Path::new("a/debug.log")));
+}
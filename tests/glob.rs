@@ -0,0 +1,42 @@
+//! This test changes the process' current directory, so it must remain the only one in this file
+
+#![cfg(unix)]
+
+use std::fs;
+
+use globby::{GlobError, glob};
+
+#[test]
+fn globbing_without_current_dir() {
+    let dir = tempfile::tempdir().unwrap();
+    let root = fs::canonicalize(dir.path()).unwrap();
+
+    fs::write(root.join("a.txt"), "").unwrap();
+    fs::create_dir(root.join("cwd")).unwrap();
+
+    std::env::set_current_dir(root.join("cwd")).unwrap();
+    fs::remove_dir(root.join("cwd")).unwrap();
+
+    assert!(std::env::current_dir().is_err());
+
+    // Relative patterns need the current directory
+    assert!(matches!(
+        glob("*.txt"),
+        Err(GlobError::FailedToGetCurrentDir(_))
+    ));
+
+    // Invalid patterns are reported first
+    assert!(matches!(glob("[a"), Err(GlobError::InvalidPattern(_))));
+    assert!(matches!(
+        glob(&format!("{}/[a", root.display())),
+        Err(GlobError::InvalidPattern(_))
+    ));
+
+    // Absolute patterns don't need it
+    let results = glob(&format!("{}/*.txt", root.display()))
+        .unwrap()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+
+    assert_eq!(results, [root.join("a.txt")]);
+}
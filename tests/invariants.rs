@@ -0,0 +1,266 @@
+mod common;
+
+use std::{fs, path::Path};
+
+use globby::{
+    Comparison, Pattern, PatternOpts,
+    check::{check_common_root_dir, check_normalization, check_walker_agreement},
+};
+use proptest::prelude::*;
+
+use self::common::Fixture;
+
+/// Generate a path component from a small alphabet, so generated patterns and trees overlap
+fn name() -> impl Strategy<Value = &'static str> {
+    prop::sample::select(&["a", "b", "ab", "a.txt", "b.rs", ".h"][..])
+}
+
+/// Generate a relative path made of 1 to 3 components
+fn relative_path() -> impl Strategy<Value = String> {
+    prop::collection::vec(name(), 1..=3).prop_map(|components| components.join("/"))
+}
+
+/// Generate a pattern from the supported grammar
+fn pattern() -> impl Strategy<Value = String> {
+    let component = prop::sample::select(
+        &[
+            "a",
+            "b",
+            ".h",
+            "*",
+            "?",
+            "*.txt",
+            "*.rs",
+            "[ab]*",
+            "[!a]*",
+            "{a|b}*",
+            "a{b|.txt}",
+            "[[:alpha:]]",
+            "**",
+        ][..],
+    );
+
+    // A trailing separator restricts matches to directories
+    (prop::collection::vec(component, 1..=4), any::<bool>()).prop_map(|(components, dir_only)| {
+        let pattern = components.join("/");
+
+        if dir_only { pattern + "/" } else { pattern }
+    })
+}
+
+/// Generate a pattern containing redundant constructs (see [`Pattern::simplify`])
+fn redundant_pattern() -> impl Strategy<Value = String> {
+    let component = prop::sample::select(
+        &[
+            "a",
+            ".",
+            "",
+            "*",
+            "**",
+            "{a|a}",
+            "{a|b|a}*",
+            "{*|*}.txt",
+            "{a|{b|b}}",
+            "[ab]{.txt|.txt}",
+            "{?|?}",
+        ][..],
+    );
+
+    prop::collection::vec(component, 1..=5).prop_map(|components| components.join("/"))
+}
+
+/// Generate an arbitrary path string, including separators, prefixes and dot components
+fn raw_path() -> impl Strategy<Value = String> {
+    prop::collection::vec(
+        prop::sample::select(&["a", "/", "\\", ".", "..", "C:", "?", "é"][..]),
+        0..12,
+    )
+    .prop_map(|parts| parts.concat())
+}
+
+/// Generate a path component made of special characters, which must be escaped to be matched literally
+fn special_component() -> impl Strategy<Value = String> {
+    prop::collection::vec(
+        prop::sample::select(&["a", "*", "?", "[", "]", "{", "}", "|", ":", "!", "\\", " "][..]),
+        1..8,
+    )
+    .prop_map(|parts| parts.concat())
+    // Paths starting with e.g. `a:` have a Windows drive prefix
+    .prop_filter("drive prefix", |component| !component.starts_with("a:"))
+}
+
+/// Create a tree from the provided paths, skipping conflicting entries (e.g. a file with children)
+fn create_tree(paths: &[String]) -> Fixture {
+    let fixture = Fixture::new(&[]);
+
+    for path in paths {
+        let path = fixture.join(path);
+
+        if fs::create_dir_all(path.parent().unwrap()).is_ok() && !path.exists() {
+            fs::write(&path, "").unwrap();
+        }
+    }
+
+    fixture
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig {
+        cases: 64,
+        ..ProptestConfig::default()
+    })]
+
+    #[test]
+    fn normalization_is_idempotent(path in raw_path()) {
+        check_normalization(Path::new(&path)).unwrap();
+    }
+
+    #[test]
+    fn matches_are_under_common_root(pattern in pattern(), path in relative_path()) {
+        let pattern = Pattern::new(&pattern).unwrap();
+        check_common_root_dir(&pattern, Path::new(&path)).unwrap();
+    }
+
+    #[test]
+    fn matches_are_within_depth_bounds(pattern in pattern(), path in relative_path()) {
+        let pattern = Pattern::new(&pattern).unwrap();
+        let depth = path.split('/').count();
+
+        if pattern.is_match(Path::new(&path)) {
+            let (min, max) = pattern.depth_bounds();
+            prop_assert!(depth >= min && max.is_none_or(|max| depth <= max));
+        }
+    }
+
+    #[test]
+    fn matches_have_literal_affixes(pattern in pattern(), path in relative_path()) {
+        let pattern = Pattern::new(&pattern).unwrap();
+
+        if pattern.is_match_str(&path) {
+            prop_assert!(path.starts_with(pattern.literal_prefix()));
+            prop_assert!(path.ends_with(pattern.literal_suffix().unwrap_or_default()));
+        }
+    }
+
+    #[test]
+    fn simplified_patterns_agree_with_matching(
+        pattern in redundant_pattern(),
+        path in relative_path(),
+        match_basename in any::<bool>()
+    ) {
+        let opts = PatternOpts::new().with_match_basename(match_basename);
+        let pattern = Pattern::new_with_opts(&pattern, opts).unwrap();
+
+        if let Some(simplified) = pattern.simplify() {
+            prop_assert_eq!(
+                simplified.is_match(Path::new(&path)),
+                pattern.is_match(Path::new(&path)),
+                "simplified pattern: {}", simplified
+            );
+        }
+    }
+
+    #[test]
+    fn subset_comparisons_agree_with_matching(
+        a in pattern(),
+        b in pattern(),
+        path in relative_path(),
+        is_dir in any::<bool>(),
+        require_literal_leading_dot in any::<bool>()
+    ) {
+        let a = Pattern::new(&a).unwrap();
+        let opts = PatternOpts::new().with_require_literal_leading_dot(require_literal_leading_dot);
+        let b = Pattern::new_with_opts(&b, opts).unwrap();
+
+        if a.matches_subset_of(&b) == Comparison::Yes && a.is_match_dir_aware(Path::new(&path), is_dir) {
+            prop_assert!(b.is_match_dir_aware(Path::new(&path), is_dir), "pattern: {}", b);
+        }
+    }
+
+    #[test]
+    fn captures_agree_with_matching(
+        pattern in pattern(),
+        path in relative_path(),
+        require_literal_leading_dot in any::<bool>()
+    ) {
+        let opts = PatternOpts::new().with_require_literal_leading_dot(require_literal_leading_dot);
+        let pattern = Pattern::new_with_opts(&pattern, opts).unwrap();
+        let path = Path::new(&path);
+
+        prop_assert_eq!(pattern.match_captures(path).is_some(), pattern.is_match(path));
+    }
+
+    #[test]
+    fn exported_regexes_agree_with_matching(
+        pattern in pattern(),
+        path in relative_path(),
+        require_literal_leading_dot in any::<bool>()
+    ) {
+        let opts = PatternOpts::new().with_require_literal_leading_dot(require_literal_leading_dot);
+        let pattern = Pattern::new_with_opts(&pattern, opts).unwrap();
+        let regex = regex::Regex::new(&pattern.to_regex_string()).unwrap();
+
+        prop_assert_eq!(regex.is_match(&path), pattern.is_match(Path::new(&path)));
+    }
+
+    #[test]
+    fn incremental_matching_agrees_with_matching(
+        pattern in pattern(),
+        path in relative_path(),
+        require_literal_leading_dot in any::<bool>(),
+        star_matches_separators in any::<bool>()
+    ) {
+        let opts = PatternOpts::new()
+            .with_require_literal_leading_dot(require_literal_leading_dot)
+            .with_star_matches_separators(star_matches_separators);
+        let pattern = Pattern::new_with_opts(&pattern, opts).unwrap();
+        let mut matcher = pattern.matcher();
+
+        let mut prefix = std::path::PathBuf::new();
+
+        for component in path.split('/') {
+            prefix.push(component);
+
+            let result = matcher.push(component.as_ref());
+            prop_assert_eq!(result, matcher.result());
+            prop_assert_eq!(result.is_match(), pattern.is_match(&prefix));
+            prop_assert_eq!(result.may_match_descendants(), pattern.can_match_descendants(&prefix));
+        }
+    }
+
+    #[test]
+    fn escaped_strings_match_literally(
+        components in prop::collection::vec(special_component(), 1..=3)
+    ) {
+        let str = components.join("/");
+        let opts = PatternOpts::new().with_backslash_escapes(true).with_slash_only(true);
+        let pattern = Pattern::new_with_opts(&Pattern::escape(&str), opts).unwrap();
+
+        prop_assert!(pattern.is_match(Path::new(&str)));
+        let longer = format!("{str}a");
+        prop_assert!(!pattern.is_match(Path::new(&longer)));
+    }
+
+    #[test]
+    fn escaped_strings_round_trip(component in special_component()) {
+        // Backslashes are separators without backslash escapes
+        let str = component.replace('\\', "");
+        prop_assume!(!str.is_empty() && !str.starts_with("a:"));
+
+        let pattern = Pattern::new(&Pattern::escape(&str)).unwrap();
+
+        prop_assert!(pattern.is_match(Path::new(&str)));
+        prop_assert!(!pattern.has_wildcard());
+    }
+
+    #[test]
+    fn walker_agrees_with_matching(
+        pattern in pattern(),
+        paths in prop::collection::vec(relative_path(), 1..12)
+    ) {
+        let fixture = create_tree(&paths);
+        let pattern = Pattern::new(&pattern).unwrap();
+
+        check_walker_agreement(&pattern, fixture.path()).unwrap();
+    }
+}
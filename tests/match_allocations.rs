@@ -0,0 +1,74 @@
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    cell::Cell,
+    path::Path,
+};
+
+use globby::{MatchContext, Pattern};
+
+/// Allocator counting allocations performed by the current thread
+struct CountingAllocator;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.with(|count| count.set(count.get() + 1));
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+/// Count the allocations performed by a function
+fn count_allocations(func: impl FnOnce()) -> usize {
+    let before = ALLOCATIONS.with(Cell::get);
+    func();
+    ALLOCATIONS.with(Cell::get) - before
+}
+
+#[test]
+fn reusing_match_context() {
+    let pattern = Pattern::new("src/**/{main|lib}.rs").unwrap();
+
+    let paths = [
+        "src/main.rs",
+        "src/a/b/c/lib.rs",
+        "src/a/b/c/d/e/f/g/h/i/j/mod.rs",
+        "other/main.rs",
+    ]
+    .map(Path::new);
+
+    let mut ctx = MatchContext::new();
+
+    // Warm up the context's buffers
+    for path in paths {
+        pattern.is_match_with_ctx(path, &mut ctx);
+    }
+
+    let with_ctx = count_allocations(|| {
+        for _ in 0..100 {
+            for path in paths {
+                pattern.is_match_with_ctx(path, &mut ctx);
+            }
+        }
+    });
+
+    let without_ctx = count_allocations(|| {
+        for _ in 0..100 {
+            for path in paths {
+                pattern.is_match(path);
+            }
+        }
+    });
+
+    assert_eq!(with_ctx, 0);
+    assert!(without_ctx >= 400, "Only {without_ctx} allocations");
+}
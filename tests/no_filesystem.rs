@@ -0,0 +1,94 @@
+//! Pattern building and matching must never access the filesystem
+//!
+//! This is enforced structurally: all filesystem-dependent code lives in the walker module,
+//! and the modules involved in matching must not use any filesystem API.
+
+use std::path::Path;
+
+use globby::{MatchContext, Pattern, PatternOpts};
+
+/// Sources of all modules involved in building and matching patterns
+const MATCHING_MODULES: &[(&str, &str)] = &[
+    ("compiler.rs", include_str!("../src/compiler.rs")),
+    ("gitignore.rs", include_str!("../src/gitignore.rs")),
+    ("opaque_os_str.rs", include_str!("../src/opaque_os_str.rs")),
+    ("parser.rs", include_str!("../src/parser.rs")),
+    ("paths.rs", include_str!("../src/paths.rs")),
+    ("pattern.rs", include_str!("../src/pattern.rs")),
+    ("pattern_cache.rs", include_str!("../src/pattern_cache.rs")),
+    ("pattern_set.rs", include_str!("../src/pattern_set.rs")),
+    ("simplify.rs", include_str!("../src/simplify.rs")),
+    ("syntax.rs", include_str!("../src/syntax.rs")),
+];
+
+/// APIs that may access the filesystem or depend on the process' environment
+const FORBIDDEN_APIS: &[&str] = &[
+    "fs::",
+    "File::",
+    "canonicalize",
+    "read_dir",
+    "read_link",
+    "metadata",
+    "current_dir",
+    "env::",
+    ".exists()",
+    ".try_exists(",
+    ".is_dir()",
+    ".is_file()",
+    ".is_symlink()",
+    "Walker",
+];
+
+#[test]
+fn matching_modules_dont_use_the_filesystem() {
+    for (module, source) in MATCHING_MODULES {
+        for (i, line) in source.lines().enumerate() {
+            let code = line.trim_start();
+
+            // Documentation and comments may refer to walking
+            if code.starts_with("//") {
+                continue;
+            }
+
+            for api in FORBIDDEN_APIS {
+                assert!(
+                    !code.contains(api),
+                    "Module '{module}' uses '{api}' at line {}: {line}",
+                    i + 1
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn matching_nonexistent_paths() {
+    let cases = [
+        ("/**/*.txt", "/definitely/missing/dir/file.txt", true),
+        ("/**/*.txt", "missing/file.txt", false),
+        ("C:/**", "C:/missing/dir", true),
+        ("C:/**", "D:/missing/dir", false),
+        (r"\\?\C:\a\*", r"C:\a\missing", true),
+        ("../../a/*", "../../a/missing", true),
+        ("../a", "../../a", false),
+        ("missing/{a|b}/**/[!x]*", "missing/b/c/d/y", true),
+        ("*", ".", false),
+    ];
+
+    let mut ctx = MatchContext::new();
+
+    for (pattern, path, expected) in cases {
+        for opts in [
+            PatternOpts::default(),
+            PatternOpts::new().with_case_insensitive(true),
+        ] {
+            let pattern = Pattern::new_with_opts(pattern, opts).unwrap();
+
+            assert_eq!(pattern.is_match(Path::new(path)), expected);
+            assert_eq!(
+                pattern.is_match_with_ctx(Path::new(path), &mut ctx),
+                expected
+            );
+        }
+    }
+}
@@ -0,0 +1,138 @@
+use std::{collections::HashMap, path::Path, sync::Arc, thread};
+
+use globby::{
+    DEFAULT_PATTERN_CACHE_CAPACITY, Pattern, PatternOpts, clear_pattern_cache,
+    set_pattern_cache_capacity,
+};
+
+// Tests share the process-wide cache, so they are all run from a single one
+#[test]
+fn caching_patterns() {
+    fetching_cached_patterns();
+    evicting_least_recently_used_patterns();
+    evicting_in_recency_order();
+    sharing_cache_between_threads();
+}
+
+fn fetching_cached_patterns() {
+    clear_pattern_cache();
+
+    let opts = PatternOpts::new();
+    let pattern = Pattern::cached("src/**/*.rs", opts).unwrap();
+
+    assert!(Arc::ptr_eq(
+        &pattern,
+        &Pattern::cached("src/**/*.rs", opts).unwrap()
+    ));
+    assert_eq!(*pattern, Pattern::new("src/**/*.rs").unwrap());
+    assert!(pattern.is_match(Path::new("src/main.rs")));
+
+    // Options are part of the key
+    let caseless = Pattern::cached("src/**/*.rs", opts.with_case_insensitive(true)).unwrap();
+    assert!(!Arc::ptr_eq(&pattern, &caseless));
+    assert!(caseless.is_match(Path::new("SRC/main.RS")));
+
+    // Errors aren't cached
+    assert!(Pattern::cached("src/[", opts).is_err());
+    assert!(Pattern::cached("src/[", opts).is_err());
+
+    // Already fetched patterns outlive the cache
+    clear_pattern_cache();
+    assert!(!Arc::ptr_eq(
+        &pattern,
+        &Pattern::cached("src/**/*.rs", opts).unwrap()
+    ));
+    assert!(pattern.is_match(Path::new("src/lib.rs")));
+}
+
+fn evicting_least_recently_used_patterns() {
+    clear_pattern_cache();
+    set_pattern_cache_capacity(2);
+
+    let opts = PatternOpts::new();
+    let a = Pattern::cached("a", opts).unwrap();
+    let b = Pattern::cached("b", opts).unwrap();
+
+    // Use `a` again, so `b` is evicted first
+    Pattern::cached("a", opts).unwrap();
+    Pattern::cached("c", opts).unwrap();
+
+    assert!(Arc::ptr_eq(&a, &Pattern::cached("a", opts).unwrap()));
+    assert!(!Arc::ptr_eq(&b, &Pattern::cached("b", opts).unwrap()));
+
+    // Shrinking the cache evicts patterns immediately
+    set_pattern_cache_capacity(1);
+    let b = Pattern::cached("b", opts).unwrap();
+    assert!(!Arc::ptr_eq(&a, &Pattern::cached("a", opts).unwrap()));
+    assert!(!Arc::ptr_eq(&b, &Pattern::cached("b", opts).unwrap()));
+
+    // A capacity of 0 disables caching
+    set_pattern_cache_capacity(0);
+    let a = Pattern::cached("a", opts).unwrap();
+    assert!(!Arc::ptr_eq(&a, &Pattern::cached("a", opts).unwrap()));
+
+    set_pattern_cache_capacity(DEFAULT_PATTERN_CACHE_CAPACITY);
+}
+
+fn evicting_in_recency_order() {
+    clear_pattern_cache();
+    set_pattern_cache_capacity(3);
+
+    let opts = PatternOpts::new();
+    let sources = ["a", "b", "c", "d", "e"];
+
+    // Compare with a list of the most recently used sources
+    let mut recent = Vec::<&str>::new();
+    let mut fetched = HashMap::<&str, Arc<Pattern>>::new();
+
+    for i in 0..200 {
+        let source = sources[(i * 7 + i / 3) % sources.len()];
+        let pattern = Pattern::cached(source, opts).unwrap();
+
+        if let Some(previous) = fetched.get(source) {
+            assert_eq!(
+                Arc::ptr_eq(previous, &pattern),
+                recent.contains(&source),
+                "Unexpected cache state when fetching '{source}' after {recent:?}"
+            );
+        }
+
+        recent.retain(|recent| *recent != source);
+        recent.insert(0, source);
+        recent.truncate(3);
+
+        fetched.insert(source, pattern);
+    }
+
+    set_pattern_cache_capacity(DEFAULT_PATTERN_CACHE_CAPACITY);
+}
+
+fn sharing_cache_between_threads() {
+    clear_pattern_cache();
+    set_pattern_cache_capacity(8);
+
+    let sources = (0..16)
+        .map(|i| format!("dir_{i}/**/*.{{rs|toml}}"))
+        .collect::<Vec<_>>();
+
+    thread::scope(|scope| {
+        for thread in 0..8 {
+            let sources = &sources;
+
+            scope.spawn(move || {
+                for i in 0..500 {
+                    let source = &sources[(thread + i) % sources.len()];
+                    let pattern = Pattern::cached(source, PatternOpts::new()).unwrap();
+
+                    assert_eq!(pattern.as_str(), source);
+                    assert!(pattern.is_match(Path::new(&format!(
+                        "dir_{}/a/b.rs",
+                        (thread + i) % sources.len()
+                    ))));
+                }
+            });
+        }
+    });
+
+    set_pattern_cache_capacity(DEFAULT_PATTERN_CACHE_CAPACITY);
+}
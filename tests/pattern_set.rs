@@ -0,0 +1,160 @@
+use std::path::Path;
+
+use globby::{Pattern, PatternOpts, PatternSet, Verdict};
+
+fn set(patterns: &[&str]) -> PatternSet {
+    patterns
+        .iter()
+        .map(|pattern| Pattern::new(pattern).unwrap())
+        .collect()
+}
+
+#[test]
+fn matching_overlapping_patterns() {
+    let set = set(&["src/**/*.rs", "**/*.rs", "src/*", "tests/**", "*.toml"]);
+
+    assert_eq!(set.len(), 5);
+    assert!(!set.is_empty());
+
+    assert_eq!(set.matches(Path::new("src/lib.rs")), vec![0, 1, 2]);
+    assert_eq!(set.matches(Path::new("src/a/b.rs")), vec![0, 1]);
+    assert_eq!(set.matches(Path::new("src/README.md")), vec![2]);
+    assert_eq!(set.matches(Path::new("tests/a.rs")), vec![1, 3]);
+    assert_eq!(set.matches(Path::new("Cargo.toml")), vec![4]);
+    assert_eq!(set.matches(Path::new("benches/a.txt")), Vec::<usize>::new());
+
+    assert!(set.is_match(Path::new("src/lib.rs")));
+    assert!(set.is_match(Path::new("benches/a.rs")));
+    assert!(!set.is_match(Path::new("benches/a.txt")));
+}
+
+#[test]
+fn matching_mixed_patterns() {
+    let set = set(&["/etc/*.conf", "etc/*.conf", "**/hosts", "/*/hosts"]);
+
+    assert_eq!(set.matches(Path::new("/etc/a.conf")), vec![0]);
+    assert_eq!(set.matches(Path::new("etc/a.conf")), vec![1]);
+    assert_eq!(set.matches(Path::new("/etc/hosts")), vec![3]);
+    assert_eq!(set.matches(Path::new("etc/hosts")), vec![2]);
+
+    // Paths are normalized before being looked up
+    assert_eq!(set.matches(Path::new("./etc//a.conf")), vec![1]);
+}
+
+#[test]
+fn matching_with_different_options() {
+    let set = PatternSet::new([
+        Pattern::new("src/*.rs").unwrap(),
+        Pattern::new_with_opts("SRC/*.rs", PatternOpts::new().with_case_insensitive(true)).unwrap(),
+        Pattern::new_with_opts("src/*.md", PatternOpts::new().with_slash_only(true)).unwrap(),
+    ]);
+
+    assert_eq!(set.matches(Path::new("src/a.rs")), vec![0, 1]);
+    assert_eq!(set.matches(Path::new("Src/a.rs")), vec![1]);
+    assert_eq!(set.matches(Path::new("src/a.md")), vec![2]);
+
+    assert!(PatternSet::new([]).is_empty());
+    assert!(!PatternSet::new([]).is_match(Path::new("a")));
+}
+
+#[test]
+fn computing_common_root_dir() {
+    assert_eq!(
+        set(&["src/a/*.rs", "src/b/**"]).common_root_dir(),
+        Some(Path::new("src"))
+    );
+    assert_eq!(
+        set(&["src/a/*.rs", "src/a/b/*"]).common_root_dir(),
+        Some(Path::new("src/a"))
+    );
+    assert_eq!(
+        set(&["src/*.rs", "tests/*.rs"]).common_root_dir(),
+        Some(Path::new(""))
+    );
+    assert_eq!(
+        set(&["/etc/*.conf", "/etc/hosts"]).common_root_dir(),
+        Some(Path::new("/etc"))
+    );
+
+    // Relative and absolute patterns don't share a root
+    assert_eq!(set(&["/etc/*.conf", "etc/*"]).common_root_dir(), None);
+    assert_eq!(set(&[]).common_root_dir(), None);
+}
+
+#[test]
+fn negating_patterns() {
+    let mut set = set(&["**/*.rs"]);
+    set.add_negated(Pattern::new("**/target/**").unwrap());
+
+    assert!(!set.is_negated(0));
+    assert!(set.is_negated(1));
+
+    assert_eq!(set.verdict(Path::new("src/lib.rs")), Verdict::Include);
+    assert_eq!(set.verdict(Path::new("target/a.rs")), Verdict::Exclude);
+    assert_eq!(set.verdict(Path::new("a/target/b/c.rs")), Verdict::Exclude);
+    assert_eq!(set.verdict(Path::new("README.md")), Verdict::None);
+
+    assert!(set.is_match(Path::new("src/lib.rs")));
+    assert!(!set.is_match(Path::new("target/a.rs")));
+
+    // Negated patterns are still reported as matching
+    assert_eq!(set.matches(Path::new("target/a.rs")), vec![0, 1]);
+    assert_eq!(set.deciding_pattern(Path::new("target/a.rs")), Some(1));
+    assert_eq!(set.deciding_pattern(Path::new("README.md")), None);
+
+    // Negated patterns don't include anything
+    assert_eq!(set.common_root_dir(), Some(Path::new("")));
+}
+
+#[test]
+fn building_from_rules() {
+    let set = PatternSet::from_rules(["src/**/*.rs", "!src/generated/**", "src/generated/keep.rs"])
+        .unwrap();
+
+    assert_eq!(set.len(), 3);
+    assert!(set.is_negated(1));
+    assert_eq!(set.patterns()[1].as_str(), "src/generated/**");
+    assert_eq!(set.common_root_dir(), Some(Path::new("src")));
+
+    assert_eq!(set.verdict(Path::new("src/lib.rs")), Verdict::Include);
+    assert_eq!(
+        set.verdict(Path::new("src/generated/a.rs")),
+        Verdict::Exclude
+    );
+    assert_eq!(
+        set.verdict(Path::new("src/generated/keep.rs")),
+        Verdict::Include
+    );
+    assert_eq!(
+        set.deciding_pattern(Path::new("src/generated/keep.rs")),
+        Some(2)
+    );
+
+    let set = PatternSet::from_rules_with_opts(
+        ["*.RS", "!A.rs"],
+        PatternOpts::new().with_case_insensitive(true),
+    )
+    .unwrap();
+
+    assert_eq!(set.verdict(Path::new("b.rs")), Verdict::Include);
+    assert_eq!(set.verdict(Path::new("a.rs")), Verdict::Exclude);
+
+    assert!(PatternSet::from_rules(["*", "!a/[b"]).is_err());
+}
+
+#[test]
+fn reincluding_under_excluded_directories() {
+    // Used as a list of ignored paths, like a `.gitignore` file
+    let set = PatternSet::from_rules(["target/**", "!target/keep.txt"]).unwrap();
+
+    assert_eq!(set.verdict(Path::new("target/a.txt")), Verdict::Include);
+
+    // Unlike with Git, which doesn't look inside of ignored directories, paths are matched
+    // independently of their parents, so a path can be re-included under an excluded directory
+    assert_eq!(set.verdict(Path::new("target/keep.txt")), Verdict::Exclude);
+    assert_eq!(set.deciding_pattern(Path::new("target/keep.txt")), Some(1));
+
+    // Only the last matching pattern counts
+    let set = PatternSet::from_rules(["!target/keep.txt", "target/**"]).unwrap();
+    assert_eq!(set.verdict(Path::new("target/keep.txt")), Verdict::Include);
+}
@@ -1,6 +1,18 @@
-use std::path::Path;
+use std::{
+    collections::HashSet,
+    ffi::OsStr,
+    mem::discriminant,
+    path::{MAIN_SEPARATOR, Path, PathBuf},
+    time::{Duration, Instant},
+};
 
-use globby::{Pattern, PatternOpts};
+use globby::{
+    CaseFolding, Comparison, JoinError, MatchCaptures, MatchContext, MatchFailureReason,
+    MatchOptions, NormalizeOpts, PathPrefix, Pattern, PatternComponent, PatternError,
+    PatternMatchResult, PatternOpts, PatternStats, Portability, TargetHint, TooManyExpansions,
+    UnicodeNormalization, clear_regex_cache, normalize_path, normalize_path_with,
+    syntax::{AstComponent, CharsMatcher, ComponentKind, PatternAst},
+};
 
 #[test]
 fn building_unix_patterns() {
@@ -313,9 +325,7 @@ fn case_sensitivity() {
                 should_match,
                 should_not_match,
             },
-            PatternOpts {
-                case_insensitive: false,
-            },
+            PatternOpts::new().with_case_insensitive(false),
         );
     }
 
@@ -326,9 +336,369 @@ fn case_sensitivity() {
                 should_match: &["hEllo", "hello", "Hello", "hellO", "HELLO"],
                 should_not_match: &[],
             },
-            PatternOpts {
-                case_insensitive: true,
+            PatternOpts::new().with_case_insensitive(true),
+        );
+    }
+}
+
+#[test]
+fn smart_case() {
+    let opts = PatternOpts::new().with_smart_case(true);
+
+    test_pattern_with(
+        PatternTest {
+            pattern_str: "readme*",
+            should_match: &["README.md", "readme.md", "ReadMe"],
+            should_not_match: &["read"],
+        },
+        opts,
+    );
+
+    test_pattern_with(
+        PatternTest {
+            pattern_str: "Readme*",
+            should_match: &["Readme.md", "Readme"],
+            should_not_match: &["readme.md", "README.md"],
+        },
+        opts,
+    );
+
+    test_pattern_with(
+        PatternTest {
+            pattern_str: "src/Main*/{lib|mod}.rs",
+            should_match: &["src/Main/lib.rs", "SRC/Main.d/MOD.RS", "Src/Main/Lib.rs"],
+            should_not_match: &["src/main/lib.rs", "src/MAIN/mod.rs"],
+        },
+        opts,
+    );
+
+    // Uppercase characters in brackets count
+    test_pattern_with(
+        PatternTest {
+            pattern_str: "[A]*",
+            should_match: &["Abc"],
+            should_not_match: &["abc"],
+        },
+        opts,
+    );
+
+    // Smart case takes precedence
+    test_pattern_with(
+        PatternTest {
+            pattern_str: "Readme",
+            should_match: &["Readme"],
+            should_not_match: &["readme"],
+        },
+        opts.with_case_insensitive(true),
+    );
+}
+
+#[test]
+fn case_folding() {
+    let unicode = PatternOpts::new()
+        .with_case_insensitive(true)
+        .with_case_folding(CaseFolding::Unicode);
+
+    let ascii = unicode.with_case_folding(CaseFolding::Ascii);
+
+    test_pattern_with(
+        PatternTest {
+            pattern_str: "straße",
+            should_match: &["straße", "STRASSE", "Strasse", "STRAẞE"],
+            should_not_match: &["strase", "STRASE"],
+        },
+        unicode,
+    );
+
+    test_pattern_with(
+        PatternTest {
+            pattern_str: "café",
+            should_match: &["café", "CAFÉ", "Café"],
+            should_not_match: &["cafe", "CAFE"],
+        },
+        unicode,
+    );
+
+    // Turkic-specific mappings are not used
+    test_pattern_with(
+        PatternTest {
+            pattern_str: "istanbul",
+            should_match: &["ISTANBUL", "Istanbul"],
+            should_not_match: &["İSTANBUL", "ıstanbul"],
+        },
+        unicode,
+    );
+
+    test_pattern_with(
+        PatternTest {
+            pattern_str: "İzmir",
+            should_match: &["İZMIR", "i\u{307}zmir"],
+            should_not_match: &["izmir", "IZMIR", "ızmır"],
+        },
+        unicode,
+    );
+
+    test_pattern_with(
+        PatternTest {
+            pattern_str: "ırmak",
+            should_match: &["ırmak"],
+            should_not_match: &["irmak", "IRMAK"],
+        },
+        unicode,
+    );
+
+    // Components with matchers use simple case folding
+    test_pattern_with(
+        PatternTest {
+            pattern_str: "é*.[ß]",
+            should_match: &["été.ß", "ÉTÉ.ẞ"],
+            should_not_match: &["ete.ß", "été.ss"],
+        },
+        unicode,
+    );
+
+    for pattern_str in ["café", "café*", "[c]afé", "{café|tea}"] {
+        test_pattern_with(
+            PatternTest {
+                pattern_str,
+                should_match: &["café", "CAFé", "Café"],
+                should_not_match: &["CAFÉ", "cafe"],
             },
+            ascii,
+        );
+    }
+
+    test_pattern_with(
+        PatternTest {
+            pattern_str: "straße",
+            should_match: &["STRAßE"],
+            should_not_match: &["STRASSE", "STRAẞE"],
+        },
+        ascii,
+    );
+
+    test_pattern_with(
+        PatternTest {
+            pattern_str: "[[:lowercase:]]?",
+            should_match: &["ab", "AB"],
+            should_not_match: &["éa", "1a"],
+        },
+        ascii,
+    );
+
+    // Case folding is only used when matching case-insensitively
+    test_pattern_with(
+        PatternTest {
+            pattern_str: "straße",
+            should_match: &["straße"],
+            should_not_match: &["STRASSE", "Straße"],
+        },
+        unicode.with_case_insensitive(false),
+    );
+}
+
+#[test]
+fn star_matching_separators() {
+    let legacy = PatternOpts::new().with_star_matches_separators(true);
+
+    // Default mode: `*` stays within a single component
+    test_pattern(PatternTest {
+        pattern_str: "src/*.c",
+        should_match: &["src/main.c"],
+        should_not_match: &["src/a/util.c", "src/a/b/util.c", "src", "main.c"],
+    });
+
+    // Legacy mode: `*` crosses directory boundaries
+    test_pattern_with(
+        PatternTest {
+            pattern_str: "src/*.c",
+            should_match: &["src/main.c", "src/a/util.c", "src/a/b/util.c"],
+            should_not_match: &["src", "main.c", "src/a/util.h", "lib/a.c"],
+        },
+        legacy,
+    );
+
+    // Following components are still matched afterwards
+    test_pattern(PatternTest {
+        pattern_str: "a*/main.rs",
+        should_match: &["app/main.rs"],
+        should_not_match: &["app/src/main.rs", "a/b/c/main.rs"],
+    });
+
+    test_pattern_with(
+        PatternTest {
+            pattern_str: "a*/main.rs",
+            should_match: &["app/main.rs", "app/src/main.rs", "a/b/c/main.rs"],
+            should_not_match: &["app", "app/lib.rs", "b/app/main.rs"],
+        },
+        legacy,
+    );
+
+    // Other matchers never match separators
+    test_pattern_with(
+        PatternTest {
+            pattern_str: "a?b*",
+            should_match: &["axb", "axb/c/d"],
+            should_not_match: &["a/b", "a/b/c"],
+        },
+        legacy,
+    );
+
+    test_pattern_with(
+        PatternTest {
+            pattern_str: "[!x]*.c",
+            should_match: &["a.c", "a/b.c"],
+            should_not_match: &["x/b.c", "a.h"],
+        },
+        legacy,
+    );
+
+    // Stars in groups count too
+    test_pattern_with(
+        PatternTest {
+            pattern_str: "{lib|src*}",
+            should_match: &["lib", "src", "src/a", "srcs/a/b"],
+            should_not_match: &["lib/a"],
+        },
+        legacy,
+    );
+
+    // Descendants of a partially matching path may still match
+    let pattern = Pattern::new_with_opts("src/*.c", legacy).unwrap();
+
+    assert!(matches!(
+        pattern.match_against(Path::new("src/a")),
+        PatternMatchResult::Starved
+    ));
+    assert!(matches!(
+        pattern.match_against(Path::new("lib/a")),
+        PatternMatchResult::NotMatched
+    ));
+}
+
+#[test]
+fn match_target_hints() {
+    for (pattern, hint) in [
+        ("", TargetHint::AnyEntry),
+        ("a", TargetHint::AnyEntry),
+        ("src/*.rs", TargetHint::AnyEntry),
+        ("**/*", TargetHint::AnyEntry),
+        ("/a/{b|c}", TargetHint::AnyEntry),
+        ("**", TargetHint::DirectoriesLikely),
+        ("src/**", TargetHint::DirectoriesLikely),
+        ("../**", TargetHint::DirectoriesLikely),
+        ("..", TargetHint::RequiresDirectory),
+        ("../..", TargetHint::RequiresDirectory),
+    ] {
+        assert_eq!(
+            Pattern::new(pattern).unwrap().match_target_hint(),
+            hint,
+            "Unexpected hint for pattern '{pattern}'"
+        );
+    }
+}
+
+#[test]
+fn shared_match_context() {
+    let patterns = [
+        "**/*.rs",
+        "src/{main|lib}.rs",
+        "/a/**",
+        "../*/b",
+        "[!.]*",
+        "C:/dir/*",
+    ]
+    .map(|pattern| Pattern::new(pattern).unwrap());
+
+    let paths = [
+        "",
+        "main.rs",
+        "src/main.rs",
+        "src//./lib.rs",
+        "/a",
+        "/a/b/c",
+        "\\a\\b",
+        "../x/b",
+        ".hidden",
+        "C:\\dir\\file",
+        "c:/dir/file",
+        "\\\\?\\C:\\dir\\file",
+        "\\\\server\\share",
+    ];
+
+    let mut ctx = MatchContext::new();
+
+    for pattern in &patterns {
+        for path in paths {
+            let path = Path::new(path);
+
+            assert_eq!(
+                pattern.is_match_with_ctx(path, &mut ctx),
+                pattern.is_match(path),
+                "Context changed the result of matching {path:?} against {pattern:?}"
+            );
+        }
+    }
+}
+
+#[test]
+fn portability() {
+    for (pattern, portability) in [
+        (r"C:\**", Portability::WindowsOnly),
+        ("c:/dir/*.txt", Portability::WindowsOnly),
+        (r"\\?\C:\dir", Portability::WindowsOnly),
+        ("/abs/**", Portability::Universal),
+        (r"\abs", Portability::Universal),
+        ("src/**/*.rs", Portability::Universal),
+        ("../a", Portability::Universal),
+    ] {
+        assert_eq!(
+            Pattern::new(pattern).unwrap().portability(),
+            portability,
+            "Unexpected portability for pattern '{pattern}'"
+        );
+    }
+}
+
+#[test]
+fn supported_platforms() {
+    for (portability, supported) in [
+        (Portability::Universal, true),
+        (Portability::WindowsOnly, cfg!(windows)),
+    ] {
+        assert_eq!(
+            portability.supports_current_platform(),
+            supported,
+            "Unexpected support for {portability:?}"
+        );
+    }
+}
+
+#[test]
+fn rejecting_foreign_prefixes() {
+    let opts = PatternOpts::new().with_reject_foreign_prefixes(true);
+
+    assert!(Pattern::new_with_opts("/abs/**", opts).is_ok());
+    assert!(Pattern::new_with_opts("src/**", opts).is_ok());
+
+    // Foreign prefixes are only rejected on demand
+    assert!(Pattern::new(r"C:\**").is_ok());
+
+    let result = Pattern::new_with_opts(r"C:\**", opts);
+
+    if cfg!(windows) {
+        assert!(result.is_ok());
+    } else {
+        let err = result.unwrap_err();
+
+        assert!(matches!(
+            err,
+            PatternError::ForeignPrefix(Portability::WindowsOnly)
+        ));
+
+        assert_eq!(
+            err.to_string(),
+            "pattern can only match paths on Windows, which is not the current platform"
         );
     }
 }
@@ -351,9 +721,7 @@ fn parent_paths() {
                 should_match,
                 should_not_match,
             },
-            PatternOpts {
-                case_insensitive: false,
-            },
+            PatternOpts::new().with_case_insensitive(false),
         );
     }
 
@@ -364,9 +732,28 @@ fn parent_paths() {
                 should_match: &["../hello", "../hello", "../Hello", "../hellO", "../HELLO"],
                 should_not_match: &[],
             },
-            PatternOpts {
-                case_insensitive: true,
-            },
+            PatternOpts::new().with_case_insensitive(true),
+        );
+    }
+}
+
+#[test]
+fn parent_dirs_count() {
+    for (pattern, parent_dirs) in [
+        ("a/b", 0),
+        ("..", 1),
+        ("../*", 1),
+        ("../../x/**", 2),
+        ("../../../*.rs", 3),
+    ] {
+        let pattern = Pattern::new(pattern).unwrap();
+
+        assert_eq!(
+            pattern
+                .components()
+                .take_while(|component| matches!(component, PatternComponent::ParentDir))
+                .count(),
+            parent_dirs
         );
     }
 }
@@ -408,4 +795,4153 @@ fn test_pattern_with(test: PatternTest, opts: PatternOpts) {
             "Pattern '{pattern_str}' unexpectedly matched path '{path}' (opts: {opts:?})"
         );
     }
+
+    // Matching split paths is equivalent
+    for path in should_match.iter().chain(should_not_match) {
+        let Ok(normalized) = normalize_path_with(Path::new(path), pattern.normalize_opts()) else {
+            continue;
+        };
+
+        let components = normalized.components().iter().map(|c| c.as_os_str());
+
+        assert_eq!(
+            discriminant(&pattern.match_components(normalized.prefix(), components)),
+            discriminant(&pattern.match_against(Path::new(path))),
+            "Pattern '{pattern_str}' matched split path '{path}' differently (opts: {opts:?})"
+        );
+    }
+}
+
+#[test]
+fn limiting_complexity() {
+    let opts = PatternOpts::new().with_max_complexity(6);
+
+    assert!(Pattern::new_with_opts("src/*.{rs|toml}", opts).is_ok());
+
+    assert!(matches!(
+        Pattern::new_with_opts("src/*.{rs|toml|md}", opts),
+        Err(PatternError::TooComplex {
+            complexity: 7,
+            max: 6
+        })
+    ));
+
+    let nested = |depth: usize| format!("{}z{}", "{a|".repeat(depth), "}".repeat(depth));
+
+    assert!(matches!(
+        Pattern::new_with_opts(&nested(90), PatternOpts::new().with_max_complexity(100)),
+        Err(PatternError::TooComplex {
+            complexity: 181,
+            max: 100
+        })
+    ));
+
+    // Groups nested too deeply are rejected before parsing, regardless of the options
+    let deep = nested(100_000);
+    let start = Instant::now();
+
+    assert!(matches!(Pattern::new(&deep), Err(PatternError::Parsing(_))));
+    assert!(
+        Pattern::parse_partial(&deep, PatternOpts::new())
+            .0
+            .is_none()
+    );
+    assert!(start.elapsed() < Duration::from_secs(5));
+
+    assert!(Pattern::new(&nested(100)).is_ok());
+
+    // Same for huge alternations
+    let alternation = (0..100_000)
+        .map(|i| i.to_string())
+        .collect::<Vec<_>>()
+        .join("|");
+
+    assert!(matches!(
+        Pattern::new_with_opts(
+            &format!("{{{alternation}}}/*"),
+            PatternOpts::new().with_max_complexity(1_000)
+        ),
+        Err(PatternError::TooComplex {
+            complexity: 100_002,
+            max: 1_000
+        })
+    ));
+}
+
+#[test]
+fn oversized_components() {
+    let alternation = format!(
+        "{{{}}}",
+        (0..5000)
+            .map(|i| format!("alternative{i}"))
+            .collect::<Vec<_>>()
+            .join("|")
+    );
+
+    let pattern = format!("dir/{alternation}/*.txt");
+
+    let err = Pattern::new_with_opts(&pattern, PatternOpts::new().with_regex_size_limit(10_000))
+        .unwrap_err();
+
+    // The underlying error is kept as the error's source
+    assert!(
+        std::error::Error::source(&err)
+            .and_then(|source| source.downcast_ref::<regex::Error>())
+            .is_some()
+    );
+
+    match err {
+        PatternError::Compilation {
+            component,
+            offset,
+            err,
+        } => {
+            assert_eq!(component, alternation);
+            assert_eq!(offset, 4);
+            assert!(matches!(err, regex::Error::CompiledTooBig(10_000)));
+        }
+
+        PatternError::Parsing(_)
+        | PatternError::ForeignPrefix(_)
+        | PatternError::TooComplex { .. }
+        | PatternError::UnsupportedPath(_) => {
+            panic!("Unexpected error: {err:?}")
+        }
+    }
+
+    // Same pattern is fine with the default limit
+    assert!(Pattern::new(&pattern).is_ok());
+}
+
+#[test]
+fn normalizing_with_slash_only() {
+    let opts = PatternOpts::new().with_slash_only(true);
+
+    let pattern = Pattern::new("dir/*").unwrap();
+    let slash_only = Pattern::new_with_opts("dir/*", opts).unwrap();
+
+    assert_eq!(pattern.normalize_opts(), NormalizeOpts::new());
+    assert_eq!(
+        slash_only.normalize_opts(),
+        NormalizeOpts::new().with_slash_only(true)
+    );
+    assert!(slash_only.normalize_opts().slash_only());
+
+    // Backslashes are separators by default, but ordinary characters in slash-only mode
+    assert!(!pattern.is_match(Path::new(r"dir/a\b")));
+    assert!(slash_only.is_match(Path::new(r"dir/a\b")));
+    assert!(!slash_only.is_match(Path::new(r"\dir\a")));
+
+    let normalized =
+        normalize_path_with(Path::new(r"\dir/a\b"), slash_only.normalize_opts()).unwrap();
+    assert_eq!(normalized.prefix(), None);
+    assert_eq!(normalized.components().len(), 2);
+
+    let normalized = normalize_path(Path::new(r"\dir/a\b")).unwrap();
+    assert_eq!(normalized.prefix(), Some(PathPrefix::RootDir));
+    assert_eq!(normalized.components().len(), 3);
+}
+
+#[test]
+fn colons_outside_brackets() {
+    test_pattern(PatternTest {
+        pattern_str: "log_12:30.txt",
+        should_match: &["log_12:30.txt"],
+        should_not_match: &["log_12.30.txt", "log_1230.txt", "log_12:30.txt/a"],
+    });
+
+    test_pattern(PatternTest {
+        pattern_str: "**/*:[[:digit:]]",
+        should_match: &["ab:1", "dir/12:3", "::0"],
+        should_not_match: &["ab:c", "ab1", "ab:12"],
+    });
+}
+
+#[test]
+fn character_classes() {
+    let classes: [(_, &[_], &[_]); 12] = [
+        ("[[:alpha:]]", &["a", "Z"], &["1", "-", " "]),
+        ("[[:digit:]]", &["0", "5", "9"], &["a", "-", " "]),
+        ("[[:alphanumeric:]]", &["a", "Z", "7"], &["-", ".", " "]),
+        ("[[:uppercase:]]", &["A", "Z"], &["a", "1", "-"]),
+        ("[[:lowercase:]]", &["a", "z"], &["A", "1", "-"]),
+        ("[[:whitespace:]]", &[" ", "\t"], &["a", "1", "-"]),
+        (
+            "[[:xdigit:]]",
+            &["0", "9", "a", "f", "A", "F"],
+            &["g", "G", "-", " "],
+        ),
+        (
+            "[[:punct:]]",
+            &["-", "!", "~", "_", "@"],
+            &["a", "1", " ", "\t"],
+        ),
+        ("[[:blank:]]", &[" ", "\t"], &["a", "1", "-", "\n"]),
+        (
+            "[[:cntrl:]]",
+            &["\t", "\n", "\u{7f}"],
+            &["a", "1", " ", "-"],
+        ),
+        (
+            "[[:graph:]]",
+            &["a", "Z", "1", "-", "~"],
+            &[" ", "\t", "\u{7f}"],
+        ),
+        (
+            "[[:print:]]",
+            &["a", "Z", "1", "-", " "],
+            &["\t", "\n", "\u{7f}"],
+        ),
+    ];
+
+    for (pattern_str, should_match, should_not_match) in classes {
+        test_pattern(PatternTest {
+            pattern_str,
+            should_match,
+            should_not_match,
+        });
+
+        // Negated brackets match the opposite characters
+        let negated = Pattern::new(&pattern_str.replacen('[', "[!", 1)).unwrap();
+
+        for path in should_match {
+            assert!(
+                !negated.is_match(Path::new(path)),
+                "{negated} matches '{path}'"
+            );
+        }
+
+        // A lone `.` is the current directory, not a name
+        for path in should_not_match.iter().filter(|path| **path != ".") {
+            assert!(
+                negated.is_match(Path::new(path)),
+                "{negated} doesn't match '{path}'"
+            );
+        }
+    }
+
+    // Classes can be combined with other characters
+    test_pattern(PatternTest {
+        pattern_str: "*-[[:xdigit:]][[:xdigit:]].bin",
+        should_match: &["data-3f.bin", "x-A0.bin"],
+        should_not_match: &["data-3g.bin", "data-3.bin"],
+    });
+
+    test_pattern(PatternTest {
+        pattern_str: "[![:punct:][:blank:]]*",
+        should_match: &["abc", "1 2"],
+        should_not_match: &["-abc", " abc", "\tabc"],
+    });
+
+    // Unknown classes are listed in errors
+    let err = Pattern::new("[[:word:]]").unwrap_err().to_string();
+    assert!(err.contains("expected a valid character class"), "{err}");
+    assert!(
+        err.contains("xdigit, punct, blank, cntrl, graph or print"),
+        "{err}"
+    );
+}
+
+#[test]
+fn character_ranges() {
+    test_pattern(PatternTest {
+        pattern_str: "a[b-d]e",
+        should_match: &["abe", "ace", "ade"],
+        should_not_match: &["ae", "aae", "aee", "a-e", "aBe", "abbe"],
+    });
+
+    test_pattern(PatternTest {
+        pattern_str: "a[!b-d]e",
+        should_match: &["aae", "aee", "a-e", "a e"],
+        should_not_match: &["ae", "abe", "ace", "ade", "aeee"],
+    });
+
+    test_pattern(PatternTest {
+        pattern_str: "[0-9A-Fa-f][0-9A-Fa-f]",
+        should_match: &["00", "9f", "A0", "eF"],
+        should_not_match: &["0", "0g", "G0", "0-", "000"],
+    });
+
+    test_pattern(PatternTest {
+        pattern_str: "[!a-f0-9]",
+        should_match: &["g", "A", "-", "_"],
+        should_not_match: &["a", "c", "f", "0", "9", "", "gg"],
+    });
+
+    test_pattern(PatternTest {
+        pattern_str: "a[x-z[:digit:]]e",
+        should_match: &["axe", "aye", "aze", "a0e", "a9e"],
+        should_not_match: &["ae", "awe", "a-e", "axye"],
+    });
+
+    test_pattern(PatternTest {
+        pattern_str: "[a-a]",
+        should_match: &["a"],
+        should_not_match: &["b", "-"],
+    });
+
+    // A `-` at the start or at the end of the brackets is an ordinary character, and so is an escaped one
+    for (pattern_str, should_match, should_not_match) in [
+        ("[-a]", &["-", "a"][..], &["b"][..]),
+        ("[a-]", &["-", "a"], &["b"]),
+        ("[!-a]", &["b"], &["-", "a"]),
+        ("[-]", &["-"], &["a"]),
+        ("[a\\-z]", &["a", "-", "z"], &["b"]),
+        ("[a-c-]", &["a", "b", "c", "-"], &["d"]),
+        ("[[:digit:]-]", &["1", "-"], &["a"]),
+    ] {
+        test_pattern(PatternTest {
+            pattern_str,
+            should_match,
+            should_not_match,
+        });
+    }
+
+    // Escaped characters can delimit ranges
+    test_pattern(PatternTest {
+        pattern_str: "[\\*-\\?]",
+        should_match: &["*", "+", "-", "?"],
+        should_not_match: &["a", ")"],
+    });
+
+    // Ranges follow case insensitivity
+    let pattern =
+        Pattern::new_with_opts("[a-c].txt", PatternOpts::new().with_case_insensitive(true))
+            .unwrap();
+    assert!(pattern.is_match(Path::new("B.TXT")));
+    assert!(!pattern.is_match(Path::new("D.txt")));
+
+    // Reversed ranges are invalid
+    for invalid in ["[z-a]", "[!9-0]", "[a-cb-a]", "[\\?-\\*]"] {
+        assert!(
+            Pattern::new(invalid).is_err(),
+            "Pattern '{invalid}' should be invalid"
+        );
+    }
+
+    // Brackets aren't parsed at all when disabled
+    let pattern =
+        Pattern::new_with_opts("[a-c]", PatternOpts::new().with_disable_brackets(true)).unwrap();
+    assert!(pattern.is_match(Path::new("[a-c]")));
+    assert!(!pattern.is_match(Path::new("b")));
+}
+
+#[test]
+fn leading_closing_brackets() {
+    test_pattern(PatternTest {
+        pattern_str: "a[]]b",
+        should_match: &["a]b"],
+        should_not_match: &["ab", "a]]b", "a[]]b"],
+    });
+
+    test_pattern(PatternTest {
+        pattern_str: "[!]]x",
+        should_match: &["ax", "[x"],
+        should_not_match: &["]x", "x", "a]x"],
+    });
+
+    test_pattern(PatternTest {
+        pattern_str: "[]a[:digit:]]",
+        should_match: &["]", "a", "1"],
+        should_not_match: &["b", "[", "]a"],
+    });
+
+    // It can start a range, and is ordinary before a closing `-`
+    test_pattern(PatternTest {
+        pattern_str: "[]-a]",
+        should_match: &["]", "^", "_", "a"],
+        should_not_match: &["-", "b", "["],
+    });
+
+    test_pattern(PatternTest {
+        pattern_str: "[]-]",
+        should_match: &["]", "-"],
+        should_not_match: &["a", "^"],
+    });
+
+    // Unterminated brackets are still reported
+    for (invalid, message) in [
+        ("[]", "invalid pattern at offset 2: unexpected end of input"),
+        (
+            "[!]",
+            "invalid pattern at offset 3: unexpected end of input",
+        ),
+        (
+            "a[]/b",
+            "invalid pattern at offset 3: expected character ']'",
+        ),
+        ("[]]]", "invalid pattern at offset 3"),
+        (
+            "[/]",
+            "invalid pattern at offset 1: expected at least one character to match",
+        ),
+    ] {
+        assert_eq!(
+            Pattern::new(invalid)
+                .unwrap_err()
+                .to_string()
+                .get(..message.len()),
+            Some(message),
+            "Pattern '{invalid}'"
+        );
+    }
+}
+
+#[test]
+fn locating_parse_errors() {
+    let parse_error = |pattern: &str| match Pattern::new(pattern) {
+        Err(PatternError::Parsing(err)) => err,
+        result => panic!("Pattern '{pattern}' didn't fail to parse: {result:?}"),
+    };
+
+    // Unterminated brackets fail at the end of the pattern
+    let err = parse_error("src/[abc");
+    assert_eq!((err.offset, err.len), (8, 0));
+    assert_eq!(err.message, "unexpected end of input");
+    assert_eq!(
+        err.render("src/[abc"),
+        "src/[abc\n        ^ unexpected end of input"
+    );
+
+    // Invalid class names are pointed at as a whole
+    let err = parse_error("a/[[:foo:]]");
+    assert_eq!((err.offset, err.len), (5, 3));
+    assert!(
+        err.render("a/[[:foo:]]")
+            .starts_with("a/[[:foo:]]\n     ^^^ expected a valid character class")
+    );
+
+    // So are globstars followed by other characters
+    let err = parse_error("a/**a");
+    assert_eq!((err.offset, err.len), (2, 2));
+    assert_eq!(
+        err.render("a/**a"),
+        "a/**a\n  ^^ Wildcard components '**' must be preceded and followed by path separators"
+    );
+
+    // The same way when they are preceded by other characters
+    let err = parse_error("a/b**");
+    assert_eq!((err.offset, err.len), (3, 2));
+    assert_eq!(
+        err.render("a/b**"),
+        "a/b**\n   ^^ Wildcard components '**' must be preceded and followed by path separators"
+    );
+
+    // Carets are aligned on characters rather than bytes
+    let err = parse_error("été/[[:bad:]]");
+    assert_eq!((err.offset, err.len), (9, 3));
+    assert!(
+        err.render("été/[[:bad:]]")
+            .starts_with("été/[[:bad:]]\n       ^^^ ")
+    );
+
+    // The pattern's error renders the same way, or after the pattern if it isn't located
+    let err = Pattern::new("src/[abc").unwrap_err();
+    assert_eq!(
+        err.render("src/[abc"),
+        "src/[abc\n        ^ unexpected end of input"
+    );
+    assert_eq!(
+        err.to_string(),
+        "invalid pattern at offset 8: unexpected end of input"
+    );
+
+    let err =
+        Pattern::new_with_opts("*{a|b}", PatternOpts::new().with_max_complexity(1)).unwrap_err();
+    assert_eq!(
+        err.render("*{a|b}"),
+        "*{a|b}\npattern is too complex (4 matchers, maximum is 1)"
+    );
+}
+
+#[test]
+fn escaping_with_backslashes() {
+    let opts = PatternOpts::new().with_backslash_escapes(true);
+
+    test_pattern_with(
+        PatternTest {
+            pattern_str: "foo\\*bar",
+            should_match: &["foo*bar"],
+            should_not_match: &["fooxbar", "foo/bar", "foo"],
+        },
+        opts,
+    );
+
+    test_pattern_with(
+        PatternTest {
+            pattern_str: "a\\b",
+            should_match: &["ab"],
+            should_not_match: &["a/b", "a\\b"],
+        },
+        opts,
+    );
+
+    test_pattern_with(
+        PatternTest {
+            pattern_str: "/\\{a\\|b\\}/*\\?",
+            should_match: &["/{a|b}/c?", "/{a|b}/?"],
+            should_not_match: &["/a/c?", "/{a|b}/c"],
+        },
+        opts,
+    );
+
+    test_pattern_with(
+        PatternTest {
+            pattern_str: "dir/a\\\\b",
+            should_match: &["dir/a\\b"],
+            should_not_match: &["dir/ab", "dir/a/b"],
+        },
+        opts.with_slash_only(true),
+    );
+
+    // Backslashes are separators by default
+    test_pattern(PatternTest {
+        pattern_str: "a\\b",
+        should_match: &["a/b"],
+        should_not_match: &["ab"],
+    });
+
+    assert!(Pattern::new_with_opts("a\\", opts).is_err());
+    assert!(Pattern::new_with_opts("\\\\server", opts).is_ok_and(|pattern| !pattern.is_absolute()));
+}
+
+#[test]
+fn matching_slash_only() {
+    let pattern = Pattern::new_slash_only("dir/file\\name").unwrap();
+
+    assert!(pattern.is_match(Path::new("dir/file\\name")));
+    assert!(pattern.is_match_str("dir/file\\name"));
+    assert!(!pattern.is_match(Path::new("dir/file/name")));
+    assert!(!pattern.is_match(Path::new("dir\\file\\name")));
+
+    let opts = PatternOpts::new()
+        .with_slash_only(true)
+        .with_literal_backslashes(true);
+
+    test_pattern_with(
+        PatternTest {
+            pattern_str: "\\*/*\\[\\*]",
+            should_match: &["\\/a\\*", "\\a/\\*"],
+            should_not_match: &["/a/*", "\\/a\\b", "a/\\*"],
+        },
+        opts,
+    );
+
+    // Backslashes don't start prefixes
+    assert!(!Pattern::new_with_opts("\\a", opts).unwrap().is_absolute());
+    assert!(Pattern::new_with_opts("/a", opts).unwrap().is_absolute());
+
+    // Escaping takes precedence
+    test_pattern_with(
+        PatternTest {
+            pattern_str: "a\\*",
+            should_match: &["a*"],
+            should_not_match: &["a\\*", "ab"],
+        },
+        opts.with_backslash_escapes(true),
+    );
+
+    // Only the pattern is affected, paths are still split on backslashes
+    let pattern =
+        Pattern::new_with_opts("a\\b", PatternOpts::new().with_literal_backslashes(true)).unwrap();
+    assert!(!pattern.is_match(Path::new("a\\b")));
+    assert!(pattern.is_match_str("a\\b"));
+}
+
+#[test]
+fn escaping_strings() {
+    let opts = PatternOpts::new().with_backslash_escapes(true);
+
+    test_pattern_with(
+        PatternTest {
+            pattern_str: "what\\?.txt",
+            should_match: &["what?.txt"],
+            should_not_match: &["whatt.txt", "what.txt", "what\\?.txt"],
+        },
+        opts,
+    );
+
+    assert_eq!(Pattern::escape("what?.txt"), "what[\\?].txt");
+    assert_eq!(
+        Pattern::escape("dir/*[a]{b|c}:\\"),
+        "dir/[\\*][\\[]a[\\]][\\{]b[\\|]c[\\}][\\:]\\\\"
+    );
+    assert_eq!(Pattern::escape("plain/path.txt"), "plain/path.txt");
+
+    // Escaped strings are matched literally, with or without backslash escapes
+    for str in [
+        "what?.txt",
+        "a/**/[!b]",
+        "{x|y}/*.rs",
+        "key:value",
+        "track [1].mp3",
+        "[[:alpha:]]",
+        "{a|b}",
+        "*",
+        "..",
+    ] {
+        for opts in [PatternOpts::new(), opts] {
+            let pattern = Pattern::new_with_opts(&Pattern::escape(str), opts).unwrap();
+
+            assert!(
+                pattern.is_match(Path::new(str)),
+                "{str} doesn't match itself"
+            );
+            assert!(!pattern.has_wildcard());
+            assert!(!pattern.is_absolute());
+        }
+    }
+
+    // Colons can't form drive prefixes
+    assert!(!Pattern::new(&Pattern::escape("c:")).unwrap().is_absolute());
+
+    // The result can be embedded in larger patterns
+    let pattern = Pattern::new(&format!("{}/**/*.rs", Pattern::escape("{a|b}[1]"))).unwrap();
+    assert!(pattern.is_match(Path::new("{a|b}[1]/src/main.rs")));
+    assert!(!pattern.is_match(Path::new("a/src/main.rs")));
+
+    let pattern =
+        Pattern::new_with_opts(&Pattern::escape("a\\b"), opts.with_slash_only(true)).unwrap();
+    assert!(pattern.is_match(Path::new("a\\b")));
+    assert!(!pattern.is_match(Path::new("a/b")));
+
+    // Parentheses are escaped as they delimit extended glob operators
+    assert_eq!(Pattern::escape("+(a)"), "+[\\(]a[\\)]");
+
+    let extglob = PatternOpts::new().with_extglob(true);
+
+    for str in ["@(a)", "+(a)", "!(a|b)", "*(x)", "?(y)", "a(b)c", "(", ")"] {
+        for opts in [PatternOpts::new(), extglob, opts.with_extglob(true)] {
+            let pattern = Pattern::new_with_opts(&Pattern::escape(str), opts).unwrap();
+
+            assert!(
+                pattern.is_match(Path::new(str)),
+                "{str} doesn't match itself with {opts:?}"
+            );
+            assert!(!pattern.has_wildcard());
+        }
+    }
+
+    let pattern =
+        Pattern::new_with_opts(&format!("@({}|b)", Pattern::escape("a)")), extglob).unwrap();
+    assert!(pattern.is_match(Path::new("a)")));
+    assert!(pattern.is_match(Path::new("b")));
+}
+
+#[test]
+fn comma_separated_alternates() {
+    test_pattern(PatternTest {
+        pattern_str: "*.{rs,toml}",
+        should_match: &["a.rs", "b.toml", ".rs"],
+        should_not_match: &["a.md", "a.{rs,toml}", "a.rs,toml", "a,rs"],
+    });
+
+    test_pattern(PatternTest {
+        pattern_str: "{a,{b|c}d,e}",
+        should_match: &["a", "bd", "cd", "e"],
+        should_not_match: &["b", "d", "a,e", "{a,e}"],
+    });
+
+    // Outside groups, commas are ordinary characters
+    test_pattern(PatternTest {
+        pattern_str: "a,b/*,*",
+        should_match: &["a,b/c,d", "a,b/,"],
+        should_not_match: &["a", "b", "a,b/cd"],
+    });
+
+    // Inside groups, they must be escaped to be matched
+    test_pattern(PatternTest {
+        pattern_str: "{a[,]b,c}",
+        should_match: &["a,b", "c"],
+        should_not_match: &["a", "b", "a[,]b"],
+    });
+
+    test_pattern_with(
+        PatternTest {
+            pattern_str: "{a\\,b,c}",
+            should_match: &["a,b", "c"],
+            should_not_match: &["a", "b", "a\\,b"],
+        },
+        PatternOpts::new().with_backslash_escapes(true),
+    );
+
+    // Alternates can't be empty, whatever their separator
+    for invalid in ["{a,}", "{,a}", "{a,,b}", "{a,b,}", "{,}", "{a|}"] {
+        assert!(
+            Pattern::new(invalid).is_err(),
+            "Pattern '{invalid}' should be invalid"
+        );
+    }
+
+    // Without alternates, braces and commas are ordinary characters
+    test_pattern_with(
+        PatternTest {
+            pattern_str: "{a,b}",
+            should_match: &["{a,b}"],
+            should_not_match: &["a", "b"],
+        },
+        PatternOpts::new().with_disable_alternates(true),
+    );
+}
+
+#[test]
+fn numeric_ranges() {
+    test_pattern(PatternTest {
+        pattern_str: "shard-{0..31}/**",
+        should_match: &["shard-0", "shard-7/a", "shard-10/a/b", "shard-31"],
+        should_not_match: &["shard-", "shard-32", "shard-00", "shard-07", "shard-0..31"],
+    });
+
+    test_pattern(PatternTest {
+        pattern_str: "app.log.{1..15}",
+        should_match: &["app.log.1", "app.log.9", "app.log.15"],
+        should_not_match: &["app.log.0", "app.log.16", "app.log.01", "app.log."],
+    });
+
+    // Zero-padded bounds
+    test_pattern(PatternTest {
+        pattern_str: "{01..12}",
+        should_match: &["01", "09", "10", "12"],
+        should_not_match: &["1", "9", "00", "13", "012"],
+    });
+
+    test_pattern(PatternTest {
+        pattern_str: "{1..010}",
+        should_match: &["001", "009", "010"],
+        should_not_match: &["1", "01", "10", "011"],
+    });
+
+    // Steps
+    test_pattern(PatternTest {
+        pattern_str: "{0..30..10}",
+        should_match: &["0", "10", "20", "30"],
+        should_not_match: &["5", "15", "40"],
+    });
+
+    test_pattern(PatternTest {
+        pattern_str: "{1..10..4}",
+        should_match: &["1", "5", "9"],
+        should_not_match: &["4", "10"],
+    });
+
+    // Ranges can be combined with other matchers
+    test_pattern(PatternTest {
+        pattern_str: "v{1..3}.{rs|toml}",
+        should_match: &["v1.rs", "v2.toml", "v3.rs"],
+        should_not_match: &["v4.rs", "v.rs", "v1.md"],
+    });
+
+    test_pattern(PatternTest {
+        pattern_str: "{a|{8..11}}",
+        should_match: &["a", "8", "11"],
+        should_not_match: &["7", "12", "{8..11}"],
+    });
+
+    assert_eq!(
+        Pattern::new("{7..7}").unwrap().expand(),
+        Ok(Some(vec![PathBuf::from("7")]))
+    );
+
+    // Other groups are left as they are
+    test_pattern(PatternTest {
+        pattern_str: "{1..a|b}",
+        should_match: &["1..a", "b"],
+        should_not_match: &["1", "a"],
+    });
+
+    // Invalid ranges
+    for (invalid, message) in [
+        ("{5..1}", "numeric ranges must not be reversed"),
+        ("{1..5..0}", "numeric range steps must not be zero"),
+        (
+            "{0..1000}",
+            "numeric ranges can't contain more than 1000 values",
+        ),
+        (
+            "{99999999999999999999..1}",
+            "numeric range bounds are too large",
+        ),
+    ] {
+        let err = Pattern::new(invalid).unwrap_err().to_string();
+        assert!(
+            err.contains(message),
+            "Pattern '{invalid}' failed with: {err}"
+        );
+    }
+
+    // Ranges are capped on the number of values, not on their bounds
+    assert!(Pattern::new("{0..999}").is_ok());
+    assert!(Pattern::new("{0..99900..100}").is_ok());
+
+    // Ranges are groups, so they are disabled along with them
+    test_pattern_with(
+        PatternTest {
+            pattern_str: "{1..3}",
+            should_match: &["{1..3}"],
+            should_not_match: &["1", "2"],
+        },
+        PatternOpts::new().with_disable_alternates(true),
+    );
+}
+
+#[test]
+fn extended_glob_operators() {
+    let opts = PatternOpts::new().with_extglob(true);
+
+    for (pattern_str, should_match, should_not_match) in [
+        (
+            "?(x)y.txt",
+            &["y.txt", "xy.txt"][..],
+            &["xxy.txt", "zy.txt"][..],
+        ),
+        (
+            "*(ab).rs",
+            &[".rs", "ab.rs", "abab.rs"],
+            &["aba.rs", "b.rs"],
+        ),
+        ("+([0-9]).log", &["1.log", "2024.log"], &[".log", "1a.log"]),
+        (
+            "@(README|LICENSE).md",
+            &["README.md", "LICENSE.md"],
+            &["READMELICENSE.md", ".md"],
+        ),
+        (
+            "file.+(a|b)",
+            &["file.a", "file.abba"],
+            &["file.", "file.c"],
+        ),
+        (
+            "src/@(lib|main).rs",
+            &["src/lib.rs", "src/main.rs"],
+            &["src/mod.rs"],
+        ),
+        // Negations
+        (
+            "!(*.bak)",
+            &["a.txt", "a.bak.txt", "bak"],
+            &["a.bak", ".bak"],
+        ),
+        (
+            "*.!(js|json)",
+            &["a.ts", "a.jsx", "a.", "a.min.js"],
+            &["a.js", "a.json", "a"],
+        ),
+        ("a!(b)c", &["ac", "axc", "abbc"], &["abc", "ab"]),
+        ("!(foo)bar", &["bar", "foobar.bar"], &["foobar"]),
+        ("@(.git|x)", &[".git", "x"], &[".gi"]),
+    ] {
+        test_pattern_with(
+            PatternTest {
+                pattern_str,
+                should_match,
+                should_not_match,
+            },
+            opts,
+        );
+    }
+
+    // Leading dots must be matched explicitly, like with `*`
+    let dot_opts = opts.with_require_literal_leading_dot(true);
+
+    test_pattern_with(
+        PatternTest {
+            pattern_str: "!(x)",
+            should_match: &["env"],
+            should_not_match: &[".env", "x"],
+        },
+        dot_opts,
+    );
+
+    test_pattern_with(
+        PatternTest {
+            pattern_str: "!(x).env",
+            should_match: &[".env", "a.env"],
+            should_not_match: &["x.env"],
+        },
+        dot_opts,
+    );
+
+    test_pattern_with(
+        PatternTest {
+            pattern_str: "+(.|a)b",
+            should_match: &[".b", "..b", ".ab", "ab"],
+            should_not_match: &["b"],
+        },
+        dot_opts,
+    );
+
+    test_pattern_with(
+        PatternTest {
+            pattern_str: "*(a)?b",
+            should_match: &["aab", "xb", "a.b"],
+            should_not_match: &[".b", "ab.b"],
+        },
+        dot_opts,
+    );
+
+    // Operators can be combined with the other matchers
+    assert!(
+        Pattern::new_with_opts("{a|+(b)}[0-9]", opts)
+            .unwrap()
+            .is_match(Path::new("bbb1"))
+    );
+
+    // Escaped operators are literals
+    assert!(
+        Pattern::new_with_opts(r"\+(a)", opts.with_backslash_escapes(true))
+            .unwrap()
+            .is_match(Path::new("+(a)"))
+    );
+
+    for (invalid, message) in [
+        ("+(a", "unexpected end of input"),
+        ("@()", "expected at least one alternative matcher"),
+        ("!(a)!(b)", "'!(...)' can only be used once per component"),
+        ("{!(a)|b}", "'!(...)' can only be used once per component"),
+        ("+(!(a))", "'!(...)' can only be used once per component"),
+    ] {
+        let err = Pattern::new_with_opts(invalid, opts)
+            .unwrap_err()
+            .to_string();
+        assert!(
+            err.contains(message),
+            "Pattern '{invalid}' failed with: {err}"
+        );
+    }
+
+    // Errors point at the misplaced negation
+    for (invalid, offset) in [
+        ("!(a)!(b)", 4),
+        ("{!(a)|b}", 1),
+        ("+(!(a))", 2),
+        ("x/{a|b!(c)}", 6),
+        ("x/{a|+(b|!(c))}", 9),
+    ] {
+        match Pattern::new_with_opts(invalid, opts) {
+            Err(PatternError::Parsing(err)) => {
+                assert_eq!((err.offset, err.len), (offset, 2), "Pattern '{invalid}'")
+            }
+            result => panic!("Pattern '{invalid}' didn't fail to parse: {result:?}"),
+        }
+    }
+
+    // Negations are allowed once per component
+    assert!(Pattern::new_with_opts("!(a)/!(b)", opts).is_ok());
+
+    // Operators count as groups for the nesting limit
+    let nested = |depth: usize| format!("{}z{}", "+(a|".repeat(depth), ")".repeat(depth));
+    assert!(Pattern::new_with_opts(&nested(50), opts).is_ok());
+    assert!(matches!(
+        Pattern::new_with_opts(&nested(100_000), opts),
+        Err(PatternError::Parsing(_))
+    ));
+
+    // Operators capture as a whole, except in components containing a negation
+    let captures = |pattern: &str, path: &str| {
+        Pattern::new_with_opts(pattern, opts)
+            .unwrap()
+            .match_captures(Path::new(path))
+            .unwrap()
+            .texts()
+            .to_vec()
+    };
+
+    assert_eq!(
+        captures("log-+([0-9])-*", "log-12-a"),
+        [Some("12".to_owned()), Some("a".to_owned())]
+    );
+    assert_eq!(
+        captures("?(x)*", "xa"),
+        [Some("x".to_owned()), Some("a".to_owned())]
+    );
+    assert_eq!(captures("!(a)/*", "b/c"), [Some("c".to_owned())]);
+
+    // Operators can be simplified and exported
+    assert_eq!(
+        Pattern::new_with_opts("@(a|{a|a}).rs", opts)
+            .unwrap()
+            .simplify()
+            .map(|simplified| simplified.to_string())
+            .as_deref(),
+        Some("@(a).rs")
+    );
+    assert_eq!(
+        Pattern::new_with_opts(r"{+\(|+\(}", opts.with_backslash_escapes(true))
+            .unwrap()
+            .simplify()
+            .map(|simplified| simplified.to_string())
+            .as_deref(),
+        Some(r"+\(")
+    );
+
+    let exported = |pattern: &str| {
+        Pattern::new_with_opts(pattern, opts)
+            .unwrap()
+            .to_regex_string()
+    };
+
+    assert_eq!(exported("src/*(ab).rs"), "^src/(?:(?:ab)*\\.rs)$");
+
+    // Negations can't be exported, so they match any component
+    assert_eq!(exported("a/!(b)"), "^a/[^/]+$");
+
+    // Without the option, operators are ordinary characters
+    test_pattern(PatternTest {
+        pattern_str: "+({a|b})",
+        should_match: &["+(a)", "+(b)"],
+        should_not_match: &["a", "aa"],
+    });
+
+    test_pattern(PatternTest {
+        pattern_str: "!(a)",
+        should_match: &["!(a)"],
+        should_not_match: &["b"],
+    });
+}
+
+#[test]
+fn disabling_alternates() {
+    let opts = PatternOpts::new().with_disable_alternates(true);
+
+    test_pattern_with(
+        PatternTest {
+            pattern_str: "{a|b}",
+            should_match: &["{a|b}"],
+            should_not_match: &["a", "b", "{a}"],
+        },
+        opts,
+    );
+
+    test_pattern_with(
+        PatternTest {
+            pattern_str: "*}/[{|]*",
+            should_match: &["a}/{b", "}/|"],
+            should_not_match: &["a/{b", "a}/b"],
+        },
+        opts,
+    );
+
+    // Combined with backslash escapes
+    test_pattern_with(
+        PatternTest {
+            pattern_str: "{a|b\\*}",
+            should_match: &["{a|b*}"],
+            should_not_match: &["a", "b*"],
+        },
+        opts.with_backslash_escapes(true),
+    );
+
+    test_pattern_with(
+        PatternTest {
+            pattern_str: "{a|b\\*}",
+            should_match: &["a", "b*"],
+            should_not_match: &["{a|b*}"],
+        },
+        PatternOpts::new().with_backslash_escapes(true),
+    );
+
+    assert!(Pattern::new("a|b").is_err());
+    assert!(Pattern::new_with_opts("a|b", opts).is_ok());
+}
+
+#[test]
+fn overriding_options_per_match() {
+    let pattern = Pattern::new("src/**/*.rs").unwrap();
+    let path = Path::new("SRC/main.RS");
+
+    assert!(!pattern.is_match(path));
+    assert!(pattern.is_match_with(path, MatchOptions::new().with_case_insensitive(true)));
+    assert!(!pattern.is_match_with(path, MatchOptions::new().with_case_insensitive(false)));
+    assert!(!pattern.is_match_with(path, MatchOptions::new()));
+
+    // The pattern's own options are left untouched
+    assert!(!pattern.is_match(path));
+
+    let pattern = Pattern::new_with_opts(
+        "src/**/*.rs",
+        PatternOpts::new().with_case_insensitive(true),
+    )
+    .unwrap();
+    assert!(pattern.is_match(path));
+    assert!(!pattern.is_match_with(path, MatchOptions::new().with_case_insensitive(false)));
+    assert!(pattern.is_match_with(
+        Path::new("src/main.rs"),
+        MatchOptions::new().with_case_insensitive(false)
+    ));
+
+    // Overrides take precedence over smart case and inline flags
+    let pattern =
+        Pattern::new_with_opts("Src/*.rs", PatternOpts::new().with_smart_case(true)).unwrap();
+    assert!(!pattern.is_match(Path::new("src/A.RS")));
+    assert!(pattern.is_match_with(
+        Path::new("src/A.RS"),
+        MatchOptions::new().with_case_insensitive(true)
+    ));
+    assert!(!pattern.is_match_with(
+        Path::new("Src/A.RS"),
+        MatchOptions::new().with_case_insensitive(false)
+    ));
+
+    let pattern =
+        Pattern::new_with_opts("(?i)*.md", PatternOpts::new().with_inline_flags(true)).unwrap();
+    assert!(pattern.is_match(Path::new("README.MD")));
+    assert!(!pattern.is_match_with(
+        Path::new("README.MD"),
+        MatchOptions::new().with_case_insensitive(false)
+    ));
+
+    assert!(matches!(
+        Pattern::new("a/*").unwrap().match_against_with(
+            Path::new("A"),
+            MatchOptions::new().with_case_insensitive(true)
+        ),
+        PatternMatchResult::Starved
+    ));
+}
+
+#[test]
+fn inline_flags() {
+    let opts = PatternOpts::new().with_inline_flags(true);
+
+    test_pattern_with(
+        PatternTest {
+            pattern_str: "(?i)readme.md",
+            should_match: &["README.MD", "readme.md", "ReadMe.md"],
+            should_not_match: &["(?i)readme.md", "README.MD/a"],
+        },
+        opts,
+    );
+
+    test_pattern_with(
+        PatternTest {
+            pattern_str: "(?i)/**/*.md",
+            should_match: &["/a/B.MD"],
+            should_not_match: &["a/B.MD"],
+        },
+        opts,
+    );
+
+    // Inline flags override the provided options
+    test_pattern_with(
+        PatternTest {
+            pattern_str: "(?-i)readme.md",
+            should_match: &["readme.md"],
+            should_not_match: &["README.MD"],
+        },
+        opts.with_case_insensitive(true).with_smart_case(true),
+    );
+
+    // Escaped flags are literal
+    test_pattern_with(
+        PatternTest {
+            pattern_str: "\\(\\?i)readme.md",
+            should_match: &["(?i)readme.md"],
+            should_not_match: &["README.MD", "readme.md"],
+        },
+        opts.with_backslash_escapes(true),
+    );
+
+    // Flags are only recognized at the start of the pattern
+    let pattern = Pattern::new_with_opts("a/(?i)b", opts).unwrap();
+    assert!(pattern.is_match(Path::new("a/(xi)b")));
+    assert!(!pattern.is_match(Path::new("a/B")));
+
+    // Disabled by default
+    let pattern = Pattern::new("(?i)readme.md").unwrap();
+    assert!(pattern.is_match(Path::new("(xi)readme.md")));
+    assert!(!pattern.is_match(Path::new("README.MD")));
+
+    let (ast, errors) = Pattern::parse_partial("(?i)*.md", opts);
+    assert!(errors.is_empty());
+    assert_eq!(ast.unwrap().case_insensitive, Some(true));
+}
+
+#[test]
+fn normalizing_parent_dirs() {
+    let opts = PatternOpts::new().with_normalize_dotdot(true);
+
+    test_pattern_with(
+        PatternTest {
+            pattern_str: "./a/../b/*.txt",
+            should_match: &["b/c.txt"],
+            should_not_match: &["a/b/c.txt", "a/../b/c.txt", "c.txt"],
+        },
+        opts,
+    );
+
+    test_pattern_with(
+        PatternTest {
+            pattern_str: "a/b/../../c",
+            should_match: &["c"],
+            should_not_match: &["a/b/c", "a/c"],
+        },
+        opts,
+    );
+
+    // Leading parent directories are kept
+    test_pattern_with(
+        PatternTest {
+            pattern_str: "../a/../../b/*.rs",
+            should_match: &["../../b/c.rs"],
+            should_not_match: &["../b/c.rs", "b/c.rs"],
+        },
+        opts,
+    );
+
+    test_pattern_with(
+        PatternTest {
+            pattern_str: "/a/../b/**",
+            should_match: &["/b", "/b/c/d"],
+            should_not_match: &["/a/b", "/a"],
+        },
+        opts,
+    );
+
+    // The walker starts from the resolved directory
+    let pattern = Pattern::new_with_opts("a/../b/../c/d/../*.txt", opts).unwrap();
+    assert_eq!(pattern.common_root_dir(), Path::new("c"));
+
+    let pattern = Pattern::new_with_opts("a/../../x/*", opts).unwrap();
+    assert_eq!(pattern.common_root_dir(), Path::new("../x"));
+
+    // Going above the root directory
+    assert!(Pattern::new_with_opts("/../x", opts).is_err());
+    assert!(Pattern::new_with_opts("/a/../../x", opts).is_err());
+
+    // Resolving against a wildcard component would be ambiguous
+    assert!(Pattern::new_with_opts("a/**/../x", opts).is_err());
+
+    // Partial parsing follows the same rules
+    assert!(Pattern::parse_partial("a/../b", opts).1.is_empty());
+    assert_eq!(Pattern::parse_partial("/../x", opts).1.len(), 1);
+
+    // Disabled by default
+    assert!(Pattern::new("./a/../b/*.txt").is_err());
+    assert!(Pattern::new("a/b/../../c").is_err());
+}
+
+#[test]
+fn limiting_components() {
+    let opts = PatternOpts::new().with_max_components(2);
+
+    test_pattern_with(
+        PatternTest {
+            pattern_str: "**/*.rs",
+            should_match: &["b.rs", "a/b.rs"],
+            should_not_match: &["a/b/c.rs", "a/b/c/d.rs"],
+        },
+        opts,
+    );
+
+    test_pattern_with(
+        PatternTest {
+            pattern_str: "/**",
+            should_match: &["/", "/a", "/a/b"],
+            should_not_match: &["/a/b/c"],
+        },
+        opts,
+    );
+
+    let pattern = Pattern::new_with_opts("**/*.rs", opts).unwrap();
+
+    // Paths at the limit can't be completed with more components
+    assert!(matches!(
+        pattern.match_against(Path::new("a")),
+        PatternMatchResult::Starved
+    ));
+    assert!(matches!(
+        pattern.match_against(Path::new("a/b")),
+        PatternMatchResult::NotMatched
+    ));
+
+    assert!(
+        Pattern::new("**/*.rs")
+            .unwrap()
+            .is_match(Path::new("a/b/c.rs"))
+    );
+}
+
+#[test]
+fn disabling_brackets() {
+    let opts = PatternOpts::new().with_disable_brackets(true);
+
+    test_pattern_with(
+        PatternTest {
+            pattern_str: "track [1].mp3",
+            should_match: &["track [1].mp3"],
+            should_not_match: &["track 1.mp3", "track [2].mp3"],
+        },
+        opts,
+    );
+
+    test_pattern_with(
+        PatternTest {
+            pattern_str: "**/[*]/{a|b}*]",
+            should_match: &["[x]/a]", "dir/[]/b.txt]"],
+            should_not_match: &["x/a]", "[x]/c]", "[x]/a"],
+        },
+        opts,
+    );
+
+    // Unbalanced brackets are ordinary characters too
+    test_pattern_with(
+        PatternTest {
+            pattern_str: "a[b",
+            should_match: &["a[b"],
+            should_not_match: &["ab"],
+        },
+        opts,
+    );
+
+    assert!(Pattern::new("a[b").is_err());
+    assert!(
+        Pattern::new("track [1].mp3")
+            .is_ok_and(|pattern| !pattern.is_match(Path::new("track [1].mp3")))
+    );
+}
+
+#[test]
+fn disabling_globstar() {
+    let opts = PatternOpts::new().with_disable_globstar(true);
+
+    test_pattern_with(
+        PatternTest {
+            pattern_str: "src/**",
+            should_match: &["src/foo", "src/.hidden"],
+            should_not_match: &["src/foo/bar", "src"],
+        },
+        opts,
+    );
+
+    test_pattern_with(
+        PatternTest {
+            pattern_str: "**/*.rs",
+            should_match: &["src/main.rs"],
+            should_not_match: &["main.rs", "src/app/main.rs"],
+        },
+        opts,
+    );
+
+    // `**` acts like `*` inside of components too
+    test_pattern_with(
+        PatternTest {
+            pattern_str: "a**b{c|d***}",
+            should_match: &["abc", "axxbd", "abdx"],
+            should_not_match: &["a/bc", "ab"],
+        },
+        opts,
+    );
+
+    let pattern = compile_pattern("src/**", opts);
+    assert!(!pattern.has_wildcard());
+    assert!(matches!(
+        pattern.match_against(Path::new("src/foo/bar")),
+        PatternMatchResult::NotMatched
+    ));
+
+    assert!(compile_pattern("src/**", PatternOpts::new()).has_wildcard());
+    assert!(Pattern::new("a**b").is_err());
+}
+
+#[test]
+fn requiring_literal_leading_dot() {
+    let opts = PatternOpts::new().with_require_literal_leading_dot(true);
+
+    for (pattern, path, default, literal_dot) in [
+        ("*", ".hidden", true, false),
+        ("*", "visible", true, true),
+        (".*", ".hidden", true, true),
+        ("?hidden", ".hidden", true, false),
+        ("?oo", ".oo", true, false),
+        ("?oo", "foo", true, true),
+        (".?o", ".ao", true, true),
+        ("a?o", "a.o", true, true),
+        ("??", "..", true, false),
+        ("[.]hidden", ".hidden", true, false),
+        ("[.a]oo", ".oo", true, false),
+        ("[.a]oo", "aoo", true, true),
+        ("[!a]hidden", ".hidden", true, false),
+        ("[!a]hidden", "xhidden", true, true),
+        ("[[:alpha:].]oo", ".oo", true, false),
+        ("[[:alpha:].]oo", "foo", true, true),
+        ("a[.]o", "a.o", true, true),
+        ("{.h|x}idden", ".hidden", true, true),
+        ("{x|?h}idden", ".hidden", true, false),
+        ("{[.]|.}hidden", ".hidden", true, true),
+        ("*.*", ".hidden", true, false),
+        ("*.*", "file.txt", true, true),
+        ("*?*", "a.", true, true),
+        ("**/*.rs", ".cargo/config.rs", true, false),
+        ("**/*.rs", "src/.main.rs", true, false),
+        ("**/*.rs", "src/main.rs", true, true),
+        (".cargo/*.rs", ".cargo/config.rs", true, true),
+        (".cargo/**", ".cargo/a/b", true, true),
+        (".cargo/**", ".cargo/.a/b", true, false),
+    ] {
+        assert_eq!(
+            Pattern::new(pattern).unwrap().is_match(Path::new(path)),
+            default,
+            "Unexpected default result for pattern '{pattern}' against '{path}'"
+        );
+
+        assert_eq!(
+            Pattern::new_with_opts(pattern, opts)
+                .unwrap()
+                .is_match(Path::new(path)),
+            literal_dot,
+            "Unexpected result for pattern '{pattern}' against '{path}' with a literal leading dot required"
+        );
+    }
+
+    // Wildcards don't consume hidden directories, so they can be pruned
+    let pattern = Pattern::new_with_opts("**/*.rs", opts).unwrap();
+    assert!(matches!(
+        pattern.match_against(Path::new(".git")),
+        PatternMatchResult::NotMatched
+    ));
+    assert!(matches!(
+        pattern.match_against(Path::new("src")),
+        PatternMatchResult::Starved
+    ));
+
+    // ...unless the pattern names them
+    let pattern = Pattern::new_with_opts("**/.git/*", opts).unwrap();
+    assert!(matches!(
+        pattern.match_against(Path::new("a/.git")),
+        PatternMatchResult::Starved
+    ));
+
+    // Hidden components can't be spanned by `*` either
+    let opts = opts.with_star_matches_separators(true);
+
+    let pattern = Pattern::new_with_opts("src/*.c", opts).unwrap();
+    assert!(pattern.is_match(Path::new("src/a/b.c")));
+    assert!(!pattern.is_match(Path::new("src/.a/b.c")));
+    assert!(!pattern.is_match(Path::new("src/.b.c")));
+}
+
+#[test]
+fn globstar_skipping_hidden() {
+    let opts = PatternOpts::new().with_globstar_skips_hidden(true);
+
+    for (pattern, path, expected) in [
+        ("**/*.rs", "src/main.rs", true),
+        ("**/*.rs", ".git/hooks/a.rs", false),
+        ("**/*.rs", "src/.cache/a.rs", false),
+        ("**", ".git", false),
+        ("**", "src/.git", false),
+        (".git/**", ".git/hooks/a.rs", true),
+        ("**/.git/*", "a/.git/config", true),
+        // Other matchers are unaffected
+        ("*", ".gitignore", true),
+        ("**/*", "src/.gitignore", true),
+    ] {
+        assert!(
+            Pattern::new(pattern).unwrap().is_match(Path::new(path)),
+            "Pattern '{pattern}' should match '{path}' by default"
+        );
+
+        assert_eq!(
+            Pattern::new_with_opts(pattern, opts)
+                .unwrap()
+                .is_match(Path::new(path)),
+            expected,
+            "Unexpected result for pattern '{pattern}' against '{path}' with `**` skipping hidden components"
+        );
+    }
+
+    // Hidden directories are pruned, while others may still contain matches
+    let pattern = Pattern::new_with_opts("**/*.rs", opts).unwrap();
+
+    assert!(matches!(
+        pattern.match_against(Path::new(".git")),
+        PatternMatchResult::NotMatched
+    ));
+    assert!(matches!(
+        pattern.match_against(Path::new("src/.git/hooks")),
+        PatternMatchResult::NotMatched
+    ));
+    assert!(matches!(
+        pattern.match_against(Path::new("src/a")),
+        PatternMatchResult::Starved
+    ));
+}
+
+#[test]
+fn compacting_repeated_matchers() {
+    for (pattern, source) in [
+        (
+            "[[:digit:]][[:digit:]][[:digit:]][[:digit:]]-[[:digit:]][[:digit:]]-*",
+            r"^[[:digit:]]{4}\-[[:digit:]]{2}\-.*$",
+        ),
+        ("????.txt", r"^.{4}\.txt$"),
+        ("a[!a][!a]", "^a[^a]{2}$"),
+        ("[ab][ab][ac]*", "^[ab]{2}[ac].*$"),
+        ("{??|[xy][xy]}z", "^(.{2}|[xy]{2})z$"),
+    ] {
+        assert_eq!(
+            Pattern::new(pattern)
+                .unwrap()
+                .regex_sources()
+                .collect::<Vec<_>>(),
+            [source],
+            "Unexpected regular expression for pattern '{pattern}'"
+        );
+    }
+
+    let pattern = Pattern::new("[[:digit:]][[:digit:]][[:digit:]][[:digit:]]-*").unwrap();
+
+    assert!(pattern.is_match(Path::new("2024-report.txt")));
+    assert!(!pattern.is_match(Path::new("202-report.txt")));
+    assert!(!pattern.is_match(Path::new("20245-report.txt")));
+}
+
+#[test]
+fn sharing_compiled_regexes() {
+    let bypass = PatternOpts::new().with_bypass_regex_cache(true);
+
+    for pattern in ["*.rs", "[[:digit:]][[:digit:]]-*", "{a|b}?"] {
+        let cached = Pattern::new(pattern).unwrap();
+        let cached_again = Pattern::new(pattern).unwrap();
+
+        clear_regex_cache();
+
+        let uncached = Pattern::new_with_opts(pattern, bypass).unwrap();
+
+        assert!(cached.regex_sources().eq(cached_again.regex_sources()));
+        assert!(cached.regex_sources().eq(uncached.regex_sources()));
+
+        for path in ["main.rs", "12-a", "ab", "a", "bcd"] {
+            let path = Path::new(path);
+
+            assert_eq!(cached.is_match(path), uncached.is_match(path));
+            assert_eq!(cached_again.is_match(path), uncached.is_match(path));
+        }
+    }
+}
+
+#[test]
+fn wildcard_positions() {
+    let pattern = Pattern::new("a/**/b/c").unwrap();
+
+    assert_eq!(
+        (0..6)
+            .map(|idx| pattern.wildcard_at_or_after(idx))
+            .collect::<Vec<_>>(),
+        [true, true, false, false, false, false]
+    );
+
+    let pattern = Pattern::new("src/*.c").unwrap();
+    assert!(!pattern.wildcard_at_or_after(0));
+    assert!(!pattern.has_wildcard());
+
+    let opts = PatternOpts::new().with_star_matches_separators(true);
+
+    let pattern = Pattern::new_with_opts("src/*.c/d", opts).unwrap();
+    assert!(pattern.wildcard_at_or_after(1));
+    assert!(!pattern.wildcard_at_or_after(2));
+}
+
+#[test]
+fn matching_single_components() {
+    let pattern = Pattern::new("src/**/*.rs").unwrap();
+    assert_eq!(pattern.component_count(), 3);
+
+    assert!(pattern.matches_file_name(OsStr::new("lib.rs")));
+    assert!(!pattern.matches_file_name(OsStr::new("lib.rson")));
+    assert!(!pattern.matches_file_name(OsStr::new("src")));
+
+    assert!(pattern.matches_component(0, OsStr::new("src")));
+    assert!(!pattern.matches_component(0, OsStr::new("lib.rs")));
+    assert!(pattern.matches_component(1, OsStr::new("anything")));
+    assert!(pattern.matches_component(2, OsStr::new("lib.rs")));
+    assert!(!pattern.matches_component(3, OsStr::new("lib.rs")));
+
+    // Components are matched with the pattern's options
+    let pattern = Pattern::new_with_opts(
+        "../{SRC|tests}/**/*.RS",
+        PatternOpts::new()
+            .with_case_insensitive(true)
+            .with_globstar_skips_hidden(true),
+    )
+    .unwrap();
+
+    assert!(pattern.matches_component(0, OsStr::new("..")));
+    assert!(!pattern.matches_component(0, OsStr::new(".")));
+    assert!(pattern.matches_component(1, OsStr::new("src")));
+    assert!(pattern.matches_component(1, OsStr::new("Tests")));
+    assert!(!pattern.matches_component(1, OsStr::new("benches")));
+    assert!(pattern.matches_component(2, OsStr::new("dir")));
+    assert!(!pattern.matches_component(2, OsStr::new(".git")));
+    assert!(pattern.matches_file_name(OsStr::new("main.rs")));
+
+    // Patterns without components don't match any name
+    let pattern = Pattern::new("/").unwrap();
+    assert_eq!(pattern.component_count(), 0);
+    assert!(!pattern.matches_file_name(OsStr::new("a")));
+}
+
+#[test]
+fn extracting_file_name_matchers() {
+    let pattern = |pattern: &str| Pattern::new(pattern).unwrap();
+
+    for (pattern_str, extension) in [
+        ("**/*.rs", Some("rs")),
+        ("src/main.rs", Some("rs")),
+        ("*.tar.gz", Some("gz")),
+        ("test_?*.py", Some("py")),
+        ("[ab]*.d/", Some("d")),
+        ("*.{rs|toml}", None),
+        ("Makefile", None),
+        ("*.", None),
+        ("*.rs/**", None),
+        ("*.r*", None),
+        ("..", None),
+    ] {
+        assert_eq!(
+            pattern(pattern_str).required_extension(),
+            extension,
+            "Pattern '{pattern_str}'"
+        );
+    }
+
+    // Case-insensitive components may match any casing of the extension
+    let opts = PatternOpts::new().with_case_insensitive(true);
+    assert_eq!(
+        Pattern::new_with_opts("*.rs", opts)
+            .unwrap()
+            .required_extension(),
+        None
+    );
+
+    let matcher = pattern("src/**/*.{rs|toml}").file_name_pattern().unwrap();
+    assert!(matcher.is_match(OsStr::new("main.rs")));
+    assert!(matcher.is_match_str("Cargo.toml"));
+    assert!(!matcher.is_match_str("src"));
+
+    // The matcher is independent from the pattern, and uses its options
+    let matcher = Pattern::new_with_opts(
+        "*.RS",
+        PatternOpts::new()
+            .with_case_insensitive(true)
+            .with_require_literal_leading_dot(true),
+    )
+    .unwrap()
+    .file_name_pattern()
+    .unwrap();
+
+    assert!(matcher.is_match_str("main.rs"));
+    assert!(!matcher.is_match_str(".hidden.rs"));
+
+    let matcher = pattern("docs/README.md").file_name_pattern().unwrap();
+    assert!(matcher.is_match_str("README.md"));
+    assert!(!matcher.is_match_str("readme.md"));
+
+    // Only components matching single names have a matcher
+    for pattern_str in ["src/**", "..", "/"] {
+        assert!(
+            pattern(pattern_str).file_name_pattern().is_none(),
+            "Pattern '{pattern_str}'"
+        );
+    }
+}
+
+#[test]
+fn matching_ancestors_of_matches() {
+    for (pattern_str, ancestors, not_ancestors) in [
+        (
+            "a/b/**/*.toml",
+            &["a", "a/b", "a/b/c", "a/b/c/d.toml", "a/b/x.toml"][..],
+            &["b", "a/c", "/a/b"][..],
+        ),
+        (
+            "src/*.rs",
+            &["src", "src/main.rs"],
+            &["tests", "src/a/b", "main.rs"],
+        ),
+        ("**", &["a", "a/b/c", ".hidden"], &["/a"]),
+        ("**/*.rs", &["a", "a/b.rs"], &[]),
+        (
+            "/etc/*.conf",
+            &["/", "/etc", "/etc/a.conf"],
+            &["etc", "/usr", "/etc/a/b"],
+        ),
+        (
+            "../sibling/*",
+            &["..", "../sibling", "../sibling/a"],
+            &["sibling", "../other"],
+        ),
+    ] {
+        let pattern = Pattern::new(pattern_str).unwrap();
+
+        for path in ancestors {
+            assert!(
+                pattern.matches_any_ancestor(Path::new(path)),
+                "Pattern '{pattern_str}' should consider '{path}' as an ancestor"
+            );
+        }
+
+        for path in not_ancestors {
+            assert!(
+                !pattern.matches_any_ancestor(Path::new(path)),
+                "Pattern '{pattern_str}' shouldn't consider '{path}' as an ancestor"
+            );
+        }
+    }
+}
+
+#[test]
+fn pruning_descendants() {
+    let cases: &[(&str, &[(&str, bool)])] = &[
+        (
+            "a/**/b",
+            &[
+                ("", true),
+                ("a", true),
+                ("a/b", true),
+                ("a/c/d", true),
+                ("b", false),
+                ("/a", false),
+            ],
+        ),
+        (
+            "a/b",
+            &[
+                ("", true),
+                ("a", true),
+                ("a/b", false),
+                ("a/c", false),
+                ("b", false),
+            ],
+        ),
+        (
+            "**",
+            &[("", true), ("a", true), ("a/b/c", true), ("/a", false)],
+        ),
+        (
+            "/a/*",
+            &[("/", true), ("/a", true), ("/a/b", false), ("a", false)],
+        ),
+    ];
+
+    let mut ctx = MatchContext::new();
+
+    for (pattern, dirs) in cases {
+        let pattern = Pattern::new(pattern).unwrap();
+
+        for (dir, expected) in *dirs {
+            assert_eq!(
+                pattern.can_match_descendants(Path::new(dir)),
+                *expected,
+                "Unexpected result for pattern '{pattern}' and directory '{dir}'"
+            );
+
+            assert_eq!(
+                pattern.can_match_descendants_with_ctx(Path::new(dir), &mut ctx),
+                *expected
+            );
+        }
+    }
+}
+
+#[test]
+fn exporting_regexes() {
+    assert_eq!(
+        Pattern::new("a/**/b*.rs").unwrap().to_regex_string(),
+        r"^a/(?:[^/]+/)*(?:b[^/\n]*\.rs)$"
+    );
+
+    let paths = [
+        "",
+        "a",
+        "b",
+        "a/b",
+        "a/b/c",
+        "A/B",
+        ".h",
+        "a/.h",
+        ".h/b",
+        "a/.h/b",
+        "b.rs",
+        "a/b.rs",
+        "a/x/y/b.rs",
+        "a/x/.y/b.rs",
+        "src/main.rs",
+        "src/lib.RS",
+        "src/a/b/mod.rs",
+        "README.md",
+        "readme.md",
+        "../a",
+        "../../src/b",
+        "/a",
+        "/a/b",
+        "/etc/hosts",
+        "C:/Users",
+        "c:/Users/me",
+    ];
+
+    let cases = [
+        ("a/**/b*.rs", PatternOpts::new()),
+        ("**", PatternOpts::new()),
+        ("**/**", PatternOpts::new()),
+        ("**/b", PatternOpts::new()),
+        ("a/**", PatternOpts::new()),
+        ("a/**/**/b", PatternOpts::new()),
+        ("*", PatternOpts::new()),
+        ("*/*", PatternOpts::new()),
+        ("?/[ab]", PatternOpts::new()),
+        ("{a|src}/{*.rs|b}", PatternOpts::new()),
+        ("../**", PatternOpts::new()),
+        ("../../src/*", PatternOpts::new()),
+        ("/", PatternOpts::new()),
+        ("/**", PatternOpts::new()),
+        ("/etc/hosts", PatternOpts::new()),
+        ("C:\\**", PatternOpts::new()),
+        ("c:\\Users\\*", PatternOpts::new()),
+        ("", PatternOpts::new()),
+        ("src/*.rs", PatternOpts::new().with_case_insensitive(true)),
+        ("readme.*", PatternOpts::new().with_smart_case(true)),
+        ("README.*", PatternOpts::new().with_smart_case(true)),
+        ("README.md", PatternOpts::new().with_case_insensitive(true)),
+        (
+            "a/*/b",
+            PatternOpts::new()
+                .with_case_insensitive(true)
+                .with_case_folding(CaseFolding::Unicode),
+        ),
+        (
+            "src/*.rs",
+            PatternOpts::new().with_star_matches_separators(true),
+        ),
+        (
+            "**/*",
+            PatternOpts::new().with_require_literal_leading_dot(true),
+        ),
+        (
+            "a/**/b",
+            PatternOpts::new().with_globstar_skips_hidden(true),
+        ),
+        ("*.rs", PatternOpts::new().with_match_basename(true)),
+        ("a/*", PatternOpts::new().with_ascii_only(true)),
+    ];
+
+    for (pattern, opts) in cases {
+        let pattern = Pattern::new_with_opts(pattern, opts).unwrap();
+        let regex = regex::bytes::Regex::new(&pattern.to_regex_string()).unwrap();
+
+        for path in paths {
+            // Relative expressions may match absolute and empty paths
+            if !pattern.is_absolute()
+                && (path.is_empty() || path.starts_with('/') || path.contains(':'))
+            {
+                continue;
+            }
+
+            assert_eq!(
+                regex.is_match(path.as_bytes()),
+                pattern.is_match(Path::new(path)),
+                "Exported regex '{regex}' of pattern '{pattern}' disagrees on path '{path}'"
+            );
+        }
+    }
+
+    // Expressions of Unicode-aware patterns can be matched against strings
+    let pattern = Pattern::new("{a|b}/**/[[:alpha:]]*").unwrap();
+    let regex = regex::Regex::new(&pattern.to_regex_string()).unwrap();
+    assert!(regex.is_match("a/x/y/aé"));
+    assert!(!regex.is_match("c/x/y/aé"));
+}
+
+#[test]
+fn literal_paths() {
+    let pattern = Pattern::new("/etc/hosts").unwrap();
+    assert!(pattern.is_literal());
+    assert_eq!(
+        pattern.as_literal_path(),
+        Some(PathBuf::from(format!(
+            "{MAIN_SEPARATOR}etc{MAIN_SEPARATOR}hosts"
+        )))
+    );
+
+    let pattern = Pattern::new("a/*/b").unwrap();
+    assert!(!pattern.is_literal());
+    assert_eq!(pattern.as_literal_path(), None);
+
+    let pattern = Pattern::new("C:\\Users").unwrap();
+    assert!(pattern.is_literal());
+    assert_eq!(
+        pattern.as_literal_path(),
+        Some(PathBuf::from(format!("C:{MAIN_SEPARATOR}Users")))
+    );
+
+    // The prefix alone denotes the root directory
+    let pattern = Pattern::new("c:\\").unwrap();
+    assert_eq!(
+        pattern.as_literal_path(),
+        Some(PathBuf::from(format!("C:{MAIN_SEPARATOR}")))
+    );
+
+    assert_eq!(
+        Pattern::new("../../src/").unwrap().as_literal_path(),
+        Some(["..", "..", "src"].iter().collect::<PathBuf>())
+    );
+
+    // Case-insensitive components may match several paths
+    let pattern =
+        Pattern::new_with_opts("/etc/hosts", PatternOpts::new().with_case_insensitive(true))
+            .unwrap();
+    assert!(!pattern.is_literal());
+    assert_eq!(pattern.as_literal_path(), None);
+
+    let pattern =
+        Pattern::new_with_opts("a/b/c", PatternOpts::new().with_max_components(2)).unwrap();
+    assert!(pattern.is_literal());
+    assert_eq!(pattern.as_literal_path(), None);
+}
+
+#[test]
+fn inspecting_components() {
+    let pattern = Pattern::new("/a/**/b*.{rs|toml}").unwrap();
+
+    assert_eq!(pattern.prefix(), Some(PathPrefix::RootDir));
+    assert_eq!(
+        pattern.components().collect::<Vec<_>>(),
+        [
+            PatternComponent::Literal {
+                name: "a",
+                source: "a"
+            },
+            PatternComponent::AnyRecursive { implicit: false },
+            PatternComponent::Matcher {
+                source: "b*.{rs|toml}"
+            },
+        ]
+    );
+
+    let pattern = Pattern::new_with_opts(
+        "../READ\\*ME",
+        PatternOpts::new()
+            .with_backslash_escapes(true)
+            .with_case_insensitive(true)
+            .with_case_folding(CaseFolding::Unicode),
+    )
+    .unwrap();
+
+    assert_eq!(
+        pattern.components().collect::<Vec<_>>(),
+        [
+            PatternComponent::ParentDir,
+            PatternComponent::Literal {
+                name: "read*me",
+                source: "READ\\*ME"
+            },
+        ]
+    );
+
+    let pattern =
+        Pattern::new_with_opts("*.rs", PatternOpts::new().with_match_basename(true)).unwrap();
+
+    assert_eq!(
+        pattern.components().collect::<Vec<_>>(),
+        [
+            PatternComponent::AnyRecursive { implicit: true },
+            PatternComponent::Matcher { source: "*.rs" },
+        ]
+    );
+}
+
+#[test]
+fn capturing_wildcards() {
+    fn captures(pattern: &str, opts: PatternOpts, path: &str) -> Option<MatchCaptures> {
+        Pattern::new_with_opts(pattern, opts)
+            .unwrap()
+            .match_captures(Path::new(path))
+    }
+
+    fn texts(captures: &MatchCaptures) -> Vec<Option<&str>> {
+        captures.texts().iter().map(Option::as_deref).collect()
+    }
+
+    let opts = PatternOpts::new();
+
+    let caps = captures("src/*/tests/*.rs", opts, "src/parser/tests/basic.rs").unwrap();
+    assert_eq!(texts(&caps), [Some("parser"), Some("basic")]);
+    assert_eq!(caps.text(1), Some("basic"));
+    assert_eq!(caps.text(2), None);
+    assert!(caps.globstars().is_empty());
+
+    assert_eq!(
+        captures("src/*/tests/*.rs", opts, "src/parser/basic.rs"),
+        None
+    );
+
+    // Single-character matchers are captured one by one
+    let caps = captures("??-[[:digit:]][[:digit:]].txt", opts, "ab-42.txt").unwrap();
+    assert_eq!(texts(&caps), [Some("a"), Some("b"), Some("4"), Some("2")]);
+
+    // Groups are captured before the matchers they contain
+    let pattern = "{a{b|c}|d*}_?.[rt]s";
+
+    let caps = captures(pattern, opts, "ac_1.rs").unwrap();
+    assert_eq!(
+        texts(&caps),
+        [Some("ac"), Some("c"), None, Some("1"), Some("r")]
+    );
+
+    let caps = captures(pattern, opts, "dxy_2.ts").unwrap();
+    assert_eq!(
+        texts(&caps),
+        [Some("dxy"), None, Some("xy"), Some("2"), Some("t")]
+    );
+
+    // Globstars capture the components they consume
+    let caps = captures("a/**/b/**/*.rs", opts, "a/x/y/b/c.rs").unwrap();
+    assert_eq!(caps.globstars(), [vec!["x", "y"], vec![]]);
+    assert_eq!(
+        caps.globstar(0),
+        Some(["x".to_owned(), "y".to_owned()].as_slice())
+    );
+    assert_eq!(caps.globstar(2), None);
+    assert_eq!(texts(&caps), [Some("c")]);
+
+    let caps = captures("**/{src|tests}/**", opts, "a/src/b/c").unwrap();
+    assert_eq!(caps.globstars(), [vec!["a"], vec!["b", "c"]]);
+    assert_eq!(texts(&caps), [Some("src")]);
+
+    // Literal patterns don't capture anything
+    let caps = captures("a/b", opts, "a/b").unwrap();
+    assert!(caps.texts().is_empty() && caps.globstars().is_empty());
+
+    // Captures are made with the pattern's options
+    let caps = captures(
+        "*.RS",
+        PatternOpts::new().with_case_insensitive(true),
+        "main.rs",
+    )
+    .unwrap();
+    assert_eq!(texts(&caps), [Some("main")]);
+
+    let caps = captures(
+        "src/*.rs",
+        PatternOpts::new().with_star_matches_separators(true),
+        "src/a/b.rs",
+    )
+    .unwrap();
+    assert_eq!(texts(&caps), [Some("a/b")]);
+
+    // The implicit `**` prefix isn't reported
+    let caps = captures(
+        "*.rs",
+        PatternOpts::new().with_match_basename(true),
+        "a/b/c.rs",
+    )
+    .unwrap();
+    assert!(caps.globstars().is_empty());
+    assert_eq!(texts(&caps), [Some("c")]);
+
+    // Leading matchers are captured the same way when leading dots must be matched literally
+    let opts = PatternOpts::new().with_require_literal_leading_dot(true);
+    let pattern = "{.cfg|*_rc}*";
+
+    let caps = captures(pattern, opts, ".cfg.toml").unwrap();
+    assert_eq!(texts(&caps), [Some(".cfg"), None, Some(".toml")]);
+
+    let caps = captures(pattern, opts, "bash_rc").unwrap();
+    assert_eq!(texts(&caps), [Some("bash_rc"), Some("bash"), Some("")]);
+
+    assert_eq!(captures(pattern, opts, ".bash_rc"), None);
+
+    let caps = captures("*.[!.]?", opts, "main.rs").unwrap();
+    assert_eq!(texts(&caps), [Some("main"), Some("r"), Some("s")]);
+
+    let caps = captures("?[abc]*", opts, "abc").unwrap();
+    assert_eq!(texts(&caps), [Some("a"), Some("b"), Some("c")]);
+}
+
+#[test]
+fn literal_affixes() {
+    for (pattern, prefix, suffix) in [
+        ("src/foo*.rs", "src/foo", Some(".rs")),
+        ("**/*.rs", "", Some(".rs")),
+        ("src/**/test_*.rs", "src", Some(".rs")),
+        ("src/*", "src/", None),
+        ("src/**", "src", None),
+        ("src/main.rs", "src/main.rs", Some("src/main.rs")),
+        ("/etc/*.conf", "/etc/", Some(".conf")),
+        ("/etc/hosts", "/etc/hosts", Some("/etc/hosts")),
+        ("**/docs/*.md", "", Some(".md")),
+        ("**/target/debug", "", Some("target/debug")),
+        ("a/*b/c", "a/", Some("b/c")),
+        ("a/{b|c}/d.rs", "a/", Some("/d.rs")),
+        ("a[bc]d/e", "a", Some("d/e")),
+        ("../shared/?.txt", "../shared/", Some(".txt")),
+        ("C:/dir/*.txt", "", Some(".txt")),
+        ("*", "", None),
+    ] {
+        let pattern = Pattern::new(pattern).unwrap();
+
+        assert_eq!(
+            pattern.literal_prefix(),
+            prefix,
+            "Invalid prefix for '{pattern}'"
+        );
+        assert_eq!(
+            pattern.literal_suffix(),
+            suffix,
+            "Invalid suffix for '{pattern}'"
+        );
+
+        // Affixes hold for matched paths
+        for path in [
+            "src/foo.rs",
+            "src/main.rs",
+            "a/xb/c",
+            "/etc/hosts",
+            "target/debug",
+        ] {
+            if pattern.is_match_str(path) {
+                assert!(path.starts_with(prefix) && path.ends_with(suffix.unwrap_or_default()));
+            }
+        }
+    }
+
+    // Case-insensitive components aren't literal
+    let opts = PatternOpts::new().with_smart_case(true);
+    let pattern = Pattern::new_with_opts("Src/main/*.Rs", opts).unwrap();
+    assert_eq!(pattern.literal_prefix(), "Src/");
+    assert_eq!(pattern.literal_suffix(), Some(".Rs"));
+
+    let pattern = Pattern::new_with_opts("src/Main/*.rs", opts).unwrap();
+    assert_eq!(pattern.literal_prefix(), "");
+    assert_eq!(pattern.literal_suffix(), None);
+
+    // Basenames may be matched at any depth
+    let opts = PatternOpts::new().with_match_basename(true);
+    let pattern = Pattern::new_with_opts("*.log", opts).unwrap();
+    assert_eq!(pattern.literal_prefix(), "");
+    assert_eq!(pattern.literal_suffix(), Some(".log"));
+
+    // Transformed paths may not contain literals as they are
+    let opts = PatternOpts::new().with_fold_diacritics(true);
+    let pattern = Pattern::new_with_opts("cafe/*.txt", opts).unwrap();
+    assert_eq!(pattern.literal_prefix(), "");
+    assert_eq!(pattern.literal_suffix(), None);
+
+    // Joined patterns combine their affixes
+    let pattern = Pattern::new("src/a*")
+        .unwrap()
+        .join(&Pattern::new("b/*.rs").unwrap())
+        .unwrap();
+    assert_eq!(pattern.literal_prefix(), "src/a");
+    assert_eq!(pattern.literal_suffix(), Some(".rs"));
+}
+
+#[test]
+fn changing_pattern_options() {
+    let opts = PatternOpts::new()
+        .with_slash_only(true)
+        .with_max_components(3);
+    let pattern = Pattern::new_with_opts("src/*.RS", opts).unwrap();
+
+    assert_eq!(pattern.opts(), opts);
+    assert_eq!(pattern.with_opts(pattern.opts()).unwrap(), pattern);
+    assert!(!pattern.is_match(Path::new("src/main.rs")));
+
+    let caseless = pattern
+        .with_opts(pattern.opts().with_case_insensitive(true))
+        .unwrap();
+
+    assert!(caseless.opts().case_insensitive());
+    assert_eq!(caseless.opts().max_components(), Some(3));
+    assert_eq!(caseless.as_str(), pattern.as_str());
+    assert!(caseless.is_match(Path::new("src/main.rs")));
+    assert!(!caseless.is_match(Path::new("src/a/main.rs")));
+
+    // Flipping the option back gives the original pattern
+    let sensitive = caseless
+        .with_opts(caseless.opts().with_case_insensitive(false))
+        .unwrap();
+
+    assert_eq!(sensitive, pattern);
+    assert!(!sensitive.is_match(Path::new("src/main.rs")));
+
+    // The source must be valid with the new options
+    let opts = PatternOpts::new().with_normalize_dotdot(true);
+    let pattern = Pattern::new_with_opts("a/../b", opts).unwrap();
+
+    assert!(pattern.with_opts(PatternOpts::new()).is_err());
+}
+
+#[test]
+fn simplifying_patterns() {
+    let simplify = |pattern: &str, opts: PatternOpts| {
+        Pattern::new_with_opts(pattern, opts)
+            .unwrap()
+            .simplify()
+            .map(|simplified| simplified.to_string())
+    };
+
+    let opts = PatternOpts::new();
+
+    for (pattern, simplified) in [
+        ("**/**/*.rs", "**/*.rs"),
+        ("a/./b", "a/b"),
+        ("a//b", "a/b"),
+        ("./a/.", "a"),
+        ("/./a/**/**/", "/a/**/"),
+        ("C:\\.\\a\\**\\**", "C:\\a\\**"),
+        ("{a|a}.rs", "a.rs"),
+        ("{a|b|a}", "{a|b}"),
+        ("x{[\\*]|[\\*]}y", "x[\\*]y"),
+        ("{a|{b|b}}c", "{a|b}c"),
+        ("*{*|*}.txt", "*.txt"),
+        ("v{1|1}.{x|x}/{[[:digit:]]|[[:digit:]]}", "v1.x/[[:digit:]]"),
+        ("{[a-z]|[a-z]}.rs", "[a-z].rs"),
+        ("{[-a]|[-a]}", "[\\-a]"),
+    ] {
+        assert_eq!(simplify(pattern, opts).as_deref(), Some(simplified));
+    }
+
+    // Already simple patterns
+    for pattern in ["src/**/*.rs", "{a|b}/c", "**", ".", "../a", "log_12:30.txt"] {
+        assert_eq!(
+            simplify(pattern, opts),
+            None,
+            "Pattern '{pattern}' was simplified"
+        );
+    }
+
+    // Results that would be interpreted differently are kept as they are
+    assert_eq!(simplify("{..|..}/a", opts), None);
+    assert_eq!(simplify("{c|c}:", opts), None);
+
+    // The simplified pattern is recomputed
+    let pattern = Pattern::new("./src/./{main|main}.rs")
+        .unwrap()
+        .simplify()
+        .unwrap();
+    assert_eq!(pattern.as_str(), "src/main.rs");
+    assert!(pattern.is_literal());
+    assert_eq!(pattern.common_root_dir(), Path::new("src"));
+
+    // Options are taken into account
+    assert_eq!(
+        simplify("(?i)./a/{b|b}", opts.with_inline_flags(true)).as_deref(),
+        Some("(?i)a/b")
+    );
+    assert_eq!(
+        simplify("x{\\*|\\*}y", opts.with_backslash_escapes(true)).as_deref(),
+        Some("x\\*y")
+    );
+    assert_eq!(
+        simplify("{a\\,b|a\\,b}", opts.with_backslash_escapes(true)).as_deref(),
+        Some("a,b")
+    );
+    assert_eq!(
+        simplify("{a\\,b|c}{d|d}", opts.with_backslash_escapes(true)).as_deref(),
+        Some("{a\\,b|c}d")
+    );
+    assert_eq!(
+        simplify("a/b/../c", opts.with_normalize_dotdot(true)).as_deref(),
+        Some("a/c")
+    );
+
+    // A separator is kept for patterns that would be matched against basenames otherwise
+    let opts = opts.with_match_basename(true);
+    assert_eq!(simplify("./*.log", opts), None);
+    assert_eq!(simplify("./a/./*.log", opts).as_deref(), Some("a/*.log"));
+    assert_eq!(simplify("././*.log", opts).as_deref(), Some("./*.log"));
+}
+
+#[test]
+fn explaining_patterns() {
+    let explain =
+        |pattern: &str, opts: PatternOpts| Pattern::new_with_opts(pattern, opts).unwrap().explain();
+
+    assert_eq!(
+        explain("/etc/*/", PatternOpts::new()),
+        "pattern `/etc/*/`
+- absolute, from the root directory
+- component 1: literal `etc`
+- component 2: any single name
+- only matches directories
+- descendants of matches can't match"
+    );
+
+    assert_eq!(
+        explain(
+            "C:\\Users\\*\\Documents\\**\\*.{doc|docx}",
+            PatternOpts::new()
+        ),
+        "pattern `C:\\Users\\*\\Documents\\**\\*.{doc|docx}`
+- absolute, from drive `C:`
+- component 1: literal `Users`
+- component 2: any single directory
+- component 3: literal `Documents`
+- component 4: recursive wildcard
+- component 5: name matching `*.{doc|docx}`
+- descendants of matches may match"
+    );
+
+    assert_eq!(
+        explain(
+            "../a[bc]/\\[x\\]",
+            PatternOpts::new().with_backslash_escapes(true)
+        ),
+        "pattern `../a[bc]/\\[x\\]`
+- relative
+- component 1: parent directory
+- component 2: directory matching `a[bc]`
+- component 3: literal `[x]`
+- descendants of matches can't match"
+    );
+
+    assert_eq!(
+        explain(
+            "README.md",
+            PatternOpts::new()
+                .with_case_insensitive(true)
+                .with_match_basename(true)
+        ),
+        "pattern `README.md`
+- relative
+- component 1: recursive wildcard (implicit)
+- component 2: literal `readme.md`, ignoring case
+- descendants of matches may match"
+    );
+
+    assert_eq!(
+        explain(
+            "src/*/a*b",
+            PatternOpts::new().with_star_matches_separators(true)
+        ),
+        "pattern `src/*/a*b`
+- relative
+- component 1: literal `src`
+- component 2: directory matching `*`, possibly across separators
+- component 3: name matching `a*b`, possibly across separators
+- descendants of matches may match"
+    );
+}
+
+#[test]
+fn literal_roots() {
+    fn roots(pattern: &str, limit: usize) -> Vec<PathBuf> {
+        Pattern::new(pattern).unwrap().literal_roots(limit)
+    }
+
+    fn paths(paths: &[&str]) -> Vec<PathBuf> {
+        paths.iter().map(PathBuf::from).collect()
+    }
+
+    // Plain literals
+    assert_eq!(roots("src/main/**/*.rs", 10), paths(&["src/main"]));
+    assert_eq!(roots("/etc/*.conf", 10), paths(&["/etc"]));
+    assert_eq!(roots("src/main.rs", 10), paths(&["src"]));
+    assert_eq!(roots("src/**", 10), paths(&[""]));
+    assert_eq!(roots("../../shared/**", 10), paths(&["../.."]));
+    assert_eq!(roots("*.rs", 10), paths(&[""]));
+
+    // Two-branch groups
+    assert_eq!(
+        roots("src/{app|lib}/**/*.rs", 10),
+        paths(&["src/app", "src/lib"])
+    );
+    assert_eq!(
+        roots("v{1|2}.x/{a|b}/*", 10),
+        paths(&["v1.x/a", "v1.x/b", "v2.x/a", "v2.x/b"])
+    );
+
+    // Nested groups
+    assert_eq!(roots("{a|{b|c}d}/x/*", 10), paths(&["a/x", "bd/x", "cd/x"]));
+
+    // Groups containing other matchers aren't expanded
+    assert_eq!(roots("src/{app|l*}/*.rs", 10), paths(&["src"]));
+
+    // Expansion stops before exceeding the limit
+    assert_eq!(roots("{a|b}/{c|d}/*", 3), paths(&["a", "b"]));
+    assert_eq!(roots("{a|b}/{c|d}/*", 1), paths(&[""]));
+    assert_eq!(roots("{a|b}/{c|d}/*", 0), paths(&[""]));
+
+    for pattern in ["src/{app|lib}/**/*.rs", "a/b/c", "{a|b}/**"] {
+        let pattern = Pattern::new(pattern).unwrap();
+        assert_eq!(pattern.literal_roots(1), [pattern.common_root_dir()]);
+    }
+
+    // Case-insensitive literals can't be expanded
+    let pattern = Pattern::new_with_opts(
+        "src/{app|lib}/*",
+        PatternOpts::new().with_case_insensitive(true),
+    )
+    .unwrap();
+
+    assert_eq!(pattern.literal_roots(10), paths(&[""]));
+}
+
+#[test]
+fn expanding_finite_patterns() {
+    fn expand(pattern: &str) -> Option<Vec<PathBuf>> {
+        Pattern::new(pattern).unwrap().expand().unwrap()
+    }
+
+    fn paths(paths: &[&str]) -> Option<Vec<PathBuf>> {
+        Some(paths.iter().map(PathBuf::from).collect())
+    }
+
+    assert_eq!(expand("src/main.rs"), paths(&["src/main.rs"]));
+    assert_eq!(
+        expand("{src|tests}/{lib|main}.rs"),
+        paths(&["src/lib.rs", "src/main.rs", "tests/lib.rs", "tests/main.rs"])
+    );
+    assert_eq!(
+        expand("/etc/{a|b}.conf"),
+        paths(&["/etc/a.conf", "/etc/b.conf"])
+    );
+    assert_eq!(expand("../{a|b}"), paths(&["../a", "../b"]));
+    assert_eq!(expand("build/"), paths(&["build"]));
+
+    // Nested groups
+    assert_eq!(
+        expand("v{1|2{a|b}}.txt"),
+        paths(&["v1.txt", "v2a.txt", "v2b.txt"])
+    );
+    assert_eq!(expand("{a|{a|b}}"), paths(&["a", "b"]));
+
+    // Patterns matching an infinite number of paths
+    for pattern in ["*.rs", "src/**", "a?", "[ab]", "{a|b*}", "src/**/main.rs"] {
+        assert_eq!(expand(pattern), None, "Pattern '{pattern}'");
+    }
+
+    // Too many expansions
+    assert_eq!(
+        expand(&"{0|1|2|3|4|5|6|7|8|9}/".repeat(4)).unwrap().len(),
+        10_000
+    );
+    assert_eq!(
+        Pattern::new(&"{0|1|2|3|4|5|6|7|8|9}/".repeat(5))
+            .unwrap()
+            .expand(),
+        Err(TooManyExpansions { count: 100_000 })
+    );
+
+    // Options are taken into account
+    let pattern = |pattern: &str, opts: PatternOpts| Pattern::new_with_opts(pattern, opts).unwrap();
+
+    assert_eq!(
+        pattern("a.rs", PatternOpts::new().with_case_insensitive(true)).expand(),
+        Ok(None)
+    );
+    assert_eq!(
+        pattern("a.rs", PatternOpts::new().with_match_basename(true)).expand(),
+        Ok(None)
+    );
+    assert_eq!(
+        pattern("a/b/c", PatternOpts::new().with_max_components(2)).expand(),
+        Ok(Some(vec![]))
+    );
+}
+
+#[test]
+fn trailing_separator_means_directories_only() {
+    for (pattern, dir_only) in [
+        ("src/", true),
+        ("**/target/", true),
+        ("a/**/", true),
+        ("src\\", true),
+        ("src", false),
+        ("/", false),
+        ("a/b/.", false),
+    ] {
+        let pattern = Pattern::new(pattern).unwrap();
+
+        assert_eq!(pattern.is_dir_only(), dir_only);
+        assert_eq!(
+            pattern.match_target_hint() == TargetHint::RequiresDirectory,
+            dir_only
+        );
+    }
+
+    let pattern = Pattern::new("src/").unwrap();
+
+    assert!(pattern.is_match_dir_aware(Path::new("src"), true));
+    assert!(!pattern.is_match_dir_aware(Path::new("src"), false));
+    assert!(!pattern.is_match_dir_aware(Path::new("lib"), true));
+
+    // The file type is unknown without the filesystem
+    assert!(pattern.is_match(Path::new("src")));
+
+    let pattern = Pattern::new("src").unwrap();
+
+    assert!(pattern.is_match_dir_aware(Path::new("src"), true));
+    assert!(pattern.is_match_dir_aware(Path::new("src"), false));
+}
+
+#[test]
+fn matching_basenames() {
+    let opts = PatternOpts::new().with_match_basename(true);
+
+    test_pattern_with(
+        PatternTest {
+            pattern_str: "*.log",
+            should_match: &["c.log", "a/c.log", "a/b/c.log"],
+            should_not_match: &["c.txt", "a/c.log/d", "/c.log"],
+        },
+        opts,
+    );
+
+    test_pattern_with(
+        PatternTest {
+            pattern_str: "Makefile",
+            should_match: &["Makefile", "a/b/Makefile"],
+            should_not_match: &["a/Makefile/b"],
+        },
+        opts,
+    );
+
+    // Patterns with separators behave as usual
+    for pattern_str in ["dir/*.log", "./*.log", "/*.log"] {
+        let pattern = compile_pattern(pattern_str, opts);
+
+        assert!(!pattern.is_match(Path::new("x/dir/a.log")));
+        assert!(!pattern.is_match(Path::new("dir/x/a.log")));
+    }
+
+    assert!(compile_pattern("dir/*.log", opts).is_match(Path::new("dir/a.log")));
+
+    // The implicit recursion is visible to the walker
+    let pattern = compile_pattern("*.log", opts);
+
+    assert!(pattern.has_wildcard());
+    assert_eq!(pattern.common_root_dir(), Path::new(""));
+    assert_eq!(pattern.literal_roots(10), [Path::new("")]);
+
+    let pattern = compile_pattern("Makefile", opts);
+
+    assert!(pattern.has_wildcard());
+    assert_eq!(pattern.common_root_dir(), Path::new(""));
+
+    assert!(!compile_pattern("*.log", PatternOpts::default()).has_wildcard());
+}
+
+#[test]
+fn pattern_stats() {
+    assert_eq!(
+        *Pattern::new("src/**/*.rs").unwrap().stats(),
+        PatternStats {
+            components: 3,
+            literals: 2,
+            wildcards: 1,
+            globstars: 1,
+            ..PatternStats::default()
+        }
+    );
+
+    assert_eq!(
+        *Pattern::new("/a/./{b|c}//*_?[!0-9][[:alpha:]x]")
+            .unwrap()
+            .stats(),
+        PatternStats {
+            components: 3,
+            literals: 4,
+            wildcards: 2,
+            classes: 2,
+            groups: 1,
+            max_nesting_depth: 1,
+            absolute: true,
+            ..PatternStats::default()
+        }
+    );
+
+    assert_eq!(
+        *Pattern::new("../{a|{b*|c{d|e}}|f}/{g|h}").unwrap().stats(),
+        PatternStats {
+            components: 3,
+            literals: 9,
+            wildcards: 1,
+            groups: 4,
+            max_nesting_depth: 3,
+            ..PatternStats::default()
+        }
+    );
+
+    assert_eq!(*Pattern::new("").unwrap().stats(), PatternStats::default());
+
+    // Case sensitivity may differ between components
+    let opts = PatternOpts::new().with_smart_case(true);
+
+    assert!(compile_pattern("Src/*.rs", opts).stats().case_insensitive);
+    assert!(!compile_pattern("Src/*.RS", opts).stats().case_insensitive);
+
+    let opts = PatternOpts::new()
+        .with_case_insensitive(true)
+        .with_match_basename(true);
+
+    // The implicit `**` of basename matching isn't part of the pattern's syntax
+    assert_eq!(
+        *compile_pattern("*.rs", opts).stats(),
+        PatternStats {
+            components: 1,
+            literals: 1,
+            wildcards: 1,
+            case_insensitive: true,
+            ..PatternStats::default()
+        }
+    );
+}
+
+#[test]
+#[cfg(feature = "serde")]
+fn serializing_pattern_stats() {
+    let stats = serde_json::to_value(Pattern::new("/{a|b}/**/*.rs").unwrap().stats()).unwrap();
+
+    assert_eq!(
+        stats,
+        serde_json::json!({
+            "components": 3,
+            "literals": 3,
+            "wildcards": 1,
+            "globstars": 1,
+            "classes": 0,
+            "groups": 1,
+            "max_nesting_depth": 1,
+            "absolute": true,
+            "case_insensitive": false,
+        })
+    );
+}
+
+#[test]
+#[cfg(feature = "serde")]
+fn serializing_options() {
+    let opts = PatternOpts::new()
+        .with_case_insensitive(true)
+        .with_case_folding(CaseFolding::Ascii)
+        .with_unicode_normalization(UnicodeNormalization::Nfc)
+        .with_max_components(4);
+
+    let value = serde_json::to_value(opts).unwrap();
+    assert_eq!(value["case_insensitive"], true);
+    assert_eq!(value["case_folding"], "ascii");
+    assert_eq!(value["unicode_normalization"], "nfc");
+    assert_eq!(value["max_components"], 4);
+    assert_eq!(value["regex_size_limit"], serde_json::Value::Null);
+
+    assert_eq!(serde_json::from_value::<PatternOpts>(value).unwrap(), opts);
+
+    // Missing keys fall back to their default value
+    assert_eq!(
+        serde_json::from_str::<PatternOpts>(r#"{ "case_insensitive": true }"#).unwrap(),
+        PatternOpts::new().with_case_insensitive(true)
+    );
+
+    assert_eq!(
+        serde_json::from_str::<PatternOpts>("{}").unwrap(),
+        PatternOpts::new()
+    );
+}
+
+#[test]
+#[cfg(feature = "serde")]
+fn serializing_patterns() {
+    let pattern = Pattern::new("src/{a|b}/**/*.rs").unwrap();
+
+    let value = serde_json::to_value(&pattern).unwrap();
+    assert_eq!(value, "src/{a|b}/**/*.rs");
+    assert_eq!(serde_json::from_value::<Pattern>(value).unwrap(), pattern);
+
+    let rules = serde_json::from_str::<Vec<Pattern>>(r#"["*.md", "C:\\Users\\**"]"#).unwrap();
+    assert!(rules[0].is_match(Path::new("README.md")));
+    assert_eq!(rules[1].as_str(), "C:\\Users\\**");
+
+    // Invalid patterns are rejected while deserializing
+    let err = serde_json::from_str::<Pattern>(r#""src/[""#).unwrap_err();
+    assert!(err.to_string().starts_with("invalid pattern at offset"));
+
+    assert!(serde_json::from_str::<Pattern>("42").is_err());
+
+    // Options aren't preserved
+    let pattern =
+        Pattern::new_with_opts("*.MD", PatternOpts::new().with_case_insensitive(true)).unwrap();
+    let deserialized =
+        serde_json::from_value::<Pattern>(serde_json::to_value(&pattern).unwrap()).unwrap();
+
+    assert_eq!(deserialized.as_str(), pattern.as_str());
+    assert_ne!(deserialized, pattern);
+    assert!(!deserialized.is_match(Path::new("readme.md")));
+}
+
+#[test]
+fn building_options() {
+    let opts = PatternOpts::new();
+
+    assert!(!opts.case_insensitive() && !opts.slash_only());
+    assert_eq!(opts.case_folding(), CaseFolding::Unicode);
+    assert_eq!(opts.regex_size_limit(), None);
+
+    let opts = opts
+        .with_case_insensitive(true)
+        .with_case_folding(CaseFolding::Ascii)
+        .with_regex_size_limit(1000)
+        .with_slash_only(true)
+        .with_slash_only(false);
+
+    assert!(opts.case_insensitive() && !opts.slash_only());
+    assert_eq!(opts.case_folding(), CaseFolding::Ascii);
+    assert_eq!(opts.regex_size_limit(), Some(1000));
+
+    // Layering options over defaults
+    let defaults = PatternOpts::new()
+        .with_smart_case(true)
+        .with_case_folding(CaseFolding::Ascii)
+        .with_regex_size_limit(10);
+
+    let layered = PatternOpts::new()
+        .with_match_basename(true)
+        .with_regex_size_limit(20)
+        .or(defaults);
+
+    assert!(layered.match_basename() && layered.smart_case());
+    assert!(!layered.case_insensitive());
+    assert_eq!(layered.case_folding(), CaseFolding::Ascii);
+    assert_eq!(layered.regex_size_limit(), Some(20));
+
+    let layered = PatternOpts::new().or(PatternOpts::new());
+
+    assert!(!layered.match_basename());
+    assert_eq!(layered.case_folding(), CaseFolding::Unicode);
+    assert_eq!(layered.regex_size_limit(), None);
+
+    // Options are applied when building patterns
+    assert!(
+        compile_pattern("*.RS", layered.with_case_insensitive(true)).is_match(Path::new("a.rs"))
+    );
+}
+
+#[test]
+fn displaying_patterns() {
+    for pattern_str in [
+        "src/**/*.rs",
+        "/abs/{a|b}/[!c]?",
+        "../shared/*.proto",
+        "C:\\Users\\**\\*.txt",
+        "c:/dir/",
+        "\\\\?\\C:\\dir",
+        "\\dir",
+        "a//b/./c/",
+        "",
+    ] {
+        let pattern = Pattern::new(pattern_str).unwrap();
+
+        assert_eq!(pattern.to_string(), pattern_str);
+        assert_eq!(pattern.as_str(), pattern_str);
+    }
+
+    // The source is kept as provided, regardless of the options
+    let pattern = Pattern::new_with_opts(
+        "(?i)src/\\*.RS",
+        PatternOpts::new()
+            .with_inline_flags(true)
+            .with_backslash_escapes(true),
+    )
+    .unwrap();
+
+    assert_eq!(pattern.to_string(), "(?i)src/\\*.RS");
+}
+
+#[test]
+fn comparing_patterns() {
+    let opts = PatternOpts::new().with_case_insensitive(true);
+
+    assert_eq!(
+        Pattern::new("src/*.rs").unwrap(),
+        Pattern::new("src/*.rs").unwrap()
+    );
+    assert_ne!(
+        Pattern::new("src/*.rs").unwrap(),
+        Pattern::new("src/*.toml").unwrap()
+    );
+
+    // Options are part of the comparison
+    assert_ne!(
+        Pattern::new("src/*.rs").unwrap(),
+        Pattern::new_with_opts("src/*.rs", opts).unwrap()
+    );
+
+    // Patterns written differently are different, even if they're equivalent
+    assert_ne!(Pattern::new("a/b").unwrap(), Pattern::new("a//b").unwrap());
+
+    let patterns = [
+        ("src/*.rs", PatternOpts::new()),
+        ("src/*.rs", PatternOpts::new()),
+        ("src/*.rs", opts),
+        ("src/*.rs", PatternOpts::new().with_case_insensitive(true)),
+        ("**/*.md", PatternOpts::new()),
+        ("**/*.md", opts),
+        ("**/*.md", PatternOpts::new()),
+    ]
+    .map(|(pattern, opts)| Pattern::new_with_opts(pattern, opts).unwrap());
+
+    // Compiled expressions have interior mutability, but equality and hashing don't depend on them
+    #[allow(clippy::mutable_key_type)]
+    let deduplicated = patterns.iter().cloned().collect::<HashSet<_>>();
+
+    assert_eq!(deduplicated.len(), 4);
+    assert!(deduplicated.contains(&Pattern::new_with_opts("**/*.md", opts).unwrap()));
+    assert!(!deduplicated.contains(&Pattern::new("**/*.txt").unwrap()));
+}
+
+#[test]
+fn parsing_from_strings() {
+    let pattern: Pattern = "src/**/*.rs".parse().unwrap();
+    assert!(pattern.is_match(Path::new("src/a/main.rs")));
+    assert_eq!(pattern, Pattern::new("src/**/*.rs").unwrap());
+
+    assert!(matches!(
+        "src/[".parse::<Pattern>(),
+        Err(PatternError::Parsing(_))
+    ));
+
+    assert_eq!(
+        Pattern::try_from("*.md").unwrap(),
+        Pattern::new("*.md").unwrap()
+    );
+    assert_eq!(
+        Pattern::try_from(String::from("*.md")).unwrap(),
+        Pattern::new("*.md").unwrap()
+    );
+    assert!(Pattern::try_from("a**b").is_err());
+    assert!(Pattern::try_from(String::from("{a")).is_err());
+}
+
+#[test]
+fn literal_patterns() {
+    let pattern = Pattern::literal("a[b]*").unwrap();
+
+    assert!(pattern.is_match(Path::new("a[b]*")));
+
+    for path in ["ab", "abc", "a[b]", "a[b]*c", "a[b]x", "A[B]*"] {
+        assert!(!pattern.is_match(Path::new(path)), "{path}");
+    }
+
+    test_pattern_with(
+        PatternTest {
+            pattern_str: "report{v1|v2}/**/?:final.txt",
+            should_match: &["report{v1|v2}/**/?:final.txt"],
+            should_not_match: &["reportv1/a/b:final.txt", "report{v1|v2}/**/x:final.txt"],
+        },
+        PatternOpts::new().with_literal(true),
+    );
+
+    // Separators and prefixes are still recognized
+    let pattern = Pattern::literal("/dir/./[x]\\*.txt").unwrap();
+
+    assert!(pattern.is_absolute());
+    assert!(pattern.is_match(Path::new("/dir/[x]/*.txt")));
+    assert!(!pattern.is_match(Path::new("/dir/[x]/a.txt")));
+    assert_eq!(pattern.common_root_dir(), Path::new("/dir/[x]"));
+
+    assert!(matches!(
+        Pattern::literal("C:\\a*").unwrap().prefix(),
+        Some(PathPrefix::WindowsDrive(_))
+    ));
+
+    // Components only look like drives if they are followed by a separator
+    let pattern = Pattern::literal("a:b").unwrap();
+    assert!(!pattern.is_absolute());
+    assert_eq!(pattern.prefix(), None);
+    assert!(matches!(
+        pattern.components().collect::<Vec<_>>().as_slice(),
+        [PatternComponent::Literal { name: "a:b", .. }]
+    ));
+
+    assert!(Pattern::literal("C:").unwrap().is_absolute());
+
+    // Backslashes are ordinary characters when they can't be separators
+    let opts = PatternOpts::new()
+        .with_literal(true)
+        .with_backslash_escapes(true)
+        .with_slash_only(true);
+
+    let pattern = compile_pattern("a\\*/b", opts);
+
+    assert!(pattern.is_match(Path::new("a\\*/b")));
+    assert!(!pattern.is_match(Path::new("a/*/b")));
+
+    // Other options still apply
+    let pattern = compile_pattern(
+        "[A]",
+        PatternOpts::new()
+            .with_literal(true)
+            .with_case_insensitive(true),
+    );
+
+    assert!(pattern.is_match(Path::new("[a]")));
+    assert!(!pattern.is_match(Path::new("a")));
+
+    assert!(Pattern::literal("a/../b").is_err());
+}
+
+#[test]
+fn parsing_partial_patterns() {
+    let parse = |input: &str| {
+        let (ast, errors) = Pattern::parse_partial(input, PatternOpts::new());
+
+        let errors = errors.iter().map(|err| err.to_string()).collect::<Vec<_>>();
+
+        (ast, errors)
+    };
+
+    let component = |span, kind| AstComponent { span, kind };
+
+    // Valid patterns are fully understood
+    assert_eq!(
+        parse("/src/*.rs/"),
+        (
+            Some(PatternAst {
+                case_insensitive: None,
+                prefix: Some(PathPrefix::RootDir),
+                components: vec![
+                    component(1..4, ComponentKind::Literal("src".to_owned())),
+                    component(
+                        5..9,
+                        ComponentKind::Matchers(vec![
+                            CharsMatcher::AnyChars,
+                            CharsMatcher::Literal(".rs".to_owned())
+                        ])
+                    ),
+                ],
+                dir_only: true
+            }),
+            vec![]
+        )
+    );
+
+    // Partially-typed group
+    assert_eq!(
+        parse("src/**/*.{rs,to"),
+        (
+            Some(PatternAst {
+                case_insensitive: None,
+                prefix: None,
+                components: vec![
+                    component(0..3, ComponentKind::Literal("src".to_owned())),
+                    component(4..6, ComponentKind::Wildcard),
+                    component(7..15, ComponentKind::Invalid),
+                ],
+                dir_only: false
+            }),
+            vec!["invalid pattern at offset 15: unexpected end of input".to_owned()]
+        )
+    );
+
+    // All errors are reported
+    assert_eq!(
+        parse("a/[b/c**d/e}/../f"),
+        (
+            Some(PatternAst {
+                case_insensitive: None,
+                prefix: None,
+                components: vec![
+                    component(0..1, ComponentKind::Literal("a".to_owned())),
+                    component(2..4, ComponentKind::Invalid),
+                    component(5..9, ComponentKind::Invalid),
+                    component(10..12, ComponentKind::Invalid),
+                    component(13..15, ComponentKind::Invalid),
+                    component(16..17, ComponentKind::Literal("f".to_owned())),
+                ],
+                dir_only: false
+            }),
+            [
+                "invalid pattern at offset 4: expected character ']'",
+                "invalid pattern at offset 6: Wildcard components '**' must be preceded and followed by path separators",
+                "invalid pattern at offset 11: Unexpected character '}'",
+                "invalid pattern at offset 13: Cannot use '..' components after the beginning of the pattern"
+            ]
+            .map(String::from)
+            .to_vec()
+        )
+    );
+
+    // Components can't be interpreted without a valid prefix
+    let (ast, errors) = parse("\\\\?\\x/a[/b");
+
+    assert_eq!(ast, None);
+    assert_eq!(errors.len(), 2);
+
+    // Syntax options are used
+    let (ast, errors) =
+        Pattern::parse_partial("a\\*/{b", PatternOpts::new().with_backslash_escapes(true));
+
+    assert_eq!(
+        ast.unwrap().components,
+        [
+            component(0..3, ComponentKind::Literal("a*".to_owned())),
+            component(4..6, ComponentKind::Invalid),
+        ]
+    );
+    assert_eq!(errors.len(), 1);
+
+    // Errors are reported for exactly the patterns that fail to build
+    for input in [
+        "", "./", "a//b", "../*", "/a/b/", "{a|b}/**", "]", "a/**b", "/..", "a/../b", "[a", "{a}",
+    ] {
+        assert_eq!(
+            parse(input).1.is_empty(),
+            Pattern::new(input).is_ok(),
+            "Unexpected errors for {input:?}"
+        );
+    }
+}
+
+#[test]
+fn case_insensitive_filesystems() {
+    let opts = PatternOpts::new().with_case_insensitive(true);
+    let fs_opts = opts.with_case_insensitive_fs(true);
+
+    // Leading literals can only be used as roots if the filesystem ignores their casing
+    for (pattern_str, root, fs_root) in [
+        ("Src/Foo/*.rs", "", "Src/Foo"),
+        ("../Src/*", "..", "../Src"),
+        ("Src/Foo/**", "", "Src"),
+        ("Src/{a|b}/*.rs", "", "Src"),
+        ("Src", "", ""),
+    ] {
+        assert_eq!(
+            compile_pattern(pattern_str, opts).common_root_dir(),
+            Path::new(root)
+        );
+        assert_eq!(
+            compile_pattern(pattern_str, fs_opts).common_root_dir(),
+            Path::new(fs_root)
+        );
+    }
+
+    assert_eq!(
+        compile_pattern("Src/{a|B}/*.rs", fs_opts).literal_roots(10),
+        [Path::new("Src/a"), Path::new("Src/B")]
+    );
+
+    // Case-sensitive components aren't affected
+    let pattern = compile_pattern(
+        "Src/*.rs",
+        PatternOpts::new()
+            .with_smart_case(true)
+            .with_case_insensitive_fs(true),
+    );
+
+    assert_eq!(pattern.common_root_dir(), Path::new("Src"));
+
+    // Matching is unchanged
+    let pattern = compile_pattern("Src/Foo/*.RS", fs_opts);
+
+    assert!(pattern.is_match(Path::new("src/foo/a.rs")));
+    assert!(!pattern.is_match(Path::new("src/bar/a.rs")));
+
+    // Platform defaults
+    let opts = PatternOpts::platform_defaults();
+    let insensitive = cfg!(any(windows, target_os = "macos"));
+
+    assert_eq!(opts.case_insensitive(), insensitive);
+    assert_eq!(opts.case_insensitive_fs(), insensitive);
+    assert_eq!(
+        compile_pattern("*.TXT", opts).is_match(Path::new("readme.txt")),
+        insensitive
+    );
+}
+
+#[test]
+fn unicode_normalization() {
+    let composed = "caf\u{e9}";
+    let decomposed = "cafe\u{301}";
+
+    let nfc = PatternOpts::new().with_unicode_normalization(UnicodeNormalization::Nfc);
+
+    // Both forms don't match each other by default
+    assert!(!compile_pattern(composed, PatternOpts::new()).is_match(Path::new(decomposed)));
+    assert!(!compile_pattern(decomposed, PatternOpts::new()).is_match(Path::new(composed)));
+
+    for (pattern, path) in [
+        (composed, decomposed),
+        (decomposed, composed),
+        (composed, composed),
+        (decomposed, decomposed),
+    ] {
+        // Literals
+        assert!(compile_pattern(pattern, nfc).is_match(Path::new(path)));
+        assert!(
+            compile_pattern(&format!("docs/{pattern}/*.md"), nfc)
+                .is_match(&Path::new("docs").join(path).join("a.md"))
+        );
+
+        // Components compiled to regular expressions
+        assert!(compile_pattern(&format!("*{pattern}"), nfc).is_match(Path::new(path)));
+        assert!(
+            compile_pattern(&format!("{{{pattern}|tea}}.txt"), nfc)
+                .is_match(Path::new(&format!("{path}.txt")))
+        );
+
+        // Case-insensitive components
+        assert!(
+            compile_pattern(&pattern.to_uppercase(), nfc.with_case_insensitive(true))
+                .is_match(Path::new(path))
+        );
+    }
+
+    assert!(!compile_pattern(composed, nfc).is_match(Path::new("cafe")));
+    assert!(compile_pattern("caf?", nfc).is_match(Path::new(decomposed)));
+
+    // Components containing non-ASCII characters are never part of the common root directory
+    assert_eq!(
+        compile_pattern(&format!("docs/{composed}/*.md"), nfc).common_root_dir(),
+        Path::new("docs")
+    );
+
+    assert_eq!(
+        compile_pattern(&format!("docs/{composed}/*.md"), PatternOpts::new()).common_root_dir(),
+        Path::new("docs").join(composed)
+    );
+
+    // Matching contexts can be reused across normalized and non-normalized paths
+    let pattern = compile_pattern(composed, nfc);
+    let mut ctx = MatchContext::new();
+
+    assert!(pattern.is_match_with_ctx(Path::new(decomposed), &mut ctx));
+    assert!(pattern.is_match_with_ctx(Path::new(composed), &mut ctx));
+    assert!(!pattern.is_match_with_ctx(Path::new("tea"), &mut ctx));
+}
+
+#[test]
+fn folding_diacritics() {
+    let opts = PatternOpts::new().with_fold_diacritics(true);
+
+    for (pattern, path, default) in [
+        ("cafe*", "caf\u{e9}_menu.pdf", false),
+        // `*` matches the combining mark by default
+        ("cafe*", "cafe\u{301}_menu.pdf", true),
+        ("caf\u{e9}*", "cafe_menu.pdf", false),
+        ("pina", "pi\u{f1}a", false),
+        ("pi\u{f1}a", "pin\u{303}a", false),
+        ("docs/re*sum\u{e9}.txt", "docs/r\u{e9}sum\u{e9}.txt", false),
+        ("caf?", "caf\u{e9}", true),
+        ("{cafe|tea}.txt", "caf\u{e9}.txt", false),
+        ("[\u{e9}]", "e", false),
+        ("[\u{e9}]", "\u{e9}", true),
+        ("pi[n]a", "pi\u{f1}a", false),
+    ] {
+        assert!(
+            compile_pattern(pattern, opts).is_match(Path::new(path)),
+            "Pattern '{pattern}' should match '{path}' with diacritics folded"
+        );
+
+        assert_eq!(
+            compile_pattern(pattern, PatternOpts::new()).is_match(Path::new(path)),
+            default,
+            "Unexpected default result for pattern '{pattern}' against '{path}'"
+        );
+    }
+
+    // Other characters are unaffected
+    assert!(!compile_pattern("cafe", opts).is_match(Path::new("cafes")));
+    assert!(!compile_pattern("[\u{e9}]", opts).is_match(Path::new("a")));
+
+    // Case sensitivity is independent
+    assert!(!compile_pattern("cafe", opts).is_match(Path::new("CAF\u{c9}")));
+    assert!(
+        compile_pattern("cafe", opts.with_case_insensitive(true)).is_match(Path::new("CAF\u{c9}"))
+    );
+
+    // Bracket expressions still work without folding
+    let pattern = compile_pattern("caf[\u{e9}e]", PatternOpts::new());
+    assert!(pattern.is_match(Path::new("caf\u{e9}")));
+    assert!(pattern.is_match(Path::new("cafe")));
+    assert!(!pattern.is_match(Path::new("cafa")));
+
+    // Components containing non-ASCII characters are never part of the common root directory
+    assert_eq!(
+        compile_pattern("docs/caf\u{e9}/*.md", opts).common_root_dir(),
+        Path::new("docs")
+    );
+}
+
+#[test]
+fn ascii_only_matching() {
+    let opts = PatternOpts::new().with_ascii_only(true);
+
+    for (pattern, path, default, ascii_only) in [
+        ("src/*.rs", "src/main.rs", true, true),
+        ("a?c", "abc", true, true),
+        ("[!x]b", "ab", true, true),
+        // Multi-byte characters are made of several bytes
+        ("caf?", "caf\u{e9}", true, false),
+        ("caf??", "caf\u{e9}", false, true),
+        ("[!a]", "\u{e9}", true, false),
+        // Character classes are ASCII-only in both modes, while negated ones match a single byte
+        ("[[:alpha:]]", "\u{e9}", false, false),
+        ("[![:alpha:]]", "\u{e9}", true, false),
+        ("[![:alpha:]][![:alpha:]]", "\u{e9}", false, true),
+        // Literal characters and `*` still match them
+        ("caf\u{e9}", "caf\u{e9}", true, true),
+        ("caf*", "caf\u{e9}", true, true),
+        ("{caf\u{e9}|tea}.txt", "caf\u{e9}.txt", true, true),
+        ("*\u{e9}*", "caf\u{e9}s", true, true),
+    ] {
+        assert_eq!(
+            compile_pattern(pattern, PatternOpts::new()).is_match(Path::new(path)),
+            default,
+            "Unexpected default result for pattern '{pattern}' against '{path}'"
+        );
+
+        assert_eq!(
+            compile_pattern(pattern, opts).is_match(Path::new(path)),
+            ascii_only,
+            "Unexpected result for pattern '{pattern}' against '{path}' in ASCII-only mode"
+        );
+    }
+
+    // Only ASCII letters are folded
+    let opts = opts.with_case_insensitive(true);
+
+    for pattern in ["README.md", "READ*.MD"] {
+        assert!(compile_pattern(pattern, opts).is_match(Path::new("readme.md")));
+    }
+
+    for pattern in ["CAF\u{c9}", "CAF\u{c9}*"] {
+        assert!(!compile_pattern(pattern, opts).is_match(Path::new("caf\u{e9}")));
+        assert!(
+            compile_pattern(pattern, PatternOpts::new().with_case_insensitive(true))
+                .is_match(Path::new("caf\u{e9}"))
+        );
+    }
+}
+
+#[test]
+fn joining_patterns() {
+    let join = |a: &str, b: &str| Pattern::new(a).unwrap().join(&Pattern::new(b).unwrap());
+
+    let joined = join("a/*", "b/**").unwrap();
+    let parsed = Pattern::new("a/*/b/**").unwrap();
+
+    assert_eq!(joined, parsed);
+    assert_eq!(joined.as_str(), "a/*/b/**");
+    assert_eq!(joined.common_root_dir(), parsed.common_root_dir());
+    assert_eq!(joined.has_wildcard(), parsed.has_wildcard());
+    assert_eq!(joined.stats(), parsed.stats());
+    assert_eq!(
+        joined.components().collect::<Vec<_>>(),
+        parsed.components().collect::<Vec<_>>()
+    );
+
+    for path in ["a/x/b", "a/x/b/c/d", "a/b", "a/x/c"] {
+        assert_eq!(
+            joined.is_match(Path::new(path)),
+            parsed.is_match(Path::new(path))
+        );
+    }
+
+    // Literal roots continue into the appended pattern
+    let joined = join("/crates/core", "src/**/*.rs").unwrap();
+    assert_eq!(
+        joined.common_root_dir(),
+        Path::new("/crates/core/src")
+            .components()
+            .collect::<PathBuf>()
+    );
+    assert!(joined.is_match(Path::new("/crates/core/src/a/lib.rs")));
+    assert!(joined.has_wildcard());
+
+    assert_eq!(join("a/", "b/").unwrap().as_str(), "a/b/");
+    assert!(join("a/", "b/").unwrap().is_dir_only());
+    assert!(!join("a/", "b").unwrap().is_dir_only());
+    assert_eq!(join(".", "*.rs").unwrap().as_str(), "*.rs");
+    assert_eq!(join("/", "etc").unwrap().as_str(), "/etc");
+    assert_eq!(join("a/*", "").unwrap().as_str(), "a/*");
+    assert_eq!(join("..", "../src").unwrap().as_str(), "../../src");
+
+    // Captures are located in the joined source
+    let captures = join("*", "*.rs")
+        .unwrap()
+        .match_captures(Path::new("a/b.rs"));
+    assert_eq!(
+        captures.unwrap().texts(),
+        [Some("a".to_owned()), Some("b".to_owned())]
+    );
+
+    assert_eq!(join("a", "/x"), Err(JoinError::Absolute));
+    assert_eq!(join("/a/**", "/x"), Err(JoinError::Absolute));
+    assert_eq!(join("a", "../x"), Err(JoinError::ParentDir));
+    assert_eq!(join("/", "../x"), Err(JoinError::ParentDir));
+
+    let insensitive =
+        Pattern::new_with_opts("b", PatternOpts::new().with_case_insensitive(true)).unwrap();
+    assert_eq!(
+        Pattern::new("a").unwrap().join(&insensitive),
+        Err(JoinError::IncompatibleOptions)
+    );
+
+    let opts = PatternOpts::new().with_inline_flags(true);
+    let flagged = Pattern::new_with_opts("(?i)b", opts).unwrap();
+    assert_eq!(
+        Pattern::new_with_opts("a", opts).unwrap().join(&flagged),
+        Err(JoinError::IncompatibleOptions)
+    );
+
+    let opts = PatternOpts::new().with_match_basename(true);
+    let basename = Pattern::new_with_opts("*.rs", opts).unwrap();
+    assert_eq!(
+        Pattern::new_with_opts("src", opts).unwrap().join(&basename),
+        Err(JoinError::IncompatibleOptions)
+    );
+}
+
+#[test]
+fn rebasing_patterns() {
+    let rebase = |pattern: &str, base: &str| Pattern::new(pattern).unwrap().rebase(Path::new(base));
+
+    let rebased = rebase("src/**/*.rs", "/home/me/project").unwrap();
+    assert_eq!(rebased.as_str(), "/home/me/project/src/**/*.rs");
+    assert!(rebased.is_absolute());
+    assert_eq!(
+        rebased.common_root_dir(),
+        ["/", "home", "me", "project", "src"]
+            .iter()
+            .collect::<PathBuf>()
+            .as_path()
+    );
+    assert!(rebased.is_match(Path::new("/home/me/project/src/a/lib.rs")));
+    assert!(!rebased.is_match(Path::new("src/a/lib.rs")));
+
+    // Windows drives
+    let rebased = rebase("*.txt", "C:\\Users\\me").unwrap();
+    assert_eq!(rebased.as_str(), "C:/Users/me/*.txt");
+    assert_eq!(
+        rebased.prefix(),
+        Some(PathPrefix::WindowsDrive('C'.try_into().unwrap()))
+    );
+    assert_eq!(
+        rebased.common_root_dir(),
+        Path::new(&format!("C:{MAIN_SEPARATOR}Users{MAIN_SEPARATOR}me"))
+    );
+    assert!(rebased.is_match(Path::new("c:\\Users\\me\\a.txt")));
+    assert!(!rebased.is_match(Path::new("D:\\Users\\me\\a.txt")));
+
+    // `..` components are resolved lexically, in the base and at the start of the pattern
+    assert_eq!(
+        rebase("*.rs", "/a/b/../c/./d/..").unwrap().as_str(),
+        "/a/c/*.rs"
+    );
+    assert_eq!(
+        rebase("../../shared/*.proto", "/a/b/c").unwrap().as_str(),
+        "/a/shared/*.proto"
+    );
+    assert_eq!(rebase("../..", "/a/b/c").unwrap().as_str(), "/a");
+    assert_eq!(rebase("../x", "/").unwrap().as_str(), "/x");
+    assert_eq!(rebase("x", "../a/..").unwrap().as_str(), "../x");
+    assert_eq!(rebase("../x", "a/b").unwrap().as_str(), "a/x");
+
+    // Special characters of the base are matched literally
+    let rebased = rebase("*", "/data/[v1]*").unwrap();
+    assert!(rebased.is_match(Path::new("/data/[v1]*/a")));
+    assert!(!rebased.is_match(Path::new("/data/v/a")));
+
+    // Escaped components aren't part of the common root directory
+    assert_eq!(
+        rebased.common_root_dir(),
+        ["/", "data"].iter().collect::<PathBuf>().as_path()
+    );
+
+    // Absolute patterns can't be anchored somewhere else
+    assert_eq!(rebase("/etc/*", "/home"), Err(JoinError::Absolute));
+    assert_eq!(rebase("C:\\*", "/home"), Err(JoinError::Absolute));
+
+    assert_eq!(
+        rebase("*", "\\\\?\\UNC\\server"),
+        Err(JoinError::InvalidBase)
+    );
+    assert_eq!(
+        Pattern::new_with_opts("*", PatternOpts::new().with_disable_brackets(true))
+            .unwrap()
+            .rebase(Path::new("/a*")),
+        Err(JoinError::InvalidBase)
+    );
+
+    // Inline flags only apply to whole patterns, with or without leading `..` components
+    let inline_flags = PatternOpts::new().with_inline_flags(true);
+
+    for pattern in ["(?i)../X", "(?i)X", "(?-i)../X"] {
+        assert_eq!(
+            Pattern::new_with_opts(pattern, inline_flags)
+                .unwrap()
+                .rebase(Path::new("/a/b")),
+            Err(JoinError::IncompatibleOptions)
+        );
+    }
+}
+
+#[test]
+fn splitting_patterns() {
+    let split = |pattern: &str, components: usize| {
+        Pattern::new(pattern)
+            .unwrap()
+            .split_prefix(components)
+            .map(|(head, tail)| (head.as_str().to_owned(), tail.as_str().to_owned()))
+    };
+
+    let owned = |head: &str, tail: &str| Some((head.to_owned(), tail.to_owned()));
+
+    assert_eq!(
+        split("crates/*/src/**/*.rs", 2),
+        owned("crates/*", "src/**/*.rs")
+    );
+    assert_eq!(
+        split("crates/*/src/**/*.rs", 1),
+        owned("crates", "*/src/**/*.rs")
+    );
+    assert_eq!(
+        split("crates/*/src/**/*.rs", 3),
+        owned("crates/*/src", "**/*.rs")
+    );
+    assert_eq!(split("/etc/*/", 1), owned("/etc", "*/"));
+    assert_eq!(split("../a/b", 1), owned("..", "a/b"));
+
+    // The first part must match a fixed number of path components
+    assert_eq!(split("crates/*/src/**/*.rs", 4), None);
+    assert_eq!(split("**/a/b", 1), None);
+
+    // Neither part may be empty
+    assert_eq!(split("a/b", 0), None);
+    assert_eq!(split("a/b", 2), None);
+    assert_eq!(split("/", 0), None);
+
+    // Both parts are built again with the same options
+    let (head, tail) = Pattern::new("crates/*/src/**/*.rs")
+        .unwrap()
+        .split_prefix(2)
+        .unwrap();
+
+    assert_eq!(head.common_root_dir(), Path::new("crates"));
+    assert!(!head.has_wildcard());
+    assert_eq!(tail.common_root_dir(), Path::new("src"));
+    assert!(tail.has_wildcard());
+    assert!(!tail.is_absolute());
+
+    let opts = PatternOpts::new()
+        .with_case_insensitive(true)
+        .with_match_basename(true);
+    let (head, tail) = Pattern::new_with_opts("src/*.rs", opts)
+        .unwrap()
+        .split_prefix(1)
+        .unwrap();
+
+    assert_eq!(head.opts(), opts);
+    assert_eq!(tail.opts(), opts);
+    assert!(head.is_match(Path::new("SRC")));
+    assert!(!head.is_match(Path::new("a/src")));
+    assert!(tail.is_match(Path::new("A.RS")));
+    assert!(!tail.is_match(Path::new("a/a.rs")));
+
+    let opts = PatternOpts::new().with_inline_flags(true);
+    let (head, tail) = Pattern::new_with_opts("(?i)a/b", opts)
+        .unwrap()
+        .split_prefix(1)
+        .unwrap();
+
+    assert!(head.is_match(Path::new("A")));
+    assert!(tail.is_match(Path::new("B")));
+
+    // Parent directory components are kept as-is when matching basenames
+    let (head, tail) =
+        Pattern::new_with_opts("../a/b", PatternOpts::new().with_match_basename(true))
+            .unwrap()
+            .split_prefix(1)
+            .unwrap();
+
+    assert_eq!(head.as_str(), "..");
+    assert_eq!(tail.as_str(), "a/b");
+
+    // The maximum number of components is split between both parts
+    let opts = PatternOpts::new().with_max_components(3);
+    let (head, tail) = Pattern::new_with_opts("a/**", opts)
+        .unwrap()
+        .split_prefix(1)
+        .unwrap();
+
+    assert_eq!(head.opts().max_components(), Some(1));
+    assert_eq!(tail.opts().max_components(), Some(2));
+
+    assert!(
+        Pattern::new_with_opts(
+            "a/*/b",
+            PatternOpts::new().with_star_matches_separators(true)
+        )
+        .unwrap()
+        .split_prefix(2)
+        .is_none()
+    );
+
+    // Matching both parts is equivalent to matching the whole pattern
+    let corpus = [
+        "crates/a/src/lib.rs",
+        "crates/a/src/x/y/main.rs",
+        "crates/a/src/lib.toml",
+        "crates/a/tests/lib.rs",
+        "crates/b/src",
+        "crates/src/lib.rs",
+        "other/a/src/lib.rs",
+        "crates",
+        "crates/a/b/src/lib.rs",
+        "src/lib.rs",
+    ];
+
+    for (pattern, opts) in [
+        "crates/*/src/**/*.rs",
+        "crates/{a|b}/src/*.rs",
+        "*/*/src",
+        "crates/?/**",
+        "crates/a/src/lib.rs",
+    ]
+    .into_iter()
+    .flat_map(|pattern| {
+        [0, 2, 3, 4]
+            .into_iter()
+            .map(|max| match max {
+                0 => PatternOpts::new(),
+                _ => PatternOpts::new().with_max_components(max),
+            })
+            .map(move |opts| (pattern, opts))
+    }) {
+        let whole = Pattern::new_with_opts(pattern, opts).unwrap();
+
+        for components in 1..whole.component_count() {
+            let Some((head, tail)) = whole.split_prefix(components) else {
+                continue;
+            };
+
+            for path in corpus {
+                let parts = path.split('/').collect::<Vec<_>>();
+
+                let split_match = parts.len() > components
+                    && head.is_match(Path::new(&parts[..components].join("/")))
+                    && tail.is_match(Path::new(&parts[components..].join("/")));
+
+                assert_eq!(
+                    split_match,
+                    whole.is_match(Path::new(path)),
+                    "Pattern '{pattern}' split after {components} components with {opts:?}, on path '{path}'"
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn computing_depth_bounds() {
+    let bounds = |pattern: &str| Pattern::new(pattern).unwrap().depth_bounds();
+
+    assert_eq!(bounds("a/b/c"), (3, Some(3)));
+    assert_eq!(bounds("/a/b/c/"), (3, Some(3)));
+    assert_eq!(bounds("**"), (0, None));
+    assert_eq!(bounds("a/**/b"), (2, None));
+    assert_eq!(bounds("*/*"), (2, Some(2)));
+    assert_eq!(bounds("../*.rs"), (2, Some(2)));
+    assert_eq!(bounds("/"), (0, Some(0)));
+
+    let opts = PatternOpts::new().with_star_matches_separators(true);
+    assert_eq!(
+        Pattern::new_with_opts("a/*.rs", opts)
+            .unwrap()
+            .depth_bounds(),
+        (2, None)
+    );
+
+    let opts = PatternOpts::new().with_max_components(4);
+    assert_eq!(
+        Pattern::new_with_opts("a/**", opts).unwrap().depth_bounds(),
+        (1, Some(4))
+    );
+    assert_eq!(
+        Pattern::new_with_opts("a/b", opts).unwrap().depth_bounds(),
+        (2, Some(2))
+    );
+
+    // Basenames are matched at any depth
+    let opts = PatternOpts::new().with_match_basename(true);
+    assert_eq!(
+        Pattern::new_with_opts("*.rs", opts).unwrap().depth_bounds(),
+        (1, None)
+    );
+}
+
+#[test]
+fn building_from_literal_paths() {
+    let path = Path::new("/data/weird [1] {copy}/a*b?.txt");
+    let pattern = Pattern::from_literal_path(path).unwrap();
+
+    assert!(pattern.is_literal());
+    assert!(pattern.is_absolute());
+    assert!(pattern.is_match(path));
+    assert!(pattern.is_match(Path::new("//data/./weird [1] {copy}/a*b?.txt")));
+    assert!(!pattern.is_match(Path::new("/data/weird 1 copy/a*b?.txt")));
+    assert!(!pattern.is_match(Path::new("/data/weird [1] {copy}/axb?.txt")));
+    assert!(!pattern.is_match(Path::new("/data/weird [1] {copy}")));
+    assert_eq!(
+        pattern.as_literal_path(),
+        Some(
+            ["/", "data", "weird [1] {copy}", "a*b?.txt"]
+                .iter()
+                .collect::<PathBuf>()
+        )
+    );
+
+    assert_eq!(Pattern::try_from(path).unwrap(), pattern);
+
+    // Prefixes are preserved
+    let pattern = Pattern::from_literal_path(Path::new("\\\\?\\c:\\Users\\**")).unwrap();
+    assert_eq!(
+        pattern.prefix(),
+        Some(PathPrefix::WindowsDrive('C'.try_into().unwrap()))
+    );
+    assert!(pattern.is_match(Path::new("C:\\Users\\**")));
+    assert!(!pattern.is_match(Path::new("C:\\Users\\me")));
+
+    let pattern = Pattern::from_literal_path(Path::new("a/{b|c}")).unwrap();
+    assert!(!pattern.is_absolute());
+    assert!(pattern.is_match(Path::new("a/{b|c}")));
+    assert!(!pattern.is_match(Path::new("a/b")));
+
+    // Relative components looking like drives aren't mistaken for ones
+    let pattern = Pattern::from_literal_path(Path::new("./c:/d")).unwrap();
+    assert!(!pattern.is_absolute());
+    assert!(pattern.is_match(Path::new("./c:/d")));
+
+    // Parent directories are resolved lexically
+    for (path, resolved) in [
+        ("./a/../b", "b"),
+        ("a/b/../../c/d", "c/d"),
+        ("../a/../../b", "../../b"),
+        ("/../a/../b", "/b"),
+    ] {
+        let pattern = Pattern::from_literal_path(Path::new(path)).unwrap();
+
+        assert_eq!(pattern.as_str(), resolved);
+        assert!(pattern.is_literal());
+    }
+
+    for unsupported in [
+        "\\\\server\\share\\a",
+        "\\\\?\\UNC\\server\\share",
+        "\\\\.\\device",
+    ] {
+        assert!(matches!(
+            Pattern::from_literal_path(Path::new(unsupported)),
+            Err(PatternError::UnsupportedPath(path)) if path == Path::new(unsupported)
+        ));
+    }
+}
+
+#[test]
+fn matching_strings() {
+    let pattern = Pattern::new("a/**/*.txt").unwrap();
+
+    for path in [
+        "a/b.txt",
+        "a/b/c.txt",
+        "./a//b.txt",
+        "a/b.rs",
+        "b/a.txt",
+        "/a/b.txt",
+    ] {
+        assert_eq!(
+            pattern.is_match_str(path),
+            pattern.is_match(Path::new(path)),
+            "Mismatch for path '{path}'"
+        );
+    }
+
+    assert!(matches!(
+        Pattern::new("/a/*").unwrap().match_against_str("a/b"),
+        PatternMatchResult::PathNotAbsolute
+    ));
+    assert!(matches!(
+        Pattern::new("a/*").unwrap().match_against_str("/a/b"),
+        PatternMatchResult::PathIsAbsolute
+    ));
+    assert!(matches!(
+        Pattern::new("a/b/c").unwrap().match_against_str("a/b"),
+        PatternMatchResult::Starved
+    ));
+
+    // Only `/` is a separator, and there is no drive prefix
+    let pattern = Pattern::new("*").unwrap();
+    assert!(pattern.is_match_str("a\\b"));
+    assert!(!pattern.is_match(Path::new("a\\b")));
+    assert!(pattern.is_match_str("C:"));
+    assert!(matches!(
+        Pattern::new("C:\\**").unwrap().match_against_str("C:/a"),
+        PatternMatchResult::PathNotAbsolute
+    ));
+    assert!(Pattern::new("C:/a").unwrap().is_match(Path::new("C:/a")));
+    assert!(!Pattern::new("C:/a").unwrap().is_match_str("C:/a"));
+    assert!(Pattern::new("\\a\\*").unwrap().is_match_str("/a/b"));
+
+    // Characters are transformed like in paths
+    let opts = PatternOpts::new().with_fold_diacritics(true);
+    assert!(
+        Pattern::new_with_opts("cafe/*", opts)
+            .unwrap()
+            .is_match_str("café/menu")
+    );
+
+    let mut ctx = MatchContext::new();
+    let pattern = Pattern::new("**/*.rs").unwrap();
+
+    for path in ["src/lib.rs", "a/b/c.rs"] {
+        assert!(matches!(
+            pattern.match_against_str_with_ctx(path, &mut ctx),
+            PatternMatchResult::Matched
+        ));
+    }
+}
+
+#[test]
+fn matching_bytes() {
+    for pattern in ["a/**/*.txt", "/a/*", "[[:alpha:]]/b?", "**/{c|d}"] {
+        let pattern = Pattern::new(pattern).unwrap();
+
+        for path in ["a/b.txt", "a/b/c.txt", "./a//b.txt", "/a/b", "a/bc", "x/d"] {
+            assert_eq!(
+                pattern.is_match_bytes(path.as_bytes()),
+                pattern.is_match_str(path),
+                "Mismatch for pattern '{pattern}' and path '{path}'"
+            );
+        }
+    }
+
+    // Non-UTF-8 bytes are matched like Unix paths
+    #[cfg(unix)]
+    for pattern in ["a/*", "a/?", "a/**", "a/b", "*/*"] {
+        use std::{ffi::OsStr, os::unix::ffi::OsStrExt};
+
+        let pattern = Pattern::new(pattern).unwrap();
+
+        for path in [&b"a/\xff"[..], b"a/b\xff/c", b"\xff/\xff"] {
+            assert_eq!(
+                pattern.is_match_bytes(path),
+                pattern.is_match(Path::new(OsStr::from_bytes(path))),
+                "Mismatch for pattern '{pattern}' and path {path:?}"
+            );
+        }
+    }
+
+    // There is no drive prefix
+    assert!(matches!(
+        Pattern::new("C:\\**").unwrap().match_against_bytes(b"C:/a"),
+        PatternMatchResult::PathNotAbsolute
+    ));
+
+    let mut ctx = MatchContext::new();
+    let pattern = Pattern::new("**/*.rs").unwrap();
+
+    for path in [&b"src/lib.rs"[..], b"a/b/c.rs"] {
+        assert!(matches!(
+            pattern.match_against_bytes_with_ctx(path, &mut ctx),
+            PatternMatchResult::Matched
+        ));
+    }
+}
+
+#[test]
+fn explaining_failed_matches() {
+    let explain = |pattern: &str, path: &str| {
+        let trace = Pattern::new(pattern)
+            .unwrap()
+            .explain_match(Path::new(path));
+        let failure = trace.failure().cloned();
+
+        (
+            trace.result(),
+            failure.map(|failure| {
+                (
+                    failure.pattern_component(),
+                    failure.path_component(),
+                    failure.reason(),
+                )
+            }),
+        )
+    };
+
+    let (result, failure) = explain("a/b*/c", "a/bx/d");
+    assert!(matches!(result, PatternMatchResult::NotMatched));
+    assert_eq!(failure, Some((2, 2, MatchFailureReason::LiteralMismatch)));
+
+    assert_eq!(
+        explain("a/b*/c", "a/x/c").1,
+        Some((1, 1, MatchFailureReason::RegexMismatch))
+    );
+    assert_eq!(
+        explain("a/b*/c", "a/bx").1,
+        Some((2, 2, MatchFailureReason::Starved))
+    );
+    assert_eq!(
+        explain("a/b*/c", "a/bx/c/d").1,
+        Some((3, 3, MatchFailureReason::ExtraComponents))
+    );
+    let (result, failure) = explain("a/b*/c", "a/bx/c");
+    assert!(matches!(result, PatternMatchResult::Matched));
+    assert_eq!(failure, None);
+
+    // Descendants can't have `..` components
+    let (result, failure) = explain("../../a", "..");
+    assert!(matches!(result, PatternMatchResult::NotMatched));
+    assert_eq!(failure, Some((1, 1, MatchFailureReason::MissingParentDir)));
+
+    assert_eq!(
+        explain("../a", "b/a").1,
+        Some((0, 0, MatchFailureReason::LiteralMismatch))
+    );
+
+    // The failure where the most of the pattern was matched is reported
+    let (result, failure) = explain("a/**/x/y/z", "a/x/y/b/x/c");
+    assert!(matches!(result, PatternMatchResult::Starved));
+    assert_eq!(failure, Some((4, 3, MatchFailureReason::LiteralMismatch)));
+
+    assert_eq!(
+        explain("a/**/x/y", "b/x/y").1,
+        Some((0, 0, MatchFailureReason::LiteralMismatch))
+    );
+    assert_eq!(
+        explain("a/**/b/c", "a/x/b").1,
+        Some((3, 3, MatchFailureReason::Starved))
+    );
+
+    // The globstar may consume more components
+    let (result, failure) = explain("**/x/y", "a/b");
+    assert!(matches!(result, PatternMatchResult::Starved));
+    assert_eq!(failure, Some((1, 2, MatchFailureReason::Starved)));
+
+    let opts = PatternOpts::new().with_globstar_skips_hidden(true);
+    let trace = Pattern::new_with_opts("a/**", opts)
+        .unwrap()
+        .explain_match(Path::new("a/b/.git/c"));
+    let failure = trace.failure().unwrap();
+    assert_eq!(
+        (
+            failure.pattern_component(),
+            failure.path_component(),
+            failure.reason()
+        ),
+        (1, 2, MatchFailureReason::HiddenComponent)
+    );
+
+    let opts = PatternOpts::new().with_max_components(2);
+    let trace = Pattern::new_with_opts("**", opts)
+        .unwrap()
+        .explain_match(Path::new("a/b/c"));
+    assert_eq!(
+        trace.failure().unwrap().reason(),
+        MatchFailureReason::TooManyComponents
+    );
+    assert_eq!(trace.failure().unwrap().path_component(), 2);
+
+    assert_eq!(
+        explain("C:\\a", "D:\\a").1,
+        Some((0, 0, MatchFailureReason::PrefixMismatch))
+    );
+
+    // Paths rejected before matching components don't have a failure location
+    let (result, failure) = explain("/a", "a");
+    assert!(matches!(result, PatternMatchResult::PathNotAbsolute));
+    assert_eq!(failure, None);
+}
+
+#[test]
+fn matching_split_paths() {
+    let pattern = Pattern::new("/src/**/*.rs").unwrap();
+    let components = ["src", "a", "lib.rs"].map(OsStr::new);
+
+    assert!(matches!(
+        pattern.match_components(Some(PathPrefix::RootDir), components),
+        PatternMatchResult::Matched
+    ));
+    assert!(matches!(
+        pattern.match_components(None, components),
+        PatternMatchResult::PathNotAbsolute
+    ));
+    assert!(matches!(
+        pattern.match_components(Some(PathPrefix::RootDir), components[..2].iter().copied()),
+        PatternMatchResult::Starved
+    ));
+
+    // Empty and `.` components are ignored, while separators aren't interpreted
+    let pattern = Pattern::new("a/*").unwrap();
+    assert!(matches!(
+        pattern.match_components(None, ["a", "", ".", "b"].map(OsStr::new)),
+        PatternMatchResult::Matched
+    ));
+    assert!(matches!(
+        pattern.match_components(None, ["a", "b/c"].map(OsStr::new)),
+        PatternMatchResult::Matched
+    ));
+
+    let drive = PathPrefix::WindowsDrive('C'.try_into().unwrap());
+    let pattern = Pattern::new("C:\\Users\\*").unwrap();
+    assert!(matches!(
+        pattern.match_components(Some(drive), ["Users", "me"].map(OsStr::new)),
+        PatternMatchResult::Matched
+    ));
+    assert!(matches!(
+        pattern.match_components(Some(PathPrefix::RootDir), ["Users", "me"].map(OsStr::new)),
+        PatternMatchResult::IncompatiblePrefix
+    ));
+
+    // Components are transformed like paths
+    let pattern =
+        Pattern::new_with_opts("cafe/*", PatternOpts::new().with_fold_diacritics(true)).unwrap();
+    let mut ctx = MatchContext::new();
+
+    for _ in 0..2 {
+        assert!(matches!(
+            pattern.match_components_with_ctx(None, ["café", "menü"].map(OsStr::new), &mut ctx),
+            PatternMatchResult::Matched
+        ));
+    }
+}
+
+#[test]
+fn semantic_comparison() {
+    use Comparison::{No, Unknown, Yes};
+
+    let pattern = |pattern: &str| Pattern::new(pattern).unwrap();
+
+    for (a, b, subset) in [
+        ("src/main.rs", "src/*.rs", Yes),
+        ("src/*.rs", "src/**", Yes),
+        ("src/test_*.rs", "src/*.rs", Yes),
+        ("src/*_test.rs", "src/*.rs", Yes),
+        ("a/*/b", "a/**/b", Yes),
+        ("a/**/b", "a/*/b", No),
+        ("a/**/**/b", "a/**/b", Yes),
+        ("a/b/c/d", "**/c/*", Yes),
+        ("../a", "../*", Yes),
+        ("/etc/*.conf", "/**", Yes),
+        ("build/", "build", Yes),
+        ("build", "build/", No),
+        ("src/*.rs", "src/main.rs", No),
+        ("**/*.rs", "src/**/*.rs", No),
+        ("*.rs", "/*.rs", No),
+        ("src/**", "src/*.rs", No),
+        ("*.{rs|toml}", "*.rs", Unknown),
+        ("[ab].rs", "[abc].rs", Unknown),
+        ("{a|b}*", "[ab]*", Unknown),
+    ] {
+        assert_eq!(
+            pattern(a).matches_subset_of(&pattern(b)),
+            subset,
+            "Comparing '{a}' with '{b}'"
+        );
+    }
+
+    // Hidden names aren't matched by wildcards with some options
+    let opts = PatternOpts::new().with_require_literal_leading_dot(true);
+    let hidden = Pattern::new_with_opts("*", opts).unwrap();
+
+    assert_eq!(pattern("a").matches_subset_of(&hidden), Yes);
+    assert_eq!(pattern(".a").matches_subset_of(&hidden), No);
+    assert_eq!(pattern("a*").matches_subset_of(&hidden), Yes);
+    assert_eq!(pattern("*a").matches_subset_of(&hidden), No);
+
+    // Equivalence requires both patterns to be subsets of each other
+    for (a, b, equivalent) in [
+        ("a/**/**/b", "a/**/b", Yes),
+        ("a/./b", "a/b", Yes),
+        ("a/*/b", "a/**/b", No),
+        ("{a|b}", "[ab]", Unknown),
+    ] {
+        assert_eq!(
+            pattern(a).is_equivalent_to(&pattern(b)),
+            equivalent,
+            "Comparing '{a}' with '{b}'"
+        );
+    }
+}
+
+#[test]
+fn matching_glob_compat() {
+    let pattern = |pattern: &str| Pattern::new_glob_compat(pattern).unwrap();
+
+    // Test vectors ported from the `glob` crate
+    for invalid in [
+        "a/**b",
+        "a/bc**",
+        "a/*****",
+        "a/b**c**d",
+        "a**b",
+        "abc[def",
+        "abc[!def",
+        "abc[",
+        "abc[!",
+        "abc[d",
+        "abc[!d",
+        "abc[]",
+        "abc[!]",
+    ] {
+        assert!(
+            Pattern::new_glob_compat(invalid).is_err(),
+            "Pattern '{invalid}' should be invalid"
+        );
+    }
+
+    for (pattern_str, matching, not_matching) in [
+        ("a*b", &["a_b"][..], &[][..]),
+        ("a*b*c", &["abc", "a_b_c", "a___b___c"], &["abcd"]),
+        (
+            "abc*abc*abc",
+            &["abcabcabcabcabcabcabc"],
+            &["abcabcabcabcabcabcabca"],
+        ),
+        (
+            "a*a*a*a*a*a*a*a*a",
+            &["aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa"],
+            &[],
+        ),
+        ("a*b[xyz]c*d", &["abxcdbxcddd"], &[]),
+        (
+            "some/**/needle.txt",
+            &[
+                "some/needle.txt",
+                "some/one/needle.txt",
+                "some/one/two/needle.txt",
+                "some/other/needle.txt",
+            ],
+            &["some/other/notthis.txt"],
+        ),
+        ("**", &["abcde", ".asdf", "x/.asdf"], &[]),
+        ("**/test", &["one/two/test", "one/test", "test"], &[]),
+        (
+            "/**/test",
+            &["/one/two/test", "/one/test", "/test"],
+            &["/one/notthis", "/notthis"],
+        ),
+        ("**/.*", &[".abc", "abc/.abc"], &["ab.c", "abc/ab.c"]),
+        ("a[0-9]b", &["a0b", "a5b", "a9b"], &["a_b"]),
+        ("a[!0-9]b", &["a_b"], &["a0b", "a5b", "a9b"]),
+        ("[-]", &["-"], &["a"]),
+        ("[!-]", &["a"], &["-"]),
+        ("a[[]b[]]c", &["a[b]c"], &["abc"]),
+        ("[]]", &["]"], &["a"]),
+        ("[!]]", &["a"], &["]"]),
+        ("[a-]", &["a", "-"], &["b"]),
+        ("a/b", &["a/b"], &["a", "b"]),
+    ] {
+        let pattern = pattern(pattern_str);
+
+        for path in matching {
+            assert!(
+                pattern.is_match(Path::new(path)),
+                "Pattern '{pattern_str}' doesn't match '{path}'"
+            );
+        }
+
+        for path in not_matching {
+            assert!(
+                !pattern.is_match(Path::new(path)),
+                "Pattern '{pattern_str}' matches '{path}'"
+            );
+        }
+    }
+
+    for ranges in ["[a-z123]", "[1a-z23]", "[123a-z]"] {
+        let (pattern, negated) = (pattern(ranges), pattern(&ranges.replace('[', "[!")));
+
+        for c in ('a'..='z').chain(['1', '2', '3']) {
+            let path = c.to_string();
+            assert!(pattern.is_match(Path::new(&path)) && !negated.is_match(Path::new(&path)));
+        }
+
+        for c in ('A'..='Z').chain(['4', '-']) {
+            let path = c.to_string();
+            assert!(!pattern.is_match(Path::new(&path)) && negated.is_match(Path::new(&path)));
+        }
+    }
+
+    // Differences with Globby's own syntax
+    for (pattern_str, matching, not_matching) in [
+        ("[^a]", &["^", "a"][..], &["b"][..]),
+        ("{a,b}|c", &["{a,b}|c"], &["a", "b"]),
+        ("a]b", &["a]b"], &[]),
+        ("[[:alpha:]]", &[":]", "a]"], &["a", "b"]),
+    ] {
+        let pattern = pattern(pattern_str);
+
+        for path in matching {
+            assert!(
+                pattern.is_match(Path::new(path)),
+                "Pattern '{pattern_str}' doesn't match '{path}'"
+            );
+        }
+
+        for path in not_matching {
+            assert!(
+                !pattern.is_match(Path::new(path)),
+                "Pattern '{pattern_str}' matches '{path}'"
+            );
+        }
+    }
+
+    // Special characters of bases are escaped with brackets
+    let rebased = pattern("*.rs").rebase(Path::new("/a*[b]")).unwrap();
+    assert_eq!(rebased.as_str(), "/a[*][[]b]/*.rs");
+    assert!(rebased.is_match(Path::new("/a*[b]/main.rs")));
+    assert!(!rebased.is_match(Path::new("/ab/main.rs")));
+
+    // Strings are escaped with brackets too
+    assert_eq!(Pattern::escape_glob_compat("a[b]*"), "a[[]b][*]");
+
+    assert!(!pattern(&Pattern::escape_glob_compat("c:")).is_absolute());
+
+    for str in [
+        "a[b]",
+        "what?.txt",
+        "*",
+        "key:value",
+        "{a|b}",
+        "[!x]",
+        "a]b",
+    ] {
+        let pattern = pattern(&Pattern::escape_glob_compat(str));
+
+        assert!(
+            pattern.is_match(Path::new(str)),
+            "{str} doesn't match itself"
+        );
+        assert!(!pattern.has_wildcard());
+        assert!(!pattern.is_absolute());
+    }
+
+    // Escape sequences of the default syntax aren't supported
+    assert!(
+        Pattern::new_with_opts(
+            &Pattern::escape("a[b]"),
+            PatternOpts::new().with_glob_compat(true)
+        )
+        .is_err()
+    );
+
+    // Reversed ranges are rejected instead of never matching
+    assert!(Pattern::new_glob_compat("[z-a]").is_err());
+
+    // `\` is an ordinary character, except on Windows
+    #[cfg(not(windows))]
+    assert!(pattern("a\\*").is_match(Path::new("a\\b")));
+
+    // Case sensitivity can still be changed
+    let opts = PatternOpts::new()
+        .with_glob_compat(true)
+        .with_case_insensitive(true);
+    let pattern = Pattern::new_with_opts("[a-c]*.TXT", opts).unwrap();
+    assert!(pattern.is_match(Path::new("B.txt")));
+
+    let opts = opts.with_ascii_only(true);
+    let pattern = Pattern::new_with_opts("[a-c]*.TXT", opts).unwrap();
+    assert!(pattern.is_match(Path::new("B.txt")));
+    assert!(!pattern.is_match(Path::new("D.txt")));
 }
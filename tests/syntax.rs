@@ -0,0 +1,82 @@
+use std::path::Path;
+
+use globby::{
+    Pattern, PatternOpts,
+    syntax::{
+        CharRole, EXTGLOB_SPECIAL_CHARS, SPECIAL_CHARS, classify, classify_in_brackets, is_special,
+        needs_escaping,
+    },
+};
+
+#[test]
+fn classifying_characters() {
+    for (c, role) in [
+        ('/', CharRole::Separator),
+        ('\\', CharRole::Separator),
+        ('*', CharRole::Wildcard),
+        ('?', CharRole::Wildcard),
+        ('[', CharRole::ClassDelim),
+        (']', CharRole::ClassDelim),
+        ('{', CharRole::GroupDelim),
+        ('}', CharRole::GroupDelim),
+        ('|', CharRole::GroupDelim),
+        ('(', CharRole::GroupDelim),
+        (')', CharRole::GroupDelim),
+        ('a', CharRole::Literal),
+        ('.', CharRole::Literal),
+        ('-', CharRole::Literal),
+        ('é', CharRole::Literal),
+        (' ', CharRole::Literal),
+        (':', CharRole::Literal),
+    ] {
+        assert_eq!(classify(c), role, "Invalid role for character '{c}'");
+    }
+
+    assert_eq!(classify_in_brackets('\\'), CharRole::Escape);
+    assert_eq!(classify_in_brackets(':'), CharRole::ClassDelim);
+    assert_eq!(classify_in_brackets('a'), CharRole::Literal);
+
+    for c in SPECIAL_CHARS {
+        assert!(is_special(*c));
+        assert_ne!(classify(*c), CharRole::Literal);
+    }
+}
+
+#[test]
+fn escaping_requirements() {
+    for str in ["a", "file.txt", "with space", "é-ü_1", "log_12:30.txt"] {
+        assert!(!needs_escaping(str));
+
+        // Strings which don't need escaping are parsed as literals
+        let pattern = Pattern::new(str).unwrap();
+        assert!(pattern.is_match(Path::new(str)));
+        assert!(!pattern.has_wildcard());
+    }
+
+    for str in ["*", "a?", "[a]", "{a|b}", "a/b", "a\\b"] {
+        assert!(needs_escaping(str));
+    }
+
+    // Special characters can be matched once escaped in brackets
+    for c in SPECIAL_CHARS {
+        if *c == '/' || *c == '\\' {
+            continue;
+        }
+
+        let pattern = Pattern::new(&format!("[\\{c}]")).unwrap();
+        assert!(pattern.is_match(Path::new(&c.to_string())));
+    }
+
+    // Parentheses can be escaped in brackets, with or without extended glob operators
+    for c in EXTGLOB_SPECIAL_CHARS {
+        for opts in [PatternOpts::new(), PatternOpts::new().with_extglob(true)] {
+            let pattern = Pattern::new_with_opts(&format!("[\\{c}]"), opts).unwrap();
+            assert!(pattern.is_match(Path::new(&c.to_string())));
+        }
+    }
+
+    // `:` only needs to be escaped inside brackets
+    let pattern = Pattern::new("[\\:]").unwrap();
+    assert!(pattern.is_match(Path::new(":")));
+    assert!(Pattern::new("[a:]").is_err());
+}
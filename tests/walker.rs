@@ -0,0 +1,1677 @@
+mod common;
+
+use std::{
+    collections::BTreeSet,
+    fs,
+    path::{Component, Path, PathBuf},
+};
+
+use globby::{
+    CachedWalker, LimitKind, Ordering, Pattern, PatternMatchResult, PatternMatcher, PatternOpts,
+    WalkError, Walker,
+};
+
+use self::common::{Fixture, collect_sorted, to_slash_string};
+
+#[test]
+fn walking_fixture() {
+    let fixture = Fixture::new(&["a/b/c.txt", "a/d.rs", "e.txt", "f/"]);
+
+    let walker = Walker::new(Pattern::new("**/*.txt").unwrap(), fixture.path());
+
+    assert_eq!(
+        collect_sorted(walker),
+        ["a/b/c.txt", "e.txt"].map(String::from).into()
+    );
+}
+
+#[test]
+fn directory_removed_before_descending() {
+    let fixture = Fixture::new(&["a/b/c.txt", "a/b/d.txt"]);
+
+    let mut walker = Walker::new(Pattern::new("a/**").unwrap(), fixture.path());
+
+    let first = walker.next().unwrap().unwrap();
+    assert_eq!(to_slash_string(&first), "a");
+
+    // The only entry in `a` is `b`, which is yielded before being descended into
+    let second = walker.next().unwrap().unwrap();
+    assert_eq!(to_slash_string(&second), "a/b");
+
+    fs::remove_dir_all(fixture.join("a/b")).unwrap();
+
+    assert!(walker.next().is_none());
+}
+
+#[test]
+fn entry_removed_after_listing() {
+    let fixture = Fixture::new(&["a/b/", "a/c/", "a/d/"]);
+
+    let mut walker = Walker::new(Pattern::new("a/*").unwrap(), fixture.path());
+
+    let first = to_slash_string(&walker.next().unwrap().unwrap());
+
+    // Remove all other entries, which may already have been listed by the directory reader
+    for name in ["a/b", "a/c", "a/d"] {
+        if name != first {
+            fs::remove_dir(fixture.join(name)).unwrap();
+        }
+    }
+
+    // Listed entries may be yielded, as their type was provided by the listing, but without errors
+    let rest = walker
+        .map(|path| to_slash_string(&path.unwrap()))
+        .collect::<Vec<_>>();
+
+    assert!(
+        rest.iter()
+            .all(|path| ["a/b", "a/c", "a/d"].contains(&path.as_str()) && *path != first),
+        "Unexpected results after removal: {rest:?}"
+    );
+}
+
+#[test]
+fn listed_entry_types_are_reused() {
+    let fixture = Fixture::new(&["a/b.txt", "a/c/d.txt", "e.txt", "f/"]);
+
+    // Matched entries don't need to be checked again, whether they are descended into or not
+    for pattern in ["*", "**/*.txt", "**/"] {
+        let mut walker = Walker::new(Pattern::new(pattern).unwrap(), fixture.path());
+        assert!(walker.by_ref().all(|result| result.is_ok()));
+
+        assert_eq!(
+            walker.stats().metadata_queries,
+            0,
+            "Unexpected metadata queries for pattern '{pattern}'"
+        );
+    }
+
+    // Symbolic links need to be followed to know if they point to a directory
+    #[cfg(unix)]
+    {
+        std::os::unix::fs::symlink(fixture.join("f"), fixture.join("link")).unwrap();
+
+        let mut walker = Walker::new(Pattern::new("**/").unwrap(), fixture.path());
+        assert_eq!(walker.by_ref().count(), 4);
+        assert_eq!(walker.stats().metadata_queries, 1);
+    }
+}
+
+#[test]
+fn broken_symlinks_are_yielded() {
+    let fixture = Fixture::new(&["a/"]);
+
+    #[cfg(unix)]
+    std::os::unix::fs::symlink(fixture.join("missing"), fixture.join("a/link")).unwrap();
+
+    #[cfg(windows)]
+    std::os::windows::fs::symlink_file(fixture.join("missing"), fixture.join("a/link")).unwrap();
+
+    let walker = Walker::new(Pattern::new("a/*").unwrap(), fixture.path());
+
+    assert_eq!(collect_sorted(walker), ["a/link"].map(String::from).into());
+}
+
+#[test]
+fn limiting_results() {
+    let fixture = Fixture::new(&["a/b/c/d/e.txt", "f.txt", "g.txt", "h/i.txt"]);
+
+    let mut walker = Walker::new(Pattern::new("**/*").unwrap(), fixture.path()).max_results(3);
+
+    for _ in 0..3 {
+        assert!(walker.next().unwrap().is_ok());
+    }
+
+    let stats = walker.stats();
+
+    assert!(matches!(
+        walker.next(),
+        Some(Err(WalkError::LimitReached {
+            kind: LimitKind::Results,
+            limit: 3
+        }))
+    ));
+
+    assert!(walker.next().is_none());
+    assert!(walker.next().is_none());
+
+    // No filesystem activity after the limit was reached
+    assert_eq!(walker.stats(), stats);
+}
+
+#[test]
+fn limiting_scanned_entries() {
+    let fixture = Fixture::new(&["a/b/c/d/e.txt", "f.txt", "g.txt", "h/i.txt"]);
+
+    let mut walker =
+        Walker::new(Pattern::new("**/*.md").unwrap(), fixture.path()).max_entries_scanned(4);
+
+    assert!(matches!(
+        walker.next(),
+        Some(Err(WalkError::LimitReached {
+            kind: LimitKind::EntriesScanned,
+            limit: 4
+        }))
+    ));
+
+    let stats = walker.stats();
+    assert_eq!(stats.entries_scanned, 4);
+
+    assert!(walker.next().is_none());
+    assert_eq!(walker.stats(), stats);
+}
+
+#[test]
+fn limits_without_error() {
+    let fixture = Fixture::new(&["a.txt", "b.txt", "c.txt"]);
+
+    let walker = Walker::new(Pattern::new("*").unwrap(), fixture.path())
+        .max_results(2)
+        .error_on_limit(false);
+
+    assert_eq!(collect_sorted(walker).len(), 2);
+
+    let walker = Walker::new(Pattern::new("*").unwrap(), fixture.path())
+        .max_results(0)
+        .error_on_limit(false);
+
+    assert!(collect_sorted(walker).is_empty());
+}
+
+#[test]
+fn grouping_by_directory() {
+    let fixture = Fixture::new(&["a/x.txt", "a/b/y.txt", "a/b/z.txt", "c.txt", "d/", "e/f.rs"]);
+    let root = fixture.path().canonicalize().unwrap();
+
+    let groups = Walker::new(Pattern::new("**/*.txt").unwrap(), fixture.path())
+        .grouped()
+        .map(|group| {
+            let (dir, mut matches) = group.unwrap();
+            matches.sort();
+
+            (
+                to_slash_string(dir.strip_prefix(&root).unwrap()),
+                matches
+                    .iter()
+                    .map(|path| to_slash_string(path))
+                    .collect::<Vec<_>>(),
+            )
+        })
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        groups,
+        [
+            ("", vec!["c.txt"]),
+            ("a", vec!["a/x.txt"]),
+            ("a/b", vec!["a/b/y.txt", "a/b/z.txt"])
+        ]
+        .map(|(dir, matches)| (
+            dir.to_owned(),
+            matches.into_iter().map(String::from).collect::<Vec<_>>()
+        ))
+    );
+}
+
+#[test]
+fn grouping_yields_parents_first() {
+    let fixture = Fixture::new(&["a/b/c/d", "a/e/f", "g/h", "i"]);
+    let root = fixture.path().canonicalize().unwrap();
+
+    let mut yielded = vec![];
+
+    for group in Walker::new(Pattern::new("**/*").unwrap(), fixture.path()).grouped() {
+        let (dir, matches) = group.unwrap();
+
+        if dir != root {
+            let dir = dir.strip_prefix(&root).unwrap().to_owned();
+            assert!(
+                yielded.contains(&dir),
+                "{dir:?} was not yielded before its content"
+            );
+        }
+
+        yielded.extend(matches);
+    }
+
+    assert_eq!(yielded.len(), 9);
+}
+
+#[test]
+fn grouping_with_limit() {
+    let fixture = Fixture::new(&["a", "b", "c"]);
+
+    let mut grouped = Walker::new(Pattern::new("*").unwrap(), fixture.path())
+        .max_results(2)
+        .grouped();
+
+    let (_, matches) = grouped.next().unwrap().unwrap();
+    assert_eq!(matches.len(), 2);
+
+    assert!(matches!(
+        grouped.next(),
+        Some(Err(WalkError::LimitReached { .. }))
+    ));
+
+    assert!(grouped.next().is_none());
+}
+
+#[test]
+fn trailing_wildcard_yields_literal_prefix() {
+    let fixture = Fixture::new(&["a/b/c", "d"]);
+
+    let walker = Walker::new(Pattern::new("a/**").unwrap(), fixture.path());
+
+    assert_eq!(
+        collect_sorted(walker),
+        ["a", "a/b", "a/b/c"].map(String::from).into()
+    );
+}
+
+#[test]
+fn walking_parent_directories() {
+    let fixture = Fixture::new(&[
+        "p/base/x.txt",
+        "p/sib/a.txt",
+        "p/sib/b.rs",
+        "p/sib/deep/c.txt",
+        "top.txt",
+    ]);
+
+    let base = fixture.join("p/base");
+
+    let walk = |pattern: &str, opts: PatternOpts| {
+        let pattern = Pattern::new_with_opts(pattern, opts).unwrap();
+        collect_sorted(Walker::new(pattern, &base))
+    };
+
+    assert_eq!(
+        walk("../*", PatternOpts::default()),
+        ["../base", "../sib"].map(String::from).into()
+    );
+
+    assert_eq!(
+        walk("../sib/**", PatternOpts::default()),
+        [
+            "../sib",
+            "../sib/a.txt",
+            "../sib/b.rs",
+            "../sib/deep",
+            "../sib/deep/c.txt"
+        ]
+        .map(String::from)
+        .into()
+    );
+
+    assert_eq!(
+        walk("../../*.txt", PatternOpts::default()),
+        ["../../top.txt"].map(String::from).into()
+    );
+
+    assert_eq!(
+        walk(
+            "../SIB/*.TXT",
+            PatternOpts::new().with_case_insensitive(true)
+        ),
+        ["../sib/a.txt"].map(String::from).into()
+    );
+}
+
+#[test]
+fn walking_multiple_parent_directories() {
+    let fixture = Fixture::new(&[
+        "ws/pkgs/app/main.rs",
+        "ws/pkgs/app/src/",
+        "ws/pkgs/lib/lib.rs",
+        "ws/shared/proto/a.proto",
+        "ws/shared/proto/sub/b.proto",
+        "ws/shared/README",
+        "ws/root.toml",
+    ]);
+
+    let walk = |base: &str, pattern: &str| {
+        collect_sorted(Walker::new(
+            Pattern::new(pattern).unwrap(),
+            &fixture.join(base),
+        ))
+    };
+
+    let expect = |paths: &[&str]| paths.iter().map(|path| path.to_string()).collect();
+
+    // One level
+    assert_eq!(
+        walk("ws/pkgs/app", "../lib/lib.rs"),
+        expect(&["../lib/lib.rs"])
+    );
+
+    assert_eq!(
+        walk("ws/pkgs/app", "../*/*.rs"),
+        expect(&["../app/main.rs", "../lib/lib.rs"])
+    );
+
+    // Two levels
+    assert_eq!(
+        walk("ws/pkgs/app", "../../shared/proto/a.proto"),
+        expect(&["../../shared/proto/a.proto"])
+    );
+
+    assert_eq!(
+        walk("ws/pkgs/app", "../../shared/**/*.proto"),
+        expect(&[
+            "../../shared/proto/a.proto",
+            "../../shared/proto/sub/b.proto"
+        ])
+    );
+
+    assert_eq!(
+        walk("ws/pkgs/app", "../../*/proto"),
+        expect(&["../../shared/proto"])
+    );
+
+    assert_eq!(
+        walk("ws/pkgs/app", "../../**/*.rs"),
+        expect(&["../../pkgs/app/main.rs", "../../pkgs/lib/lib.rs"])
+    );
+
+    // Three levels
+    assert_eq!(
+        walk("ws/pkgs/app/src", "../../../root.toml"),
+        expect(&["../../../root.toml"])
+    );
+
+    assert_eq!(
+        walk("ws/pkgs/app/src", "../../../*/*"),
+        expect(&[
+            "../../../pkgs/app",
+            "../../../pkgs/lib",
+            "../../../shared/README",
+            "../../../shared/proto"
+        ])
+    );
+
+    assert_eq!(
+        walk("ws/pkgs/app/src", "../../../**/*.proto"),
+        expect(&[
+            "../../../shared/proto/a.proto",
+            "../../../shared/proto/sub/b.proto"
+        ])
+    );
+}
+
+#[test]
+fn rendering_with_unix_separators() {
+    let fixture = Fixture::new(&["p/base/", "p/sib/a/b.txt"]);
+
+    let results = Walker::new(
+        Pattern::new("../sib/**/*.txt").unwrap(),
+        &fixture.join("p/base"),
+    )
+    .unix_separators(true)
+    .map(|path| path.unwrap().to_str().unwrap().to_owned())
+    .collect::<Vec<_>>();
+
+    assert_eq!(results, ["../sib/a/b.txt"]);
+}
+
+#[cfg(windows)]
+#[test]
+fn rendering_backslashes_with_unix_separators() {
+    let fixture = Fixture::new(&["p/base/", "p/sib/a/b.txt"]);
+
+    let walk = |unix_separators| {
+        Walker::new(
+            Pattern::new("../sib/**/*.txt").unwrap(),
+            &fixture.join(r"p\base"),
+        )
+        .unix_separators(unix_separators)
+        .map(|path| path.unwrap().to_str().unwrap().to_owned())
+        .collect::<Vec<_>>()
+    };
+
+    assert_eq!(walk(false), [r"..\sib\a\b.txt"]);
+    assert_eq!(walk(true), ["../sib/a/b.txt"]);
+}
+
+#[cfg(unix)]
+#[test]
+fn rendering_backslashes_with_unix_separators() {
+    // Backslashes aren't separators on Unix, so they are left as-is in file names
+    let fixture = Fixture::new(&["p/base/", r"p/sib/a\b.txt"]);
+
+    let results = Walker::new(
+        Pattern::new("../sib/**/*.txt").unwrap(),
+        &fixture.join("p/base"),
+    )
+    .unix_separators(true)
+    .map(|path| path.unwrap().to_str().unwrap().to_owned())
+    .collect::<Vec<_>>();
+
+    assert_eq!(results, [r"../sib/a\b.txt"]);
+}
+
+#[test]
+fn walking_multiple_patterns() {
+    let fixture = Fixture::new(&[
+        "src/main.rs",
+        "src/lib.rs",
+        "src/util/mod.rs",
+        "docs/guide.md",
+        "README.md",
+        "target/out.rs",
+    ]);
+
+    let patterns = ["src/**/*.rs", "**/*.md", "src/main.rs", "../**/lib.rs"]
+        .map(|pattern| Pattern::new(pattern).unwrap());
+
+    let results = Walker::new_multi(patterns, &fixture.join("src"))
+        .with_pattern_indices()
+        .map(|result| {
+            let result = result.unwrap();
+            (
+                to_slash_string(&result.path),
+                result.pattern_indices.to_vec(),
+            )
+        })
+        .collect::<std::collections::BTreeMap<_, _>>();
+
+    // Patterns are applied relatively to `src`, so only the last one can match anything
+    assert_eq!(
+        results,
+        [("../src/lib.rs".to_owned(), vec![3])]
+            .into_iter()
+            .collect()
+    );
+
+    let patterns = ["src/**/*.rs", "**/*.md", "src/main.rs", "src/*"]
+        .map(|pattern| Pattern::new(pattern).unwrap());
+
+    let results = Walker::new_multi(patterns, fixture.path())
+        .with_pattern_indices()
+        .map(|result| {
+            let result = result.unwrap();
+            (
+                to_slash_string(&result.path),
+                result.pattern_indices.to_vec(),
+            )
+        })
+        .collect::<std::collections::BTreeMap<_, _>>();
+
+    let expected = [
+        ("README.md", vec![1]),
+        ("docs/guide.md", vec![1]),
+        ("src/lib.rs", vec![0, 3]),
+        ("src/main.rs", vec![0, 2, 3]),
+        ("src/util", vec![3]),
+        ("src/util/mod.rs", vec![0]),
+    ]
+    .map(|(path, indices)| (path.to_owned(), indices));
+
+    assert_eq!(results, expected.into_iter().collect());
+}
+
+#[test]
+fn bounded_patterns_dont_read_deeper_directories() {
+    let fixture = Fixture::new(&[
+        "a/b/c/d/e/f/g.lock",
+        "a/b/c/d/e/f.lock",
+        "a/b/x.lock",
+        "a/b/y.lock/z.lock",
+        "a/w/v/u/t/s.lock",
+        "q/r.lock",
+        "p.lock",
+    ]);
+
+    let mut walker = Walker::new(Pattern::new("*/*/*.lock").unwrap(), fixture.path());
+    let results = (&mut walker)
+        .map(|path| to_slash_string(&path.unwrap()))
+        .collect::<BTreeSet<_>>();
+
+    assert_eq!(
+        results,
+        ["a/b/x.lock", "a/b/y.lock"].map(String::from).into()
+    );
+
+    // Only the base directory, `a`, `q`, `a/b` and `a/w` are read
+    assert_eq!(walker.stats().dirs_read, 5);
+
+    // Unbounded patterns still read every directory
+    let mut walker = Walker::new(Pattern::new("*/**/*.lock").unwrap(), fixture.path());
+    assert_eq!(walker.by_ref().count(), 7);
+    assert_eq!(walker.stats().dirs_read, 13);
+}
+
+#[test]
+fn walking_with_max_components() {
+    let fixture = Fixture::new(&["a.rs", "a/b.rs", "a/b/c.rs", "a/b/c/d.rs", "x/y/z/w/v.rs"]);
+
+    let opts = PatternOpts::new().with_max_components(2);
+
+    let mut walker = Walker::new(
+        Pattern::new_with_opts("**/*.rs", opts).unwrap(),
+        fixture.path(),
+    );
+    let results = (&mut walker)
+        .map(|path| to_slash_string(&path.unwrap()))
+        .collect::<BTreeSet<_>>();
+
+    assert_eq!(results, ["a.rs", "a/b.rs"].map(String::from).into());
+
+    // Only the base directory, `a` and `x` are read
+    assert_eq!(walker.stats().dirs_read, 3);
+
+    // Literal paths beyond the limit aren't yielded either
+    let pattern = Pattern::new_with_opts("a/b/c.rs", opts).unwrap();
+    assert!(collect_sorted(Walker::new(pattern.clone(), fixture.path())).is_empty());
+    assert!(!pattern.any_match_in(fixture.path()).unwrap());
+}
+
+#[test]
+fn walking_without_globstar() {
+    let fixture = Fixture::new(&["src/a.rs", "src/b/c.rs", "src/b/d/e.rs"]);
+
+    let mut walker = Walker::new(
+        Pattern::new_with_opts("src/**", PatternOpts::new().with_disable_globstar(true)).unwrap(),
+        fixture.path(),
+    );
+    let results = (&mut walker)
+        .map(|path| to_slash_string(&path.unwrap()))
+        .collect::<BTreeSet<_>>();
+
+    assert_eq!(results, ["src/a.rs", "src/b"].map(String::from).into());
+
+    // Only `src` is read
+    assert_eq!(walker.stats().dirs_read, 1);
+}
+
+#[test]
+fn walking_multiple_disjoint_roots() {
+    let fixture = Fixture::new(&["a/x/1.txt", "a/y/2.txt", "b/3.txt", "c/4.txt"]);
+
+    let patterns = ["a/x/*.txt", "b/*.txt"].map(|pattern| Pattern::new(pattern).unwrap());
+
+    let walker = Walker::new_multi(patterns, fixture.path());
+
+    assert_eq!(
+        collect_sorted(walker),
+        ["a/x/1.txt", "b/3.txt"].map(String::from).into()
+    );
+
+    // Without any pattern, nothing is walked at all
+    let mut walker = Walker::new_multi([], fixture.path());
+
+    assert!(walker.next().is_none());
+    assert_eq!(walker.stats().dirs_read, 0);
+}
+
+#[test]
+fn walking_with_star_matching_separators() {
+    let fixture = Fixture::new(&["src/main.c", "src/a/util.c", "src/a/b/lib.c", "src/a/lib.h"]);
+
+    let walk = |star_matches_separators| {
+        let opts = PatternOpts::new().with_star_matches_separators(star_matches_separators);
+
+        collect_sorted(Walker::new(
+            Pattern::new_with_opts("src/*.c", opts).unwrap(),
+            fixture.path(),
+        ))
+    };
+
+    assert_eq!(walk(false), ["src/main.c"].map(String::from).into());
+
+    assert_eq!(
+        walk(true),
+        ["src/a/b/lib.c", "src/a/util.c", "src/main.c"]
+            .map(String::from)
+            .into()
+    );
+}
+
+#[test]
+fn target_hints_dont_change_results() {
+    let fixture = Fixture::new(&["a/b/c.txt", "a/d/", "e.txt", "f/g/"]);
+
+    for (pattern, expected) in [
+        ("*", &["a", "e.txt", "f"][..]),
+        ("**", &["a", "a/b", "a/b/c.txt", "a/d", "e.txt", "f", "f/g"]),
+        ("a/**", &["a", "a/b", "a/b/c.txt", "a/d"]),
+        ("a/*", &["a/b", "a/d"]),
+        ("..", &[]),
+    ] {
+        assert_eq!(
+            collect_sorted(Walker::new(Pattern::new(pattern).unwrap(), fixture.path())),
+            expected.iter().copied().map(String::from).collect(),
+            "Unexpected results for pattern '{pattern}'"
+        );
+    }
+}
+
+#[test]
+fn finding_any_match() {
+    let mut entries = vec!["hit.txt".to_owned(), "deep/a/b/c/hit.rs".to_owned()];
+
+    for i in 0..50 {
+        entries.push(format!("huge/dir{i}/sub/file{i}.txt"));
+    }
+
+    let fixture = Fixture::new(&entries.iter().map(String::as_str).collect::<Vec<_>>());
+
+    let any_match = |pattern: &str, dir: &str| {
+        Pattern::new(pattern)
+            .unwrap()
+            .any_match_in(&fixture.join(dir))
+            .unwrap()
+    };
+
+    assert!(any_match("**/hit*", ""));
+    assert!(any_match("**/*.rs", ""));
+    assert!(any_match("deep/a/b/c/hit.rs", ""));
+    assert!(any_match("../hit.txt", "deep"));
+
+    assert!(!any_match("**/missing*", ""));
+    assert!(!any_match("deep/a/missing.rs", ""));
+    assert!(!any_match("..", "deep"));
+    assert!(!any_match("**/hit*", "missing"));
+
+    // Shallow matches are found without reading the unrelated subtrees
+    let mut walker = Walker::new(Pattern::new("**/hit*").unwrap(), fixture.path())
+        .breadth_first()
+        .max_results(1)
+        .error_on_limit(false);
+
+    assert_eq!(to_slash_string(&walker.next().unwrap().unwrap()), "hit.txt");
+    assert!(walker.next().is_none());
+    assert_eq!(walker.stats().dirs_read, 1);
+}
+
+#[test]
+fn walking_breadth_first() {
+    let fixture = Fixture::new(&["a/b/c/d.txt", "a/e.txt", "f/g.txt", "h.txt"]);
+
+    let depths = Walker::new(Pattern::new("**/*").unwrap(), fixture.path())
+        .breadth_first()
+        .map(|path| path.unwrap().components().count())
+        .collect::<Vec<_>>();
+
+    assert_eq!(depths.len(), 8);
+    assert!(
+        depths.is_sorted(),
+        "Results are not sorted by depth: {depths:?}"
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn rewalking_with_cache() {
+    use std::time::{Duration, SystemTime};
+
+    /// Make a directory and all its subdirectories look like they weren't modified recently
+    fn backdate(dir: &Path) {
+        for entry in fs::read_dir(dir).unwrap() {
+            let path = entry.unwrap().path();
+
+            if path.is_dir() {
+                backdate(&path);
+            }
+        }
+
+        fs::File::open(dir)
+            .unwrap()
+            .set_modified(SystemTime::now() - Duration::from_secs(3600))
+            .unwrap();
+    }
+
+    let fixture = Fixture::new(&["a/b/c.txt", "a/d.txt", "e/f/g.txt", "h.txt"]);
+    backdate(fixture.path());
+
+    let mut walker = CachedWalker::new(Pattern::new("**/*.txt").unwrap(), fixture.path());
+
+    let mut run = || {
+        let results = walker
+            .run()
+            .map(|path| to_slash_string(&path.unwrap()))
+            .collect::<std::collections::BTreeSet<_>>();
+
+        (results, walker.stats())
+    };
+
+    let expected = ["a/b/c.txt", "a/d.txt", "e/f/g.txt", "h.txt"].map(String::from);
+
+    // First run reads everything
+    let (results, stats) = run();
+    assert_eq!(results, expected.clone().into());
+    assert_eq!((stats.dirs_read, stats.dirs_cached), (5, 0));
+
+    // Second run reads nothing
+    let (results, stats) = run();
+    assert_eq!(results, expected.clone().into());
+    assert_eq!((stats.dirs_read, stats.dirs_cached), (0, 5));
+
+    // Only the modified directory is read again
+    fs::write(fixture.join("a/b/new.txt"), "").unwrap();
+
+    let (results, stats) = run();
+    assert_eq!(
+        results,
+        ["a/b/c.txt", "a/b/new.txt", "a/d.txt", "e/f/g.txt", "h.txt"]
+            .map(String::from)
+            .into()
+    );
+    assert_eq!((stats.dirs_read, stats.dirs_cached), (1, 4));
+
+    // Removing a subtree only re-reads its parent, as well as the directory
+    // that was too recently modified to be cached during the previous run
+    fs::remove_dir_all(fixture.join("e")).unwrap();
+
+    fs::File::open(fixture.path())
+        .unwrap()
+        .set_modified(SystemTime::now() - Duration::from_secs(60))
+        .unwrap();
+
+    let (results, stats) = run();
+    assert_eq!(
+        results,
+        ["a/b/c.txt", "a/b/new.txt", "a/d.txt", "h.txt"]
+            .map(String::from)
+            .into()
+    );
+    assert_eq!((stats.dirs_read, stats.dirs_cached), (2, 1));
+
+    // Recently modified directories keep being read until their modification time is old enough
+    let (_, stats) = run();
+    assert_eq!((stats.dirs_read, stats.dirs_cached), (1, 2));
+}
+
+#[test]
+fn rewalking_without_trusting_mtime() {
+    let fixture = Fixture::new(&["a/b.txt", "c.txt"]);
+
+    let mut walker =
+        CachedWalker::new(Pattern::new("**/*.txt").unwrap(), fixture.path()).trust_dir_mtime(false);
+
+    for _ in 0..2 {
+        assert_eq!(walker.run().count(), 2);
+        assert_eq!(walker.stats().dirs_read, 2);
+        assert_eq!(walker.stats().dirs_cached, 0);
+    }
+}
+
+#[test]
+fn filtering_existing_candidates() {
+    let fixture = Fixture::new(&[
+        "lock/a.rs",
+        "lock/b.rs",
+        "lock/c.rs",
+        "lock/d.rs",
+        "lock/e.rs",
+        "src/main.rs",
+        "src/notes.txt",
+    ]);
+
+    let pattern = Pattern::new("*/*.rs").unwrap();
+
+    let candidates = [
+        "lock/a.rs",
+        "lock/missing.rs",
+        "lock/b.rs",
+        "lock/c.rs",
+        "lock/d.txt",
+        "src/main.rs",
+        "src/lib.rs",
+        "src/notes.txt",
+        "missing/x.rs",
+        "deeply/nested/x.rs",
+    ]
+    .map(Path::new);
+
+    let (results, stats) = Walker::filter_existing_with_stats(&pattern, fixture.path(), candidates);
+
+    let results = results
+        .iter()
+        .map(|(path, exists)| (to_slash_string(path), *exists))
+        .collect::<Vec<_>>();
+
+    let expected = [
+        ("lock/a.rs", true),
+        ("lock/missing.rs", false),
+        ("lock/b.rs", true),
+        ("lock/c.rs", true),
+        ("lock/d.txt", false),
+        ("src/main.rs", true),
+        ("src/lib.rs", false),
+        ("src/notes.txt", false),
+        ("missing/x.rs", false),
+        ("deeply/nested/x.rs", false),
+    ]
+    .map(|(path, exists)| (path.to_owned(), exists));
+
+    assert_eq!(results, expected);
+
+    // `lock` is listed once, while the candidates of `src` and `missing` are checked individually,
+    // and non-matching candidates are never looked up
+    assert_eq!(stats.dirs_read, 1);
+    assert_eq!(stats.metadata_queries, 3);
+    assert_eq!(stats.results, 4);
+
+    // Absolute candidates are supported as well
+    let absolute = fixture.join("src/main.rs");
+
+    assert_eq!(
+        Walker::filter_existing(
+            &Pattern::new("/**/*.rs").unwrap(),
+            fixture.path(),
+            [absolute.as_path()]
+        ),
+        [(absolute.clone(), true)]
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn walking_with_slash_only() {
+    let fixture = Fixture::new(&[r"dir/a\b", "dir/c"]);
+
+    let walk = |slash_only| {
+        let opts = PatternOpts::new().with_slash_only(slash_only);
+
+        collect_sorted(Walker::new(
+            Pattern::new_with_opts("dir/*", opts).unwrap(),
+            fixture.path(),
+        ))
+    };
+
+    // Backslashes in entry names are separators for the pattern unless it is slash-only
+    assert_eq!(walk(false), ["dir/c"].map(String::from).into());
+    assert_eq!(walk(true), [r"dir/a\b", "dir/c"].map(String::from).into());
+}
+
+#[test]
+fn walking_with_literal_leading_dot() {
+    let fixture = Fixture::new(&[
+        ".cargo/config.rs",
+        ".hidden.rs",
+        "src/main.rs",
+        "src/.generated/lib.rs",
+    ]);
+
+    let walk = |pattern| {
+        let opts = PatternOpts::new().with_require_literal_leading_dot(true);
+
+        collect_sorted(Walker::new(
+            Pattern::new_with_opts(pattern, opts).unwrap(),
+            fixture.path(),
+        ))
+    };
+
+    assert_eq!(walk("**/*.rs"), ["src/main.rs"].map(String::from).into());
+    assert_eq!(walk("*"), ["src"].map(String::from).into());
+
+    // Hidden directories are traversed when named explicitly
+    assert_eq!(
+        walk(".cargo/*.rs"),
+        [".cargo/config.rs"].map(String::from).into()
+    );
+    assert_eq!(
+        walk("**/.generated/*"),
+        ["src/.generated/lib.rs"].map(String::from).into()
+    );
+    assert_eq!(
+        walk(".*"),
+        [".cargo", ".hidden.rs"].map(String::from).into()
+    );
+}
+
+/// Collect the results of a walker in order, checking that directories are yielded before their content
+fn collect_parents_first(walker: Walker) -> Vec<String> {
+    let results = walker
+        .map(|path| to_slash_string(&path.unwrap()))
+        .collect::<Vec<_>>();
+
+    for (i, result) in results.iter().enumerate() {
+        for ancestor in Path::new(result).ancestors().skip(1) {
+            if let Some(pos) = results
+                .iter()
+                .position(|other| Path::new(other) == ancestor)
+            {
+                assert!(
+                    pos < i,
+                    "'{result}' was yielded before its parent '{}' in {results:?}",
+                    ancestor.display()
+                );
+            }
+        }
+    }
+
+    results
+}
+
+#[test]
+fn walking_in_sorted_order() {
+    let fixture = Fixture::new(&["b/z.txt", "b/a/y.txt", "a.txt", "c/", "b/m.txt"]);
+
+    let walk = |ordering| {
+        collect_parents_first(
+            Walker::new(Pattern::new("**").unwrap(), fixture.path()).ordering(ordering),
+        )
+    };
+
+    let sorted = walk(Ordering::ParentsFirstSorted);
+
+    assert_eq!(
+        sorted,
+        ["a.txt", "b", "b/a", "b/a/y.txt", "b/m.txt", "b/z.txt", "c"]
+    );
+
+    // Other orderings yield the same results
+    for ordering in [Ordering::ParentsFirst, Ordering::Unspecified] {
+        let mut results = walk(ordering);
+        results.sort();
+
+        assert_eq!(results, sorted);
+    }
+
+    // Sorting also applies to breadth-first traversal
+    assert_eq!(
+        collect_parents_first(
+            Walker::new(Pattern::new("**").unwrap(), fixture.path())
+                .ordering(Ordering::ParentsFirstSorted)
+                .breadth_first()
+        ),
+        ["a.txt", "b", "c", "b/a", "b/m.txt", "b/z.txt", "b/a/y.txt"]
+    );
+}
+
+#[test]
+fn walking_in_sorted_chunks() {
+    let fixture = Fixture::new(&["b/z.txt", "b/a/y.txt", "a.txt", "c/", "b/m.txt", "big/"]);
+
+    for i in 0..10_000 {
+        fs::write(fixture.join(&format!("big/{i:x}.txt")), "").unwrap();
+    }
+
+    let walk_with_stats = |pattern, ordering| {
+        let mut walker =
+            Walker::new(Pattern::new(pattern).unwrap(), fixture.path()).ordering(ordering);
+
+        let results = walker
+            .by_ref()
+            .map(|path| to_slash_string(&path.unwrap()))
+            .collect::<Vec<_>>();
+
+        (results, walker.stats().dirs_read)
+    };
+
+    let walk = |pattern, ordering| walk_with_stats(pattern, ordering).0;
+
+    let (sorted, dirs_read) = walk_with_stats("**", Ordering::ParentsFirstSorted);
+    assert_eq!(sorted.len(), 10_008);
+
+    for chunk in [256, 10_000, 20_000] {
+        // Each directory is only read once, whatever the chunk size
+        assert_eq!(
+            walk_with_stats("**", Ordering::ParentsFirstSortedChunked { chunk }),
+            (sorted.clone(), dirs_read),
+            "Unexpected results with chunks of {chunk} entries"
+        );
+    }
+
+    // Small chunks
+    let sorted = walk("{a.txt|b|c}/**", Ordering::ParentsFirstSorted);
+    assert_eq!(sorted.len(), 7);
+
+    for chunk in [0, 1, 2, 3] {
+        assert_eq!(
+            walk(
+                "{a.txt|b|c}/**",
+                Ordering::ParentsFirstSortedChunked { chunk }
+            ),
+            sorted,
+            "Unexpected results with chunks of {chunk} entries"
+        );
+    }
+}
+
+#[cfg(unix)]
+#[test]
+fn spilled_chunks_are_private() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let fixture = Fixture::new(&["a.txt", "b.txt", "c.txt"]);
+
+    let mut walker = Walker::new(Pattern::new("*").unwrap(), fixture.path())
+        .ordering(Ordering::ParentsFirstSortedChunked { chunk: 1 });
+
+    // The temporary file exists while the directory is being read
+    assert!(walker.next().unwrap().is_ok());
+
+    let prefix = format!("globby-walk-{}-", std::process::id());
+
+    let modes = fs::read_dir(std::env::temp_dir())
+        .unwrap()
+        .filter_map(Result::ok)
+        .filter(|entry| entry.file_name().to_string_lossy().starts_with(&prefix))
+        .filter_map(|entry| entry.metadata().ok())
+        .map(|metadata| metadata.permissions().mode() & 0o777)
+        .collect::<Vec<_>>();
+
+    assert!(!modes.is_empty());
+    assert!(
+        modes.iter().all(|&mode| mode == 0o600),
+        "Unexpected modes: {modes:?}"
+    );
+}
+
+#[test]
+fn parents_are_yielded_first() {
+    let fixture = Fixture::new(&["base/a/b/c.txt", "base/a/d/", "other/e/f.txt", "g.txt"]);
+
+    for ordering in [Ordering::ParentsFirst, Ordering::ParentsFirstSorted] {
+        // Patterns starting with `..` components
+        let results = collect_parents_first(
+            Walker::new(Pattern::new("../**").unwrap(), &fixture.join("base")).ordering(ordering),
+        );
+
+        assert_eq!(results.len(), 9);
+        assert!(results.contains(&"../other/e/f.txt".to_owned()));
+
+        // Multiple patterns walked from a common ancestor
+        let results = collect_parents_first(
+            Walker::new_multi(
+                [
+                    Pattern::new("../other/**").unwrap(),
+                    Pattern::new("**").unwrap(),
+                ],
+                &fixture.join("base"),
+            )
+            .ordering(ordering),
+        );
+
+        assert_eq!(results.len(), 7);
+    }
+}
+
+#[cfg(unix)]
+#[test]
+fn parents_are_yielded_first_through_symlinks_and_absolute_patterns() {
+    let fixture = Fixture::new(&["real/a/b.txt", "real/c.txt"]);
+
+    std::os::unix::fs::symlink(fixture.join("real"), fixture.join("link")).unwrap();
+
+    for ordering in [Ordering::ParentsFirst, Ordering::ParentsFirstSorted] {
+        // Symbolic links to directories
+        let results = collect_parents_first(
+            Walker::new(Pattern::new("**").unwrap(), fixture.path()).ordering(ordering),
+        );
+
+        assert_eq!(results.len(), 8);
+        assert!(results.contains(&"link/a/b.txt".to_owned()));
+
+        // Absolute patterns
+        let root = fs::canonicalize(fixture.path()).unwrap();
+        let pattern = Pattern::new(&format!("{}/**", root.display())).unwrap();
+
+        let results =
+            collect_parents_first(Walker::new(pattern, Path::new("/")).ordering(ordering));
+
+        // The pattern's root directory is matched by `**` too
+        assert_eq!(results.len(), 9);
+        assert!(
+            results
+                .iter()
+                .any(|result| result.ends_with("/link/a/b.txt"))
+        );
+    }
+}
+
+#[test]
+fn walking_with_globstar_skipping_hidden() {
+    let fixture = Fixture::new(&[
+        ".git/hooks/a.rs",
+        ".git/config",
+        "src/main.rs",
+        "src/.cache/b.rs",
+    ]);
+
+    let opts = PatternOpts::new().with_globstar_skips_hidden(true);
+
+    let mut walker = Walker::new(
+        Pattern::new_with_opts("**/*.rs", opts).unwrap(),
+        fixture.path(),
+    );
+
+    let results = walker
+        .by_ref()
+        .map(|path| to_slash_string(&path.unwrap()))
+        .collect::<Vec<_>>();
+
+    assert_eq!(results, ["src/main.rs"]);
+
+    // Hidden directories are not even read
+    assert_eq!(walker.stats().dirs_read, 2);
+
+    // ...unless the pattern names them
+    assert_eq!(
+        collect_sorted(Walker::new(
+            Pattern::new_with_opts(".git/**", opts).unwrap(),
+            fixture.path(),
+        )),
+        [".git", ".git/config", ".git/hooks", ".git/hooks/a.rs"]
+            .map(String::from)
+            .into()
+    );
+}
+
+#[test]
+fn pruning_matched_directories() {
+    let fixture = Fixture::new(&[
+        "a/.git/objects/.git/config",
+        "b/.git/config",
+        "c/src/d/src/e.rs",
+    ]);
+
+    let opts = PatternOpts::new().with_globstar_skips_hidden(true);
+
+    // `**` can't consume the matched `.git` directories, so their content can't match
+    let mut walker = Walker::new(
+        Pattern::new_with_opts("**/.git", opts).unwrap(),
+        fixture.path(),
+    );
+
+    let mut results = walker
+        .by_ref()
+        .map(|path| to_slash_string(&path.unwrap()))
+        .collect::<Vec<_>>();
+
+    results.sort();
+
+    assert_eq!(results, ["a/.git", "b/.git"]);
+
+    // The `.git` directories themselves are never read
+    assert_eq!(walker.stats().dirs_read, 7);
+
+    // Matched directories whose descendants may match are still traversed
+    assert_eq!(
+        collect_sorted(Walker::new(
+            Pattern::new_with_opts("**/src", opts).unwrap(),
+            fixture.path(),
+        )),
+        ["c/src", "c/src/d/src"].map(String::from).into()
+    );
+}
+
+#[test]
+fn walking_absolute_patterns_without_base_dir() {
+    let fixture = Fixture::new(&["a/b.txt", "c.txt", "d.rs"]);
+
+    let root = fs::canonicalize(fixture.path()).unwrap();
+    let pattern = Pattern::new(&format!("{}/**/*.txt", root.display())).unwrap();
+
+    let results = collect_sorted(Walker::new_absolute(pattern.clone()));
+
+    assert_eq!(results.len(), 2);
+    assert_eq!(
+        results,
+        collect_sorted(Walker::new(pattern, fixture.path()))
+    );
+
+    // Relative patterns require a base directory
+    let mut walker = Walker::new_absolute(Pattern::new("**").unwrap());
+
+    assert!(matches!(
+        walker.next(),
+        Some(Err(WalkError::PatternInapplicable {
+            pattern: 0,
+            reason: PatternMatchResult::PathNotAbsolute
+        }))
+    ));
+    assert!(walker.next().is_none());
+}
+
+#[test]
+fn walking_from_case_insensitive_roots() {
+    let fixture = Fixture::new(&["src/a.rs", "src/b/c.rs", "other/d.rs", "e.rs"]);
+
+    let walk = |opts: PatternOpts| {
+        let pattern = Pattern::new_with_opts("src/B/*.RS", opts).unwrap();
+        let mut walker = Walker::new(pattern, fixture.path());
+        let results = (&mut walker)
+            .map(|path| to_slash_string(&path.unwrap()))
+            .collect::<Vec<_>>();
+
+        (results, walker.stats().dirs_read)
+    };
+
+    let opts = PatternOpts::new().with_case_insensitive(true);
+
+    assert_eq!(walk(opts), (vec!["src/b/c.rs".to_owned()], 3));
+
+    // Only the root directory is read, and results are rendered with the casing of the pattern
+    let (results, dirs_read) = walk(opts.with_case_insensitive_fs(true));
+
+    assert_eq!(dirs_read, 1);
+
+    if cfg!(windows) {
+        assert_eq!(results, ["src/B/c.rs"]);
+    } else if cfg!(target_os = "linux") {
+        // Entries with another casing can't be found on case-sensitive filesystems
+        assert!(results.is_empty());
+    }
+}
+
+#[test]
+fn walking_literal_paths() {
+    let fixture = Fixture::new(&["report[final].txt", "reportf.txt", "{a,b}/c.txt", "a/c.txt"]);
+
+    let walk =
+        |path: &str| collect_sorted(Walker::new(Pattern::literal(path).unwrap(), fixture.path()));
+
+    assert_eq!(
+        walk("report[final].txt"),
+        BTreeSet::from(["report[final].txt".to_owned()])
+    );
+    assert_eq!(
+        walk("{a,b}/c.txt"),
+        BTreeSet::from(["{a,b}/c.txt".to_owned()])
+    );
+    assert_eq!(walk("*"), BTreeSet::new());
+
+    // Absolute paths can be walked too
+    let path = fs::canonicalize(fixture.join("report[final].txt")).unwrap();
+    let results = collect_sorted(Walker::new_absolute(
+        Pattern::literal(path.to_str().unwrap()).unwrap(),
+    ));
+
+    assert_eq!(results, BTreeSet::from([to_slash_string(&path)]));
+}
+
+#[cfg(unix)]
+#[test]
+fn reporting_inapplicable_patterns() {
+    let fixture = Fixture::new(&["a.txt", "b.rs"]);
+
+    // Windows drives don't exist on Unix platforms
+    for pattern in ["D:\\**", "C:/*.txt", "\\\\?\\D:\\**"] {
+        let mut walker = Walker::new(Pattern::new(pattern).unwrap(), fixture.path());
+
+        assert!(matches!(
+            walker.next(),
+            Some(Err(WalkError::PatternInapplicable {
+                pattern: 0,
+                reason: PatternMatchResult::IncompatiblePrefix
+            }))
+        ));
+        assert!(walker.next().is_none());
+    }
+
+    // Other patterns are still applied
+    let mut walker = Walker::new_multi(
+        [
+            Pattern::new("*.txt").unwrap(),
+            Pattern::new("D:\\**").unwrap(),
+        ],
+        fixture.path(),
+    );
+
+    assert!(matches!(
+        walker.next(),
+        Some(Err(WalkError::PatternInapplicable {
+            pattern: 1,
+            reason: PatternMatchResult::IncompatiblePrefix
+        }))
+    ));
+    assert_eq!(collect_sorted(walker), BTreeSet::from(["a.txt".to_owned()]));
+
+    // Root-prefixed patterns apply on any platform
+    let root = fs::canonicalize(fixture.path()).unwrap();
+    let pattern = Pattern::new(&format!("{}/*.rs", root.display())).unwrap();
+
+    assert_eq!(collect_sorted(Walker::new_absolute(pattern)).len(), 1);
+}
+
+#[test]
+fn reporting_relative_patterns_without_base_dir() {
+    let mut walker = Walker::new_absolute(Pattern::new("../*.txt").unwrap());
+
+    let err = walker.next().unwrap().unwrap_err();
+    assert!(matches!(
+        err,
+        WalkError::PatternInapplicable {
+            pattern: 0,
+            reason: PatternMatchResult::PathNotAbsolute
+        }
+    ));
+    assert_eq!(
+        err.to_string(),
+        "pattern 0 can't match any entry: it is relative while no base directory was provided"
+    );
+    assert!(walker.next().is_none());
+}
+
+#[cfg(windows)]
+#[test]
+fn reporting_patterns_on_other_drives() {
+    use globby::PathPrefix;
+
+    let fixture = Fixture::new(&["a.txt"]);
+
+    let root = fs::canonicalize(fixture.path()).unwrap();
+    let drive = Pattern::from_literal_path(&root).unwrap().prefix();
+
+    let Some(PathPrefix::WindowsDrive(drive)) = drive else {
+        panic!("Unexpected prefix: {drive:?}");
+    };
+
+    let other_drive = if drive.uppercase_letter() == 'Z' {
+        'Y'
+    } else {
+        'Z'
+    };
+
+    let mut walker = Walker::new(
+        Pattern::new(&format!("{other_drive}:/**")).unwrap(),
+        fixture.path(),
+    );
+
+    assert!(matches!(
+        walker.next(),
+        Some(Err(WalkError::PatternInapplicable {
+            pattern: 0,
+            reason: PatternMatchResult::PathIsAbsolute
+        }))
+    ));
+    assert!(walker.next().is_none());
+
+    // Patterns on the base directory's drive are still applied
+    let pattern = Pattern::from_literal_path(&root.join("a.txt")).unwrap();
+    assert_eq!(
+        collect_sorted(Walker::new(pattern, fixture.path())).len(),
+        1
+    );
+}
+
+#[test]
+fn trailing_separator_only_yields_directories() {
+    let fixture = Fixture::new(&["src/", "a/src", "a/target/", "b/target", "b/c/target/x"]);
+
+    let walk =
+        |pattern: &str| collect_sorted(Walker::new(Pattern::new(pattern).unwrap(), fixture.path()));
+
+    assert_eq!(walk("src/"), BTreeSet::from(["src".to_owned()]));
+    assert_eq!(walk("*/src/"), BTreeSet::new());
+    assert_eq!(walk("*/src"), BTreeSet::from(["a/src".to_owned()]));
+    assert_eq!(
+        walk("**/target/"),
+        BTreeSet::from(["a/target".to_owned(), "b/c/target".to_owned()])
+    );
+
+    assert!(
+        Pattern::new("src/")
+            .unwrap()
+            .any_match_in(fixture.path())
+            .unwrap()
+    );
+    assert!(
+        !Pattern::new("a/src/")
+            .unwrap()
+            .any_match_in(fixture.path())
+            .unwrap()
+    );
+}
+
+#[test]
+fn walking_with_basename_matching() {
+    let fixture = Fixture::new(&[
+        "c.log",
+        "a/c.log",
+        "a/b/d.log",
+        "a/b/e.txt",
+        "Makefile",
+        "a/Makefile",
+    ]);
+
+    let opts = PatternOpts::new().with_match_basename(true);
+
+    let walk = |pattern: &str| {
+        collect_sorted(Walker::new(
+            Pattern::new_with_opts(pattern, opts).unwrap(),
+            fixture.path(),
+        ))
+    };
+
+    assert_eq!(
+        walk("*.log"),
+        BTreeSet::from([
+            "c.log".to_owned(),
+            "a/c.log".to_owned(),
+            "a/b/d.log".to_owned()
+        ])
+    );
+    assert_eq!(
+        walk("Makefile"),
+        BTreeSet::from(["Makefile".to_owned(), "a/Makefile".to_owned()])
+    );
+    assert_eq!(walk("a/*.log"), BTreeSet::from(["a/c.log".to_owned()]));
+}
+
+#[test]
+fn matching_under_base_directory_agrees_with_walking() {
+    let fixture = Fixture::new(&[
+        "p/base/x.txt",
+        "p/base/src/main.rs",
+        "p/base/src/lib/mod.rs",
+        "p/sib/a.txt",
+        "p/sib/b.rs",
+        "p/sib/deep/c.txt",
+        "top.txt",
+    ]);
+
+    let root = fs::canonicalize(fixture.path()).unwrap();
+    let base = root.join("p/base");
+
+    // Resolve a walked path against the base directory, without touching the filesystem
+    let resolve = |walked: &str| {
+        let mut path = base.clone();
+
+        for component in Path::new(walked).components() {
+            match component {
+                Component::ParentDir => assert!(path.pop()),
+                component => path.push(component),
+            }
+        }
+
+        path
+    };
+
+    fn list_all(dir: &Path, out: &mut Vec<PathBuf>) {
+        for entry in fs::read_dir(dir).unwrap() {
+            let path = entry.unwrap().path();
+
+            if path.is_dir() {
+                list_all(&path, out);
+            }
+
+            out.push(path);
+        }
+    }
+
+    let mut all = vec![];
+    list_all(&root, &mut all);
+
+    for pattern in [
+        "*.txt",
+        "src/**/*.rs",
+        "**",
+        "../sib/*",
+        "../sib/**/*.txt",
+        "../../*.txt",
+        "../*/*.rs",
+    ] {
+        let pattern_str = pattern;
+        let pattern = Pattern::new(pattern).unwrap();
+
+        let walked = collect_sorted(Walker::new(pattern.clone(), &base))
+            .iter()
+            .map(|walked| resolve(walked))
+            .collect::<BTreeSet<_>>();
+
+        for path in &all {
+            assert_eq!(
+                pattern.is_match_under(&base, path),
+                walked.contains(path),
+                "pattern {pattern_str} disagrees with walking on {}",
+                path.display()
+            );
+        }
+    }
+
+    // Relative patterns never match outside of the base directory's reach, nor relative paths
+    let pattern = Pattern::new("*.txt").unwrap();
+    assert!(pattern.is_match_under(&base, &base.join("x.txt")));
+    assert!(!pattern.is_match_under(&base, &root.join("top.txt")));
+    assert!(!pattern.is_match_under(&base, Path::new("x.txt")));
+    assert!(!pattern.is_match(&base.join("x.txt")));
+}
+
+#[test]
+fn matching_dir_entries() {
+    let fixture = Fixture::new(&["a.txt", "b.rs", "c.txt/", "sub/d.txt"]);
+
+    let matching = |pattern: &str, dir: &str| {
+        let pattern = Pattern::new(pattern).unwrap();
+
+        fs::read_dir(fixture.join(dir))
+            .unwrap()
+            .map(|entry| entry.unwrap())
+            .filter(|entry| {
+                matches!(
+                    pattern.matches_dir_entry(fixture.path(), entry),
+                    PatternMatchResult::Matched
+                )
+            })
+            .map(|entry| entry.file_name().into_string().unwrap())
+            .collect::<BTreeSet<_>>()
+    };
+
+    assert_eq!(
+        matching("*.txt", ""),
+        ["a.txt", "c.txt"].map(String::from).into()
+    );
+    assert_eq!(matching("*.txt/", ""), ["c.txt"].map(String::from).into());
+    assert_eq!(matching("sub/*", "sub"), ["d.txt"].map(String::from).into());
+    assert_eq!(matching("*", "sub"), BTreeSet::new());
+
+    // Results are the same as when matching the path under the base directory
+    let pattern = Pattern::new("sub/**").unwrap();
+    let entry = fs::read_dir(fixture.path())
+        .unwrap()
+        .map(|entry| entry.unwrap())
+        .find(|entry| entry.file_name() == "sub")
+        .unwrap();
+
+    assert!(matches!(
+        pattern.matches_dir_entry(fixture.path(), &entry),
+        PatternMatchResult::Matched
+    ));
+    assert!(matches!(
+        Pattern::new("sub/*.txt")
+            .unwrap()
+            .matches_dir_entry(fixture.path(), &entry),
+        PatternMatchResult::Starved
+    ));
+    assert!(matches!(
+        pattern.matches_dir_entry(&fixture.join("sub"), &entry),
+        PatternMatchResult::Starved
+    ));
+
+    // Entries located outside of the base directory never match
+    let entry = fs::read_dir(fixture.path())
+        .unwrap()
+        .map(|entry| entry.unwrap())
+        .find(|entry| entry.file_name() == "a.txt")
+        .unwrap();
+
+    assert!(matches!(
+        Pattern::new("**")
+            .unwrap()
+            .matches_dir_entry(&fixture.join("sub"), &entry),
+        PatternMatchResult::NotMatched
+    ));
+}
+
+#[test]
+fn incremental_matching_agrees_with_walking() {
+    let fixture = Fixture::new(&[
+        "a/b/c.txt",
+        "a/b/d.rs",
+        "a/.hidden/e.txt",
+        "a/f.txt",
+        "g/h/i/j.txt",
+        "g/k/",
+        "l.txt",
+    ]);
+
+    /// Walk a directory like the walker does, only using the incremental matcher
+    fn walk(
+        dir: &Path,
+        relative: &Path,
+        pattern: &Pattern,
+        matcher: &mut PatternMatcher,
+        out: &mut BTreeSet<String>,
+    ) {
+        for entry in fs::read_dir(dir).unwrap() {
+            let entry = entry.unwrap();
+            let path = relative.join(entry.file_name());
+            let is_dir = entry.file_type().unwrap().is_dir();
+
+            let result = matcher.push(&entry.file_name());
+
+            if result.is_match() && (is_dir || !pattern.is_dir_only()) {
+                out.insert(to_slash_string(&path));
+            }
+
+            if is_dir && result.may_match_descendants() {
+                walk(&entry.path(), &path, pattern, matcher, out);
+            }
+
+            matcher.pop();
+        }
+    }
+
+    for (pattern, opts) in [
+        ("**/*.txt", PatternOpts::new()),
+        ("a/*", PatternOpts::new()),
+        ("a/**", PatternOpts::new()),
+        ("**/", PatternOpts::new()),
+        ("*/*/*", PatternOpts::new()),
+        ("**/b/**", PatternOpts::new()),
+        (
+            "**/*.txt",
+            PatternOpts::new().with_globstar_skips_hidden(true),
+        ),
+        (
+            "g/*.txt",
+            PatternOpts::new().with_star_matches_separators(true),
+        ),
+        ("**", PatternOpts::new().with_max_components(2)),
+        ("*.rs", PatternOpts::new().with_match_basename(true)),
+    ] {
+        let pattern = Pattern::new_with_opts(pattern, opts).unwrap();
+
+        let mut matcher = pattern.matcher();
+        let mut walked = BTreeSet::new();
+        walk(
+            fixture.path(),
+            Path::new(""),
+            &pattern,
+            &mut matcher,
+            &mut walked,
+        );
+
+        assert_eq!(matcher.depth(), 0);
+        assert_eq!(
+            walked,
+            collect_sorted(Walker::new(pattern.clone(), fixture.path())),
+            "Mismatch for pattern '{}'",
+            pattern.as_str()
+        );
+    }
+}